@@ -94,6 +94,20 @@ fn bench_engine(c: &mut Criterion) {
             b.iter(|| engine.lint_paths(&paths).expect("lint"));
         });
 
+        // Single-threaded baseline for comparing against the default one-thread-per-core run.
+        let sequential_config = Config {
+            jobs: Some(1),
+            ..Config::default()
+        };
+        let sequential = LintEngine::new(sequential_config).expect("create engine");
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{label}_jobs_1")),
+            label,
+            |b, _| {
+                b.iter(|| sequential.lint_paths(&paths).expect("lint"));
+            },
+        );
+
         // `_dir` is dropped here which cleans up the tempdir.
     }
 
@@ -0,0 +1,18 @@
+//! Lint a directory through the library API and print the report as JSON.
+//!
+//! ```text
+//! cargo run --example lint_report -- examples/project
+//! ```
+
+use std::path::PathBuf;
+
+use pytest_linter::{lint_path, Config};
+
+fn main() -> anyhow::Result<()> {
+    let path = std::env::args_os()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("examples/project"), PathBuf::from);
+    let report = lint_path(&path, &Config::default())?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,11 @@
+import pytest
+
+
+@pytest.fixture
+def cart():
+    return {"items": [], "total": 0}
+
+
+@pytest.fixture
+def legacy_gateway():
+    return object()
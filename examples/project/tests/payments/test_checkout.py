@@ -0,0 +1,17 @@
+import time
+
+
+def test_add_item_updates_total(cart):
+    cart["items"].append(("book", 12))
+    cart["total"] += 12
+    assert cart["total"] == 12
+
+
+def test_payment_is_captured():
+    start_payment()
+    time.sleep(2)
+    assert payment_status() == "captured"
+
+
+def test_payment_is_captured():
+    assert payment_status() == "captured"
@@ -0,0 +1,6 @@
+import pytest
+
+
+def test_refund_of_unknown_order_is_rejected():
+    with pytest.raises(KeyError):
+        refund("missing")
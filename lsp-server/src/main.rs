@@ -1,12 +1,30 @@
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use pytest_linter::config::Config;
+use pytest_linter::models::Violation;
+use pytest_linter::span::LineIndex;
 use tower_lsp::lsp_types::*;
 use tower_lsp::Client;
 
+/// Quiet period after an edit before the buffer is re-linted.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// An open buffer and the violations last published for it.
+struct Document {
+    version: i32,
+    text: String,
+    violations: Vec<Violation>,
+}
+
+type Documents = Arc<Mutex<HashMap<Url, Document>>>;
+
 struct Backend {
     client: Client,
     config: Arc<RwLock<Config>>,
+    config_error: Arc<RwLock<Option<String>>>,
+    documents: Documents,
 }
 
 #[tower_lsp::async_trait]
@@ -29,9 +47,16 @@ impl tower_lsp::LanguageServer for Backend {
             });
 
         if let Some(ref root) = workspace_root {
-            if let Ok(cfg) = Config::discover(root) {
-                if let Ok(mut guard) = self.config.write() {
-                    *guard = cfg;
+            match Config::discover(root) {
+                Ok(cfg) => {
+                    if let Ok(mut guard) = self.config.write() {
+                        *guard = cfg;
+                    }
+                }
+                Err(e) => {
+                    if let Ok(mut guard) = self.config_error.write() {
+                        *guard = Some(format!("{e:#}"));
+                    }
                 }
             }
         }
@@ -47,6 +72,12 @@ impl tower_lsp::LanguageServer for Backend {
                         save: None,
                     },
                 )),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        ..Default::default()
+                    },
+                )),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -56,78 +87,299 @@ impl tower_lsp::LanguageServer for Backend {
         })
     }
 
-    async fn initialized(&self, _: InitializedParams) {}
+    async fn initialized(&self, _: InitializedParams) {
+        let error = self.config_error.read().ok().and_then(|e| e.clone());
+        if let Some(error) = error {
+            self.client
+                .show_message(
+                    MessageType::ERROR,
+                    format!("pytest-linter: invalid configuration, using defaults: {error}"),
+                )
+                .await;
+        }
+    }
 
     async fn shutdown(&self) -> anyhow::Result<(), tower_lsp::jsonrpc::Error> {
         Ok(())
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let uri = params.text_document.uri;
-        let text = params.text_document.text;
-        let config = self.config.read().unwrap().clone();
-        let diagnostics = Self::lint_document(&uri, &text, &config);
-        self.client
-            .publish_diagnostics(uri, diagnostics, None)
-            .await;
+        let doc = params.text_document;
+        self.documents.lock().unwrap().insert(
+            doc.uri.clone(),
+            Document {
+                version: doc.version,
+                text: doc.text,
+                violations: vec![],
+            },
+        );
+        self.schedule_lint(doc.uri, doc.version, None);
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
-        let text = params
-            .content_changes
-            .last()
-            .map(|c| c.text.clone())
-            .unwrap_or_default();
-        let config = self.config.read().unwrap().clone();
-        let diagnostics = Self::lint_document(&uri, &text, &config);
-        self.client
-            .publish_diagnostics(uri, diagnostics, None)
-            .await;
+        let version = params.text_document.version;
+        let Some(change) = params.content_changes.into_iter().last() else {
+            return;
+        };
+        self.documents
+            .lock()
+            .unwrap()
+            .entry(uri.clone())
+            .and_modify(|d| {
+                d.version = version;
+                d.text.clone_from(&change.text);
+            })
+            .or_insert_with(|| Document {
+                version,
+                text: change.text,
+                violations: vec![],
+            });
+        self.schedule_lint(uri, version, Some(DEBOUNCE));
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.lock().unwrap().remove(&uri);
+        self.client.publish_diagnostics(uri, vec![], None).await;
+    }
+
+    async fn code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> anyhow::Result<Option<CodeActionResponse>, tower_lsp::jsonrpc::Error> {
+        let uri = params.text_document.uri;
+        let documents = self.documents.lock().unwrap();
+        let Some(doc) = documents.get(&uri) else {
+            return Ok(None);
+        };
+        let actions: Vec<CodeActionOrCommand> = doc
+            .violations
+            .iter()
+            .filter(|v| overlaps(to_range(v, &doc.text), params.range))
+            .flat_map(|v| code_actions(&uri, v, &doc.text))
+            .map(CodeActionOrCommand::CodeAction)
+            .collect();
+        Ok((!actions.is_empty()).then_some(actions))
     }
 }
 
 impl Backend {
-    fn lint_document(uri: &Url, text: &str, config: &Config) -> Vec<Diagnostic> {
-        let file_path = match uri.to_file_path() {
-            Ok(p) => p,
-            Err(_) => return vec![],
-        };
+    /// Lint the buffer after `delay`, unless it has been edited again in the meantime.
+    fn schedule_lint(&self, uri: Url, version: i32, delay: Option<Duration>) {
+        let client = self.client.clone();
+        let config = Arc::clone(&self.config);
+        let documents = Arc::clone(&self.documents);
+        tokio::spawn(async move {
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            let current = |docs: &HashMap<Url, Document>| {
+                docs.get(&uri)
+                    .filter(|d| d.version == version)
+                    .map(|d| d.text.clone())
+            };
+            let Some(text) = current(&documents.lock().unwrap()) else {
+                return;
+            };
+            let config = config.read().unwrap().clone();
+            let violations = lint_text(&uri, &text, &config);
+            let diagnostics = violations.iter().map(|v| to_diagnostic(v, &text)).collect();
+            {
+                let mut docs = documents.lock().unwrap();
+                match docs.get_mut(&uri) {
+                    Some(doc) if doc.version == version => doc.violations = violations,
+                    _ => return,
+                }
+            }
+            client
+                .publish_diagnostics(uri, diagnostics, Some(version))
+                .await;
+        });
+    }
+}
 
-        let engine = match pytest_linter::engine::LintEngine::new(config.clone()) {
-            Ok(e) => e,
-            Err(_) => return vec![],
-        };
+/// Run the rule pipeline on an in-memory buffer. Buffers that do not parse (typically
+/// mid-edit) get no diagnostics, and a panic in a rule is contained to this request.
+fn lint_text(uri: &Url, text: &str, config: &Config) -> Vec<Violation> {
+    let Ok(file_path) = uri.to_file_path() else {
+        return vec![];
+    };
+    let incomplete = pytest_linter::rules::ast::parse_python(text)
+        .is_none_or(|tree| tree.root_node().has_error());
+    if incomplete {
+        return vec![];
+    }
+    std::panic::catch_unwind(|| {
+        let engine = pytest_linter::engine::LintEngine::new(config.clone()).ok()?;
+        engine.lint_source(text, &file_path).ok()
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
 
-        let violations = match engine.lint_source(text, &file_path) {
-            Ok(v) => v,
-            Err(_) => return vec![],
-        };
+/// LSP position of a 1-based line and 1-based byte column.
+fn lsp_position(index: &LineIndex, line: usize, col: usize) -> Position {
+    position_at(line, units(index.utf16_col(line, col).unwrap_or(0)))
+}
 
-        violations
-            .into_iter()
-            .map(|v| Diagnostic {
-                range: Range {
-                    start: Position {
-                        line: v.line.saturating_sub(1) as u32,
-                        character: v.col.map(|c| c.saturating_sub(1) as u32).unwrap_or(0),
-                    },
-                    end: Position {
-                        line: v.line.saturating_sub(1) as u32,
-                        character: v.col.map(|c| c.saturating_sub(1) as u32).unwrap_or(0),
-                    },
+fn units(count: usize) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+/// Convert a violation's 1-based byte span to an LSP range in UTF-16 positions.
+/// Violations without a column cover their whole line.
+fn to_range(v: &Violation, text: &str) -> Range {
+    span_range(&LineIndex::new(text), v.line, v.col, v.end_line, v.end_col)
+}
+
+fn span_range(
+    index: &LineIndex,
+    line: usize,
+    col: Option<usize>,
+    end_line: Option<usize>,
+    end_col: Option<usize>,
+) -> Range {
+    match col {
+        Some(col) => Range {
+            start: lsp_position(index, line, col),
+            end: lsp_position(index, end_line.unwrap_or(line), end_col.unwrap_or(col)),
+        },
+        None => {
+            let len = index.line_text(line).map_or(0, str::len);
+            Range {
+                start: lsp_position(index, line, 1),
+                end: lsp_position(index, line, len + 1),
+            }
+        }
+    }
+}
+
+/// The violation's related locations. Positions in other files are converted against
+/// those files as they are on disk.
+fn related_information(v: &Violation, text: &str) -> Option<Vec<DiagnosticRelatedInformation>> {
+    let related: Vec<_> = v
+        .related
+        .iter()
+        .filter_map(|r| {
+            let uri = Url::from_file_path(&r.file_path).ok()?;
+            let on_disk;
+            let source = if r.file_path == v.file_path {
+                text
+            } else {
+                on_disk = std::fs::read_to_string(&r.file_path).unwrap_or_default();
+                &on_disk
+            };
+            let index = LineIndex::new(source);
+            Some(DiagnosticRelatedInformation {
+                location: Location {
+                    uri,
+                    range: span_range(&index, r.line, r.col, r.end_line, r.end_col),
                 },
-                severity: Some(match v.severity {
-                    pytest_linter::models::Severity::Error => DiagnosticSeverity::ERROR,
-                    pytest_linter::models::Severity::Warning => DiagnosticSeverity::WARNING,
-                    pytest_linter::models::Severity::Info => DiagnosticSeverity::INFORMATION,
-                }),
-                code: Some(NumberOrString::String(v.rule_id.clone())),
-                source: Some("pytest-linter".to_string()),
-                message: v.message,
-                ..Diagnostic::default()
+                message: r.message.clone(),
             })
-            .collect()
+        })
+        .collect();
+    (!related.is_empty()).then_some(related)
+}
+
+fn overlaps(a: Range, b: Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+fn to_diagnostic(v: &Violation, text: &str) -> Diagnostic {
+    Diagnostic {
+        range: to_range(v, text),
+        severity: Some(match v.severity {
+            pytest_linter::models::Severity::Error => DiagnosticSeverity::ERROR,
+            pytest_linter::models::Severity::Warning => DiagnosticSeverity::WARNING,
+            pytest_linter::models::Severity::Info => DiagnosticSeverity::INFORMATION,
+            pytest_linter::models::Severity::Hint => DiagnosticSeverity::HINT,
+        }),
+        code: Some(NumberOrString::String(v.rule_id.clone())),
+        source: Some("pytest-linter".to_string()),
+        message: v.message.clone(),
+        tags: (!v.tags.is_empty()).then(|| {
+            v.tags
+                .iter()
+                .map(|tag| match tag {
+                    pytest_linter::models::Tag::Unnecessary => DiagnosticTag::UNNECESSARY,
+                    pytest_linter::models::Tag::Deprecated => DiagnosticTag::DEPRECATED,
+                })
+                .collect()
+        }),
+        related_information: related_information(v, text),
+        ..Diagnostic::default()
+    }
+}
+
+/// LSP position of a byte offset into `text`.
+fn byte_position(index: &LineIndex, byte: usize) -> Position {
+    let (line, col) = index.position(byte).unwrap_or((index.line_count(), 1));
+    lsp_position(index, line, col)
+}
+
+/// Quick fixes for one violation: its suggestion (with the rule's automatic fix when it
+/// has one), and a `# pytl: ignore[...]` comment.
+fn code_actions(uri: &Url, v: &Violation, text: &str) -> Vec<CodeAction> {
+    let diagnostic = to_diagnostic(v, text);
+    let index = LineIndex::new(text);
+    let mut actions = Vec::new();
+    let fix = v.fix.as_ref().map(|fix| WorkspaceEdit {
+        changes: Some(HashMap::from([(
+            uri.clone(),
+            fix.edits
+                .iter()
+                .map(|e| TextEdit {
+                    range: Range {
+                        start: byte_position(&index, e.start_byte),
+                        end: byte_position(&index, e.end_byte),
+                    },
+                    new_text: e.replacement.clone(),
+                })
+                .collect(),
+        )])),
+        ..Default::default()
+    });
+    if v.suggestion.is_some() || fix.is_some() {
+        actions.push(CodeAction {
+            title: v
+                .suggestion
+                .clone()
+                .unwrap_or_else(|| format!("Fix {}", v.rule_id)),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            is_preferred: fix.as_ref().map(|_| true),
+            edit: fix,
+            ..Default::default()
+        });
+    }
+    let line = index.line_text(v.line).unwrap_or("");
+    if !line.contains("pytl: ignore") && !line.contains("noqa") {
+        let end = lsp_position(&index, v.line, line.len() + 1);
+        let edit = TextEdit {
+            range: Range { start: end, end },
+            new_text: format!("  # pytl: ignore[{}]", v.rule_id),
+        };
+        actions.push(CodeAction {
+            title: format!("Suppress {} on this line", v.rule_id),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic]),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+    actions
+}
+
+fn position_at(line: usize, character: u32) -> Position {
+    Position {
+        line: u32::try_from(line.saturating_sub(1)).unwrap_or(u32::MAX),
+        character,
     }
 }
 
@@ -136,9 +388,129 @@ async fn main() {
     let (service, socket) = tower_lsp::LspService::new(|client| Backend {
         client,
         config: Arc::new(RwLock::new(Config::default())),
+        config_error: Arc::new(RwLock::new(None)),
+        documents: Arc::new(Mutex::new(HashMap::new())),
     });
 
     tower_lsp::Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
         .serve(service)
         .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(line: usize, col: Option<usize>, end: Option<(usize, usize)>) -> Violation {
+        let mut v = pytest_linter::engine::make_violation(
+            "PYTEST-FLK-001",
+            "TimeSleepRule",
+            pytest_linter::models::Severity::Warning,
+            pytest_linter::models::Category::Flakiness,
+            "slow".to_string(),
+            "test_a.py".into(),
+            line,
+            Some("use an event".to_string()),
+            None,
+        );
+        v.col = col;
+        v.end_line = end.map(|e| e.0);
+        v.end_col = end.map(|e| e.1);
+        v
+    }
+
+    #[test]
+    fn test_range_counts_utf16_units() {
+        // 'é' is 2 bytes / 1 UTF-16 unit; '𝄞' is 4 bytes / 2 units
+        let text = "x = 'é𝄞'; time.sleep(1)\n";
+        let start = text.find("time").unwrap() + 1;
+        let range = to_range(&violation(1, Some(start), Some((1, start + 13))), text);
+        assert_eq!(range.start, Position::new(0, 11));
+        assert_eq!(range.end, Position::new(0, 24));
+    }
+
+    #[test]
+    fn test_range_without_column_covers_line() {
+        let range = to_range(
+            &violation(2, None, None),
+            "import time\r\ndef test_a():\r\n",
+        );
+        assert_eq!(range.start, Position::new(1, 0));
+        assert_eq!(range.end, Position::new(1, 13));
+    }
+
+    #[test]
+    fn test_incomplete_buffer_has_no_diagnostics() {
+        let uri = Url::parse("file:///tmp/test_a.py").unwrap();
+        let text = "def test_a():\n    time.sleep(\n";
+        assert!(lint_text(&uri, text, &Config::default()).is_empty());
+        let complete = "import time\n\ndef test_a():\n    time.sleep(1)\n";
+        assert!(!lint_text(&uri, complete, &Config::default()).is_empty());
+    }
+
+    #[test]
+    fn test_code_actions_offer_suggestion_and_suppression() {
+        let uri = Url::parse("file:///tmp/test_a.py").unwrap();
+        let text = "def test_a():\n    time.sleep(1)\n";
+        let actions = code_actions(&uri, &violation(2, Some(5), Some((2, 18))), text);
+        assert_eq!(actions[0].title, "use an event");
+        let edit = &actions[1].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri][0];
+        assert_eq!(edit.new_text, "  # pytl: ignore[PYTEST-FLK-001]");
+        assert_eq!(edit.range.start, Position::new(1, 17));
+    }
+
+    #[test]
+    fn test_code_action_carries_fix_edits() {
+        let text = "x = 'é'\n@pytest.mark.skip\ndef test_a(): pass\n";
+        let mut v = violation(2, None, None);
+        let at = text.find("\ndef").unwrap();
+        v.fix = Some(pytest_linter::models::Fix::replace(
+            at,
+            at,
+            "(reason=\"TODO\")",
+        ));
+        let uri = Url::parse("file:///tmp/test_a.py").unwrap();
+        let actions = code_actions(&uri, &v, text);
+        assert_eq!(actions[0].is_preferred, Some(true));
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.start, Position::new(1, 17));
+        assert_eq!(edits[0].new_text, "(reason=\"TODO\")");
+    }
+
+    #[test]
+    fn test_diagnostic_maps_hint_severity_and_tags() {
+        let text = "def test_a(db):\n    pass\n";
+        let mut v = violation(1, Some(12), Some((1, 14)));
+        assert_eq!(to_diagnostic(&v, text).tags, None);
+        v.severity = pytest_linter::models::Severity::Hint;
+        v.tags = vec![pytest_linter::models::Tag::Unnecessary];
+        let diagnostic = to_diagnostic(&v, text);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+    }
+
+    #[test]
+    fn test_diagnostic_links_related_locations() {
+        let text = "def test_é():\n    pass\n\ndef test_é():\n    pass\n";
+        let mut v = violation(4, Some(5), Some((4, 12)));
+        v.file_path = "/tmp/test_a.py".into();
+        v.related = vec![pytest_linter::models::RelatedLocation {
+            file_path: v.file_path.clone(),
+            line: 1,
+            col: Some(5),
+            end_line: Some(1),
+            end_col: Some(12),
+            message: "previous definition".to_string(),
+        }];
+        let related = to_diagnostic(&v, text).related_information.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.uri.as_str(), "file:///tmp/test_a.py");
+        assert_eq!(related[0].location.range.start, Position::new(0, 4));
+        // 'é' is 2 bytes but one UTF-16 unit
+        assert_eq!(related[0].location.range.end, Position::new(0, 10));
+        assert_eq!(related[0].message, "previous definition");
+
+        v.related.clear();
+        assert_eq!(to_diagnostic(&v, text).related_information, None);
+    }
+}
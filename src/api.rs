@@ -0,0 +1,466 @@
+//! The library API: lint a buffer or a path from another tool without going through the
+//! command line. The CLI runs on the same functions, so both report the same diagnostics.
+//!
+//! ```no_run
+//! use pytest_linter::{lint_path, lint_source, Config, Language, Severity};
+//!
+//! let config = Config::default();
+//! let diagnostics = lint_source(
+//!     "import time\n\ndef test_wait():\n    time.sleep(1)\n",
+//!     Language::Python,
+//!     &config,
+//! );
+//! assert_eq!(diagnostics[0].rule_id.as_str(), "PYTEST-FLK-001");
+//!
+//! let report = lint_path("tests/".as_ref(), &config)?;
+//! let errors = report
+//!     .diagnostics
+//!     .iter()
+//!     .filter(|d| d.severity == Severity::Error)
+//!     .count();
+//! println!("{errors} errors in {} files", report.files.analyzed);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::{format_report, Baseline};
+use crate::cache::CacheStats;
+use crate::config::Config;
+use crate::engine::{
+    failure_diagnostic, format_run_summary, lint_buffer, output_order, write_format, LintEngine,
+    LintStatus,
+};
+use crate::fix::{apply_fixes, fix_files, unified_diff, FixSummary};
+use crate::models::{self, Category, Fix, Severity, Tag, Violation};
+use crate::output::summary::{FileCounts, Summary};
+use crate::output::SourceCache;
+
+/// Path reported for diagnostics of [`lint_source`], which has no file.
+pub const SOURCE_PATH: &str = "<source>";
+
+/// Language of a buffer passed to [`lint_source`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Language {
+    /// Python test modules and `conftest.py` files.
+    #[default]
+    Python,
+}
+
+impl Language {
+    /// The language of a file, by extension.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .is_some_and(|e| e == "py")
+            .then_some(Self::Python)
+    }
+}
+
+/// ID of a rule, e.g. `PYTEST-FLK-001`. Serialized as a plain string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RuleId(String);
+
+impl RuleId {
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RuleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Where a diagnostic is in its file. Lines and columns are 1-based, columns and byte
+/// offsets count UTF-8 bytes, and the end is exclusive. Rules that report a whole line
+/// leave the columns and the end unset. The JSON output uses the same span, with the
+/// whole line filled in and the byte offsets into the file; the library leaves those unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Span {
+    pub line: usize,
+    pub column: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    #[serde(default)]
+    pub start_byte: Option<usize>,
+    #[serde(default)]
+    pub end_byte: Option<usize>,
+}
+
+impl Span {
+    /// A span as rules report it, without byte offsets.
+    pub(crate) fn new(
+        line: usize,
+        column: Option<usize>,
+        end_line: Option<usize>,
+        end_column: Option<usize>,
+    ) -> Self {
+        Self {
+            line,
+            column,
+            end_line,
+            end_column,
+            start_byte: None,
+            end_byte: None,
+        }
+    }
+}
+
+/// A problem found by a rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub rule_id: RuleId,
+    /// Name of the rule, e.g. `TimeSleepRule`.
+    pub rule_name: String,
+    /// Severity after configuration, i.e. what the run reports.
+    pub severity: Severity,
+    pub category: Category,
+    pub message: String,
+    /// The file, as it was found from the linted path; [`SOURCE_PATH`] for a buffer.
+    pub path: PathBuf,
+    pub span: Span,
+    pub suggestion: Option<String>,
+    /// The test the diagnostic is about, if any.
+    pub test_name: Option<String>,
+    /// Machine-applicable edits that resolve the problem; byte offsets into the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+    /// Rule-specific measurements behind the diagnostic.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// How editors should render the span; see [`Tag`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+    /// Other places the diagnostic refers to, e.g. the first definition of a duplicate.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedLocation>,
+}
+
+/// A second location of a [`Diagnostic`], with a message saying what is there.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RelatedLocation {
+    pub path: PathBuf,
+    pub span: Span,
+    pub message: String,
+}
+
+impl From<models::RelatedLocation> for RelatedLocation {
+    fn from(r: models::RelatedLocation) -> Self {
+        Self {
+            path: r.file_path,
+            span: Span::new(r.line, r.col, r.end_line, r.end_col),
+            message: r.message,
+        }
+    }
+}
+
+impl From<RelatedLocation> for models::RelatedLocation {
+    fn from(r: RelatedLocation) -> Self {
+        Self {
+            file_path: r.path,
+            line: r.span.line,
+            col: r.span.column,
+            end_line: r.span.end_line,
+            end_col: r.span.end_column,
+            message: r.message,
+        }
+    }
+}
+
+impl From<Violation> for Diagnostic {
+    fn from(v: Violation) -> Self {
+        Self {
+            rule_id: RuleId(v.rule_id),
+            rule_name: v.rule_name,
+            severity: v.severity,
+            category: v.category,
+            message: v.message,
+            path: v.file_path,
+            span: Span::new(v.line, v.col, v.end_line, v.end_col),
+            suggestion: v.suggestion,
+            test_name: v.test_name,
+            fix: v.fix,
+            metadata: v.metadata,
+            tags: v.tags,
+            related: v.related.into_iter().map(RelatedLocation::from).collect(),
+        }
+    }
+}
+
+impl From<Diagnostic> for Violation {
+    fn from(d: Diagnostic) -> Self {
+        Self {
+            rule_id: d.rule_id.0,
+            rule_name: d.rule_name,
+            severity: d.severity,
+            category: d.category,
+            message: d.message,
+            file_path: d.path,
+            line: d.span.line,
+            col: d.span.column,
+            end_line: d.span.end_line,
+            end_col: d.span.end_column,
+            suggestion: d.suggestion,
+            test_name: d.test_name,
+            fix: d.fix,
+            metadata: d.metadata,
+            tags: d.tags,
+            related: d
+                .related
+                .into_iter()
+                .map(models::RelatedLocation::from)
+                .collect(),
+        }
+    }
+}
+
+/// Result of linting a path: the diagnostics in output order (by path, then position),
+/// and what happened to the files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LintReport {
+    pub diagnostics: Vec<Diagnostic>,
+    pub files: FileCounts,
+    /// Cache hits and misses, when the configuration enables the cache.
+    pub cache: Option<CacheStats>,
+}
+
+impl LintReport {
+    /// Counts per rule, severity and file, as printed by `--statistics`.
+    #[must_use]
+    pub fn summary(&self) -> Summary {
+        Summary::new(&self.violations(), Some(self.files))
+    }
+
+    /// What `--statistics` and `--top-files` print after the diagnostics, as set in
+    /// `config`; empty when neither is set.
+    #[must_use]
+    pub fn run_summary(&self, config: &Config) -> String {
+        format_run_summary(&self.violations(), self.files, self.cache, config)
+    }
+
+    /// The diagnostics in the form the output formats take.
+    pub(crate) fn violations(&self) -> Vec<Violation> {
+        to_violations(&self.diagnostics)
+    }
+}
+
+/// Lint a buffer. Every rule enabled in `config` runs on it as if it were a test module;
+/// its diagnostics are reported at [`SOURCE_PATH`]. A failure to set up the linter, such
+/// as an invalid override glob in `config`, is reported as a `PYTEST-INT-001` diagnostic.
+#[must_use]
+pub fn lint_source(source: &str, language: Language, config: &Config) -> Vec<Diagnostic> {
+    match language {
+        Language::Python => {}
+    }
+    let path = Path::new(SOURCE_PATH);
+    let mut violations = LintEngine::new(config.clone())
+        .and_then(|engine| engine.lint_source(source, path))
+        .unwrap_or_else(|e| {
+            vec![failure_diagnostic(
+                path,
+                format!("Could not lint the source: {e:#}"),
+                "Check the configuration passed to lint_source",
+            )]
+        });
+    violations.sort_by(output_order);
+    violations.into_iter().map(Diagnostic::from).collect()
+}
+
+/// Lint a buffer as if it were the file at `path`, which need not exist: `path` decides
+/// whether it is a test file, which overrides of `config` apply and where diagnostics are
+/// reported. Blank buffers and non-test paths have no diagnostics.
+///
+/// # Errors
+///
+/// When the configuration is invalid, e.g. a malformed override glob.
+pub fn lint_source_at(source: &str, path: &Path, config: &Config) -> Result<Vec<Diagnostic>> {
+    let violations = lint_buffer(source, path, config.clone())?;
+    Ok(violations.into_iter().map(Diagnostic::from).collect())
+}
+
+/// Lint the test files under `path` (a file or directory), as the CLI does for one path.
+///
+/// # Errors
+///
+/// When the configuration is invalid, e.g. a malformed exclude glob. Files that cannot be
+/// read or parsed are skipped and counted in [`LintReport::files`].
+pub fn lint_path(path: &Path, config: &Config) -> Result<LintReport> {
+    lint_paths(&[path.to_path_buf()], config)
+}
+
+/// Lint the test files under several paths in one run; see [`lint_path`].
+///
+/// # Errors
+///
+/// As for [`lint_path`].
+pub fn lint_paths(paths: &[PathBuf], config: &Config) -> Result<LintReport> {
+    run(paths, config.clone(), 256)
+}
+
+/// [`lint_paths`] with a memory budget in MB instead of the default 256: a run that is
+/// estimated to need more prints a warning on stderr.
+///
+/// # Errors
+///
+/// As for [`lint_path`].
+pub fn lint_paths_with_memory_limit(
+    paths: &[PathBuf],
+    config: &Config,
+    memory_limit_mb: usize,
+) -> Result<LintReport> {
+    run(paths, config.clone(), memory_limit_mb)
+}
+
+/// One run over `paths` with a memory budget in MB (a warning threshold, see
+/// [`LintEngine`]). The CLI entry points in [`crate::engine`] go through here.
+pub(crate) fn run(paths: &[PathBuf], config: Config, memory_limit_mb: usize) -> Result<LintReport> {
+    let engine = LintEngine::with_memory_limit(config, memory_limit_mb)?;
+    let violations = engine.lint_paths(paths)?;
+    Ok(LintReport {
+        diagnostics: violations.into_iter().map(Diagnostic::from).collect(),
+        files: engine.file_counts(),
+        cache: engine.cache_stats(),
+    })
+}
+
+/// Whether `diagnostics` pass the `fail_on` / `max_warnings` policy of `config`, as the CLI
+/// exit code reports it. `PYTEST-INT-001` diagnostics take precedence over the policy.
+#[must_use]
+pub fn lint_status(diagnostics: &[Diagnostic], config: &Config) -> LintStatus {
+    crate::engine::lint_status(&to_violations(diagnostics), config)
+}
+
+/// Write `diagnostics` to `out` in one of the CLI's output formats (`terminal`, `short`,
+/// `json`, `json-lines`, `sarif`, `github`, `checkstyle` or `junit`). `files` goes into the
+/// summary of the JSON report. Snippets and character columns are read from disk, except
+/// for `buffer`: the path and text of a file linted with [`lint_source_at`].
+///
+/// # Errors
+///
+/// When writing to `out` fails.
+pub fn write_diagnostics(
+    out: &mut dyn std::io::Write,
+    diagnostics: &[Diagnostic],
+    format: &str,
+    config: &Config,
+    files: Option<FileCounts>,
+    buffer: Option<(&Path, &str)>,
+) -> Result<()> {
+    let mut sources = match buffer {
+        Some((path, source)) => SourceCache::with_source(path, source),
+        None => SourceCache::default(),
+    };
+    write_format(
+        &to_violations(diagnostics),
+        format,
+        out,
+        config,
+        files,
+        &mut sources,
+    )
+}
+
+/// A buffer with the fixes of its diagnostics applied; see [`fix_source`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FixedSource {
+    /// The fixed text; the original when no fix applied.
+    pub source: String,
+    /// What was applied, with the change as a unified diff.
+    pub summary: FixSummary,
+}
+
+/// Apply the fixes of `diagnostics`, found by [`lint_source_at`] on `source`, to `source`.
+/// Overlapping fixes are skipped, and the whole set is rejected if it would break the
+/// syntax. `path` only labels the diff.
+#[must_use]
+pub fn fix_source(source: &str, path: &Path, diagnostics: &[Diagnostic]) -> FixedSource {
+    let fixes: Vec<&Fix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    let outcome = apply_fixes(source, &fixes);
+    FixedSource {
+        summary: FixSummary {
+            applied: outcome.applied,
+            skipped: outcome.skipped,
+            files: usize::from(outcome.applied > 0),
+            rejected: usize::from(outcome.rejected),
+            diff: unified_diff(&path.to_string_lossy(), source, &outcome.edits),
+        },
+        source: outcome.source,
+    }
+}
+
+/// Lint `paths` and apply every available fix to the files, or with `dry_run` only compute
+/// the diff.
+///
+/// # Errors
+///
+/// When the configuration is invalid, or a fixed file cannot be written.
+pub fn fix_paths(paths: &[PathBuf], config: &Config, dry_run: bool) -> Result<FixSummary> {
+    fix_files(&lint_paths(paths, config)?.violations(), dry_run)
+}
+
+/// Record `diagnostics` in a baseline file at `path`, for [`check_baseline`].
+///
+/// # Errors
+///
+/// When the file cannot be written.
+pub fn write_baseline(diagnostics: &[Diagnostic], path: &Path) -> Result<()> {
+    Baseline::from_violations(&to_violations(diagnostics)).save(path)
+}
+
+/// A [`LintReport`] with the diagnostics recorded in a baseline taken out.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct BaselineCheck {
+    /// The report of the run, keeping only the diagnostics the baseline does not record.
+    pub new: LintReport,
+    /// Entries in the baseline file.
+    pub recorded: usize,
+    /// How many entries still match and which are stale, as `--baseline-report` prints.
+    pub breakdown: String,
+}
+
+/// Take the diagnostics recorded in the baseline file at `path` out of `report`.
+///
+/// # Errors
+///
+/// When the baseline file cannot be read or has an unknown layout.
+pub fn check_baseline(report: LintReport, path: &Path) -> Result<BaselineCheck> {
+    let baseline = Baseline::load(path)?;
+    let outcome = baseline.apply(&report.violations());
+    Ok(BaselineCheck {
+        breakdown: format_report(&baseline, &outcome),
+        recorded: baseline.len(),
+        new: LintReport {
+            diagnostics: outcome
+                .new_violations
+                .into_iter()
+                .map(Diagnostic::from)
+                .collect(),
+            ..report
+        },
+    })
+}
+
+fn to_violations(diagnostics: &[Diagnostic]) -> Vec<Violation> {
+    diagnostics.iter().cloned().map(Violation::from).collect()
+}
@@ -0,0 +1,262 @@
+//! Baseline files: a snapshot of known violations that later runs suppress, so the linter
+//! can be adopted in CI before every existing issue is fixed.
+//!
+//! Entries are keyed by rule, path and a fingerprint of the enclosing test function's body
+//! rather than a line number, so unrelated edits that shift lines do not invalidate them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+use crate::models::Violation;
+use crate::rules::ast::parse_python;
+
+/// Version of the baseline file layout.
+const BASELINE_VERSION: u32 = 1;
+
+/// One known violation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub rule_id: String,
+    pub path: String,
+    /// Hash of the enclosing test function (or of the flagged line outside functions).
+    pub fingerprint: String,
+    /// For readers of the file only; not used for matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
+}
+
+impl BaselineEntry {
+    fn key(&self) -> (&str, &str, &str) {
+        (&self.rule_id, &self.path, &self.fingerprint)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    entries: Vec<BaselineEntry>,
+}
+
+/// A loaded or freshly computed baseline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// Result of applying a baseline to a run's violations.
+#[derive(Debug, Clone, Default)]
+pub struct BaselineOutcome {
+    /// Violations not covered by the baseline.
+    pub new_violations: Vec<Violation>,
+    /// Number of baseline entries that matched a violation.
+    pub matched: usize,
+    /// Baseline entries that matched nothing and can be pruned.
+    pub stale: Vec<BaselineEntry>,
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` because fingerprints are persisted and
+/// must not change between Rust releases.
+pub(crate) fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Hash text with indentation, trailing whitespace and blank lines ignored, so re-indenting
+/// or moving a test does not change it.
+fn content_hash(text: &str) -> String {
+    let normalized: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    format!("{:016x}", fnv1a(&normalized.join("\n")))
+}
+
+/// The innermost function definition spanning `line` (1-based).
+fn enclosing_function(node: Node, line: usize) -> Option<Node> {
+    let row = line.checked_sub(1)?;
+    if node.start_position().row > row || node.end_position().row < row {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let inner = node
+        .children(&mut cursor)
+        .find_map(|child| enclosing_function(child, line));
+    inner.or_else(|| (node.kind() == "function_definition").then_some(node))
+}
+
+/// Fingerprint of the code a violation belongs to: the enclosing function's text, or the
+/// flagged line itself for module-level violations.
+fn fingerprint(v: &Violation, source: Option<&(String, Option<Tree>)>) -> String {
+    let Some((source, tree)) = source else {
+        return content_hash("");
+    };
+    let function = tree
+        .as_ref()
+        .and_then(|t| enclosing_function(t.root_node(), v.line));
+    match function {
+        Some(f) => content_hash(&source[f.byte_range()]),
+        None => content_hash(source.lines().nth(v.line.saturating_sub(1)).unwrap_or("")),
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Compute entries for violations, reading and parsing each file once.
+fn entries_for(violations: &[Violation]) -> Vec<BaselineEntry> {
+    let mut sources: HashMap<&PathBuf, Option<(String, Option<Tree>)>> = HashMap::new();
+    violations
+        .iter()
+        .map(|v| {
+            let source = sources.entry(&v.file_path).or_insert_with(|| {
+                let text = std::fs::read_to_string(&v.file_path).ok()?;
+                let tree = parse_python(&text);
+                Some((text, tree))
+            });
+            BaselineEntry {
+                rule_id: v.rule_id.clone(),
+                path: display_path(&v.file_path),
+                fingerprint: fingerprint(v, source.as_ref()),
+                test_name: v.test_name.clone(),
+            }
+        })
+        .collect()
+}
+
+impl Baseline {
+    /// Snapshot the given violations.
+    #[must_use]
+    pub fn from_violations(violations: &[Violation]) -> Self {
+        let mut entries = entries_for(violations);
+        entries.sort();
+        Self { entries }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Read a baseline file written by [`Baseline::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("read baseline {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("parse baseline {}", path.display()))?;
+        if value.is_array() {
+            bail!(
+                "baseline {} uses the old line-based format; regenerate it with --write-baseline",
+                path.display()
+            );
+        }
+        let file: BaselineFile = serde_json::from_value(value)
+            .with_context(|| format!("parse baseline {}", path.display()))?;
+        if file.version != BASELINE_VERSION {
+            bail!(
+                "baseline {} has unsupported version {}",
+                path.display(),
+                file.version
+            );
+        }
+        Ok(Self {
+            entries: file.entries,
+        })
+    }
+
+    /// Write the baseline as pretty-printed JSON, entries sorted for stable diffs.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut entries = self.entries.clone();
+        entries.sort();
+        let file = BaselineFile {
+            version: BASELINE_VERSION,
+            entries,
+        };
+        let json = serde_json::to_string_pretty(&file)?;
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("write baseline {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Suppress violations covered by the baseline. Each entry absorbs one violation, so a
+    /// second identical issue added to an already-baselined test is still reported.
+    #[must_use]
+    pub fn apply(&self, violations: &[Violation]) -> BaselineOutcome {
+        let current = entries_for(violations);
+        let mut remaining: HashMap<(&str, &str, &str), Vec<&BaselineEntry>> = HashMap::new();
+        for entry in &self.entries {
+            remaining.entry(entry.key()).or_default().push(entry);
+        }
+        let mut outcome = BaselineOutcome::default();
+        for (v, entry) in violations.iter().zip(&current) {
+            match remaining.get_mut(&entry.key()).and_then(Vec::pop) {
+                Some(_) => outcome.matched += 1,
+                None => outcome.new_violations.push(v.clone()),
+            }
+        }
+        outcome.stale = remaining.into_values().flatten().cloned().collect();
+        outcome.stale.sort();
+        outcome
+    }
+}
+
+/// Summarise how a baseline matched, for `--baseline-report`.
+#[must_use]
+pub fn format_report(baseline: &Baseline, outcome: &BaselineOutcome) -> String {
+    let mut out = format!(
+        "Baseline: {} of {} entries still match existing violations, {} stale\n",
+        outcome.matched,
+        baseline.len(),
+        outcome.stale.len()
+    );
+    for entry in &outcome.stale {
+        let test = entry
+            .test_name
+            .as_ref()
+            .map_or_else(String::new, |t| format!(" (test '{t}')"));
+        out.push_str(&format!(
+            "  stale: {} {}{test}\n",
+            entry.path, entry.rule_id
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_ignores_indentation_and_blank_lines() {
+        assert_eq!(
+            content_hash("def test_a():\n    x = 1\n\n    assert x\n"),
+            content_hash("    def test_a():\n        x = 1\n        assert x   \n")
+        );
+        assert_ne!(
+            content_hash("def test_a():\n    assert 1\n"),
+            content_hash("def test_a():\n    assert 2\n")
+        );
+    }
+
+    #[test]
+    fn test_enclosing_function_is_innermost() {
+        let source = "def test_a():\n    def helper():\n        pass\n    helper()\n";
+        let tree = parse_python(source).unwrap();
+        let inner = enclosing_function(tree.root_node(), 3).unwrap();
+        assert_eq!(inner.start_position().row, 1);
+        let outer = enclosing_function(tree.root_node(), 4).unwrap();
+        assert_eq!(outer.start_position().row, 0);
+    }
+}
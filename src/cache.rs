@@ -0,0 +1,320 @@
+//! On-disk analysis cache (`.pytl-cache/`): per-file parse results and diagnostics, so
+//! unchanged files are neither re-parsed nor re-checked on the next run.
+//!
+//! An entry is reused only when the file content hash and linter version match. Its
+//! diagnostics are reused only when the file's effective rule configuration and the
+//! cross-file fixture context also match. Unreadable, corrupted or outdated entries are
+//! treated as misses and overwritten.
+//!
+//! In `--project-mode` the project index is stored next to the entries, in
+//! `project-index.json`; its per-file entries are reused while the file content is unchanged.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::fnv1a;
+use crate::config::RuleConfig;
+use crate::models::{Fixture, ParsedModule, Violation};
+use crate::project::ProjectIndex;
+
+/// Default cache directory name, created next to the configuration file.
+pub const CACHE_DIR_NAME: &str = ".pytl-cache";
+
+/// File of the project index inside the cache directory.
+const INDEX_FILE_NAME: &str = "project-index.json";
+
+const LINTER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn hash_hex(text: &str) -> String {
+    format!("{:016x}", fnv1a(text))
+}
+
+/// Diagnostics computed for a file under a specific rule configuration and context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedDiagnostics {
+    key: String,
+    violations: Vec<Violation>,
+}
+
+#[derive(Deserialize)]
+struct CacheEntry {
+    version: String,
+    content_hash: String,
+    module: ParsedModule,
+    diagnostics: Option<CachedDiagnostics>,
+}
+
+#[derive(Serialize)]
+struct CacheEntryRef<'a> {
+    version: &'a str,
+    content_hash: &'a str,
+    module: &'a ParsedModule,
+    diagnostics: Option<&'a CachedDiagnostics>,
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    version: String,
+    index: ProjectIndex,
+}
+
+#[derive(Serialize)]
+struct IndexEntryRef<'a> {
+    version: &'a str,
+    index: &'a ProjectIndex,
+}
+
+/// A file's cached state, as loaded for the current content.
+pub struct CachedFile {
+    pub module: ParsedModule,
+    pub diagnostics: Option<CachedDiagnostics>,
+}
+
+/// Cache hit and miss counts for one run. A hit is a file whose diagnostics were served
+/// from the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// Handle to a cache directory. Safe to share between worker threads.
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl Cache {
+    #[must_use]
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Hash of a file's contents, used to detect changes.
+    #[must_use]
+    pub fn content_hash(source: &str) -> String {
+        hash_hex(source)
+    }
+
+    fn entry_path(&self, file: &Path) -> PathBuf {
+        let absolute = std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.dir
+            .join(format!("{}.json", hash_hex(&absolute.to_string_lossy())))
+    }
+
+    /// Load the entry for `file` if it was written by this version for the same content.
+    #[must_use]
+    pub fn load(&self, file: &Path, content_hash: &str) -> Option<CachedFile> {
+        let content = std::fs::read_to_string(self.entry_path(file)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+        (entry.version == LINTER_VERSION && entry.content_hash == content_hash).then_some(
+            CachedFile {
+                module: entry.module,
+                diagnostics: entry.diagnostics,
+            },
+        )
+    }
+
+    /// Cached violations for `key`, counting the lookup as a hit or miss.
+    #[must_use]
+    pub fn diagnostics(
+        &self,
+        cached: Option<&CachedDiagnostics>,
+        key: &str,
+    ) -> Option<Vec<Violation>> {
+        match cached.filter(|d| d.key == key) {
+            Some(d) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(d.violations.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Create the cache directory with its `.gitignore`; false when that fails.
+    fn prepare(&self) -> bool {
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return false;
+        }
+        let gitignore = self.dir.join(".gitignore");
+        if !gitignore.exists() {
+            let _ = std::fs::write(gitignore, "# Created by pytest-linter\n*\n");
+        }
+        true
+    }
+
+    /// Write `json` to `path` through a temporary file, so a concurrent reader never sees a
+    /// partial entry.
+    fn write_atomically(path: &Path, json: &str) {
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        if std::fs::write(&tmp, json).is_ok() && std::fs::rename(&tmp, path).is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+    }
+
+    /// Write the entry for a freshly checked file. Failures are ignored: the cache is an
+    /// optimisation and must never fail a run.
+    pub fn store(
+        &self,
+        module: &ParsedModule,
+        content_hash: &str,
+        key: String,
+        violations: Vec<Violation>,
+    ) {
+        if !self.prepare() {
+            return;
+        }
+        let diagnostics = CachedDiagnostics { key, violations };
+        let entry = CacheEntryRef {
+            version: LINTER_VERSION,
+            content_hash,
+            module,
+            diagnostics: Some(&diagnostics),
+        };
+        let Ok(json) = serde_json::to_string(&entry) else {
+            return;
+        };
+        Self::write_atomically(&self.entry_path(&module.file_path), &json);
+    }
+
+    /// The project index stored by a previous run of this version, if any.
+    #[must_use]
+    pub fn load_index(&self) -> Option<ProjectIndex> {
+        let content = std::fs::read_to_string(self.dir.join(INDEX_FILE_NAME)).ok()?;
+        let entry: IndexEntry = serde_json::from_str(&content).ok()?;
+        (entry.version == LINTER_VERSION).then_some(entry.index)
+    }
+
+    /// Store the project index for the next run. Failures are ignored, as for `store`.
+    pub fn store_index(&self, index: &ProjectIndex) {
+        if !self.prepare() {
+            return;
+        }
+        let entry = IndexEntryRef {
+            version: LINTER_VERSION,
+            index,
+        };
+        if let Ok(json) = serde_json::to_string(&entry) {
+            Self::write_atomically(&self.dir.join(INDEX_FILE_NAME), &json);
+        }
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Hash of the cross-file inputs rules see through `RuleContext`. Any change to fixture
+/// definitions or usage elsewhere invalidates cached diagnostics for every file.
+#[must_use]
+pub fn context_hash(
+    fixture_map: &HashMap<String, Vec<&Fixture>>,
+    used_fixture_names: &HashSet<String>,
+    fixture_locations: &HashMap<String, Vec<PathBuf>>,
+    session_mutable_fixtures: &HashSet<String>,
+) -> String {
+    let mut used: Vec<&String> = used_fixture_names.iter().collect();
+    used.sort();
+    let mut session: Vec<&String> = session_mutable_fixtures.iter().collect();
+    session.sort();
+    let context = (
+        fixture_map.iter().collect::<BTreeMap<_, _>>(),
+        used,
+        fixture_locations.iter().collect::<BTreeMap<_, _>>(),
+        session,
+    );
+    hash_hex(&serde_json::to_string(&context).unwrap_or_default())
+}
+
+/// Key for a file's diagnostics: its effective rule configuration plus the run context.
+#[must_use]
+pub fn diagnostics_key(effective_rules: &HashMap<String, RuleConfig>, context: &str) -> String {
+    let rules: BTreeMap<_, _> = effective_rules.iter().collect();
+    let rules = serde_json::to_string(&rules).unwrap_or_default();
+    hash_hex(&format!("{LINTER_VERSION}\n{rules}\n{context}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(path: &Path, source: &str) -> ParsedModule {
+        crate::parser::PythonParser::new()
+            .unwrap()
+            .parse_source(source, path)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_store_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("test_a.py");
+        let source = "def test_a():\n    assert True\n";
+        std::fs::write(&file, source).unwrap();
+        let cache = Cache::new(dir.path().join(CACHE_DIR_NAME));
+        let hash = Cache::content_hash(source);
+
+        assert!(cache.load(&file, &hash).is_none());
+        cache.store(&module(&file, source), &hash, "k".to_string(), vec![]);
+        let cached = cache.load(&file, &hash).unwrap();
+        assert_eq!(cached.module.test_functions.len(), 1);
+        assert_eq!(
+            cache.diagnostics(cached.diagnostics.as_ref(), "k"),
+            Some(vec![])
+        );
+        assert_eq!(
+            cache.diagnostics(cached.diagnostics.as_ref(), "other"),
+            None
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // A different content hash is a miss
+        assert!(cache.load(&file, "0000").is_none());
+        assert!(dir.path().join(CACHE_DIR_NAME).join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_project_index_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().join(CACHE_DIR_NAME));
+        assert!(cache.load_index().is_none());
+        let mut index = ProjectIndex::default();
+        index.files.insert(
+            dir.path().join("conftest.py"),
+            crate::project::FileIndex {
+                content_hash: "abc".to_string(),
+                helpers: vec!["make_user".to_string()],
+                ..Default::default()
+            },
+        );
+        cache.store_index(&index);
+        assert_eq!(cache.load_index(), Some(index));
+    }
+
+    #[test]
+    fn test_corrupted_entry_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("test_a.py");
+        std::fs::write(&file, "x = 1\n").unwrap();
+        let cache = Cache::new(dir.path().join(CACHE_DIR_NAME));
+        std::fs::create_dir_all(dir.path().join(CACHE_DIR_NAME)).unwrap();
+        std::fs::write(cache.entry_path(&file), "{not json").unwrap();
+        assert!(cache.load(&file, &Cache::content_hash("x = 1\n")).is_none());
+    }
+}
@@ -1,24 +1,50 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
-use anyhow::{Context, Result};
-use serde::Deserialize;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
 
+use crate::diff::ChangedLines;
 use crate::models::Severity;
+use crate::rules::custom::{load_rules_file, CustomRuleDef, CUSTOM_RULE_PREFIX};
+use crate::rules::{resolve_rule_key, unknown_rule_message, OptionKind};
+
+/// Standalone config file names, checked in this order in each directory.
+pub const STANDALONE_CONFIG_FILES: &[&str] = &["pytest-linter.toml", "pytl.toml"];
 
 /// Per-rule configuration options for pytest-linter
-#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
 pub struct RuleConfig {
     /// None means "not explicitly set" (inherit/enable by default).
     /// Some(true) explicitly enables, Some(false) explicitly disables.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
     /// Optional severity override for this rule
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub severity: Option<Severity>,
+    /// Rule-specific options (e.g. `max = 8`), checked against the options the rule declares
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+impl RuleConfig {
+    /// Layer `other` on top of this config; only explicitly set values are taken.
+    fn apply(&mut self, other: &RuleConfig) {
+        if other.enabled.is_some() {
+            self.enabled = other.enabled;
+        }
+        if other.severity.is_some() {
+            self.severity = other.severity;
+        }
+        self.options
+            .extend(other.options.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
 }
 
 /// Per-glob override configuration. Allows enabling/disabling rules or changing
 /// severity for files matching a glob pattern.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OverrideConfig {
     pub path: String,
     pub rules: HashMap<String, RuleConfig>,
@@ -30,26 +56,83 @@ pub struct OverrideConfig {
 /// structure of a standalone pytest-linter.toml file.
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct ToolConfig {
-    /// Per-rule overrides. Key is the rule ID (e.g., "PYTEST-FLK-001")
+    /// Per-rule overrides. Key is the rule ID (e.g., "PYTEST-FLK-001"), name, or slug
     pub rules: Option<HashMap<String, RuleConfig>>,
+    /// Severity per rule (`sleepy-test = "info"`), a shorthand for `rules.<rule>.severity`
+    pub severity: Option<HashMap<String, Severity>>,
+    /// Rules to explicitly enable
+    pub enable: Option<Vec<String>>,
+    /// Rules to disable
+    pub disable: Option<Vec<String>>,
     /// Optional output format override
     pub format: Option<String>,
     /// Optional output path override
     pub output: Option<PathBuf>,
+    /// Baseline file whose entries are suppressed on every run
+    pub baseline: Option<PathBuf>,
     /// Per-glob override configurations
     pub overrides: Option<Vec<OverrideConfig>>,
-    /// Additional directory names to exclude during file discovery
+    /// Globs or directory names to exclude during file discovery (`exclude` is accepted too)
+    #[serde(alias = "exclude")]
     pub excludes: Option<Vec<String>>,
+    /// Globs a discovered file must match to be linted
+    pub include: Option<Vec<String>>,
+    /// File-name patterns of test files (default `test_*.py`, `*_test.py`, `conftest.py`)
+    pub test_patterns: Option<Vec<String>>,
+    /// Project base classes that derive from `unittest.TestCase` in another module
+    pub unittest_base_classes: Option<Vec<String>>,
+    /// Lowest severity that fails the run (default `error`)
+    pub fail_on: Option<Severity>,
+    /// Fail the run when there are more warnings than this
+    pub max_warnings: Option<usize>,
+    /// Rules file of project-specific rules (`custom_rules.toml`)
+    pub custom_rules: Option<PathBuf>,
+}
+
+/// Rule settings from a configuration file in a directory below the root configuration.
+/// They apply to the files under that directory, on top of the root configuration.
+#[derive(Debug, Clone)]
+pub struct DirectoryConfig {
+    /// The configuration file.
+    pub path: PathBuf,
+    /// Rules the file configures, by canonical ID.
+    pub rules: HashMap<String, RuleConfig>,
+    pub overrides: Vec<OverrideConfig>,
+}
+
+/// Directory configurations by directory, read on first use and shared between clones of a
+/// [`Config`]. `None` records a directory without one.
+#[derive(Debug, Clone, Default)]
+#[doc(hidden)]
+pub struct DirectoryConfigs(Arc<Mutex<HashMap<PathBuf, Option<Arc<DirectoryConfig>>>>>);
+
+impl DirectoryConfigs {
+    fn get(&self, dir: &Path) -> Result<Option<Arc<DirectoryConfig>>> {
+        let lock = || self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(known) = lock().get(dir) {
+            return Ok(known.clone());
+        }
+        let loaded = read_directory_config(dir)?.map(Arc::new);
+        lock().insert(dir.to_path_buf(), loaded.clone());
+        Ok(loaded)
+    }
 }
 
 /// Final, merged configuration used by the linter.
 ///
 /// Config priority (highest to lowest):
 /// 1. CLI arguments
-/// 2. pytest-linter.toml (standalone, walks up directories)
-/// 3. pyproject.toml [tool.pytest-linter] (walks up directories)
-/// 4. Built-in defaults
-#[derive(Debug, Clone)]
+/// 2. Configuration files in the directories between the root configuration and a file,
+///    nearest last (rule settings only, see [`DirectoryConfig`])
+/// 3. pytest-linter.toml or pytl.toml (standalone, walks up directories)
+/// 4. pyproject.toml [tool.pytest-linter] (walks up directories)
+/// 5. Built-in defaults
+///
+/// The library functions take a `Config` directly: start from [`Config::default`] or
+/// [`Config::discover`] and set fields. It serializes to JSON or TOML with the field names
+/// below; missing fields take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Resolved rule configurations. Each rule has its own enabled flag (defaults applied)
     pub rules: HashMap<String, RuleConfig>,
@@ -57,12 +140,48 @@ pub struct Config {
     pub format: Option<String>,
     /// Optional global output path override
     pub output: Option<PathBuf>,
+    /// Baseline file of known violations to suppress, if configured
+    pub baseline: Option<PathBuf>,
     /// Per-glob override configurations for per-directory rule scoping
     pub overrides: Vec<OverrideConfig>,
     /// Directory containing the config file, used for resolving override glob patterns
     pub config_dir: Option<PathBuf>,
-    /// Directory names to exclude during file discovery (in addition to built-in defaults)
+    /// Files the root configuration was read from, lowest priority first
+    pub config_files: Vec<PathBuf>,
+    /// Configuration files of directories below `config_dir`, read on first use
+    #[serde(skip)]
+    #[doc(hidden)]
+    pub directory_configs: DirectoryConfigs,
+    /// Globs or directory names to exclude during file discovery (in addition to built-in defaults)
     pub excludes: Vec<String>,
+    /// Globs a discovered file must match; empty includes every test file
+    pub include: Vec<String>,
+    /// File-name patterns of test files; empty uses the built-in defaults
+    pub test_patterns: Vec<String>,
+    /// Extra base classes (dotted or bare names) that make a class a `unittest.TestCase`
+    pub unittest_base_classes: Vec<String>,
+    /// Worker threads for parsing and checking; `None` uses one per core
+    pub jobs: Option<usize>,
+    /// Directory of the analysis cache; `None` disables caching
+    pub cache_dir: Option<PathBuf>,
+    /// Print a statistics summary to stderr after the run
+    pub statistics: bool,
+    /// Print the N files with the most diagnostics to stderr after the run
+    pub top_files: Option<usize>,
+    /// Lowest severity that fails the run; `None` means `Error`
+    pub fail_on: Option<Severity>,
+    /// Fail the run when there are more warnings than this
+    pub max_warnings: Option<usize>,
+    /// Report syntax errors without letting them fail the run (`--ignore-syntax-errors`)
+    pub ignore_syntax_errors: bool,
+    /// Only report diagnostics touching these lines (diff-aware mode); analysis still
+    /// covers every file
+    pub changed_lines: Option<ChangedLines>,
+    /// Index the whole project before checking, so rules see fixtures and tests of files
+    /// outside the linted paths (`--project-mode`)
+    pub project_mode: bool,
+    /// Project-specific rules, from the `custom_rules` file of the configuration
+    pub custom_rules: Vec<CustomRuleDef>,
 }
 
 impl Default for Config {
@@ -74,6 +193,7 @@ impl Default for Config {
                 RuleConfig {
                     enabled: None,
                     severity: None,
+                    options: toml::Table::new(),
                 },
             );
         }
@@ -81,9 +201,25 @@ impl Default for Config {
             rules,
             format: None,
             output: None,
+            baseline: None,
             overrides: vec![],
             config_dir: None,
+            config_files: vec![],
+            directory_configs: DirectoryConfigs::default(),
             excludes: vec![],
+            include: vec![],
+            test_patterns: vec![],
+            unittest_base_classes: vec![],
+            jobs: None,
+            cache_dir: None,
+            statistics: false,
+            top_files: None,
+            fail_on: None,
+            max_warnings: None,
+            ignore_syntax_errors: false,
+            changed_lines: None,
+            project_mode: false,
+            custom_rules: vec![],
         }
     }
 }
@@ -152,24 +288,48 @@ impl Config {
     }
 
     /// Build a Config from a parsed ToolConfig, resolving paths relative to config_dir.
-    fn build_from_tool_config(tool_config: ToolConfig, config_dir: &Path) -> Self {
+    ///
+    /// Rule references are resolved to canonical IDs and rule options are type-checked;
+    /// errors name the offending key, prefixed with `key_prefix` (e.g. `tool.pytest-linter.`).
+    /// In the `root` configuration a non-empty `enable` list is an allow-list: every rule it
+    /// does not name and whose table does not set `enabled` is disabled.
+    fn build_from_tool_config(
+        tool_config: ToolConfig,
+        config_dir: &Path,
+        key_prefix: &str,
+        root: bool,
+    ) -> Result<Self> {
         let mut cfg = Config::default();
         if let Some(rules) = tool_config.rules {
-            for (id, override_rc) in rules.into_iter() {
+            let rules = normalize_rule_table(rules, &format!("{key_prefix}rules"))?;
+            for (id, override_rc) in rules {
                 cfg.rules
                     .entry(id)
-                    .and_modify(|existing| {
-                        if let Some(e) = override_rc.enabled {
-                            existing.enabled = Some(e);
-                        }
-                        if let Some(sev) = override_rc.severity {
-                            existing.severity = Some(sev);
-                        }
-                    })
-                    .or_insert(RuleConfig {
-                        enabled: override_rc.enabled,
-                        severity: override_rc.severity,
-                    });
+                    .and_modify(|existing| existing.apply(&override_rc))
+                    .or_insert(override_rc);
+            }
+        }
+        if let Some(severities) = tool_config.severity {
+            let path = format!("{key_prefix}severity");
+            for (key, severity) in severities {
+                let id = resolve_rule_key(&key)
+                    .ok_or_else(|| anyhow!("{} at `{path}.{key}`", unknown_rule_message(&key)))?;
+                let rc = cfg.rules.entry(id.clone()).or_default();
+                if rc.severity.is_some() {
+                    bail!("the severity of {id} is set in both `{key_prefix}rules` and `{path}`");
+                }
+                rc.severity = Some(severity);
+            }
+        }
+        let enabled = resolve_rule_list(tool_config.enable, &format!("{key_prefix}enable"))?;
+        let disabled = resolve_rule_list(tool_config.disable, &format!("{key_prefix}disable"))?;
+        if let Some(id) = enabled.iter().find(|id| disabled.contains(id)) {
+            bail!("rule `{id}` is listed in both `{key_prefix}enable` and `{key_prefix}disable`");
+        }
+        let allow_list = root && !enabled.is_empty();
+        for (ids, value) in [(enabled, true), (disabled, false)] {
+            for id in ids {
+                cfg.rules.entry(id).or_default().enabled = Some(value);
             }
         }
         if tool_config.format.is_some() {
@@ -182,118 +342,112 @@ impl Config {
                 cfg.output = Some(config_dir.join(output_path));
             }
         }
+        cfg.baseline = tool_config.baseline.map(|path| config_dir.join(path));
+        cfg.fail_on = tool_config.fail_on;
+        cfg.max_warnings = tool_config.max_warnings;
+        if let Some(path) = tool_config.custom_rules {
+            cfg.custom_rules = load_rules_file(&config_dir.join(path))?;
+        }
+        if allow_list {
+            let ids: Vec<String> = crate::rules::all_rules()
+                .iter()
+                .map(|rule| rule.id().to_string())
+                .chain(cfg.custom_rules.iter().map(|rule| rule.id.clone()))
+                .collect();
+            for id in ids {
+                let rc = cfg.rules.entry(id).or_default();
+                if rc.enabled.is_none() {
+                    rc.enabled = Some(false);
+                }
+            }
+        }
         cfg.overrides = tool_config.overrides.unwrap_or_default();
-        for override_cfg in &mut cfg.overrides {
+        for (idx, override_cfg) in cfg.overrides.iter_mut().enumerate() {
+            override_cfg.rules = normalize_rule_table(
+                std::mem::take(&mut override_cfg.rules),
+                &format!("{key_prefix}overrides[{idx}].rules"),
+            )?;
             override_cfg.base_dir = Some(config_dir.to_path_buf());
         }
+        cfg.excludes = tool_config.excludes.unwrap_or_default();
+        cfg.include = tool_config.include.unwrap_or_default();
+        cfg.test_patterns = tool_config.test_patterns.unwrap_or_default();
+        cfg.unittest_base_classes = tool_config.unittest_base_classes.unwrap_or_default();
         cfg.config_dir = Some(config_dir.to_path_buf());
-        cfg
+        Ok(cfg)
     }
 
     /// Load configuration by walking up from `dir` to find pyproject.toml and the [tool.pytest-linter] section
     pub fn from_pyproject(dir: &Path) -> Result<Option<Self>> {
-        let mut current = dir;
-        loop {
-            let candidate = current.join("pyproject.toml");
-            if candidate.exists() {
-                let contents = std::fs::read_to_string(&candidate)
-                    .with_context(|| format!("read {}", candidate.display()))?;
-                let full: toml::Value = toml::from_str(&contents)
-                    .with_context(|| format!("parse TOML in {}", candidate.display()))?;
-
-                let tool_table = full
-                    .get("tool")
-                    .and_then(|t| t.as_table())
-                    .and_then(|t| t.get("pytest-linter"))
-                    .cloned()
-                    .unwrap_or_else(|| toml::Value::Table(toml::map::Map::new()));
-
-                let table = match tool_table.as_table() {
-                    Some(t) => t,
-                    None => {
-                        match current.parent() {
-                            Some(parent) => current = parent,
-                            None => break,
-                        }
-                        continue;
-                    }
-                };
-                if table.is_empty() {
-                    match current.parent() {
-                        Some(parent) => current = parent,
-                        None => break,
-                    }
-                    continue;
-                }
-
-                let tool_config: ToolConfig = tool_table.try_into().with_context(|| {
-                    format!(
-                        "deserialize tool.pytest-linter from {}",
-                        candidate.display()
-                    )
-                })?;
-
-                let config_dir = candidate.parent().unwrap_or(Path::new("."));
-                let cfg = Self::build_from_tool_config(tool_config, config_dir);
-                return Ok(Some(cfg));
-            }
-
-            match current.parent() {
-                Some(parent) => current = parent,
-                None => break,
-            }
+        for current in dir.ancestors() {
+            let Some((path, tool_config)) = read_pyproject(current)? else {
+                continue;
+            };
+            let mut cfg =
+                Self::build_from_tool_config(tool_config, current, "tool.pytest-linter.", true)
+                    .with_context(|| format!("invalid configuration in {}", path.display()))?;
+            cfg.config_files = vec![path];
+            return Ok(Some(cfg));
         }
         Ok(None)
     }
 
-    /// Load configuration by walking up from `dir` to find a standalone pytest-linter.toml file.
+    /// Load configuration by walking up from `dir` to find a standalone pytest-linter.toml
+    /// (or pytl.toml) file.
     /// The standalone file uses a flat structure (no `[tool]` prefix).
     pub fn from_standalone(dir: &Path) -> Result<Option<Self>> {
-        let mut current = dir;
-        loop {
-            let candidate = current.join("pytest-linter.toml");
-            if candidate.exists() {
-                let contents = std::fs::read_to_string(&candidate)
-                    .with_context(|| format!("read {}", candidate.display()))?;
-
-                if contents.trim().is_empty() {
-                    match current.parent() {
-                        Some(parent) => current = parent,
-                        None => break,
-                    }
-                    continue;
-                }
-
-                let tool_config: ToolConfig = toml::from_str(&contents)
-                    .with_context(|| format!("parse {}", candidate.display()))?;
-
-                let config_dir = candidate.parent().unwrap_or(Path::new("."));
-                let cfg = Self::build_from_tool_config(tool_config, config_dir);
-                return Ok(Some(cfg));
-            }
-
-            match current.parent() {
-                Some(parent) => current = parent,
-                None => break,
-            }
+        for current in dir.ancestors() {
+            let Some((path, tool_config)) = read_standalone(current)? else {
+                continue;
+            };
+            let mut cfg = Self::build_from_tool_config(tool_config, current, "", true)
+                .with_context(|| format!("invalid configuration in {}", path.display()))?;
+            cfg.config_files = vec![path];
+            return Ok(Some(cfg));
         }
         Ok(None)
     }
 
-    /// Discover configuration by searching for both pytest-linter.toml and
-    /// pyproject.toml, walking up from `start_dir`.
+    /// Discover the root configuration: the outermost directory, from `start_dir` up to the
+    /// repository root (the first directory holding `.git`), with a pytest-linter.toml,
+    /// pytl.toml or pyproject.toml [tool.pytest-linter]. Configuration files below it are
+    /// directory configurations, applied per file (see [`Config::rule_layers`]).
     ///
-    /// Priority: pytest-linter.toml > pyproject.toml [tool.pytest-linter] > defaults.
+    /// Priority: pytest-linter.toml / pytl.toml > pyproject.toml [tool.pytest-linter] > defaults.
     /// CLI arguments are applied separately via `merge_cli`.
     pub fn discover(start_dir: &Path) -> Result<Self> {
         let mut config = Config::default();
 
-        if let Some(pyproject_cfg) = Self::from_pyproject(start_dir)? {
-            config = config.merge(pyproject_cfg);
+        // A relative start stops at the working directory; continue above it
+        let above_cwd = if start_dir.is_relative() {
+            absolute_path(Path::new("")).and_then(|cwd| cwd.parent().map(Path::to_path_buf))
+        } else {
+            None
+        };
+        let mut root = None;
+        for dir in start_dir
+            .ancestors()
+            .chain(above_cwd.iter().flat_map(|dir| dir.ancestors()))
+        {
+            let pyproject = read_pyproject(dir)?;
+            let standalone = read_standalone(dir)?;
+            if pyproject.is_some() || standalone.is_some() {
+                root = Some((dir, pyproject, standalone));
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
         }
-
-        if let Some(standalone_cfg) = Self::from_standalone(start_dir)? {
-            config = config.merge(standalone_cfg);
+        if let Some((dir, pyproject, standalone)) = root {
+            for (found, key_prefix) in [(pyproject, "tool.pytest-linter."), (standalone, "")] {
+                let Some((path, tool_config)) = found else {
+                    continue;
+                };
+                let mut cfg = Self::build_from_tool_config(tool_config, dir, key_prefix, true)
+                    .with_context(|| format!("invalid configuration in {}", path.display()))?;
+                cfg.config_files = vec![path];
+                config = config.merge(cfg);
+            }
         }
 
         if config.config_dir.is_none() {
@@ -309,14 +463,7 @@ impl Config {
         for (id, rc) in other.rules {
             self.rules
                 .entry(id)
-                .and_modify(|existing| {
-                    if rc.enabled.is_some() {
-                        existing.enabled = rc.enabled;
-                    }
-                    if rc.severity.is_some() {
-                        existing.severity = rc.severity;
-                    }
-                })
+                .and_modify(|existing| existing.apply(&rc))
                 .or_insert(rc);
         }
 
@@ -326,61 +473,159 @@ impl Config {
         if other.output.is_some() {
             self.output = other.output;
         }
+        if other.baseline.is_some() {
+            self.baseline = other.baseline;
+        }
+        if other.fail_on.is_some() {
+            self.fail_on = other.fail_on;
+        }
+        if other.max_warnings.is_some() {
+            self.max_warnings = other.max_warnings;
+        }
 
         self.overrides.extend(other.overrides);
+        self.custom_rules.extend(other.custom_rules);
 
         if other.config_dir.is_some() {
             self.config_dir = other.config_dir;
         }
+        self.config_files.extend(other.config_files);
 
         self.excludes.extend(other.excludes);
+        self.include.extend(other.include);
+        if !other.test_patterns.is_empty() {
+            self.test_patterns = other.test_patterns;
+        }
+        self.unittest_base_classes
+            .extend(other.unittest_base_classes);
 
         self
     }
 
     /// Compute the effective rule configuration for a specific file path,
-    /// applying any matching override entries on top of the global config.
+    /// applying any matching override entries and directory configurations on top of the
+    /// global config.
     pub fn effective_rules_for_file(
         &self,
         file_path: &Path,
     ) -> Result<HashMap<String, RuleConfig>> {
-        let mut effective = self.rules.clone();
-
-        let relative_path = self
-            .config_dir
-            .as_ref()
-            .and_then(|dir| file_path.strip_prefix(dir).ok())
-            .unwrap_or(file_path);
-
-        for override_cfg in &self.overrides {
-            let override_base = override_cfg.base_dir.as_ref().or(self.config_dir.as_ref());
-            let relative_path = override_base
-                .and_then(|dir| file_path.strip_prefix(dir).ok())
-                .unwrap_or(relative_path);
-            let pattern = glob::Pattern::new(&override_cfg.path).with_context(|| {
-                format!(
-                    "invalid glob pattern '{}' in override configuration",
-                    override_cfg.path
-                )
-            })?;
-            if pattern.matches_path(relative_path) {
-                for (rule_id, rule_config) in &override_cfg.rules {
-                    effective
-                        .entry(rule_id.clone())
-                        .and_modify(|existing| {
-                            if rule_config.enabled.is_some() {
-                                existing.enabled = rule_config.enabled;
-                            }
-                            if rule_config.severity.is_some() {
-                                existing.severity = rule_config.severity;
-                            }
-                        })
-                        .or_insert((*rule_config).clone());
-                }
+        let mut layers = self.rule_layers(file_path)?.into_iter();
+        let mut effective = layers.next().map(|layer| layer.rules).unwrap_or_default();
+        for layer in layers {
+            apply_rules(&mut effective, &layer.rules);
+        }
+        Ok(effective)
+    }
+
+    /// The rule settings that apply to a file, lowest priority first: the root configuration
+    /// and its matching overrides, then the configuration of each directory between the root
+    /// and the file, each followed by its own matching overrides.
+    pub fn rule_layers(&self, file_path: &Path) -> Result<Vec<RuleLayer>> {
+        let root = self
+            .config_files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        let mut layers = vec![RuleLayer {
+            source: if root.is_empty() {
+                "defaults".to_string()
+            } else {
+                root.join(" + ")
+            },
+            rules: self.rules.clone(),
+        }];
+        push_matching_overrides(
+            &mut layers,
+            &self.overrides,
+            self.config_dir.as_deref(),
+            file_path,
+        )?;
+        for directory in self.directory_layers(file_path)? {
+            layers.push(RuleLayer {
+                source: directory.path.display().to_string(),
+                rules: directory.rules.clone(),
+            });
+            push_matching_overrides(
+                &mut layers,
+                &directory.overrides,
+                directory.path.parent(),
+                file_path,
+            )?;
+        }
+        Ok(layers)
+    }
+
+    /// Configurations of the directories from below the root configuration down to the
+    /// file's directory, outermost first.
+    fn directory_layers(&self, file_path: &Path) -> Result<Vec<Arc<DirectoryConfig>>> {
+        let Some(root) = self.config_dir.as_deref() else {
+            return Ok(vec![]);
+        };
+        let (Some(absolute_root), Some(dir)) = (
+            absolute_path(root),
+            file_path.parent().and_then(absolute_path),
+        ) else {
+            return Ok(vec![]);
+        };
+        let Ok(below) = dir.strip_prefix(&absolute_root) else {
+            return Ok(vec![]);
+        };
+        // Paths stay relative to the root as given, so they read like the linted paths
+        let mut layers = Vec::new();
+        let mut current = root.to_path_buf();
+        for component in below.components() {
+            current.push(component);
+            if let Some(directory) = self.directory_configs.get(&current)? {
+                layers.push(directory);
             }
         }
+        Ok(layers)
+    }
 
-        Ok(effective)
+    /// The effective configuration of a file with where each rule setting comes from, as
+    /// printed by `pytest-linter show-config`.
+    pub fn resolve_for_file(&self, file_path: &Path) -> Result<FileConfig> {
+        let layers = self.rule_layers(file_path)?;
+        let resolve = |id: &str, slug: String, default_severity: Severity| {
+            let mut effective = RuleConfig::default();
+            let mut set_by = Vec::new();
+            for (idx, layer) in layers.iter().enumerate() {
+                if let Some(rc) = layer
+                    .rules
+                    .get(id)
+                    .filter(|rc| **rc != RuleConfig::default())
+                {
+                    effective.apply(rc);
+                    set_by.push(idx);
+                }
+            }
+            ResolvedRule {
+                slug,
+                enabled: effective.enabled.unwrap_or(true),
+                severity: effective.severity.unwrap_or(default_severity),
+                options: effective.options,
+                set_by,
+            }
+        };
+        let mut rules = BTreeMap::new();
+        for rule in crate::rules::all_rules() {
+            let slug = crate::rules::rule_slug(rule.name());
+            rules.insert(
+                rule.id().to_string(),
+                resolve(rule.id(), slug, rule.severity()),
+            );
+        }
+        for rule in &self.custom_rules {
+            let slug = rule.id.trim_start_matches(CUSTOM_RULE_PREFIX).to_string();
+            rules.insert(rule.id.clone(), resolve(&rule.id, slug, rule.severity));
+        }
+        Ok(FileConfig {
+            file: file_path.to_path_buf(),
+            layers: layers.into_iter().map(|layer| layer.source).collect(),
+            fail_on: self.fail_on.unwrap_or(Severity::Error),
+            max_warnings: self.max_warnings,
+            rules,
+        })
     }
 
     /// Apply CLI overrides on top of existing config. If value is None, keep current value
@@ -389,6 +634,7 @@ impl Config {
         format: Option<String>,
         output: Option<PathBuf>,
         excludes: Vec<String>,
+        include: Vec<String>,
     ) -> Self {
         if format.is_some() {
             self.format = format;
@@ -397,10 +643,305 @@ impl Config {
             self.output = output;
         }
         self.excludes.extend(excludes);
+        self.include.extend(include);
         self
     }
 }
 
+/// One layer of a file's rule settings; see [`Config::rule_layers`].
+#[derive(Debug, Clone)]
+pub struct RuleLayer {
+    /// Where the settings come from: a configuration file or one of its overrides.
+    pub source: String,
+    pub rules: HashMap<String, RuleConfig>,
+}
+
+/// A file's effective configuration, from [`Config::resolve_for_file`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileConfig {
+    pub file: PathBuf,
+    /// Sources of the rule settings, lowest priority first.
+    pub layers: Vec<String>,
+    pub fail_on: Severity,
+    pub max_warnings: Option<usize>,
+    /// Every rule by ID.
+    pub rules: BTreeMap<String, ResolvedRule>,
+}
+
+/// A rule's effective settings for one file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedRule {
+    pub slug: String,
+    pub enabled: bool,
+    pub severity: Severity,
+    pub options: toml::Table,
+    /// Indexes into [`FileConfig::layers`] of the layers that configure the rule.
+    pub set_by: Vec<usize>,
+}
+
+/// `pytest-linter show-config` output: the sources of the rule settings, then every rule with
+/// its state, severity, options and the sources that configure it.
+#[must_use]
+pub fn format_file_config(config: &FileConfig) -> String {
+    let mut out = format!("Configuration of {}\n\n", config.file.display());
+    out.push_str("Sources, lowest priority first:\n");
+    for (idx, source) in config.layers.iter().enumerate() {
+        out.push_str(&format!("  [{idx}] {source}\n"));
+    }
+    out.push_str(&format!(
+        "\nfail_on = {}, max_warnings = {}\n\n",
+        config.fail_on.to_string().to_lowercase(),
+        config
+            .max_warnings
+            .map_or_else(|| "none".to_string(), |n| n.to_string())
+    ));
+    let slug_width = config
+        .rules
+        .values()
+        .map(|r| r.slug.len())
+        .max()
+        .unwrap_or(0);
+    for (id, rule) in &config.rules {
+        let set_by = if rule.set_by.is_empty() {
+            String::new()
+        } else {
+            let indexes: Vec<String> = rule.set_by.iter().map(ToString::to_string).collect();
+            format!("[{}]", indexes.join(", "))
+        };
+        let options: Vec<String> = rule
+            .options
+            .iter()
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect();
+        let line = format!(
+            "{id:<17} {:<slug_width$}  {:<8} {:<4} {set_by:<8} {}",
+            rule.slug,
+            rule.severity.to_string().to_lowercase(),
+            if rule.enabled { "on" } else { "off" },
+            options.join(", ")
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn apply_rules(effective: &mut HashMap<String, RuleConfig>, rules: &HashMap<String, RuleConfig>) {
+    for (rule_id, rule_config) in rules {
+        effective
+            .entry(rule_id.clone())
+            .and_modify(|existing| existing.apply(rule_config))
+            .or_insert_with(|| rule_config.clone());
+    }
+}
+
+/// Append the overrides whose glob matches the file, resolved against their configuration's
+/// directory (or `default_base`).
+fn push_matching_overrides(
+    layers: &mut Vec<RuleLayer>,
+    overrides: &[OverrideConfig],
+    default_base: Option<&Path>,
+    file_path: &Path,
+) -> Result<()> {
+    // Compare absolute paths, so that relative file paths and configuration directories
+    // found above the working directory still line up
+    let file = absolute_path(file_path);
+    let relative_to = |dir: &Path| -> Option<PathBuf> {
+        let relative = file.as_deref()?.strip_prefix(absolute_path(dir)?).ok()?;
+        Some(relative.to_path_buf())
+    };
+    let fallback = default_base
+        .and_then(relative_to)
+        .unwrap_or_else(|| file_path.to_path_buf());
+    for (idx, override_cfg) in overrides.iter().enumerate() {
+        let base = override_cfg.base_dir.as_deref().or(default_base);
+        let relative_path = base
+            .and_then(relative_to)
+            .unwrap_or_else(|| fallback.clone());
+        let pattern = glob::Pattern::new(&override_cfg.path).with_context(|| {
+            format!(
+                "invalid glob pattern '{}' in override configuration",
+                override_cfg.path
+            )
+        })?;
+        if pattern.matches_path(&relative_path) {
+            let dir = base
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map_or_else(String::new, |dir| format!(" in {}", dir.display()));
+            layers.push(RuleLayer {
+                source: format!("overrides[{idx}] `{}`{dir}", override_cfg.path),
+                rules: override_cfg.rules.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// `path` made absolute against the working directory, without touching the filesystem.
+/// The empty path (the parent of a bare file name) is the working directory.
+fn absolute_path(path: &Path) -> Option<PathBuf> {
+    if path.as_os_str().is_empty() {
+        return std::path::absolute(".").ok();
+    }
+    std::path::absolute(path).ok()
+}
+
+/// The `[tool.pytest-linter]` section of `dir/pyproject.toml`, if the file has a non-empty one.
+fn read_pyproject(dir: &Path) -> Result<Option<(PathBuf, ToolConfig)>> {
+    let candidate = dir.join("pyproject.toml");
+    if !candidate.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&candidate)
+        .with_context(|| format!("read {}", candidate.display()))?;
+    let full: toml::Value = toml::from_str(&contents)
+        .with_context(|| format!("parse TOML in {}", candidate.display()))?;
+    let Some(section) = full
+        .get("tool")
+        .and_then(|t| t.as_table())
+        .and_then(|t| t.get("pytest-linter"))
+        .filter(|section| section.as_table().is_some_and(|t| !t.is_empty()))
+        .cloned()
+    else {
+        return Ok(None);
+    };
+    let tool_config: ToolConfig = section.try_into().with_context(|| {
+        format!(
+            "deserialize tool.pytest-linter from {}",
+            candidate.display()
+        )
+    })?;
+    Ok(Some((candidate, tool_config)))
+}
+
+/// The first non-empty standalone configuration file in `dir`.
+fn read_standalone(dir: &Path) -> Result<Option<(PathBuf, ToolConfig)>> {
+    for name in STANDALONE_CONFIG_FILES {
+        let candidate = dir.join(name);
+        if !candidate.exists() {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&candidate)
+            .with_context(|| format!("read {}", candidate.display()))?;
+        if contents.trim().is_empty() {
+            continue;
+        }
+        let tool_config: ToolConfig =
+            toml::from_str(&contents).with_context(|| format!("parse {}", candidate.display()))?;
+        return Ok(Some((candidate, tool_config)));
+    }
+    Ok(None)
+}
+
+/// The configuration of a directory below the root: a standalone file, or else a
+/// `pyproject.toml` section. Only rule settings are allowed there.
+fn read_directory_config(dir: &Path) -> Result<Option<DirectoryConfig>> {
+    let (path, tool_config, key_prefix) = match read_standalone(dir)? {
+        Some((path, tool_config)) => (path, tool_config, ""),
+        None => match read_pyproject(dir)? {
+            Some((path, tool_config)) => (path, tool_config, "tool.pytest-linter."),
+            None => return Ok(None),
+        },
+    };
+    let invalid = || format!("invalid configuration in {}", path.display());
+    let run_settings = [
+        ("format", tool_config.format.is_some()),
+        ("output", tool_config.output.is_some()),
+        ("baseline", tool_config.baseline.is_some()),
+        ("exclude", tool_config.excludes.is_some()),
+        ("include", tool_config.include.is_some()),
+        ("test_patterns", tool_config.test_patterns.is_some()),
+        (
+            "unittest_base_classes",
+            tool_config.unittest_base_classes.is_some(),
+        ),
+        ("fail_on", tool_config.fail_on.is_some()),
+        ("max_warnings", tool_config.max_warnings.is_some()),
+        ("custom_rules", tool_config.custom_rules.is_some()),
+    ];
+    if let Some((key, _)) = run_settings.iter().find(|(_, set)| *set) {
+        return Err(anyhow!(
+            "`{key_prefix}{key}` applies to the whole run and can only be set in the root configuration"
+        ))
+        .with_context(invalid);
+    }
+    let cfg = Config::build_from_tool_config(tool_config, dir, key_prefix, false)
+        .with_context(invalid)?;
+    Ok(Some(DirectoryConfig {
+        path,
+        rules: cfg
+            .rules
+            .into_iter()
+            .filter(|(_, rc)| *rc != RuleConfig::default())
+            .collect(),
+        overrides: cfg.overrides,
+    }))
+}
+
+/// Resolve rule keys (ID, name, or slug) to canonical IDs and type-check their options.
+fn normalize_rule_table(
+    rules: HashMap<String, RuleConfig>,
+    path: &str,
+) -> Result<HashMap<String, RuleConfig>> {
+    let mut out: HashMap<String, RuleConfig> = HashMap::new();
+    for (key, rc) in rules {
+        let id = resolve_rule_key(&key)
+            .ok_or_else(|| anyhow!("{} at `{path}.{key}`", unknown_rule_message(&key)))?;
+        validate_rule_options(&id, &rc.options, &format!("{path}.{key}"))?;
+        out.entry(id)
+            .and_modify(|existing| existing.apply(&rc))
+            .or_insert(rc);
+    }
+    Ok(out)
+}
+
+/// Check every configured option against the options declared by the rule.
+fn validate_rule_options(rule_id: &str, options: &toml::Table, path: &str) -> Result<()> {
+    let rules = crate::rules::all_rules();
+    let declared = rules
+        .iter()
+        .find(|r| r.id() == rule_id)
+        .map(|r| r.options())
+        .unwrap_or_default();
+    for (name, value) in options {
+        let Some(option) = declared.iter().find(|o| o.name == name) else {
+            let accepted: Vec<&str> = declared.iter().map(|o| o.name).collect();
+            if accepted.is_empty() {
+                bail!("unknown option `{path}.{name}`: {rule_id} takes no options");
+            }
+            bail!(
+                "unknown option `{path}.{name}`: {rule_id} accepts {}",
+                accepted.join(", ")
+            );
+        };
+        if let (OptionKind::Regex, Some(pattern)) = (option.kind, value.as_str()) {
+            if let Err(e) = regex::Regex::new(pattern) {
+                bail!("`{path}.{name}` is not a valid regular expression: {e}");
+            }
+        }
+        if !option.kind.accepts(value) {
+            bail!(
+                "`{path}.{name}` must be {}, got {}",
+                option.kind,
+                value.type_str()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Resolve an `enable`/`disable` list to canonical rule IDs.
+fn resolve_rule_list(keys: Option<Vec<String>>, path: &str) -> Result<Vec<String>> {
+    keys.unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(idx, key)| {
+            resolve_rule_key(key)
+                .ok_or_else(|| anyhow!("{} at `{path}[{idx}]`", unknown_rule_message(key)))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,11 +969,12 @@ mod tests {
             RuleConfig {
                 enabled: Some(false),
                 severity: None,
+                ..Default::default()
             },
         );
-        assert_eq!(cfg.is_rule_enabled("UNKNOWN-001"), false);
-        assert_eq!(cfg.is_rule_enabled("PYTEST-FLK-001"), true);
-        assert_eq!(cfg.is_rule_enabled("SOME-NONEXISTENT"), true);
+        assert!(!cfg.is_rule_enabled("UNKNOWN-001"));
+        assert!(cfg.is_rule_enabled("PYTEST-FLK-001"));
+        assert!(cfg.is_rule_enabled("SOME-NONEXISTENT"));
     }
 
     #[test]
@@ -443,6 +985,7 @@ mod tests {
             RuleConfig {
                 enabled: Some(true),
                 severity: Some(Severity::Info),
+                ..Default::default()
             },
         );
         assert_eq!(
@@ -497,6 +1040,7 @@ severity = "info"
             Some("json".to_string()),
             Some(PathBuf::from("out.log")),
             vec![],
+            vec![],
         );
         assert_eq!(merged.format, Some("json".to_string()));
         assert_eq!(merged.output, Some(PathBuf::from("out.log")));
@@ -509,6 +1053,28 @@ severity = "info"
         assert!(res.is_none());
     }
 
+    #[test]
+    fn test_from_standalone_parses_discovery_and_exit_policy_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_content = r#"
+exclude = ["slow"]
+include = ["unit/**"]
+test_patterns = ["check_*.py"]
+unittest_base_classes = ["tests.base.ApiTestCase"]
+fail_on = "warning"
+max_warnings = 50
+"#;
+        std::fs::write(dir.path().join("pytl.toml"), toml_content).unwrap();
+
+        let cfg = Config::default().merge(Config::from_standalone(dir.path()).unwrap().unwrap());
+        assert_eq!(cfg.excludes, ["slow"]);
+        assert_eq!(cfg.include, ["unit/**"]);
+        assert_eq!(cfg.test_patterns, ["check_*.py"]);
+        assert_eq!(cfg.unittest_base_classes, ["tests.base.ApiTestCase"]);
+        assert_eq!(cfg.fail_on, Some(Severity::Warning));
+        assert_eq!(cfg.max_warnings, Some(50));
+    }
+
     #[test]
     fn test_from_standalone_parses_flat_toml() {
         let dir = tempfile::tempdir().unwrap();
@@ -566,7 +1132,7 @@ enabled = true
 
         let cfg = Config::discover(dir.path()).unwrap();
         assert_eq!(cfg.format, Some("json".to_string()));
-        assert_eq!(cfg.is_rule_enabled("PYTEST-FLK-001"), true);
+        assert!(cfg.is_rule_enabled("PYTEST-FLK-001"));
     }
 
     #[test]
@@ -602,12 +1168,13 @@ format = "terminal"
             RuleConfig {
                 enabled: Some(false),
                 severity: Some(Severity::Info),
+                ..Default::default()
             },
         );
         higher.format = Some("json".to_string());
 
         let merged = base.merge(higher);
-        assert_eq!(merged.is_rule_enabled("PYTEST-FLK-001"), false);
+        assert!(!merged.is_rule_enabled("PYTEST-FLK-001"));
         assert_eq!(merged.format, Some("json".to_string()));
     }
 
@@ -643,13 +1210,13 @@ format = "json"
             RuleConfig {
                 enabled: Some(false),
                 severity: None,
+                ..Default::default()
             },
         );
         let higher = Config::default();
         let merged = base.merge(higher);
-        assert_eq!(
-            merged.is_rule_enabled("PYTEST-FLK-001"),
-            false,
+        assert!(
+            !merged.is_rule_enabled("PYTEST-FLK-001"),
             "default (None) should not override explicit Some(false)"
         );
     }
@@ -749,4 +1316,267 @@ rules = { PYTEST-FLK-001 = { enabled = false } }
         let cfg = Config::from_standalone(&subdir).unwrap().unwrap();
         assert_eq!(cfg.format, Some("json".to_string()));
     }
+
+    #[test]
+    fn test_pytl_toml_is_discovered() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("pytl.toml"), r#"format = "sarif""#).unwrap();
+        let cfg = Config::discover(dir.path()).unwrap();
+        assert_eq!(cfg.format, Some("sarif".to_string()));
+    }
+
+    #[test]
+    fn test_enable_and_disable_lists_accept_slugs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pytl.toml"),
+            r#"
+enable = ["PYTEST-MNT-004"]
+disable = ["time-sleep", "TestNameLengthRule"]
+"#,
+        )
+        .unwrap();
+        let cfg = Config::from_standalone(dir.path()).unwrap().unwrap();
+        assert_eq!(cfg.rules["PYTEST-MNT-004"].enabled, Some(true));
+        assert!(!cfg.is_rule_enabled("PYTEST-FLK-001"));
+        assert!(!cfg.is_rule_enabled("PYTEST-MNT-017"));
+    }
+
+    #[test]
+    fn test_root_enable_list_is_an_allow_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let e2e = dir.path().join("tests").join("e2e");
+        std::fs::create_dir_all(&e2e).unwrap();
+        std::fs::write(
+            dir.path().join("pytl.toml"),
+            "enable = [\"no-assertion\"]\n\n[rules.time-sleep]\nenabled = true\n\n\
+             [rules.PYTEST-MNT-017]\nmax_length = 40\n",
+        )
+        .unwrap();
+        std::fs::write(e2e.join("pytl.toml"), "enable = [\"PYTEST-FLK-002\"]\n").unwrap();
+
+        let config = Config::discover(dir.path()).unwrap();
+        assert!(config.is_rule_enabled("PYTEST-MNT-004"));
+        assert!(config.is_rule_enabled("PYTEST-FLK-001"));
+        assert!(!config.is_rule_enabled("PYTEST-MNT-017"));
+        assert!(!config.is_rule_enabled("PYTEST-FLK-002"));
+        assert!(!config.is_rule_enabled("PYTEST-FIX-001"));
+
+        // A directory `enable` list only turns rules back on below it
+        let rules = config
+            .effective_rules_for_file(&e2e.join("test_flow.py"))
+            .unwrap();
+        assert_eq!(rules["PYTEST-FLK-002"].enabled, Some(true));
+        assert_eq!(rules["PYTEST-MNT-004"].enabled, Some(true));
+        assert_eq!(rules["PYTEST-FIX-001"].enabled, Some(false));
+    }
+
+    #[test]
+    fn test_rule_table_by_slug_keeps_options() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pytl.toml"),
+            r#"
+[rules.test-name-length]
+max_length = 120
+severity = "warning"
+"#,
+        )
+        .unwrap();
+        let cfg = Config::from_standalone(dir.path()).unwrap().unwrap();
+        let rc = &cfg.rules["PYTEST-MNT-017"];
+        assert_eq!(rc.severity, Some(Severity::Warning));
+        assert_eq!(rc.options["max_length"].as_integer(), Some(120));
+        assert!(!cfg.rules.contains_key("test-name-length"));
+    }
+
+    #[test]
+    fn test_options_merge_across_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            r#"
+[tool.pytest-linter.rules.PYTEST-MNT-017]
+max_length = 100
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pytl.toml"),
+            r#"
+[rules.PYTEST-MNT-017]
+severity = "error"
+"#,
+        )
+        .unwrap();
+        let cfg = Config::discover(dir.path()).unwrap();
+        let rc = &cfg.rules["PYTEST-MNT-017"];
+        assert_eq!(rc.severity, Some(Severity::Error));
+        assert_eq!(rc.options["max_length"].as_integer(), Some(100));
+    }
+
+    fn config_error(file: &str, contents: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(file), contents).unwrap();
+        let err = Config::discover(dir.path()).unwrap_err();
+        format!("{err:#}")
+    }
+
+    #[test]
+    fn test_unknown_rule_reports_key_path() {
+        let err = config_error("pytl.toml", "[rules.no-such-rule]\nenabled = false\n");
+        assert!(
+            err.contains("unknown rule `no-such-rule` at `rules.no-such-rule`"),
+            "{err}"
+        );
+        assert!(err.contains("pytl.toml"), "{err}");
+    }
+
+    #[test]
+    fn test_unknown_rule_suggests_closest_match() {
+        let err = config_error("pytl.toml", "disable = [\"no-asertion\"]\n");
+        assert!(
+            err.contains(
+                "unknown rule `no-asertion` (did you mean `no-assertion`?) at `disable[0]`"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_rule_in_pyproject_uses_tool_prefix() {
+        let err = config_error(
+            "pyproject.toml",
+            "[tool.pytest-linter]\ndisable = [\"PYTEST-FLK-001\", \"PYTEST-NOPE-001\"]\n",
+        );
+        assert!(
+            err.contains("unknown rule `PYTEST-NOPE-001` at `tool.pytest-linter.disable[1]`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_wrong_option_type_reports_key_path() {
+        let err = config_error(
+            "pytl.toml",
+            "[rules.PYTEST-MNT-017]\nmax_length = \"long\"\n",
+        );
+        assert!(
+            err.contains(
+                "`rules.PYTEST-MNT-017.max_length` must be a non-negative integer, got string"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_option_lists_accepted_options() {
+        let err = config_error("pytl.toml", "[rules.PYTEST-MNT-017]\nmax = 3\n");
+        assert!(
+            err.contains("unknown option `rules.PYTEST-MNT-017.max`"),
+            "{err}"
+        );
+        assert!(err.contains("accepts max_length"), "{err}");
+    }
+
+    #[test]
+    fn test_override_rules_are_validated() {
+        let err = config_error(
+            "pytl.toml",
+            "[[overrides]]\npath = \"tests/**\"\nrules = { bogus = { enabled = false } }\n",
+        );
+        assert!(err.contains("at `overrides[0].rules.bogus`"), "{err}");
+    }
+
+    #[test]
+    fn test_rule_in_both_enable_and_disable_is_rejected() {
+        let err = config_error(
+            "pytl.toml",
+            "enable = [\"no-assertion\"]\ndisable = [\"PYTEST-MNT-004\"]\n",
+        );
+        assert!(
+            err.contains("listed in both `enable` and `disable`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_directory_config_layers_over_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let e2e = dir.path().join("tests").join("e2e");
+        std::fs::create_dir_all(&e2e).unwrap();
+        std::fs::write(
+            dir.path().join("pytl.toml"),
+            "[rules.PYTEST-MNT-017]\nmax_length = 40\n\n[severity]\nPYTEST-FLK-001 = \"error\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            e2e.join("pytl.toml"),
+            "disable = [\"PYTEST-FLK-002\"]\n\n[severity]\nsleepy-test = \"info\"\n\n\
+             [rules.PYTEST-MNT-017]\nmax_length = 100\n",
+        )
+        .unwrap();
+
+        // Discovery from a subdirectory still finds the outermost configuration
+        let config = Config::discover(&e2e).unwrap();
+        let rules = config
+            .effective_rules_for_file(&e2e.join("test_flow.py"))
+            .unwrap();
+        assert_eq!(rules["PYTEST-FLK-001"].severity, Some(Severity::Info));
+        assert_eq!(rules["PYTEST-FLK-002"].enabled, Some(false));
+        assert_eq!(
+            rules["PYTEST-MNT-017"].options.get("max_length"),
+            Some(&toml::Value::Integer(100))
+        );
+
+        let outside = config
+            .effective_rules_for_file(&dir.path().join("tests").join("test_unit.py"))
+            .unwrap();
+        assert_eq!(outside["PYTEST-FLK-001"].severity, Some(Severity::Error));
+        assert_ne!(outside["PYTEST-FLK-002"].enabled, Some(false));
+
+        let resolved = config.resolve_for_file(&e2e.join("test_flow.py")).unwrap();
+        assert_eq!(resolved.layers.len(), 2);
+        assert!(
+            resolved.layers[1].ends_with("pytl.toml"),
+            "{:?}",
+            resolved.layers
+        );
+        assert_eq!(resolved.rules["PYTEST-MNT-017"].set_by, [0, 1]);
+        assert_eq!(resolved.rules["PYTEST-FLK-002"].set_by, [1]);
+        assert!(!resolved.rules["PYTEST-FLK-002"].enabled);
+        assert!(resolved.rules["PYTEST-MNT-004"].set_by.is_empty());
+    }
+
+    #[test]
+    fn test_severity_in_both_rules_and_severity_is_rejected() {
+        let err = config_error(
+            "pytl.toml",
+            "[rules.PYTEST-FLK-001]\nseverity = \"error\"\n\n[severity]\nsleepy-test = \"info\"\n",
+        );
+        assert!(
+            err.contains("the severity of PYTEST-FLK-001 is set in both `rules` and `severity`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_directory_config_rejects_run_level_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("tests");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("pytl.toml"), "[rules]\n").unwrap();
+        std::fs::write(nested.join("pytl.toml"), "fail_on = \"warning\"\n").unwrap();
+        let config = Config::discover(dir.path()).unwrap();
+        let err = config
+            .effective_rules_for_file(&nested.join("test_a.py"))
+            .unwrap_err();
+        let err = format!("{err:#}");
+        assert!(
+            err.contains(
+                "`fail_on` applies to the whole run and can only be set in the root configuration"
+            ),
+            "{err}"
+        );
+    }
 }
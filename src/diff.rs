@@ -0,0 +1,246 @@
+//! Diff-aware output: keep only diagnostics whose span touches lines added or modified in
+//! a unified diff (from `git diff` against a base ref, or read from stdin).
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Violation;
+
+/// Lines added or modified per file, keyed by the file's path after the change (so renamed
+/// files are found under their new name).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedLines {
+    files: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+/// Canonical form of a path, for comparing diff paths with linted paths.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+/// Old-side line count, new-side start line and new-side line count of a hunk header
+/// `@@ -a,b +c,d @@` (counts default to 1).
+fn hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = header.split_whitespace().skip(1);
+    let side = |part: &str| -> Option<(usize, usize)> {
+        let mut numbers = part[1..].split(',');
+        let start = numbers.next()?.parse().ok()?;
+        let count = numbers.next().map_or(Some(1), |c| c.parse().ok())?;
+        Some((start, count))
+    };
+    let (_, old_count) = side(parts.next().filter(|p| p.starts_with('-'))?)?;
+    let (new_start, new_count) = side(parts.next().filter(|p| p.starts_with('+'))?)?;
+    Some((old_count, new_start, new_count))
+}
+
+impl ChangedLines {
+    /// Parse a unified diff. File paths in the diff are resolved against `base_dir`; the
+    /// `a/` and `b/` prefixes of git diffs are stripped.
+    #[must_use]
+    pub fn parse(diff: &str, base_dir: &Path) -> Self {
+        let mut files: HashMap<PathBuf, Vec<RangeInclusive<usize>>> = HashMap::new();
+        let mut prefixed = false;
+        let mut current: Option<PathBuf> = None;
+        let mut line_no = 0usize;
+        // Lines still expected in the current hunk, old side and new side.
+        let (mut old_left, mut new_left) = (0usize, 0usize);
+
+        for line in diff.lines() {
+            if old_left > 0 || new_left > 0 {
+                match line.as_bytes().first() {
+                    Some(b'+') => {
+                        if let Some(path) = &current {
+                            let ranges = files.entry(path.clone()).or_default();
+                            match ranges.last_mut() {
+                                Some(last) if *last.end() + 1 == line_no => {
+                                    *last = *last.start()..=line_no;
+                                }
+                                _ => ranges.push(line_no..=line_no),
+                            }
+                        }
+                        line_no += 1;
+                        new_left = new_left.saturating_sub(1);
+                    }
+                    Some(b'-') => old_left = old_left.saturating_sub(1),
+                    Some(b'\\') => {}
+                    _ => {
+                        line_no += 1;
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                    }
+                }
+            } else if let Some(old) = line.strip_prefix("--- ") {
+                prefixed = old.starts_with("a/") || old.starts_with("/dev/null");
+            } else if let Some(new) = line.strip_prefix("+++ ") {
+                let new = new.split('\t').next().unwrap_or(new).trim_end();
+                current = (new != "/dev/null").then(|| {
+                    let path = if prefixed {
+                        new.strip_prefix("b/").unwrap_or(new)
+                    } else {
+                        new
+                    };
+                    normalize(&base_dir.join(path))
+                });
+            } else if line.starts_with("@@") {
+                if let Some((old_count, start, new_count)) = hunk_header(line) {
+                    line_no = start;
+                    (old_left, new_left) = (old_count, new_count);
+                }
+            }
+        }
+        Self { files }
+    }
+
+    /// Lines changed in the working tree relative to the git ref `base`, with paths
+    /// resolved against the top of the work tree.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_git(base: &str) -> Result<Self> {
+        let run = |args: &[&str]| -> Result<String> {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .output()
+                .context("could not run git")?;
+            if !output.status.success() {
+                bail!(
+                    "git {} failed: {}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        };
+        let top = run(&["rev-parse", "--show-toplevel"])?;
+        let diff = run(&[
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--find-renames",
+            base,
+        ])?;
+        Ok(Self::parse(&diff, Path::new(top.trim())))
+    }
+
+    /// Number of files with added or modified lines.
+    #[must_use]
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Drop violations whose span does not intersect a changed line.
+    #[must_use]
+    pub fn filter(&self, violations: Vec<Violation>) -> Vec<Violation> {
+        let mut resolved: HashMap<PathBuf, PathBuf> = HashMap::new();
+        violations
+            .into_iter()
+            .filter(|v| {
+                let path = resolved
+                    .entry(v.file_path.clone())
+                    .or_insert_with(|| normalize(&v.file_path));
+                let Some(ranges) = self.files.get(path.as_path()) else {
+                    return false;
+                };
+                let (start, end) = (v.line, v.end_line.unwrap_or(v.line).max(v.line));
+                ranges
+                    .iter()
+                    .any(|r| *r.start() <= end && start <= *r.end())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::make_violation;
+    use crate::models::{Category, Severity};
+
+    const DIFF: &str = "\
+diff --git a/tests/test_old.py b/tests/test_new.py
+similarity index 90%
+rename from tests/test_old.py
+rename to tests/test_new.py
+--- a/tests/test_old.py
++++ b/tests/test_new.py
+@@ -3,3 +3,4 @@ def test_a():
+ context
+-removed
++added one
++added two
+ context
+@@ -10,0 +12 @@
++appended
+diff --git a/tests/test_gone.py b/tests/test_gone.py
+deleted file mode 100644
+--- a/tests/test_gone.py
++++ /dev/null
+@@ -1 +0,0 @@
+-x
+";
+
+    fn violation(path: &Path, line: usize, end_line: Option<usize>) -> Violation {
+        let mut v = make_violation(
+            "PYTEST-MNT-004",
+            "NoAssertionRule",
+            Severity::Error,
+            Category::Maintenance,
+            "m".to_string(),
+            path.to_path_buf(),
+            line,
+            None,
+            None,
+        );
+        v.end_line = end_line;
+        v
+    }
+
+    #[test]
+    fn test_parse_maps_renames_and_tracks_new_side_lines() {
+        let base = Path::new("/repo");
+        let changed = ChangedLines::parse(DIFF, base);
+        assert_eq!(changed.file_count(), 1);
+        assert_eq!(
+            changed
+                .files
+                .get(&normalize(&base.join("tests/test_new.py"))),
+            Some(&vec![4..=5, 12..=12])
+        );
+    }
+
+    #[test]
+    fn test_filter_keeps_only_spans_touching_changed_lines() {
+        let base = Path::new("/repo");
+        let changed = ChangedLines::parse(DIFF, base);
+        let new = base.join("tests/test_new.py");
+        let kept = changed.filter(vec![
+            violation(&new, 3, None),
+            violation(&new, 4, None),
+            violation(&new, 1, Some(12)),
+            violation(&new, 6, Some(11)),
+            violation(&base.join("tests/test_old.py"), 4, None),
+            violation(&base.join("tests/test_other.py"), 4, None),
+        ]);
+        let lines: Vec<(usize, Option<usize>)> =
+            kept.iter().map(|v| (v.line, v.end_line)).collect();
+        assert_eq!(lines, [(4, None), (1, Some(12))]);
+    }
+
+    #[test]
+    fn test_parse_without_prefixes_and_header_like_hunk_lines() {
+        let diff = "--- tests/test_x.py\n+++ tests/test_x.py\n@@ -1 +1 @@\n--- a\n+++ b\n";
+        let base = Path::new("/repo");
+        let changed = ChangedLines::parse(diff, base);
+        assert_eq!(
+            changed.files.get(&normalize(&base.join("tests/test_x.py"))),
+            Some(&vec![1..=1])
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! File discovery: walk the given paths for test files, honouring `.gitignore` / `.ignore`
+//! files, exclude and include globs, and configurable test-file name patterns.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+
+/// File-name patterns that identify test files when none are configured.
+pub const DEFAULT_TEST_PATTERNS: &[&str] = &["test_*.py", "*_test.py", "conftest.py"];
+
+/// Ignore files read in every walked directory. `.gitignore` only counts inside a git
+/// work tree; `.ignore` applies everywhere.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// `*` and `?` stop at `/`, as in git.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A gitignore-style glob. Without a `/` it matches the entry's file name at any depth;
+/// with one it is anchored and matches the path relative to its base directory.
+#[derive(Debug)]
+struct PathGlob {
+    pattern: Pattern,
+    anchored: bool,
+}
+
+impl PathGlob {
+    fn new(raw: &str) -> Result<Self, glob::PatternError> {
+        let trimmed = raw.trim_end_matches('/');
+        let trimmed = trimmed.strip_prefix("./").unwrap_or(trimmed);
+        let anchored = trimmed.contains('/');
+        let body = trimmed.strip_prefix('/').unwrap_or(trimmed);
+        Ok(Self {
+            pattern: Pattern::new(body)?,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative: &Path) -> bool {
+        if self.anchored {
+            self.pattern.matches_path_with(relative, MATCH_OPTIONS)
+        } else {
+            relative
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| self.pattern.matches_with(name, MATCH_OPTIONS))
+        }
+    }
+}
+
+/// One line of an ignore file.
+#[derive(Debug)]
+struct IgnoreRule {
+    glob: PathGlob,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Parse an ignore file, skipping comments, blank lines and patterns that are not valid globs.
+fn parse_ignore_file(text: &str) -> Vec<IgnoreRule> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let glob = PathGlob::new(pattern).ok()?;
+            Some(IgnoreRule {
+                glob,
+                negated,
+                dir_only: pattern.ends_with('/'),
+            })
+        })
+        .collect()
+}
+
+/// Ignore rules of the directories seen during one walk, keyed by directory relative to the
+/// walk's top (the enclosing git work tree, or the walked directory outside of git).
+struct IgnoreStack {
+    in_git: bool,
+    rules: HashMap<PathBuf, Vec<IgnoreRule>>,
+}
+
+impl IgnoreStack {
+    fn load(&mut self, key: PathBuf, dir: &Path) {
+        let mut rules = Vec::new();
+        for name in IGNORE_FILES {
+            if *name == ".gitignore" && !self.in_git {
+                continue;
+            }
+            if let Ok(text) = std::fs::read_to_string(dir.join(name)) {
+                rules.extend(parse_ignore_file(&text));
+            }
+        }
+        self.rules.insert(key, rules);
+    }
+
+    /// Whether `path` (relative to the walk's top) is ignored. The last matching rule wins,
+    /// and rules of deeper directories come after those of their parents.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        let mut dir = PathBuf::new();
+        let components: Vec<Component> = path.components().collect();
+        for (i, component) in components.iter().enumerate() {
+            if let Some(rules) = self.rules.get(&dir) {
+                let relative: PathBuf = components[i..].iter().collect();
+                for rule in rules {
+                    if (is_dir || !rule.dir_only) && rule.glob.matches(&relative) {
+                        ignored = !rule.negated;
+                    }
+                }
+            }
+            dir.push(component);
+        }
+        ignored
+    }
+}
+
+/// The git work tree containing `dir`, if any.
+fn git_root(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .find(|a| a.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Finds the test files to lint under a set of paths.
+#[derive(Debug)]
+pub struct FileDiscovery {
+    excludes: Vec<PathGlob>,
+    includes: Vec<PathGlob>,
+    test_patterns: Vec<Pattern>,
+}
+
+impl Default for FileDiscovery {
+    fn default() -> Self {
+        Self::new(&[], &[], &[]).expect("default test patterns are valid globs")
+    }
+}
+
+impl FileDiscovery {
+    /// Build a discovery from exclude and include globs (or plain directory names) and
+    /// test-file name patterns; empty `test_patterns` uses [`DEFAULT_TEST_PATTERNS`].
+    #[allow(clippy::missing_errors_doc)]
+    pub fn new(excludes: &[String], includes: &[String], test_patterns: &[String]) -> Result<Self> {
+        let globs = |raw: &[String], what: &str| -> Result<Vec<PathGlob>> {
+            raw.iter()
+                .map(|p| PathGlob::new(p).with_context(|| format!("invalid {what} glob '{p}'")))
+                .collect()
+        };
+        let test_patterns = if test_patterns.is_empty() {
+            DEFAULT_TEST_PATTERNS
+                .iter()
+                .map(|p| Pattern::new(p).expect("default test patterns are valid globs"))
+                .collect()
+        } else {
+            test_patterns
+                .iter()
+                .map(|p| {
+                    Pattern::new(p).with_context(|| format!("invalid test file pattern '{p}'"))
+                })
+                .collect::<Result<_>>()?
+        };
+        Ok(Self {
+            excludes: globs(excludes, "exclude")?,
+            includes: globs(includes, "include")?,
+            test_patterns,
+        })
+    }
+
+    /// Discovery settings of a configuration.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(&config.excludes, &config.include, &config.test_patterns)
+    }
+
+    /// Whether a path is a Python test file by name.
+    #[must_use]
+    pub fn is_test_file(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        path.extension().is_some_and(|e| e == "py")
+            && self
+                .test_patterns
+                .iter()
+                .any(|p| p.matches_with(name, MATCH_OPTIONS))
+    }
+
+    /// Test files under `paths`, sorted and deduplicated. Files passed explicitly are kept
+    /// whenever they are test files; exclude, include and ignore rules only apply to what
+    /// is found by walking directories. Symlinks are followed, and links back into a
+    /// directory being walked are skipped.
+    #[must_use]
+    pub fn discover(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        self.discover_with_dirs(paths).0
+    }
+
+    /// Like [`Self::discover`], also returning the directories that were walked, sorted:
+    /// a file created in any of them changes its modification time.
+    #[must_use]
+    pub fn discover_with_dirs(&self, paths: &[PathBuf]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for path in paths {
+            if path.is_file() {
+                if self.is_test_file(path) {
+                    files.push(path.clone());
+                }
+            } else if path.is_dir() {
+                self.walk(path, &mut files, &mut dirs);
+            }
+        }
+        files.sort();
+        files.dedup();
+        dirs.sort();
+        dirs.dedup();
+        (files, dirs)
+    }
+
+    fn walk(&self, root: &Path, files: &mut Vec<PathBuf>, dirs: &mut Vec<PathBuf>) {
+        let git_top = git_root(root);
+        let mut ignores = IgnoreStack {
+            in_git: git_top.is_some(),
+            rules: HashMap::new(),
+        };
+        // Ignore files between the work tree's top and the walked directory also apply.
+        let root_key = match (&git_top, root.canonicalize()) {
+            (Some(top), Ok(canonical)) => {
+                let key = canonical
+                    .strip_prefix(top)
+                    .unwrap_or(Path::new(""))
+                    .to_path_buf();
+                let mut dir = PathBuf::new();
+                for component in key.components() {
+                    ignores.load(dir.clone(), &top.join(&dir));
+                    dir.push(component);
+                }
+                key
+            }
+            _ => PathBuf::new(),
+        };
+
+        let walker = WalkDir::new(root).follow_links(true).into_iter();
+        let entries = walker.filter_entry(|entry| {
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let is_dir = entry.file_type().is_dir();
+            let key = root_key.join(relative);
+            if entry.depth() > 0
+                && (self.excludes.iter().any(|g| g.matches(relative))
+                    || ignores.is_ignored(&key, is_dir))
+            {
+                return false;
+            }
+            if is_dir {
+                ignores.load(key, entry.path());
+            }
+            true
+        });
+        for entry in entries.filter_map(std::result::Result::ok) {
+            let path = entry.path();
+            if entry.file_type().is_dir() {
+                dirs.push(path.to_path_buf());
+                continue;
+            }
+            if !entry.file_type().is_file() || !self.is_test_file(path) {
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            if self.includes.is_empty() || self.includes.iter().any(|g| g.matches(relative)) {
+                files.push(path.to_path_buf());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn names(files: &[PathBuf], root: &Path) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| {
+                f.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    fn touch(root: &Path, relative: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "def test_x():\n    assert f()\n").unwrap();
+    }
+
+    #[test]
+    fn test_gitignore_applies_only_inside_git_and_supports_negation() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "tests/test_a.py");
+        touch(root, "build/test_generated.py");
+        touch(root, "tests/test_skip.py");
+        touch(root, "tests/test_keep.py");
+        fs::write(root.join(".gitignore"), "build/\ntest_*.py\n!test_a.py\n").unwrap();
+        fs::write(root.join("tests/.ignore"), "test_skip.py\n").unwrap();
+
+        let discovery = FileDiscovery::default();
+        let outside_git = names(&discovery.discover(&[root.to_path_buf()]), root);
+        assert_eq!(
+            outside_git,
+            [
+                "build/test_generated.py",
+                "tests/test_a.py",
+                "tests/test_keep.py"
+            ]
+        );
+
+        fs::create_dir(root.join(".git")).unwrap();
+        let in_git = names(&discovery.discover(&[root.to_path_buf()]), root);
+        assert_eq!(in_git, ["tests/test_a.py"]);
+
+        // Rules from the work tree's top apply when walking a subdirectory.
+        let sub = root.join("tests");
+        assert_eq!(
+            names(&discovery.discover(std::slice::from_ref(&sub)), &sub),
+            ["test_a.py"]
+        );
+    }
+
+    #[test]
+    fn test_include_exclude_globs_and_explicit_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "unit/test_a.py");
+        touch(root, "unit/slow/test_b.py");
+        touch(root, "e2e/test_c.py");
+        let discovery =
+            FileDiscovery::new(&["slow".to_string()], &["unit/**".to_string()], &[]).unwrap();
+        assert_eq!(
+            names(&discovery.discover(&[root.to_path_buf()]), root),
+            ["unit/test_a.py"]
+        );
+        let anchored = FileDiscovery::new(&["e2e/test_*.py".to_string()], &[], &[]).unwrap();
+        assert_eq!(
+            names(&anchored.discover(&[root.to_path_buf()]), root),
+            ["unit/slow/test_b.py", "unit/test_a.py"]
+        );
+        // An explicitly passed file bypasses excludes and includes.
+        let explicit = root.join("unit/slow/test_b.py");
+        assert_eq!(
+            discovery.discover(std::slice::from_ref(&explicit)),
+            [explicit]
+        );
+    }
+
+    #[test]
+    fn test_custom_test_patterns() {
+        let discovery = FileDiscovery::new(&[], &[], &["check_*.py".to_string()]).unwrap();
+        assert!(discovery.is_test_file(Path::new("check_parser.py")));
+        assert!(!discovery.is_test_file(Path::new("test_parser.py")));
+        assert!(!discovery.is_test_file(Path::new("check_parser.txt")));
+        assert!(FileDiscovery::new(&[], &[], &["[".to_string()]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycle_terminates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "pkg/test_a.py");
+        std::os::unix::fs::symlink(root, root.join("pkg/loop")).unwrap();
+        let files = FileDiscovery::default().discover(&[root.to_path_buf()]);
+        assert_eq!(names(&files, root), ["pkg/test_a.py"]);
+    }
+}
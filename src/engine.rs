@@ -1,16 +1,29 @@
 //! Core linting engine: file discovery, parallel parsing, rule execution, and output formatting.
 
+use crate::baseline::Baseline;
+use crate::cache::{Cache, CacheStats, CachedDiagnostics};
 use crate::config::Config;
+use crate::discovery::FileDiscovery;
+use crate::fix::FixSummary;
 use crate::models::{Category, Fixture, FixtureScope, ParsedModule, Severity, Violation};
-use crate::rules::{Rule, RuleContext};
+use crate::output::summary::{format_statistics, format_top_files, FileCounts, Summary};
+use crate::output::{char_columns, SourceCache};
+use crate::parser::{ParseOptions, PythonParser};
+use crate::project::{ProjectContext, ProjectIndex};
+use crate::rules::ast::parse_python;
+use crate::rules::custom::{compile_rules, CustomRule, CustomRuleDef};
+use crate::rules::syntax::SYNTAX_ERROR_RULE_ID;
+use crate::rules::{Rule, RuleContext, RuleOptions};
 use anyhow::Result;
 use colored::Colorize;
 use rayon::prelude::*;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasher;
 use std::io::Write;
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 /// Single-pass rule dispatcher. Instead of each rule walking the parsed module
 /// data independently, the dispatcher iterates all rules in a single pass per
@@ -18,6 +31,7 @@ use walkdir::WalkDir;
 /// point for per-file override resolution.
 pub struct RuleDispatcher {
     all_rules: Vec<Box<dyn Rule>>,
+    custom_rules: Vec<CustomRule>,
 }
 
 impl Default for RuleDispatcher {
@@ -30,9 +44,19 @@ impl RuleDispatcher {
     pub fn new() -> Self {
         Self {
             all_rules: crate::rules::all_rules(),
+            custom_rules: vec![],
         }
     }
 
+    /// A dispatcher that also runs the given custom rules.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn with_custom_rules(defs: &[CustomRuleDef]) -> Result<Self> {
+        Ok(Self {
+            custom_rules: compile_rules(defs)?,
+            ..Self::new()
+        })
+    }
+
     /// Check all rules against a single module in one pass, applying per-file
     /// config (global + overrides) for rule enablement and severity.
     pub fn check_module(
@@ -43,32 +67,60 @@ impl RuleDispatcher {
         config: &Config,
     ) -> Result<Vec<Violation>> {
         let effective = config.effective_rules_for_file(&module.file_path)?;
+        let tree = parse_python(&module.source);
         let mut violations = Vec::new();
 
-        for rule in &self.all_rules {
-            let rule_id = rule.id();
-
-            let enabled = effective
+        let enabled = |rule_id: &str| {
+            effective
                 .get(rule_id)
                 .map(|rc| rc.enabled.unwrap_or(true))
-                .unwrap_or(true);
+                .unwrap_or(true)
+        };
+        // A configured severity replaces the one each violation was reported at
+        let severity = |rule_id: &str| effective.get(rule_id).and_then(|rc| rc.severity);
 
-            if !enabled {
+        for rule in &self.all_rules {
+            let rule_id = rule.id();
+            if !enabled(rule_id) {
                 continue;
             }
-
-            let default_severity = rule.severity();
-            let severity = effective
-                .get(rule_id)
-                .and_then(|rc| rc.severity)
-                .unwrap_or(default_severity);
-
-            let mut v = rule.check(module, all_modules, ctx);
-            for violation in &mut v {
-                violation.severity = severity;
+            let severity = severity(rule_id);
+
+            let rule_ctx = RuleContext {
+                options: effective
+                    .get(rule_id)
+                    .map(|rc| RuleOptions::new(&rc.options))
+                    .unwrap_or_default(),
+                tree: tree.as_ref(),
+                ..*ctx
+            };
+            let mut v = rule.check(module, all_modules, &rule_ctx);
+            if !rule.reliable_on_partial_input() {
+                v.retain(|v| !module.syntax_errors.iter().any(|e| e.covers(v.line)));
+            }
+            for violation in v.iter_mut().filter(|v| v.tags.is_empty()) {
+                violation.tags = rule.tags().to_vec();
+            }
+            if let Some(severity) = severity {
+                for violation in &mut v {
+                    violation.severity = severity;
+                }
+            }
+            violations.append(&mut v);
+        }
+        for rule in &self.custom_rules {
+            if !enabled(rule.id()) {
+                continue;
+            }
+            let mut v = rule.check(module, tree.as_ref());
+            if let Some(severity) = severity(rule.id()) {
+                for violation in &mut v {
+                    violation.severity = severity;
+                }
             }
             violations.append(&mut v);
         }
+        crate::span::narrow_spans(&mut violations, &module.file_path, tree.as_ref());
 
         Ok(violations)
     }
@@ -85,9 +137,11 @@ impl RuleDispatcher {
 ///    dropped after parsing — only extracted metadata (names, flags, fixtures)
 ///    is retained.
 /// 3. Cross-module context: Fixture maps and usage sets are computed once from
-///    all parsed modules.
+///    all parsed modules. In `--project-mode` a compact index of the whole project
+///    ([`crate::project`]) is built first, holding names and scopes only.
 /// 4. Rule checking: The `RuleDispatcher` iterates all rules per module in a
-///    single pass, applying per-file overrides.
+///    single pass, applying per-file overrides. Modules are checked in parallel
+///    (`Config::jobs` threads) and the results sorted afterwards.
 ///
 /// For a 1 GB Python repo (~10K test files), estimated peak memory:
 ///   - ParsedModule structs: ~10-50 MB (lightweight metadata, no source text)
@@ -99,33 +153,35 @@ pub struct LintEngine {
     dispatcher: RuleDispatcher,
     config: Config,
     memory_limit_mb: usize,
+    cache: Option<Cache>,
+    analyzed: AtomicUsize,
+    skipped: AtomicUsize,
 }
 
 impl LintEngine {
     /// Create a new engine with rules filtered by the given configuration.
     #[allow(clippy::missing_errors_doc)]
     pub fn new(config: Config) -> Result<Self> {
-        Ok(Self {
-            dispatcher: RuleDispatcher::new(),
-            config,
-            memory_limit_mb: 256,
-        })
+        Self::with_memory_limit(config, 256)
     }
 
     /// Create a LintEngine with an explicit memory limit (in MB).
     #[allow(clippy::missing_errors_doc)]
     pub fn with_memory_limit(config: Config, memory_limit_mb: usize) -> Result<Self> {
         Ok(Self {
-            dispatcher: RuleDispatcher::new(),
+            dispatcher: RuleDispatcher::with_custom_rules(&config.custom_rules)?,
+            cache: config.cache_dir.clone().map(Cache::new),
             config,
             memory_limit_mb,
+            analyzed: AtomicUsize::new(0),
+            skipped: AtomicUsize::new(0),
         })
     }
 
     /// Lint all test files discovered under the given paths and return violations.
     #[allow(clippy::missing_errors_doc)]
     pub fn lint_paths(&self, paths: &[PathBuf]) -> Result<Vec<Violation>> {
-        let files = discover_files(paths, &self.config.excludes);
+        let files = FileDiscovery::from_config(&self.config)?.discover(paths);
 
         let (estimated_mb, over_budget) = exceeds_memory_budget(&files, self.memory_limit_mb);
         if over_budget {
@@ -136,7 +192,86 @@ impl LintEngine {
             );
         }
 
-        let modules = parse_files_parallel(&files);
+        let run = || {
+            let index = self
+                .config
+                .project_mode
+                .then(|| self.project_index(paths))
+                .transpose()?;
+            self.lint_files(&files, index.as_ref())
+        };
+        let violations = self.on_pool(run)?;
+        Ok(self.filter_changed_lines(violations))
+    }
+
+    /// Run `run` on a pool of `--jobs` threads, or on the global pool.
+    fn on_pool<T: Send>(&self, run: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+        match self.config.jobs {
+            Some(jobs) => rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()?
+                .install(run),
+            None => run(),
+        }
+    }
+
+    fn filter_changed_lines(&self, violations: Vec<Violation>) -> Vec<Violation> {
+        match &self.config.changed_lines {
+            Some(changed) => changed.filter(violations),
+            None => violations,
+        }
+    }
+
+    /// Cache hit/miss counts so far, if caching is enabled.
+    #[must_use]
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(Cache::stats)
+    }
+
+    /// Files analyzed, skipped and served from the cache by `lint_paths` so far.
+    #[must_use]
+    pub fn file_counts(&self) -> FileCounts {
+        FileCounts {
+            analyzed: self.analyzed.load(AtomicOrdering::Relaxed),
+            skipped: self.skipped.load(AtomicOrdering::Relaxed),
+            cached: self.cache_stats().map_or(0, |s| s.hits),
+        }
+    }
+
+    /// First pass of `--project-mode`: index the projects of `paths`, reusing and updating
+    /// the cached index.
+    fn project_index(&self, paths: &[PathBuf]) -> Result<ProjectIndex> {
+        let previous = self.cache.as_ref().and_then(Cache::load_index);
+        let index = ProjectIndex::build(
+            paths,
+            &FileDiscovery::from_config(&self.config)?,
+            &ParseOptions::from_config(&self.config),
+            previous.as_ref(),
+        );
+        if let Some(cache) = &self.cache {
+            if previous.as_ref() != Some(&index) {
+                cache.store_index(&index);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Parse and check `files` on the current rayon pool, with the project index in
+    /// `--project-mode`.
+    fn lint_files(
+        &self,
+        files: &[PathBuf],
+        project: Option<&ProjectIndex>,
+    ) -> Result<Vec<Violation>> {
+        let options = ParseOptions::from_config(&self.config);
+        let (parsed, mut internal_errors) =
+            parse_files_parallel(files, self.cache.as_ref(), &options);
+        let (modules, cache_states): (Vec<ParsedModule>, Vec<CacheState>) =
+            parsed.into_iter().unzip();
+        self.analyzed
+            .fetch_add(modules.len(), AtomicOrdering::Relaxed);
+        self.skipped
+            .fetch_add(files.len() - modules.len(), AtomicOrdering::Relaxed);
 
         let fixture_map = collect_all_fixtures(&modules);
         let used_fixture_names = compute_used_fixture_names(&modules);
@@ -148,25 +283,154 @@ impl LintEngine {
             used_fixture_names: &used_fixture_names,
             fixture_locations: &fixture_locations,
             session_mutable_fixtures: &session_mutable_fixtures,
+            project: project.map(ProjectContext::new),
+            options: RuleOptions::default(),
+            tree: None,
         };
 
+        let context = self.cache.as_ref().map(|_| {
+            let context = crate::cache::context_hash(
+                &fixture_map,
+                &used_fixture_names,
+                &fixture_locations,
+                &session_mutable_fixtures,
+            );
+            let mut context = match project {
+                Some(index) => format!("{context}\n{}", index.fingerprint()),
+                None => context,
+            };
+            // A changed rules file changes what the custom rules report
+            if !self.config.custom_rules.is_empty() {
+                context.push('\n');
+                context.push_str(
+                    &serde_json::to_string(&self.config.custom_rules).unwrap_or_default(),
+                );
+            }
+            context
+        });
+
+        let per_module = modules
+            .par_iter()
+            .zip(&cache_states)
+            .map(|(module, state)| {
+                let cache_key = match (&self.cache, &context) {
+                    (Some(cache), Some(context)) => {
+                        let effective = self.config.effective_rules_for_file(&module.file_path)?;
+                        let key = crate::cache::diagnostics_key(&effective, context);
+                        if let Some(v) = cache.diagnostics(state.diagnostics.as_ref(), &key) {
+                            return Ok((v, None));
+                        }
+                        Some((cache, key))
+                    }
+                    _ => None,
+                };
+                let checked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    self.dispatcher
+                        .check_module(module, &modules, &ctx, &self.config)
+                }));
+                match checked {
+                    Ok(Ok(v)) => {
+                        if let Some((cache, key)) = cache_key {
+                            cache.store(module, &state.content_hash, key, v.clone());
+                        }
+                        Ok((v, None))
+                    }
+                    Ok(Err(e)) => Err(e),
+                    Err(panic) => Ok((
+                        vec![],
+                        Some(internal_error(&module.file_path, "checking", &*panic)),
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
         let mut violations = Vec::new();
-        for module in &modules {
-            let mut v = self
-                .dispatcher
-                .check_module(module, &modules, &ctx, &self.config)?;
+        for (mut v, internal) in per_module {
             violations.append(&mut v);
+            internal_errors.extend(internal);
         }
 
         let suppressions = collect_suppressions(&modules);
+        let directives = collect_directive_suppressions(&modules);
         let mut violations: Vec<Violation> = violations
             .into_iter()
-            .filter(|v| !is_suppressed(v, &suppressions))
+            .filter(|v| !is_suppressed(v, &suppressions) && !directives.suppresses(v))
+            .chain(internal_errors)
             .collect();
-        violations.sort();
+        violations.sort_by(output_order);
+        dedup_diagnostics(&mut violations);
         Ok(violations)
     }
 
+    /// Parse `files` for a session that keeps the modules between runs, such as `--watch`.
+    /// Files that cannot be read or parsed are left out; a panic while parsing is returned
+    /// as a `PYTEST-INT-001` diagnostic.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse_modules(&self, files: &[PathBuf]) -> Result<(Vec<ParsedModule>, Vec<Violation>)> {
+        let options = ParseOptions::from_config(&self.config);
+        let (parsed, errors) = self.on_pool(|| Ok(parse_files_parallel(files, None, &options)))?;
+        Ok((parsed.into_iter().map(|(m, _)| m).collect(), errors))
+    }
+
+    /// Check only `modules[i]` for each `i` in `targets`, with all of `modules` as the
+    /// cross-module context, so the result is what `lint_paths` over the same files reports
+    /// for those files. The diagnostics cache is not used.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn check_in_context(
+        &self,
+        modules: &[ParsedModule],
+        targets: &[usize],
+        project: Option<&ProjectIndex>,
+    ) -> Result<Vec<Violation>> {
+        let fixture_map = collect_all_fixtures(modules);
+        let used_fixture_names = compute_used_fixture_names(modules);
+        let fixture_locations = compute_fixture_locations(modules);
+        let session_mutable_fixtures = compute_session_mutable_fixtures(modules);
+        let ctx = RuleContext {
+            fixture_map: &fixture_map,
+            used_fixture_names: &used_fixture_names,
+            fixture_locations: &fixture_locations,
+            session_mutable_fixtures: &session_mutable_fixtures,
+            project: project.map(ProjectContext::new),
+            options: RuleOptions::default(),
+            tree: None,
+        };
+        self.analyzed
+            .fetch_add(targets.len(), AtomicOrdering::Relaxed);
+
+        let per_module = self.on_pool(|| {
+            targets
+                .par_iter()
+                .map(|&i| {
+                    let module = &modules[i];
+                    let checked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        self.dispatcher
+                            .check_module(module, modules, &ctx, &self.config)
+                    }));
+                    match checked {
+                        Ok(result) => result.map(|v| (v, None)),
+                        Err(panic) => Ok((
+                            vec![],
+                            Some(internal_error(&module.file_path, "checking", &*panic)),
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+        let (violations, internal_errors): (Vec<_>, Vec<_>) = per_module.into_iter().unzip();
+
+        let suppressions = collect_suppressions(modules);
+        let directives = collect_directive_suppressions(modules);
+        let mut violations: Vec<Violation> = violations
+            .into_iter()
+            .flatten()
+            .filter(|v| !is_suppressed(v, &suppressions) && !directives.suppresses(v))
+            .chain(internal_errors.into_iter().flatten())
+            .collect();
+        violations.sort_by(output_order);
+        dedup_diagnostics(&mut violations);
+        Ok(self.filter_changed_lines(violations))
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub fn lint_source(&self, source: &str, file_path: &Path) -> Result<Vec<Violation>> {
         self.lint_source_with_context(source, file_path, &[])
@@ -179,7 +443,7 @@ impl LintEngine {
         file_path: &Path,
         context_modules: &[ParsedModule],
     ) -> Result<Vec<Violation>> {
-        let mut parser = crate::parser::PythonParser::new()?;
+        let mut parser = PythonParser::with_options(ParseOptions::from_config(&self.config))?;
         let module = parser.parse_source(source, file_path)?;
 
         let mut all_modules: Vec<ParsedModule> = context_modules.to_vec();
@@ -196,13 +460,22 @@ impl LintEngine {
             used_fixture_names: &used_fixture_names,
             fixture_locations: &fixture_locations,
             session_mutable_fixtures: &session_mutable_fixtures,
+            project: None,
+            options: RuleOptions::default(),
+            tree: None,
         };
 
         let violations = self
             .dispatcher
             .check_module(primary, &all_modules, &ctx, &self.config)?;
 
-        Ok(violations)
+        let primary_slice = std::slice::from_ref(primary);
+        let suppressions = collect_suppressions(primary_slice);
+        let directives = collect_directive_suppressions(primary_slice);
+        Ok(violations
+            .into_iter()
+            .filter(|v| !is_suppressed(v, &suppressions) && !directives.suppresses(v))
+            .collect())
     }
 }
 
@@ -231,46 +504,6 @@ pub const DEFAULT_EXCLUDED_DIRS: &[&str] = &[
     "__pycache__",
 ];
 
-/// Discover test files from the given paths (files or directories),
-/// skipping any directory whose name is in `exclude_dirs`.
-fn discover_files(paths: &[PathBuf], exclude_dirs: &[String]) -> Vec<PathBuf> {
-    let exclude_set: std::collections::HashSet<&str> =
-        exclude_dirs.iter().map(|s| s.as_str()).collect();
-
-    let mut files = Vec::new();
-
-    for path in paths {
-        if path.is_file() && is_py_test_file(path) {
-            files.push(path.clone());
-        } else if path.is_dir() {
-            for entry in WalkDir::new(path)
-                .into_iter()
-                .filter_entry(|e| {
-                    // Prune directories that match any exclusion pattern
-                    if e.file_type().is_dir() {
-                        if let Some(name) = e.file_name().to_str() {
-                            if exclude_set.contains(name) {
-                                return false;
-                            }
-                        }
-                    }
-                    true
-                })
-                .filter_map(std::result::Result::ok)
-            {
-                let p = entry.path();
-                if p.is_file() && is_py_test_file(p) {
-                    files.push(p.to_path_buf());
-                }
-            }
-        }
-    }
-
-    files.sort();
-    files.dedup();
-    files
-}
-
 /// Check if a file is a Python test file by naming convention.
 fn is_test_file(path: &Path) -> bool {
     let name = path
@@ -280,21 +513,152 @@ fn is_test_file(path: &Path) -> bool {
     name.starts_with("test_") || name.ends_with("_test.py") || name == "conftest.py"
 }
 
-/// Parse multiple files in parallel using rayon.
-fn parse_files_parallel(files: &[PathBuf]) -> Vec<ParsedModule> {
-    files
+/// Rule ID of the diagnostic reported when the linter itself fails on a file.
+pub const INTERNAL_ERROR_RULE_ID: &str = "PYTEST-INT-001";
+
+/// Turn a panic caught while processing `file` into an error diagnostic for that file.
+fn internal_error(file: &Path, stage: &str, panic: &(dyn std::any::Any + Send)) -> Violation {
+    let detail = panic
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    failure_diagnostic(
+        file,
+        format!("Internal error while {stage} this file: {detail}"),
+        "Please report this as a pytest-linter bug, including the file if possible",
+    )
+}
+
+/// A `PYTEST-INT-001` error diagnostic: the linter could not check `file`.
+pub(crate) fn failure_diagnostic(file: &Path, message: String, suggestion: &str) -> Violation {
+    make_violation(
+        INTERNAL_ERROR_RULE_ID,
+        "InternalError",
+        Severity::Error,
+        Category::Maintenance,
+        message,
+        file.to_path_buf(),
+        1,
+        Some(suggestion.to_string()),
+        None,
+    )
+}
+
+/// Order in which violations are reported: by path, then span, then rule, so output is
+/// identical however the work was scheduled.
+pub(crate) fn output_order(a: &Violation, b: &Violation) -> Ordering {
+    a.file_path
+        .cmp(&b.file_path)
+        .then(a.line.cmp(&b.line))
+        .then(a.col.cmp(&b.col))
+        .then(a.end_line.cmp(&b.end_line))
+        .then(a.end_col.cmp(&b.end_col))
+        .then(a.rule_id.cmp(&b.rule_id))
+        .then_with(|| a.message.cmp(&b.message))
+}
+
+/// Drop diagnostics that repeat an earlier one exactly: same file, span, rule and message.
+/// This happens when a file is reached through two paths, e.g. `tests` and
+/// `tests/unit/../test_a.py`, or through a symlink. Files are compared by canonical path, and the
+/// copy first in output order is kept, so `violations` must already be sorted.
+pub(crate) fn dedup_diagnostics(violations: &mut Vec<Violation>) {
+    let mut canonical: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut seen = HashSet::new();
+    violations.retain(|v| {
+        let file = canonical
+            .entry(v.file_path.clone())
+            .or_insert_with(|| {
+                v.file_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| v.file_path.clone())
+            })
+            .clone();
+        seen.insert((
+            file,
+            v.line,
+            v.col,
+            v.end_line,
+            v.end_col,
+            v.rule_id.clone(),
+            v.message.clone(),
+        ))
+    });
+}
+
+/// What the cache knows about a parsed file.
+#[derive(Default)]
+struct CacheState {
+    content_hash: String,
+    diagnostics: Option<CachedDiagnostics>,
+}
+
+/// Parse one file, reusing the cached parse when its content is unchanged.
+fn parse_file_cached(
+    file: &Path,
+    cache: Option<&Cache>,
+    options: &ParseOptions,
+) -> Result<(ParsedModule, CacheState)> {
+    let Some(cache) = cache else {
+        let module = PythonParser::with_options(options.clone())?.parse_file(file)?;
+        return Ok((module, CacheState::default()));
+    };
+    let source = std::fs::read_to_string(file)?;
+    let content_hash = Cache::content_hash(&format!("{}{source}", options.cache_salt()));
+    if let Some(cached) = cache.load(file, &content_hash) {
+        return Ok((
+            cached.module,
+            CacheState {
+                content_hash,
+                diagnostics: cached.diagnostics,
+            },
+        ));
+    }
+    let module = PythonParser::with_options(options.clone())?.parse_source(&source, file)?;
+    Ok((
+        module,
+        CacheState {
+            content_hash,
+            diagnostics: None,
+        },
+    ))
+}
+
+type ParsedFile = (ParsedModule, CacheState);
+
+/// Parse multiple files in parallel using rayon. A panic while parsing one file becomes an
+/// internal-error diagnostic for it instead of aborting the run.
+fn parse_files_parallel(
+    files: &[PathBuf],
+    cache: Option<&Cache>,
+    options: &ParseOptions,
+) -> (Vec<ParsedFile>, Vec<Violation>) {
+    let results: Vec<Option<std::result::Result<ParsedFile, Violation>>> = files
         .par_iter()
-        .filter_map(|file| {
-            let mut parser = crate::parser::PythonParser::new().ok()?;
-            match parser.parse_file(file) {
-                Ok(m) => Some(m),
-                Err(e) => {
-                    eprintln!("Warning: failed to parse {}: {}", file.display(), e);
-                    None
-                }
+        .map(|file| {
+            let parsed =
+                std::panic::catch_unwind(|| match parse_file_cached(file, cache, options) {
+                    Ok(m) => Some(m),
+                    Err(e) => {
+                        eprintln!("Warning: failed to parse {}: {}", file.display(), e);
+                        None
+                    }
+                });
+            match parsed {
+                Ok(module) => module.map(Ok),
+                Err(panic) => Some(Err(internal_error(file, "parsing", &*panic))),
             }
         })
-        .collect()
+        .collect();
+    let mut modules = Vec::new();
+    let mut errors = Vec::new();
+    for result in results.into_iter().flatten() {
+        match result {
+            Ok(module) => modules.push(module),
+            Err(error) => errors.push(error),
+        }
+    }
+    (modules, errors)
 }
 
 type SuppressionMap = HashMap<(PathBuf, usize), HashSet<String>>;
@@ -371,6 +735,68 @@ fn is_suppressed(violation: &Violation, suppressions: &SuppressionMap) -> bool {
     false
 }
 
+/// Suppressions declared with `# pytl: ignore[...]` / `# pytl: ignore-file[...]` comments,
+/// keyed by canonical rule ID (`*` means every rule).
+#[derive(Debug, Default)]
+struct DirectiveSuppressions {
+    lines: SuppressionMap,
+    files: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl DirectiveSuppressions {
+    fn suppresses(&self, violation: &Violation) -> bool {
+        let matches =
+            |rules: &HashSet<String>| rules.contains("*") || rules.contains(&violation.rule_id);
+        self.files.get(&violation.file_path).is_some_and(matches)
+            || self
+                .lines
+                .get(&(violation.file_path.clone(), violation.line))
+                .is_some_and(matches)
+    }
+}
+
+fn collect_directive_suppressions(modules: &[ParsedModule]) -> DirectiveSuppressions {
+    use crate::rules::suppression::{
+        directive_target_line, first_statement_line, parse_suppression_directive, SuppressionScope,
+    };
+
+    let mut out = DirectiveSuppressions::default();
+    for module in modules {
+        let first_stmt = first_statement_line(&module.source);
+        for comment in &module.comments {
+            let Some(directive) = parse_suppression_directive(&comment.text) else {
+                continue;
+            };
+            // Unknown rule names are reported by PYTEST-SUP-001 rather than silently matching nothing.
+            let rules: HashSet<String> = if directive.rules.is_empty() {
+                HashSet::from(["*".to_string()])
+            } else {
+                directive
+                    .rules
+                    .iter()
+                    .filter_map(|r| crate::rules::resolve_rule_key(r))
+                    .collect()
+            };
+            match directive.scope {
+                SuppressionScope::File if comment.line <= first_stmt => {
+                    out.files
+                        .entry(module.file_path.clone())
+                        .or_default()
+                        .extend(rules);
+                }
+                SuppressionScope::File => {}
+                SuppressionScope::Line => {
+                    out.lines
+                        .entry((module.file_path.clone(), directive_target_line(comment)))
+                        .or_default()
+                        .extend(rules);
+                }
+            }
+        }
+    }
+    out
+}
+
 /// Build a map of fixture name to all fixture definitions across modules.
 #[must_use]
 pub fn collect_all_fixtures(modules: &[ParsedModule]) -> HashMap<String, Vec<&Fixture>> {
@@ -501,8 +927,14 @@ pub fn make_violation(
         file_path,
         line,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion,
         test_name,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }
 }
 
@@ -535,7 +967,67 @@ pub fn get_changed_files(base: &str) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
-/// Run the full linter pipeline: discover, lint, format output. Returns true if errors found.
+/// Result of a lint run, as reflected in the process exit code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintStatus {
+    /// Nothing violates the exit-code policy.
+    Clean,
+    /// The policy failed, for the given reason (e.g. "2 errors").
+    Failed(String),
+    /// The linter itself failed on at least one file.
+    InternalError,
+}
+
+/// Why `violations` fail the run under the configured `fail_on` / `max_warnings` policy,
+/// or `None` if they pass.
+#[must_use]
+pub fn policy_failure(violations: &[Violation], config: &Config) -> Option<String> {
+    let fail_on = config.fail_on.unwrap_or(Severity::Error);
+    let counted =
+        |v: &&Violation| !(config.ignore_syntax_errors && v.rule_id == SYNTAX_ERROR_RULE_ID);
+    let count = |severity: Severity| {
+        violations
+            .iter()
+            .filter(counted)
+            .filter(|v| v.severity == severity)
+            .count()
+    };
+    let mut reasons = Vec::new();
+    for (severity, singular, plural) in [
+        (Severity::Error, "error", "errors"),
+        (Severity::Warning, "warning", "warnings"),
+        (Severity::Info, "info", "info"),
+        (Severity::Hint, "hint", "hints"),
+    ] {
+        let n = count(severity);
+        if n > 0 && severity.is_at_least(fail_on) {
+            reasons.push(format!("{n} {}", if n == 1 { singular } else { plural }));
+        }
+    }
+    if let Some(limit) = config.max_warnings {
+        let warnings = count(Severity::Warning);
+        if warnings > limit {
+            reasons.push(format!("warnings exceeded limit {limit}: found {warnings}"));
+        }
+    }
+    (!reasons.is_empty()).then(|| reasons.join(", "))
+}
+
+/// Exit status for a set of reported violations: internal errors take precedence over
+/// policy failures.
+#[must_use]
+pub fn lint_status(violations: &[Violation], config: &Config) -> LintStatus {
+    if violations
+        .iter()
+        .any(|v| v.rule_id == INTERNAL_ERROR_RULE_ID)
+    {
+        return LintStatus::InternalError;
+    }
+    policy_failure(violations, config).map_or(LintStatus::Clean, LintStatus::Failed)
+}
+
+/// Run the full linter pipeline: discover, lint, format output. Returns true if the run
+/// fails (see [`lint_status`]).
 #[allow(clippy::missing_errors_doc)]
 pub fn run_linter(
     paths: &[PathBuf],
@@ -544,7 +1036,8 @@ pub fn run_linter(
     no_color: bool,
     config: Config,
 ) -> Result<bool> {
-    run_linter_with_memory_limit(paths, format, output, no_color, config, 256)
+    let status = run_linter_with_memory_limit(paths, format, output, no_color, config, 256)?;
+    Ok(status != LintStatus::Clean)
 }
 
 #[allow(clippy::missing_errors_doc)]
@@ -555,95 +1048,255 @@ pub fn run_linter_with_memory_limit(
     no_color: bool,
     config: Config,
     memory_limit_mb: usize,
-) -> Result<bool> {
-    if no_color {
-        colored::control::set_override(false);
-    }
+) -> Result<LintStatus> {
+    colored::control::set_override(crate::output::terminal::use_color(no_color, output));
+
+    let report_config = config.clone();
+    let report = crate::api::run(paths, config, memory_limit_mb)?;
+    let violations = report.violations();
+
+    write_output(
+        &violations,
+        format,
+        output,
+        &report_config,
+        Some(report.files),
+        &mut SourceCache::default(),
+    )?;
+    eprint!(
+        "{}",
+        format_run_summary(&violations, report.files, report.cache, &report_config)
+    );
 
-    let engine = LintEngine::with_memory_limit(config, memory_limit_mb)?;
-    let violations = engine.lint_paths(paths)?;
+    Ok(lint_status(&violations, &report_config))
+}
 
-    match format {
-        "json" => format_json(&violations, output)?,
-        "sarif" => format_sarif(&violations, output)?,
-        _ => format_terminal(&violations, output)?,
+/// What `--statistics` and `--top-files` print after the diagnostics, or an empty string
+/// when neither is set.
+#[must_use]
+pub fn format_run_summary(
+    violations: &[Violation],
+    files: FileCounts,
+    cache: Option<CacheStats>,
+    config: &Config,
+) -> String {
+    if !config.statistics && config.top_files.is_none() {
+        return String::new();
+    }
+    let summary = Summary::new(violations, Some(files));
+    let mut out = String::new();
+    if config.statistics {
+        out.push_str(&format_statistics(&summary, cache));
     }
+    if let Some(n) = config.top_files {
+        out.push_str(&format_top_files(&summary, n));
+    }
+    out
+}
 
-    Ok(violations.iter().any(|v| v.severity == Severity::Error))
+/// Lint `paths` like [`collect_violations`], also returning the file counts of the run and
+/// the cache statistics.
+#[allow(clippy::missing_errors_doc)]
+pub fn collect_run(
+    paths: &[PathBuf],
+    config: Config,
+) -> Result<(Vec<Violation>, FileCounts, Option<CacheStats>)> {
+    let report = crate::api::lint_paths(paths, &config)?;
+    Ok((report.violations(), report.files, report.cache))
 }
 
 /// Collect all violations from the given paths without producing output.
 #[allow(clippy::missing_errors_doc)]
 pub fn collect_violations(paths: &[PathBuf], config: Config) -> Result<Vec<Violation>> {
-    let engine = LintEngine::new(config)?;
-    engine.lint_paths(paths)
+    Ok(crate::api::lint_paths(paths, &config)?.violations())
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct BaselineEntry {
-    file_path: String,
-    line: usize,
-    rule_id: String,
+/// Lint a buffer as if it were the file at `path`, which need not exist: `path` decides
+/// whether it is a test file, which overrides apply and where diagnostics are reported.
+/// Blank buffers and non-test paths have no diagnostics.
+#[allow(clippy::missing_errors_doc)]
+pub fn lint_buffer(source: &str, path: &Path, config: Config) -> Result<Vec<Violation>> {
+    if source.trim().is_empty() || !FileDiscovery::from_config(&config)?.is_test_file(path) {
+        return Ok(vec![]);
+    }
+    let mut violations = LintEngine::new(config)?.lint_source(source, path)?;
+    violations.sort_by(output_order);
+    Ok(violations)
+}
+
+/// Lint the given paths and apply every available fix (or, with `dry_run`, only compute
+/// the diff). Re-lint afterwards to report what remains.
+#[allow(clippy::missing_errors_doc)]
+pub fn fix_paths(paths: &[PathBuf], config: Config, dry_run: bool) -> Result<FixSummary> {
+    let violations = collect_violations(paths, config)?;
+    crate::fix::fix_files(&violations, dry_run)
 }
 
 /// Save a baseline of known violations to a JSON file.
 #[allow(clippy::missing_errors_doc)]
 pub fn save_baseline(violations: &[Violation], path: &Path) -> Result<()> {
-    let entries: Vec<BaselineEntry> = violations
-        .iter()
-        .map(|v| BaselineEntry {
-            file_path: v.file_path.to_string_lossy().to_string(),
-            line: v.line,
-            rule_id: v.rule_id.clone(),
-        })
-        .collect();
-    let json = serde_json::to_string_pretty(&entries)?;
-    std::fs::write(path, json)?;
-    Ok(())
+    Baseline::from_violations(violations).save(path)
 }
 
 /// Load a baseline of known violations from a JSON file.
 #[allow(clippy::missing_errors_doc)]
-pub fn load_baseline(path: &Path) -> Result<HashSet<(String, usize, String)>> {
-    let content = std::fs::read_to_string(path)?;
-    let entries: Vec<BaselineEntry> = serde_json::from_str(&content)?;
-    let set: HashSet<(String, usize, String)> = entries
-        .into_iter()
-        .map(|e| (e.file_path, e.line, e.rule_id))
-        .collect();
-    Ok(set)
+pub fn load_baseline(path: &Path) -> Result<Baseline> {
+    Baseline::load(path)
 }
 
 /// Filter violations to only those not present in the baseline.
+#[must_use]
+pub fn filter_new_violations(violations: &[Violation], baseline: &Baseline) -> Vec<Violation> {
+    baseline.apply(violations).new_violations
+}
+
+/// Write violations in `format` to the given path or stdout. `files` feeds the JSON summary
+/// and `sources` provides file contents for snippets and byte offsets.
 #[allow(clippy::missing_errors_doc)]
-pub fn filter_new_violations(
+pub fn write_output(
     violations: &[Violation],
-    baseline: &HashSet<(String, usize, String)>,
-) -> Vec<Violation> {
-    violations
-        .iter()
-        .filter(|v| {
-            let key = (
-                v.file_path.to_string_lossy().to_string(),
-                v.line,
-                v.rule_id.clone(),
+    format: &str,
+    output: Option<&Path>,
+    config: &Config,
+    files: Option<FileCounts>,
+    sources: &mut SourceCache,
+) -> Result<()> {
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    write_format(violations, format, &mut writer, config, files, sources)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write violations in `format` to `writer`.
+#[allow(clippy::missing_errors_doc)]
+pub fn write_format(
+    violations: &[Violation],
+    format: &str,
+    writer: &mut dyn Write,
+    config: &Config,
+    files: Option<FileCounts>,
+    sources: &mut SourceCache,
+) -> Result<()> {
+    match format {
+        "json" => {
+            let json = crate::output::json::format_json(violations, config, files, sources)?;
+            writeln!(writer, "{json}")?;
+        }
+        "json-lines" => crate::output::json::write_json_lines(violations, writer, sources)?,
+        "sarif" => writeln!(
+            writer,
+            "{}",
+            crate::output::sarif::format_sarif(&char_columns(violations, sources))?
+        )?,
+        "github" => {
+            write!(
+                writer,
+                "{}",
+                crate::output::github::format_github(&char_columns(violations, sources))
+            )?;
+            // The annotations are for the runner; people reading the job log still get the
+            // summary.
+            let (error_count, warning_count, info_count, hint_count) = severity_counts(violations);
+            eprintln!(
+                "Summary: {error_count} errors, {warning_count} warnings, {info_count} info{}",
+                hint_suffix(hint_count)
             );
-            !baseline.contains(&key)
-        })
-        .cloned()
-        .collect()
+        }
+        "checkstyle" => write!(
+            writer,
+            "{}",
+            crate::output::checkstyle::format_checkstyle(&char_columns(violations, sources))
+        )?,
+        "junit" => write!(
+            writer,
+            "{}",
+            crate::output::junit::format_junit(&char_columns(violations, sources))
+        )?,
+        "short" => write_terminal(violations, writer, true, sources)?,
+        _ => write_terminal(violations, writer, false, sources)?,
+    }
+    Ok(())
 }
 
-/// Format violations as JSON and write to the given path or stdout.
+/// Format violations as a JSON report and write to the given path or stdout.
 #[allow(clippy::missing_errors_doc)]
-pub fn format_json_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
-    format_json(violations, output)
+pub fn format_json_output(
+    violations: &[Violation],
+    config: &Config,
+    output: Option<&Path>,
+) -> Result<()> {
+    write_output(
+        violations,
+        "json",
+        output,
+        config,
+        None,
+        &mut SourceCache::default(),
+    )
+}
+
+/// Like [`format_json_output`], with the file counts of the run in the report summary.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_json_run_output(
+    violations: &[Violation],
+    config: &Config,
+    files: FileCounts,
+    output: Option<&Path>,
+) -> Result<()> {
+    write_output(
+        violations,
+        "json",
+        output,
+        config,
+        Some(files),
+        &mut SourceCache::default(),
+    )
+}
+
+/// Write violations in a format that needs no configuration.
+fn write_plain(violations: &[Violation], format: &str, output: Option<&Path>) -> Result<()> {
+    write_output(
+        violations,
+        format,
+        output,
+        &Config::default(),
+        None,
+        &mut SourceCache::default(),
+    )
+}
+
+/// Write violations as JSON lines (one diagnostic per line) to the given path or stdout.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_json_lines_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
+    write_plain(violations, "json-lines", output)
 }
 
 /// Format violations as SARIF and write to the given path or stdout.
 #[allow(clippy::missing_errors_doc)]
 pub fn format_sarif_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
-    format_sarif(violations, output)
+    write_plain(violations, "sarif", output)
+}
+
+/// Write violations as GitHub Actions workflow commands to the given path or stdout, with
+/// the summary line on stderr.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_github_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
+    write_plain(violations, "github", output)
+}
+
+/// Format violations as Checkstyle XML and write to the given path or stdout.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_checkstyle_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
+    write_plain(violations, "checkstyle", output)
+}
+
+/// Format violations as JUnit XML and write to the given path or stdout.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_junit_output(violations: &[Violation], output: Option<&Path>) -> Result<()> {
+    write_plain(violations, "junit", output)
 }
 
 /// Format violations for terminal display and write to the given path or stdout.
@@ -653,104 +1306,80 @@ pub fn format_terminal_output(
     output: Option<&Path>,
     no_color: bool,
 ) -> Result<()> {
-    if no_color {
-        colored::control::set_override(false);
-    }
-    format_terminal(violations, output)
+    colored::control::set_override(crate::output::terminal::use_color(no_color, output));
+    write_plain(violations, "terminal", output)
 }
 
-fn format_terminal(violations: &[Violation], output_path: Option<&Path>) -> Result<()> {
-    let mut writer: Box<dyn Write> = match output_path {
-        Some(path) => Box::new(std::fs::File::create(path)?),
-        None => Box::new(std::io::stdout()),
-    };
+/// Format violations one per line (`--format short`) and write to the given path or stdout.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_short_output(
+    violations: &[Violation],
+    output: Option<&Path>,
+    no_color: bool,
+) -> Result<()> {
+    colored::control::set_override(crate::output::terminal::use_color(no_color, output));
+    write_plain(violations, "short", output)
+}
 
+fn write_terminal(
+    violations: &[Violation],
+    writer: &mut dyn Write,
+    short: bool,
+    sources: &mut SourceCache,
+) -> Result<()> {
     if violations.is_empty() {
         writeln!(writer, "{} No violations found", "✓".green())?;
         return Ok(());
     }
 
-    let error_count = violations
-        .iter()
-        .filter(|v| v.severity == Severity::Error)
-        .count();
-    let warning_count = violations
-        .iter()
-        .filter(|v| v.severity == Severity::Warning)
-        .count();
-    let info_count = violations
-        .iter()
-        .filter(|v| v.severity == Severity::Info)
-        .count();
-
-    for v in violations {
-        let severity_str = match v.severity {
-            Severity::Error => "ERROR".red().bold(),
-            Severity::Warning => "WARNING".yellow().bold(),
-            Severity::Info => "INFO".blue().bold(),
-        };
+    let (error_count, warning_count, info_count, hint_count) = severity_counts(violations);
 
-        let location = format!(
-            "{}:{}:{}",
-            v.file_path.display(),
-            v.line,
-            v.col.map_or_else(|| "-".to_string(), |c| c.to_string())
-        );
-
-        writeln!(
+    if short {
+        write!(
             writer,
-            "{} [{}] {} ({})",
-            severity_str, v.rule_id, v.message, location
+            "{}",
+            crate::output::terminal::render_short(&char_columns(violations, sources))
+        )?;
+        writeln!(writer)?;
+    } else {
+        write!(
+            writer,
+            "{}",
+            crate::output::terminal::render_pretty_with(violations, sources)
         )?;
-
-        if let Some(ref suggestion) = v.suggestion {
-            writeln!(writer, "  {} {}", "→".cyan(), suggestion)?;
-        }
-
-        if let Some(ref test_name) = v.test_name {
-            writeln!(writer, "  {} test: {}", "→".dimmed(), test_name)?;
-        }
     }
-
-    writeln!(writer)?;
     writeln!(
         writer,
-        "{}: {} errors, {} warnings, {} info",
+        "{}: {} errors, {} warnings, {} info{}",
         "Summary".bold(),
         error_count.to_string().red(),
         warning_count.to_string().yellow(),
-        info_count.to_string().blue()
+        info_count.to_string().blue(),
+        hint_suffix(hint_count)
     )?;
 
     Ok(())
 }
 
-fn format_json(violations: &[Violation], output_path: Option<&Path>) -> Result<()> {
-    let json = serde_json::to_string_pretty(violations)?;
-
-    match output_path {
-        Some(path) => {
-            let mut file = std::fs::File::create(path)?;
-            file.write_all(json.as_bytes())?;
-        }
-        None => println!("{json}"),
-    }
-
-    Ok(())
+/// Number of errors, warnings, info and hint diagnostics.
+fn severity_counts(violations: &[Violation]) -> (usize, usize, usize, usize) {
+    let count = |severity: Severity| violations.iter().filter(|v| v.severity == severity).count();
+    (
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info),
+        count(Severity::Hint),
+    )
 }
 
-fn format_sarif(violations: &[Violation], output_path: Option<&Path>) -> Result<()> {
-    let json = crate::output::format_sarif(violations)?;
-
-    match output_path {
-        Some(path) => {
-            let mut file = std::fs::File::create(path)?;
-            file.write_all(json.as_bytes())?;
-        }
-        None => println!("{json}"),
+/// `, N hints` for the summary line; empty without hints, so the line reads as before for
+/// runs that report none.
+fn hint_suffix(hints: usize) -> String {
+    match hints {
+        0 => String::new(),
+        1 => ", 1 hint".to_string(),
+        n => format!(", {n} hints"),
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -759,6 +1388,12 @@ mod tests {
     use std::fs;
     use std::path::PathBuf;
 
+    fn discover_files(paths: &[PathBuf], excludes: &[String]) -> Vec<PathBuf> {
+        FileDiscovery::new(excludes, &[], &[])
+            .unwrap()
+            .discover(paths)
+    }
+
     #[test]
     fn test_is_test_file_detects_test_prefix() {
         assert!(is_test_file(Path::new("test_foo.py")));
@@ -821,6 +1456,111 @@ mod tests {
         );
     }
 
+    fn lint_with_engine(source: &str) -> Vec<Violation> {
+        let engine = LintEngine::new(crate::config::Config::default()).unwrap();
+        engine.lint_source(source, Path::new("test_x.py")).unwrap()
+    }
+
+    #[test]
+    fn test_rule_options_reach_rules() {
+        let source = format!("def test_{}():\n    assert True\n", "a".repeat(90));
+        assert!(lint_with_engine(&source)
+            .iter()
+            .any(|v| v.rule_id == "PYTEST-MNT-017"));
+
+        let mut config = crate::config::Config::default();
+        let rc = config
+            .rules
+            .entry("PYTEST-MNT-017".to_string())
+            .or_default();
+        rc.options
+            .insert("max_length".to_string(), toml::Value::Integer(120));
+        let engine = LintEngine::new(config).unwrap();
+        let violations = engine.lint_source(&source, Path::new("test_x.py")).unwrap();
+        assert!(!violations.iter().any(|v| v.rule_id == "PYTEST-MNT-017"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_trailing_suppresses_named_rule() {
+        let source = "def test_a():  # pytl: ignore[no-assertion]\n    pass\n";
+        let violations = lint_with_engine(source);
+        assert!(!violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_own_line_applies_to_next_line() {
+        let source = "# pytl: ignore[PYTEST-MNT-004]\ndef test_a():\n    pass\n";
+        let violations = lint_with_engine(source);
+        assert!(!violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_other_rule_keeps_violation() {
+        let source = "def test_a():  # pytl: ignore[PYTEST-FLK-001]\n    pass\n";
+        let violations = lint_with_engine(source);
+        assert!(violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+    }
+
+    #[test]
+    fn test_pytl_bare_ignore_suppresses_all_rules_on_line() {
+        let source = "import time\ndef test_a():  # pytl: ignore\n    time.sleep(1)\n";
+        let violations = lint_with_engine(source);
+        assert!(!violations.iter().any(|v| v.line == 2));
+    }
+
+    #[test]
+    fn test_pytl_ignore_file_suppresses_whole_file() {
+        let source = "# pytl: ignore-file[no-assertion]\ndef test_a():\n    pass\n\ndef test_b():\n    pass\n";
+        let violations = lint_with_engine(source);
+        assert!(!violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_file_after_first_statement_is_not_honoured() {
+        let source = "import os\n# pytl: ignore-file[no-assertion]\ndef test_a():\n    pass\n";
+        let violations = lint_with_engine(source);
+        assert!(violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+        assert!(violations.iter().any(|v| v.rule_id == "PYTEST-SUP-001"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_file_on_first_statement_or_after_docstring_is_honoured() {
+        for source in [
+            "import os  # pytl: ignore-file[no-assertion]\ndef test_a():\n    pass\n",
+            "\"\"\"Tests.\"\"\"\n\n# pytl: ignore-file[no-assertion]\ndef test_a():\n    pass\n",
+        ] {
+            let violations = lint_with_engine(source);
+            assert!(
+                !violations
+                    .iter()
+                    .any(|v| v.rule_id == "PYTEST-MNT-004" || v.rule_id == "PYTEST-SUP-001"),
+                "{source}: {violations:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pytl_ignore_unknown_rule_is_reported() {
+        let source = "def test_a():  # pytl: ignore[no-asertion]\n    pass\n";
+        let violations = lint_with_engine(source);
+        let unknown = violations
+            .iter()
+            .find(|v| v.rule_id == "PYTEST-SUP-001")
+            .expect("typo in suppression should be reported");
+        assert!(unknown.message.contains("no-asertion"));
+        assert!(violations.iter().any(|v| v.rule_id == "PYTEST-MNT-004"));
+    }
+
+    #[test]
+    fn test_pytl_ignore_inside_string_is_not_a_directive() {
+        let source = "def test_a():\n    s = '# pytl: ignore'\n    assert s\n";
+        let module = crate::parser::PythonParser::new()
+            .unwrap()
+            .parse_source(source, Path::new("test_x.py"))
+            .unwrap();
+        assert!(module.comments.is_empty());
+    }
+
     #[test]
     fn test_is_suppressed_by_rule_id() {
         use crate::models::Violation;
@@ -833,8 +1573,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let mut suppressions = std::collections::HashMap::new();
         suppressions.insert(
@@ -856,8 +1602,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let mut suppressions = std::collections::HashMap::new();
         suppressions.insert(
@@ -882,13 +1634,92 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let suppressions = std::collections::HashMap::new();
         assert!(!is_suppressed(&v, &suppressions));
     }
 
+    #[test]
+    fn test_internal_error_from_panic() {
+        let panic = std::panic::catch_unwind(|| panic!("index out of bounds")).unwrap_err();
+        let v = internal_error(Path::new("test_x.py"), "parsing", &*panic);
+        assert_eq!(v.rule_id, INTERNAL_ERROR_RULE_ID);
+        assert_eq!(v.severity, Severity::Error);
+        assert_eq!(
+            v.message,
+            "Internal error while parsing this file: index out of bounds"
+        );
+    }
+
+    #[test]
+    fn test_output_order_uses_span() {
+        let at = |line, col| {
+            let mut v = make_violation(
+                "PYTEST-FLK-001",
+                "TimeSleepRule",
+                Severity::Warning,
+                Category::Flakiness,
+                String::new(),
+                PathBuf::from("test_a.py"),
+                line,
+                None,
+                None,
+            );
+            v.col = col;
+            v
+        };
+        let mut violations = [at(2, Some(9)), at(2, Some(5)), at(1, None)];
+        violations.sort_by(output_order);
+        let positions: Vec<_> = violations.iter().map(|v| (v.line, v.col)).collect();
+        assert_eq!(positions, vec![(1, None), (2, Some(5)), (2, Some(9))]);
+    }
+
+    #[test]
+    fn test_dedup_diagnostics_merges_paths_to_the_same_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("test_a.py");
+        fs::write(&file, "").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let detour = dir.path().join("sub").join("..").join("test_a.py");
+        let at = |path: PathBuf, line, message: &str| {
+            make_violation(
+                "PYTEST-FLK-001",
+                "TimeSleepRule",
+                Severity::Warning,
+                Category::Flakiness,
+                message.to_string(),
+                path,
+                line,
+                None,
+                None,
+            )
+        };
+        let mut violations = vec![
+            at(file.clone(), 1, "a"),
+            at(detour.clone(), 1, "a"),
+            at(file.clone(), 1, "b"),
+            at(file.clone(), 2, "a"),
+        ];
+        violations.sort_by(output_order);
+        dedup_diagnostics(&mut violations);
+        let kept: Vec<_> = violations
+            .iter()
+            .map(|v| (v.file_path.clone(), v.line, v.message.as_str()))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![(detour, 1, "a"), (file.clone(), 1, "b"), (file, 2, "a"),]
+        );
+    }
+
     #[test]
     fn test_violation_equality_same_key_different_rest() {
         use crate::models::Violation;
@@ -901,8 +1732,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = Violation {
             rule_id: "PYTEST-FLK-001".to_string(),
@@ -913,8 +1750,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: Some(10),
+            end_line: None,
+            end_col: None,
             suggestion: Some("fix".to_string()),
             test_name: Some("test_x".to_string()),
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         assert_eq!(v1, v2);
     }
@@ -931,8 +1774,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = Violation {
             rule_id: "PYTEST-FLK-001".to_string(),
@@ -943,8 +1792,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 6,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         assert_ne!(v1, v2);
     }
@@ -961,8 +1816,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = Violation {
             rule_id: "PYTEST-FLK-002".to_string(),
@@ -973,8 +1834,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         assert_ne!(v1, v2);
     }
@@ -992,8 +1859,14 @@ mod tests {
                 file_path: PathBuf::from("a.py"),
                 line: 1,
                 col: None,
+                end_line: None,
+                end_col: None,
                 suggestion: None,
                 test_name: None,
+                fix: None,
+                metadata: Default::default(),
+                tags: Vec::new(),
+                related: Vec::new(),
             },
             Violation {
                 rule_id: "PYTEST-FLK-002".to_string(),
@@ -1004,8 +1877,14 @@ mod tests {
                 file_path: PathBuf::from("a.py"),
                 line: 2,
                 col: None,
+                end_line: None,
+                end_col: None,
                 suggestion: None,
                 test_name: None,
+                fix: None,
+                metadata: Default::default(),
+                tags: Vec::new(),
+                related: Vec::new(),
             },
             Violation {
                 rule_id: "PYTEST-FLK-003".to_string(),
@@ -1016,13 +1895,19 @@ mod tests {
                 file_path: PathBuf::from("a.py"),
                 line: 3,
                 col: None,
+                end_line: None,
+                end_col: None,
                 suggestion: None,
                 test_name: None,
+                fix: None,
+                metadata: Default::default(),
+                tags: Vec::new(),
+                related: Vec::new(),
             },
         ];
         let tmp = tempfile::tempdir().unwrap();
         let path = tmp.path().join("out.txt");
-        format_terminal(&violations, Some(&path)).unwrap();
+        write_plain(&violations, "terminal", Some(&path)).unwrap();
         let contents = std::fs::read_to_string(&path).unwrap();
         assert!(contents.contains("2 error"), "should count 2 errors");
         assert!(contents.contains("1 warning"), "should count 1 warning");
@@ -1169,8 +2054,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 1,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let mut suppressions = std::collections::HashMap::new();
         // Insert a suppression at line 0 (which should NOT suppress line 1)
@@ -1206,6 +2097,7 @@ mod tests {
                 parametrize_count: None,
                 has_assertions: false,
                 assertion_count: 0,
+                assertion_site_count: 0,
                 has_mock_verifications: false,
                 has_state_assertions: false,
                 fixture_deps: vec![],
@@ -1234,6 +2126,7 @@ mod tests {
                 has_magic_mock: false,
                 mock_count: 0,
                 uses_shutil_copy: false,
+                is_unittest: false,
                 end_line: 1,
             }],
             fixtures: vec![
@@ -1269,6 +2162,8 @@ mod tests {
                     used_by: vec![],
                 },
             ],
+            comments: vec![],
+            syntax_errors: vec![],
         };
         assert!(
             !is_fixture_used_by_any_test_or_fixture(unused_fixture, &[module]),
@@ -1295,6 +2190,7 @@ mod tests {
                 parametrize_count: None,
                 has_assertions: false,
                 assertion_count: 0,
+                assertion_site_count: 0,
                 has_mock_verifications: false,
                 has_state_assertions: false,
                 fixture_deps: vec!["db_connection".to_string()],
@@ -1323,6 +2219,7 @@ mod tests {
                 has_magic_mock: false,
                 mock_count: 0,
                 uses_shutil_copy: false,
+                is_unittest: false,
                 end_line: 1,
             }],
             fixtures: vec![Fixture {
@@ -1340,6 +2237,8 @@ mod tests {
                 uses_file_io: false,
                 used_by: vec![],
             }],
+            comments: vec![],
+            syntax_errors: vec![],
         };
         assert!(
             is_fixture_used_by_any_test_or_fixture(fixture_name, &[module]),
@@ -1366,6 +2265,7 @@ mod tests {
                 parametrize_count: None,
                 has_assertions: false,
                 assertion_count: 0,
+                assertion_site_count: 0,
                 has_mock_verifications: false,
                 has_state_assertions: false,
                 fixture_deps: vec!["api_client".to_string()],
@@ -1394,6 +2294,7 @@ mod tests {
                 has_magic_mock: false,
                 mock_count: 0,
                 uses_shutil_copy: false,
+                is_unittest: false,
                 end_line: 1,
             }],
             fixtures: vec![
@@ -1428,6 +2329,8 @@ mod tests {
                     used_by: vec![],
                 },
             ],
+            comments: vec![],
+            syntax_errors: vec![],
         };
         assert!(
             is_fixture_used_by_any_test_or_fixture(fixture_name, &[module]),
@@ -1535,4 +2438,73 @@ mod tests {
             "should exclude .venv test files"
         );
     }
+
+    fn violation_with(severity: Severity) -> Violation {
+        make_violation(
+            "PYTEST-MNT-004",
+            "NoAssertionRule",
+            severity,
+            Category::Maintenance,
+            "m".to_string(),
+            PathBuf::from("test_x.py"),
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_policy_failure_respects_fail_on_and_max_warnings() {
+        let violations: Vec<Violation> = [Severity::Error, Severity::Error, Severity::Warning]
+            .into_iter()
+            .chain(std::iter::repeat_n(Severity::Info, 3))
+            .map(violation_with)
+            .collect();
+        let warnings_only = &violations[2..];
+        let mut config = Config::default();
+        assert_eq!(
+            policy_failure(&violations, &config).as_deref(),
+            Some("2 errors")
+        );
+        assert_eq!(policy_failure(warnings_only, &config), None);
+
+        config.fail_on = Some(Severity::Warning);
+        assert_eq!(
+            policy_failure(warnings_only, &config).as_deref(),
+            Some("1 warning")
+        );
+
+        config.fail_on = Some(Severity::Error);
+        config.max_warnings = Some(0);
+        assert_eq!(
+            policy_failure(warnings_only, &config).as_deref(),
+            Some("warnings exceeded limit 0: found 1")
+        );
+        config.max_warnings = Some(1);
+        assert_eq!(lint_status(warnings_only, &config), LintStatus::Clean);
+
+        let mut crashed = violation_with(Severity::Error);
+        crashed.rule_id = INTERNAL_ERROR_RULE_ID.to_string();
+        assert_eq!(lint_status(&[crashed], &config), LintStatus::InternalError);
+    }
+
+    #[test]
+    fn test_hints_fail_only_with_fail_on_hint() {
+        let violations = vec![
+            violation_with(Severity::Hint),
+            violation_with(Severity::Hint),
+        ];
+        let mut config = Config {
+            fail_on: Some(Severity::Info),
+            ..Config::default()
+        };
+        assert_eq!(policy_failure(&violations, &config), None);
+        config.fail_on = Some(Severity::Hint);
+        assert_eq!(
+            policy_failure(&violations, &config).as_deref(),
+            Some("2 hints")
+        );
+        assert!(Severity::Info.is_at_least(Severity::Hint));
+        assert!(!Severity::Hint.is_at_least(Severity::Info));
+    }
 }
@@ -0,0 +1,183 @@
+//! Rule catalogue behind `pytest-linter rules` and `pytest-linter explain`. Everything is
+//! read from the [`Rule`] trait of the registered rules, so the listing cannot drift from
+//! what the linter actually runs.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::models::{Category, Severity, Tag};
+use crate::rules::{
+    all_rules, resolve_rule_id, rule_docs_url, rule_slug, unknown_rule_message, Rule,
+};
+
+/// One configurable option of a rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionInfo {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub description: &'static str,
+}
+
+/// Metadata of one registered rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleInfo {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub slug: String,
+    pub aliases: &'static [&'static str],
+    pub description: String,
+    /// Severity before any configuration is applied.
+    pub default_severity: Severity,
+    pub category: Category,
+    pub fixable: bool,
+    /// Tags the rule's diagnostics carry, e.g. `unnecessary`.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub tags: &'static [Tag],
+    pub options: Vec<OptionInfo>,
+    pub docs_url: String,
+}
+
+impl RuleInfo {
+    fn from_rule(rule: &dyn Rule) -> Self {
+        Self {
+            id: rule.id(),
+            name: rule.name(),
+            slug: rule_slug(rule.name()),
+            aliases: rule.aliases(),
+            description: rule.description(),
+            default_severity: rule.severity(),
+            category: rule.category(),
+            fixable: rule.fixable(),
+            tags: rule.tags(),
+            options: rule
+                .options()
+                .iter()
+                .map(|o| OptionInfo {
+                    name: o.name,
+                    kind: o.kind.as_str(),
+                    description: o.description,
+                })
+                .collect(),
+            docs_url: rule_docs_url(rule.id()),
+        }
+    }
+}
+
+/// Every registered rule, sorted by ID.
+#[must_use]
+pub fn catalog() -> Vec<RuleInfo> {
+    let mut rules: Vec<RuleInfo> = all_rules()
+        .iter()
+        .map(|r| RuleInfo::from_rule(r.as_ref()))
+        .collect();
+    rules.sort_by_key(|r| r.id);
+    rules
+}
+
+/// Look a rule up by ID, name, slug or alias. Unknown references get the closest match
+/// as a suggestion.
+#[allow(clippy::missing_errors_doc)]
+pub fn find_rule(key: &str) -> Result<RuleInfo> {
+    let id = resolve_rule_id(key).ok_or_else(|| anyhow!("{}", unknown_rule_message(key)))?;
+    catalog()
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or_else(|| anyhow!("{}", unknown_rule_message(key)))
+}
+
+/// Table of rules, one per line: ID, slug, default severity, `fix` when fixable.
+#[must_use]
+pub fn format_rules_text(rules: &[RuleInfo]) -> String {
+    let slug_width = rules.iter().map(|r| r.slug.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for r in rules {
+        let line = format!(
+            "{:<17} {:<slug_width$}  {:<8} {}",
+            r.id,
+            r.slug,
+            r.default_severity.to_string().to_lowercase(),
+            if r.fixable { "fix" } else { "" }
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// The catalogue as a JSON array.
+#[allow(clippy::missing_errors_doc)]
+pub fn format_rules_json(rules: &[RuleInfo]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(rules)?)
+}
+
+/// Long-form description of one rule for `pytest-linter explain`.
+#[must_use]
+pub fn explain(rule: &RuleInfo) -> String {
+    let mut out = format!(
+        "{} — {} ({})\n\n{}\n\n",
+        rule.id, rule.slug, rule.name, rule.description
+    );
+    out.push_str(&format!(
+        "Severity:  {}\n",
+        rule.default_severity.to_string().to_lowercase()
+    ));
+    out.push_str(&format!(
+        "Category:  {}\n",
+        rule.category.to_string().to_lowercase()
+    ));
+    if !rule.aliases.is_empty() {
+        out.push_str(&format!("Aliases:   {}\n", rule.aliases.join(", ")));
+    }
+    if !rule.tags.is_empty() {
+        let tags: Vec<String> = rule.tags.iter().map(Tag::to_string).collect();
+        out.push_str(&format!("Tags:      {}\n", tags.join(", ")));
+    }
+    out.push_str(&format!(
+        "Autofix:   {}\n",
+        if rule.fixable { "yes (--fix)" } else { "no" }
+    ));
+    if !rule.options.is_empty() {
+        out.push_str(&format!("Options:   [rules.{}]\n", rule.id));
+        for o in &rule.options {
+            out.push_str(&format!("  {} ({})  {}\n", o.name, o.kind, o.description));
+        }
+    }
+    out.push_str(&format!("Docs:      {}\n", rule.docs_url));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_covers_every_rule_with_metadata() {
+        let rules = catalog();
+        assert_eq!(rules.len(), all_rules().len());
+        let sleep = rules.iter().find(|r| r.id == "PYTEST-FLK-001").unwrap();
+        assert_eq!(sleep.aliases, ["sleepy-test"]);
+        assert_eq!(sleep.options[0].name, "allowed_functions");
+        assert!(!sleep.fixable);
+        assert!(rules.iter().any(|r| r.fixable));
+    }
+
+    #[test]
+    fn test_find_rule_by_alias_and_suggestion_for_typo() {
+        assert_eq!(find_rule("sleepy-test").unwrap().id, "PYTEST-FLK-001");
+        let err = find_rule("PYTEST-MNT-04").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "unknown rule `PYTEST-MNT-04` (did you mean `PYTEST-MNT-004`?)"
+        );
+    }
+
+    #[test]
+    fn test_explain_lists_options_and_fix_support() {
+        let text = explain(&find_rule("PYTEST-FLK-001").unwrap());
+        assert!(text.starts_with("PYTEST-FLK-001 — time-sleep (TimeSleepRule)\n"));
+        assert!(text.contains("Aliases:   sleepy-test\n"));
+        assert!(text.contains("Autofix:   no\n"));
+        assert!(text.contains("  allowed_functions (string-list)  "));
+    }
+}
@@ -0,0 +1,420 @@
+//! Applying machine-applicable fixes (`--fix`) and rendering them as a unified diff
+//! (`--fix-dry-run`).
+//!
+//! Fixes are applied in diagnostic order. A fix whose edits overlap an already accepted
+//! edit is skipped as a whole; running `--fix` again picks it up once the file has been
+//! re-checked. When the accepted fixes together delete every statement of a block, the last
+//! deletion leaves `pass` behind. Before anything is written the fixed source is parsed
+//! again, and a file whose fixes would add a syntax error, empty suites included, is left
+//! as it was.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::models::{Fix, TextEdit, Violation};
+use crate::parser::syntax_errors;
+use crate::rules::ast::parse_python;
+
+/// Lines of unchanged context around each change in a diff hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Result of applying fixes to one source text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixOutcome {
+    /// The fixed source.
+    pub source: String,
+    /// The accepted edits against the original source, sorted by position.
+    pub edits: Vec<TextEdit>,
+    pub applied: usize,
+    /// Fixes skipped because they overlapped an earlier fix or were out of range.
+    pub skipped: usize,
+    /// The fixes were all dropped because the result had more syntax errors than `source`,
+    /// e.g. a block left without a statement; `source` is then returned unchanged.
+    pub rejected: bool,
+}
+
+fn overlaps(a: &TextEdit, b: &TextEdit) -> bool {
+    // Two insertions at the same point conflict too: their order would be arbitrary
+    (a.start_byte < b.end_byte && b.start_byte < a.end_byte) || a.start_byte == b.start_byte
+}
+
+fn is_valid(edit: &TextEdit, source: &str) -> bool {
+    edit.start_byte <= edit.end_byte
+        && edit.end_byte <= source.len()
+        && source.is_char_boundary(edit.start_byte)
+        && source.is_char_boundary(edit.end_byte)
+}
+
+/// Totals for a `--fix` or `--fix-dry-run` run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixSummary {
+    pub applied: usize,
+    pub skipped: usize,
+    /// Files that were (or, in a dry run, would be) changed.
+    pub files: usize,
+    /// Files left unchanged because their fixes would have broken the syntax.
+    pub rejected: usize,
+    /// Unified diff of all changes; only filled in for dry runs.
+    pub diff: String,
+}
+
+impl FixSummary {
+    /// One-line report, e.g. `Applied 3 fixes (1 skipped due to conflicts)`.
+    #[must_use]
+    pub fn message(&self, dry_run: bool) -> String {
+        let verb = if dry_run { "Would apply" } else { "Applied" };
+        let plural = if self.applied == 1 { "" } else { "es" };
+        let mut message = format!("{verb} {} fix{plural}", self.applied);
+        if self.files > 0 {
+            let files = if self.files == 1 { "file" } else { "files" };
+            message.push_str(&format!(" in {} {files}", self.files));
+        }
+        if self.skipped > 0 {
+            message.push_str(&format!(" ({} skipped due to conflicts)", self.skipped));
+        }
+        if self.rejected > 0 {
+            let files = if self.rejected == 1 { "file" } else { "files" };
+            message.push_str(&format!(
+                "; {} {files} left unchanged because the fixes would break the syntax",
+                self.rejected
+            ));
+        }
+        message
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Apply the fixes attached to `violations` to the files they were reported in, or in a
+/// dry run only collect the diff. Violations must be in output order.
+#[allow(clippy::missing_errors_doc)]
+pub fn fix_files(violations: &[Violation], dry_run: bool) -> Result<FixSummary> {
+    let mut by_file: BTreeMap<&PathBuf, Vec<&Fix>> = BTreeMap::new();
+    for v in violations {
+        if let Some(fix) = &v.fix {
+            by_file.entry(&v.file_path).or_default().push(fix);
+        }
+    }
+    let mut summary = FixSummary::default();
+    for (path, fixes) in by_file {
+        let source =
+            std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let outcome = apply_fixes(&source, &fixes);
+        summary.applied += outcome.applied;
+        summary.skipped += outcome.skipped;
+        summary.rejected += usize::from(outcome.rejected);
+        if outcome.applied == 0 {
+            continue;
+        }
+        summary.files += 1;
+        if dry_run {
+            summary
+                .diff
+                .push_str(&unified_diff(&display_path(path), &source, &outcome.edits));
+        } else {
+            std::fs::write(path, &outcome.source)
+                .with_context(|| format!("write {}", path.display()))?;
+        }
+    }
+    Ok(summary)
+}
+
+/// Apply `fixes` to `source`, first fix winning on overlap. The whole set is rejected
+/// when the fixed source would not parse where `source` did.
+#[must_use]
+pub fn apply_fixes(source: &str, fixes: &[&Fix]) -> FixOutcome {
+    let mut outcome = FixOutcome::default();
+    for fix in fixes {
+        // Several diagnostics can share one fix, e.g. marks applied through one variable
+        if !fix.edits.is_empty() && fix.edits.iter().all(|e| outcome.edits.contains(e)) {
+            continue;
+        }
+        let acceptable = !fix.edits.is_empty()
+            && fix.edits.iter().enumerate().all(|(i, edit)| {
+                is_valid(edit, source)
+                    && !fix.edits[..i].iter().any(|other| overlaps(edit, other))
+                    && !outcome.edits.iter().any(|other| overlaps(edit, other))
+            });
+        if acceptable {
+            outcome.edits.extend(fix.edits.iter().cloned());
+            outcome.applied += 1;
+        } else {
+            outcome.skipped += 1;
+        }
+    }
+    outcome.edits.sort_by_key(|e| e.start_byte);
+    keep_blocks_non_empty(source, &mut outcome.edits);
+    outcome.source = splice(source, &outcome.edits);
+    if outcome.applied > 0 && syntax_errors(&outcome.source).len() > syntax_errors(source).len() {
+        return FixOutcome {
+            source: source.to_string(),
+            edits: vec![],
+            applied: 0,
+            skipped: outcome.skipped,
+            rejected: true,
+        };
+    }
+    outcome
+}
+
+/// The deletion among `edits` that removes `start..end`, if any.
+fn deletion_covering(edits: &[TextEdit], start: usize, end: usize) -> Option<usize> {
+    edits
+        .iter()
+        .position(|e| e.replacement.is_empty() && e.start_byte <= start && end <= e.end_byte)
+}
+
+/// Each fix only knows whether its own deletion empties a block; when the accepted fixes
+/// together delete every statement of one, the deletion of its last statement leaves
+/// `pass` behind instead.
+fn keep_blocks_non_empty(source: &str, edits: &mut [TextEdit]) {
+    let Some(tree) = parse_python(source) else {
+        return;
+    };
+    let mut passes = Vec::new();
+    let mut to_visit = vec![tree.root_node()];
+    while let Some(node) = to_visit.pop() {
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.named_children(&mut cursor).collect();
+        // A block whose whole statement is deleted needs nothing left in it
+        let owner_deleted = node
+            .parent()
+            .is_some_and(|p| deletion_covering(edits, p.start_byte(), p.end_byte()).is_some());
+        if node.kind() == "block" && !owner_deleted {
+            let statements: Vec<_> = children.iter().filter(|n| n.kind() != "comment").collect();
+            let all_deleted = statements
+                .iter()
+                .all(|s| deletion_covering(edits, s.start_byte(), s.end_byte()).is_some());
+            if let (true, Some(last)) = (all_deleted, statements.last()) {
+                let (start, end) = (last.start_byte(), last.end_byte());
+                let i = deletion_covering(edits, start, end).unwrap_or_default();
+                let before = &source[edits[i].start_byte..start];
+                let after = &source[end..edits[i].end_byte];
+                if before.trim().is_empty() && after.trim().is_empty() {
+                    passes.push((i, format!("{before}pass{after}")));
+                }
+            }
+        }
+        to_visit.extend(children);
+    }
+    for (i, replacement) in passes {
+        edits[i].replacement = replacement;
+    }
+}
+
+/// Apply sorted, non-overlapping edits.
+fn splice(source: &str, edits: &[TextEdit]) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    for edit in edits {
+        out.push_str(&source[pos..edit.start_byte]);
+        out.push_str(&edit.replacement);
+        pos = edit.end_byte;
+    }
+    out.push_str(&source[pos..]);
+    out
+}
+
+/// A run of changed lines: old lines `first..=last` become `new_lines`.
+struct Change {
+    first: usize,
+    last: usize,
+    new_lines: Vec<String>,
+}
+
+/// Render sorted, non-overlapping `edits` of `source` as a unified diff for `path`.
+/// Returns an empty string when there are no edits.
+#[must_use]
+pub fn unified_diff(path: &str, source: &str, edits: &[TextEdit]) -> String {
+    if edits.is_empty() {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        source
+            .match_indices('\n')
+            .map(|(i, _)| i + 1)
+            .filter(|&i| i < source.len()),
+    );
+    let line_of = |byte: usize| {
+        line_starts
+            .partition_point(|&s| s <= byte)
+            .saturating_sub(1)
+    };
+    let line_end = |line: usize| line_starts.get(line + 1).copied().unwrap_or(source.len());
+
+    // Group edits touching the same lines into changes
+    let mut groups: Vec<(usize, usize, Vec<&TextEdit>)> = Vec::new();
+    for edit in edits {
+        let first = line_of(edit.start_byte);
+        let last = if edit.end_byte > edit.start_byte {
+            line_of(edit.end_byte - 1)
+        } else {
+            first
+        };
+        match groups.last_mut() {
+            Some(group) if first <= group.1 => {
+                group.1 = group.1.max(last);
+                group.2.push(edit);
+            }
+            _ => groups.push((first, last, vec![edit])),
+        }
+    }
+    let changes: Vec<Change> = groups
+        .into_iter()
+        .map(|(first, last, group)| {
+            let start = line_starts.get(first).copied().unwrap_or(source.len());
+            let mut text = String::new();
+            let mut pos = start;
+            for edit in group {
+                text.push_str(&source[pos..edit.start_byte]);
+                text.push_str(&edit.replacement);
+                pos = edit.end_byte;
+            }
+            text.push_str(&source[pos..line_end(last).max(pos)]);
+            Change {
+                first,
+                last,
+                new_lines: text.split_inclusive('\n').map(str::to_string).collect(),
+            }
+        })
+        .collect();
+
+    // Absolute paths keep a single separator so `patch -p1` still applies the diff
+    let sep = if path.starts_with('/') { "" } else { "/" };
+    let mut out = format!("--- a{sep}{path}\n+++ b{sep}{path}\n");
+    let mut delta: isize = 0;
+    let mut i = 0;
+    while i < changes.len() {
+        // Extend the hunk while the next change's context would touch this one
+        let mut j = i;
+        while j + 1 < changes.len()
+            && changes[j + 1].first <= changes[j].last + 1 + 2 * CONTEXT_LINES
+        {
+            j += 1;
+        }
+        let hunk_start = changes[i].first.saturating_sub(CONTEXT_LINES);
+        let hunk_end = (changes[j].last + 1 + CONTEXT_LINES).min(old_lines.len());
+        let mut body = String::new();
+        let (mut old_count, mut new_count) = (0usize, 0usize);
+        let mut line = hunk_start;
+        for change in &changes[i..=j] {
+            for context in old_lines.get(line..change.first).unwrap_or(&[]) {
+                push_line(&mut body, ' ', context);
+            }
+            old_count += change.first.saturating_sub(line);
+            new_count += change.first.saturating_sub(line);
+            let removed = old_lines.get(change.first..=change.last).unwrap_or(&[]);
+            for old in removed {
+                push_line(&mut body, '-', old);
+            }
+            for new in &change.new_lines {
+                push_line(&mut body, '+', new);
+            }
+            old_count += removed.len();
+            new_count += change.new_lines.len();
+            line = change.last + 1;
+        }
+        for context in &old_lines[line.min(hunk_end)..hunk_end] {
+            push_line(&mut body, ' ', context);
+        }
+        old_count += hunk_end.saturating_sub(line);
+        new_count += hunk_end.saturating_sub(line);
+
+        let new_start = (hunk_start as isize + delta).max(0) as usize;
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk_start + usize::from(old_count > 0),
+            old_count,
+            new_start + usize::from(new_count > 0),
+            new_count
+        ));
+        out.push_str(&body);
+        delta += new_count as isize - old_count as isize;
+        i = j + 1;
+    }
+    out
+}
+
+fn push_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_fixes_keeps_blocks_non_empty_or_rejects_the_set() {
+        let source = "if x: print(x)\ny = 1\n";
+        let outcome = apply_fixes(source, &[&Fix::replace(6, 14, "")]);
+        assert_eq!(outcome.source, "if x: pass\ny = 1\n");
+
+        // Where `pass` cannot stand in for the deleted code, nothing is applied
+        let source = "if x:\n    a = 1; b = 2\ny = 1\n";
+        let outcome = apply_fixes(source, &[&Fix::replace(10, 22, "")]);
+        assert!(outcome.rejected);
+        assert_eq!(outcome.source, source);
+        assert_eq!(outcome.applied, 0);
+        assert!(outcome.edits.is_empty());
+
+        // A file that was already broken elsewhere can still be fixed
+        let broken = "x = (\nif y: print(y)\n";
+        let outcome = apply_fixes(broken, &[&Fix::replace(0, 1, "z")]);
+        assert!(!outcome.rejected);
+        assert_eq!(outcome.source, "z = (\nif y: print(y)\n");
+    }
+
+    #[test]
+    fn test_apply_fixes_skips_overlapping() {
+        let source = "abcdef";
+        let first = Fix::replace(1, 3, "X");
+        let overlapping = Fix::replace(2, 4, "Y");
+        let later = Fix::replace(4, 4, "Z");
+        let outcome = apply_fixes(source, &[&first, &overlapping, &later]);
+        assert_eq!(outcome.source, "aXdZef");
+        assert_eq!(outcome.applied, 2);
+        assert_eq!(outcome.skipped, 1);
+
+        let out_of_range = Fix::replace(3, 99, "");
+        let outcome = apply_fixes(source, &[&out_of_range]);
+        assert_eq!(outcome.source, source);
+        assert_eq!(outcome.skipped, 1);
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let source = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        // Replace "b" and delete the "k" line: far enough apart for two hunks
+        let edits = vec![
+            TextEdit {
+                start_byte: 2,
+                end_byte: 3,
+                replacement: "B".to_string(),
+            },
+            TextEdit {
+                start_byte: 20,
+                end_byte: 22,
+                replacement: String::new(),
+            },
+        ];
+        let diff = unified_diff("test_a.py", source, &edits);
+        assert_eq!(
+            diff,
+            "--- a/test_a.py\n+++ b/test_a.py\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,5 +8,4 @@\n h\n i\n j\n-k\n l\n"
+        );
+        assert_eq!(unified_diff("test_a.py", source, &[]), "");
+    }
+}
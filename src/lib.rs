@@ -1,6 +1,54 @@
+//! pytest-linter: detect test smells in Python/pytest test suites.
+//!
+//! The stable library surface is at the crate root: [`lint_source`] for a buffer,
+//! [`lint_path`] and [`lint_paths`] for files and directories, the fix, baseline and output
+//! functions that work on their results, and the types they take and return. The
+//! command-line tool is built on the same functions. The modules are the
+//! linter's internals, shared with the CLI and the LSP server; they are public but hidden
+//! from the documentation and may change in any release.
+
+#[doc(hidden)]
+pub mod api;
+#[doc(hidden)]
+pub mod baseline;
+#[doc(hidden)]
+pub mod cache;
+#[doc(hidden)]
 pub mod config;
+#[doc(hidden)]
+pub mod diff;
+#[doc(hidden)]
+pub mod discovery;
+#[doc(hidden)]
 pub mod engine;
+#[doc(hidden)]
+pub mod explain;
+#[doc(hidden)]
+pub mod fix;
+#[doc(hidden)]
 pub mod models;
+#[doc(hidden)]
 pub mod output;
+#[doc(hidden)]
 pub mod parser;
+#[doc(hidden)]
+pub mod project;
+#[doc(hidden)]
 pub mod rules;
+#[doc(hidden)]
+pub mod span;
+#[doc(hidden)]
+pub mod watch;
+
+pub use api::{
+    check_baseline, fix_paths, fix_source, lint_path, lint_paths, lint_paths_with_memory_limit,
+    lint_source, lint_source_at, lint_status, write_baseline, write_diagnostics, BaselineCheck,
+    Diagnostic, FixedSource, Language, LintReport, RelatedLocation, RuleId, Span, SOURCE_PATH,
+};
+pub use cache::CacheStats;
+pub use config::{Config, OverrideConfig, RuleConfig};
+pub use engine::LintStatus;
+pub use fix::FixSummary;
+pub use models::{Category, Fix, Severity, Tag, TextEdit};
+pub use output::summary::{FileCount, FileCounts, RuleCount, SeverityCounts, Summary};
+pub use rules::custom::{CustomRuleDef, MatchScope};
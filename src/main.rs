@@ -1,20 +1,40 @@
 //! pytest-linter: detect test smells in Python/pytest test files.
 
-use anyhow::Result;
-use clap::Parser;
-use pytest_linter::config::Config;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use pytest_linter::cache::CACHE_DIR_NAME;
+use pytest_linter::diff::ChangedLines;
 use pytest_linter::engine::DEFAULT_EXCLUDED_DIRS;
-use std::path::PathBuf;
+use pytest_linter::explain;
+use pytest_linter::output::terminal::use_color;
+use pytest_linter::{
+    lint_status, write_diagnostics, Config, Diagnostic, FileCounts, LintStatus, Severity,
+};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Parser)]
 #[command(name = "pytest-linter")]
 #[command(about = "Detect test smells in Python/pytest test files")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(required = true)]
     paths: Vec<PathBuf>,
 
-    #[arg(long, value_parser = ["terminal", "json", "sarif"])]
+    /// Output format: terminal (source snippets), short (one line per violation), json,
+    /// json-lines (one diagnostic per line), sarif, github (workflow-command annotations),
+    /// checkstyle, junit. Defaults to github when GITHUB_ACTIONS=true, terminal otherwise.
+    #[arg(
+        long,
+        value_parser = [
+            "terminal", "short", "json", "json-lines", "sarif", "github", "checkstyle", "junit",
+        ]
+    )]
     format: Option<String>,
 
     #[arg(long)]
@@ -23,6 +43,28 @@ struct Cli {
     #[arg(long)]
     no_color: bool,
 
+    /// Worker threads for parsing and checking files (default: one per core).
+    #[arg(long, short = 'j', value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+
+    /// Analyse every file instead of reusing results from `.pytl-cache/`.
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Print diagnostics per rule, totals by severity, file counts and cache hits and misses
+    /// to stderr after the run.
+    #[arg(long)]
+    statistics: bool,
+
+    /// Print the N files with the most diagnostics to stderr after the run.
+    #[arg(long, value_name = "N")]
+    top_files: Option<usize>,
+
+    /// Index every test file and conftest.py of the project before checking, so that rules
+    /// see fixtures and tests outside the linted paths, e.g. in parent conftest.py files.
+    #[arg(long)]
+    project_mode: bool,
+
     /// Soft memory limit in MB. Warns if estimated usage exceeds this limit (default: 256).
     #[arg(long, default_value_t = 256)]
     memory_limit: usize,
@@ -30,24 +72,205 @@ struct Cli {
     #[arg(long)]
     incremental: bool,
 
-    /// Additional directory names to exclude during file discovery (can be repeated).
-    #[arg(long, value_name = "DIR")]
+    /// Glob or directory name to skip during directory traversal (can be repeated).
+    #[arg(long, value_name = "GLOB")]
     exclude: Vec<String>,
 
+    /// Only lint discovered files matching this glob (can be repeated).
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
     #[arg(long, default_value = "HEAD")]
     base: String,
 
-    #[arg(long, conflicts_with = "check_baseline")]
-    baseline: Option<PathBuf>,
+    /// Only report diagnostics on lines added or modified since this git ref.
+    #[arg(long, value_name = "REF")]
+    diff_base: Option<String>,
+
+    /// Only report diagnostics on lines added or modified in a unified diff read from stdin.
+    #[arg(long, conflicts_with = "diff_base")]
+    diff_from_stdin: bool,
+
+    /// Lowest severity that makes the run fail (default: error).
+    #[arg(long, value_name = "SEVERITY", value_parser = ["error", "warning", "info", "hint"])]
+    fail_on: Option<String>,
+
+    /// Fail the run when there are more than N warnings, even without errors.
+    #[arg(long, value_name = "N")]
+    max_warnings: Option<usize>,
 
-    #[arg(long, conflicts_with = "baseline")]
+    /// Report syntax errors (PYTEST-SYN-001) without failing the run on them.
+    #[arg(long)]
+    ignore_syntax_errors: bool,
+
+    /// Apply automatic fixes to the files, then report the remaining violations.
+    #[arg(long, conflicts_with_all = ["fix_dry_run", "write_baseline"])]
+    fix: bool,
+
+    /// Print the automatic fixes as a unified diff instead of applying them.
+    #[arg(long, conflicts_with = "write_baseline")]
+    fix_dry_run: bool,
+
+    /// Record the current violations in a baseline file and exit.
+    #[arg(
+        long,
+        value_name = "FILE",
+        alias = "baseline",
+        conflicts_with = "check_baseline"
+    )]
+    write_baseline: Option<PathBuf>,
+
+    /// Suppress violations recorded in a baseline file and fail on any new ones.
+    #[arg(long, value_name = "FILE", conflicts_with = "write_baseline")]
     check_baseline: Option<PathBuf>,
+
+    /// Print how many baseline entries still match and which are stale.
+    #[arg(long, conflicts_with = "write_baseline")]
+    baseline_report: bool,
+
+    /// Keep running and re-lint whenever a test file or configuration file changes; Ctrl-C
+    /// exits with status 0.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "output", "fix", "fix_dry_run", "write_baseline", "check_baseline", "baseline_report",
+            "incremental", "diff_base", "diff_from_stdin", "stdin_filepath",
+        ]
+    )]
+    watch: bool,
+
+    /// Lint source read from stdin (pass `-` as the path) as if it were the file at PATH.
+    /// With --fix, the fixed source is written to stdout and the diagnostics to stderr.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "diff_base", "diff_from_stdin", "incremental", "write_baseline", "check_baseline",
+            "baseline_report",
+        ]
+    )]
+    stdin_filepath: Option<PathBuf>,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Run the language server over stdio (starts the `pytest-linter-lsp` binary).
+    Lsp,
+    /// Describe a rule: severity, category, aliases, options and autofix support.
+    Explain {
+        /// Rule ID, name, slug or alias (e.g. PYTEST-FLK-001 or sleepy-test).
+        rule: String,
+    },
+    /// List every rule with its ID, slug, default severity and autofix support.
+    Rules {
+        #[arg(long, value_parser = ["text", "json"], default_value = "text")]
+        format: String,
+    },
+    /// Print the configuration that applies to a file: every rule's state, severity and
+    /// options, and the configuration files and overrides they come from.
+    ShowConfig {
+        /// File (or directory) to resolve the configuration for.
+        path: PathBuf,
+        #[arg(long, value_parser = ["text", "json"], default_value = "text")]
+        format: String,
+    },
+}
+
+/// Hand the process over to the LSP server, looked up next to this executable, then on PATH.
+fn run_lsp() -> Result<()> {
+    let name = format!("pytest-linter-lsp{}", std::env::consts::EXE_SUFFIX);
+    let sibling = std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(&name))
+        .filter(|path| path.is_file());
+    let program = sibling.unwrap_or_else(|| PathBuf::from(&name));
+    let status = process::Command::new(&program)
+        .arg("--stdio")
+        .status()
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "could not start {}: {e} (install it with `cargo install --path lsp-server`)",
+                program.display()
+            )
+        })?;
+    process::exit(status.code().unwrap_or(1));
+}
 
-    let mut config = Config::discover(&cli.paths[0])?;
+/// Directory the cache lives in: next to the configuration file, or the linted directory.
+fn cache_root(config: &Config, first_path: &Path) -> PathBuf {
+    let dir = config.config_dir.as_deref().unwrap_or(first_path);
+    if dir.is_file() {
+        dir.parent().unwrap_or(Path::new(".")).to_path_buf()
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// Exit code of a run without policy failures.
+const EXIT_CLEAN: i32 = 0;
+/// Exit code when the diagnostics fail the `--fail-on` / `--max-warnings` policy.
+const EXIT_FAILURE: i32 = 1;
+/// Exit code for usage errors (also used by clap) and internal failures.
+const EXIT_INTERNAL_ERROR: i32 = 2;
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {err:#}");
+        process::exit(EXIT_INTERNAL_ERROR);
+    }
+}
+
+/// Output format when none is configured: annotations inside GitHub Actions, the terminal
+/// report everywhere else.
+fn default_format() -> String {
+    let in_github_actions = std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+    if in_github_actions {
+        "github"
+    } else {
+        "terminal"
+    }
+    .to_string()
+}
+
+/// Exit with the code for `status`, explaining a policy failure on stderr.
+fn exit_with(status: &LintStatus) -> ! {
+    match status {
+        LintStatus::Clean => process::exit(EXIT_CLEAN),
+        LintStatus::Failed(reason) => {
+            eprintln!("Lint failed: {reason}");
+            process::exit(EXIT_FAILURE);
+        }
+        LintStatus::InternalError => {
+            eprintln!("Lint failed: internal error while linting (see PYTEST-INT-001 diagnostics)");
+            process::exit(EXIT_INTERNAL_ERROR);
+        }
+    }
+}
+
+/// Write the report to the `--output` file, or stdout. `buffer` is the linted text of a
+/// file read from stdin.
+fn write_report(
+    output: Option<&Path>,
+    diagnostics: &[Diagnostic],
+    format: &str,
+    config: &Config,
+    files: Option<FileCounts>,
+    buffer: Option<(&Path, &str)>,
+) -> Result<()> {
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("create {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    write_diagnostics(&mut out, diagnostics, format, config, files, buffer)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// The configuration found from `start`, with the command-line options applied on top.
+fn configure(cli: &Cli, start: &Path) -> Result<Config> {
+    let mut config = Config::discover(start)?;
     let default_excludes: Vec<String> = DEFAULT_EXCLUDED_DIRS
         .iter()
         .map(|s| s.to_string())
@@ -55,75 +278,266 @@ fn main() -> Result<()> {
     let mut all_excludes = default_excludes;
     all_excludes.extend(cli.exclude.iter().cloned());
 
-    config = config.merge_cli(cli.format.clone(), cli.output.clone(), all_excludes);
+    config = config.merge_cli(
+        cli.format.clone(),
+        cli.output.clone(),
+        all_excludes,
+        cli.include.clone(),
+    );
+    config.jobs = cli.jobs.map(usize::from);
+    config.statistics = cli.statistics;
+    config.top_files = cli.top_files;
+    config.project_mode = cli.project_mode;
+    config.ignore_syntax_errors = cli.ignore_syntax_errors;
+    if let Some(fail_on) = &cli.fail_on {
+        config.fail_on = Some(match fail_on.as_str() {
+            "warning" => Severity::Warning,
+            "info" => Severity::Info,
+            "hint" => Severity::Hint,
+            _ => Severity::Error,
+        });
+    }
+    if cli.max_warnings.is_some() {
+        config.max_warnings = cli.max_warnings;
+    }
+    Ok(config)
+}
+
+/// Set by the Ctrl-C handler to end `--watch`.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
 
-    let format_str = config
-        .format
-        .clone()
-        .unwrap_or_else(|| "terminal".to_string());
+/// `--watch`: re-lint on changes until Ctrl-C, which exits 0 whatever the diagnostics.
+fn run_watch(cli: &Cli) -> Result<()> {
+    #[cfg(unix)]
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        signal_hook_registry::register(libc::SIGINT, || INTERRUPTED.store(true, Ordering::Relaxed))
+            .context("install the Ctrl-C handler")?;
+    }
+    let load_config = || {
+        let mut config = configure(cli, &cli.paths[0])?;
+        if !cli.no_cache {
+            config.cache_dir = Some(cache_root(&config, &cli.paths[0]).join(CACHE_DIR_NAME));
+        }
+        Ok(config)
+    };
+    let format = cli.format.clone().unwrap_or_else(|| "terminal".to_string());
+    colored::control::set_override(use_color(cli.no_color, None));
+    pytest_linter::watch::watch(&cli.paths, &format, &load_config, &INTERRUPTED)?;
+    process::exit(EXIT_CLEAN);
+}
+
+/// `--stdin-filepath PATH -`: lint stdin as the file at PATH. Like a formatter, `--fix`
+/// writes the fixed source to stdout and the remaining diagnostics to stderr.
+fn run_stdin(cli: &Cli, path: &Path, config: &Config) -> Result<()> {
+    let mut source = String::new();
+    std::io::stdin()
+        .read_to_string(&mut source)
+        .context("read source from stdin")?;
+    let format = config.format.clone().unwrap_or_else(default_format);
+    let mut diagnostics = pytest_linter::lint_source_at(&source, path, config)?;
+
+    if cli.fix || cli.fix_dry_run {
+        let fixed = pytest_linter::fix_source(&source, path, &diagnostics);
+        let summary = &fixed.summary;
+        eprintln!("{}", summary.message(cli.fix_dry_run));
+        if cli.fix_dry_run {
+            print!("{}", summary.diff);
+            process::exit(if summary.applied > 0 {
+                EXIT_FAILURE
+            } else {
+                EXIT_CLEAN
+            });
+        }
+        print!("{}", fixed.source);
+        diagnostics = pytest_linter::lint_source_at(&fixed.source, path, config)?;
+        colored::control::set_override(false);
+        write_diagnostics(
+            &mut std::io::stderr(),
+            &diagnostics,
+            &format,
+            config,
+            None,
+            Some((path, &fixed.source)),
+        )?;
+    } else {
+        let output = config.output.as_deref();
+        colored::control::set_override(use_color(cli.no_color, output));
+        write_report(
+            output,
+            &diagnostics,
+            &format,
+            config,
+            None,
+            Some((path, &source)),
+        )?;
+    }
+    exit_with(&lint_status(&diagnostics, config));
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    match &cli.command {
+        Some(Command::Lsp) => return run_lsp(),
+        Some(Command::Explain { rule }) => {
+            print!("{}", explain::explain(&explain::find_rule(rule)?));
+            return Ok(());
+        }
+        Some(Command::Rules { format }) => {
+            let rules = explain::catalog();
+            if format == "json" {
+                println!("{}", explain::format_rules_json(&rules)?);
+            } else {
+                print!("{}", explain::format_rules_text(&rules));
+            }
+            return Ok(());
+        }
+        Some(Command::ShowConfig { path, format }) => {
+            let resolved = Config::discover(path)?.resolve_for_file(path)?;
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&resolved)?);
+            } else {
+                print!("{}", pytest_linter::config::format_file_config(&resolved));
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let stdin_path = match &cli.stdin_filepath {
+        Some(path) if cli.paths == [PathBuf::from("-")] => Some(path.clone()),
+        Some(_) => {
+            anyhow::bail!("--stdin-filepath reads the source from stdin: pass `-` as the path")
+        }
+        None => None,
+    };
+    if stdin_path.is_none() {
+        if let Some(missing) = cli.paths.iter().find(|p| !p.exists()) {
+            anyhow::bail!("path not found: {}", missing.display());
+        }
+    }
+
+    // A buffer from stdin is configured like the file it stands for
+    let mut config = configure(&cli, stdin_path.as_deref().unwrap_or(&cli.paths[0]))?;
+    if let Some(base) = &cli.diff_base {
+        config.changed_lines = Some(ChangedLines::from_git(base)?);
+    } else if cli.diff_from_stdin {
+        let mut diff = String::new();
+        std::io::stdin()
+            .read_to_string(&mut diff)
+            .context("read diff from stdin")?;
+        config.changed_lines = Some(ChangedLines::parse(&diff, Path::new(".")));
+    }
+    if let Some(path) = stdin_path {
+        return run_stdin(&cli, &path, &config);
+    }
+    if !cli.no_cache {
+        config.cache_dir = Some(cache_root(&config, &cli.paths[0]).join(CACHE_DIR_NAME));
+    }
+    if cli.watch {
+        return run_watch(&cli);
+    }
+
+    let format_str = config.format.clone().unwrap_or_else(default_format);
     let output_path = config.output.clone();
 
     let paths = if cli.incremental {
         let changed = pytest_linter::engine::get_changed_files(&cli.base)?;
         if changed.is_empty() {
             eprintln!("No changed Python test files found.");
-            process::exit(0);
+            process::exit(EXIT_CLEAN);
         }
         changed
     } else {
         cli.paths.clone()
     };
 
-    if let Some(ref baseline_path) = cli.baseline {
-        let violations = pytest_linter::engine::collect_violations(&paths, config.clone())?;
-        pytest_linter::engine::save_baseline(&violations, baseline_path)?;
-        eprintln!("Baseline saved to {}", baseline_path.display());
-        process::exit(0);
+    if cli.fix || cli.fix_dry_run {
+        let summary = pytest_linter::fix_paths(&paths, &config, cli.fix_dry_run)?;
+        eprintln!("{}", summary.message(cli.fix_dry_run));
+        if cli.fix_dry_run {
+            print!("{}", summary.diff);
+            process::exit(if summary.applied > 0 {
+                EXIT_FAILURE
+            } else {
+                EXIT_CLEAN
+            });
+        }
+    }
+
+    if let Some(ref baseline_path) = cli.write_baseline {
+        let report = pytest_linter::lint_paths(&paths, &config)?;
+        pytest_linter::write_baseline(&report.diagnostics, baseline_path)?;
+        eprintln!(
+            "Baseline saved to {} ({} violations)",
+            baseline_path.display(),
+            report.diagnostics.len()
+        );
+        process::exit(EXIT_CLEAN);
+    }
+
+    // An explicit --check-baseline fails on any new violation; a baseline from the config
+    // only filters, keeping the usual exit code of failing on errors.
+    let strict_baseline = cli.check_baseline.is_some();
+    let baseline_path = cli
+        .check_baseline
+        .clone()
+        .or_else(|| config.baseline.clone());
+    if cli.baseline_report && baseline_path.is_none() {
+        anyhow::bail!(
+            "--baseline-report needs a baseline: pass --check-baseline FILE or set `baseline` in the configuration"
+        );
     }
 
-    if let Some(ref baseline_path) = cli.check_baseline {
-        let violations = pytest_linter::engine::collect_violations(&paths, config.clone())?;
-        let baseline = pytest_linter::engine::load_baseline(baseline_path)?;
-        let new_violations = pytest_linter::engine::filter_new_violations(&violations, &baseline);
-        if new_violations.is_empty() {
+    if let Some(ref baseline_path) = baseline_path {
+        let report = pytest_linter::lint_paths(&paths, &config)?;
+        let check = pytest_linter::check_baseline(report, baseline_path)?;
+        if cli.baseline_report {
+            eprint!("{}", check.breakdown);
+        }
+        let new = &check.new;
+        let summary = new.run_summary(&config);
+        if new.diagnostics.is_empty() {
+            eprint!("{summary}");
             eprintln!(
                 "No new violations found (baseline: {} violations)",
-                baseline.len()
+                check.recorded
             );
-            process::exit(0);
+            process::exit(EXIT_CLEAN);
         }
         eprintln!(
             "{} new violations found (not in baseline)",
-            new_violations.len()
+            new.diagnostics.len()
         );
-        match format_str.as_str() {
-            "json" => {
-                pytest_linter::engine::format_json_output(&new_violations, output_path.as_deref())?
-            }
-            "sarif" => {
-                pytest_linter::engine::format_sarif_output(&new_violations, output_path.as_deref())?
+        colored::control::set_override(use_color(cli.no_color, output_path.as_deref()));
+        write_report(
+            output_path.as_deref(),
+            &new.diagnostics,
+            &format_str,
+            &config,
+            Some(new.files),
+            None,
+        )?;
+        eprint!("{summary}");
+        let status = match lint_status(&new.diagnostics, &config) {
+            LintStatus::Clean if strict_baseline => {
+                LintStatus::Failed(format!("{} new violations", new.diagnostics.len()))
             }
-            _ => pytest_linter::engine::format_terminal_output(
-                &new_violations,
-                output_path.as_deref(),
-                cli.no_color,
-            )?,
-        }
-        process::exit(1);
+            status => status,
+        };
+        exit_with(&status);
     }
 
-    let has_errors = pytest_linter::engine::run_linter_with_memory_limit(
-        &paths,
-        &format_str,
+    colored::control::set_override(use_color(cli.no_color, output_path.as_deref()));
+    let report = pytest_linter::lint_paths_with_memory_limit(&paths, &config, cli.memory_limit)?;
+    write_report(
         output_path.as_deref(),
-        cli.no_color,
-        config,
-        cli.memory_limit,
+        &report.diagnostics,
+        &format_str,
+        &config,
+        Some(report.files),
+        None,
     )?;
-
-    if has_errors {
-        process::exit(1);
-    }
-
-    Ok(())
+    eprint!("{}", report.run_summary(&config));
+    exit_with(&lint_status(&report.diagnostics, &config));
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Severity level for a lint violation.
@@ -9,6 +10,23 @@ pub enum Severity {
     Error,
     Warning,
     Info,
+    /// Below `Info`: a suggestion editors show unobtrusively, e.g. as a faded span.
+    Hint,
+}
+
+impl Severity {
+    /// Whether this severity is `threshold` or more severe
+    /// (`Hint` < `Info` < `Warning` < `Error`).
+    #[must_use]
+    pub fn is_at_least(self, threshold: Severity) -> bool {
+        let rank = |s: Severity| match s {
+            Self::Hint => 0,
+            Self::Info => 1,
+            Self::Warning => 2,
+            Self::Error => 3,
+        };
+        rank(self) >= rank(threshold)
+    }
 }
 
 impl std::fmt::Display for Severity {
@@ -17,6 +35,27 @@ impl std::fmt::Display for Severity {
             Self::Error => write!(f, "error"),
             Self::Warning => write!(f, "warning"),
             Self::Info => write!(f, "info"),
+            Self::Hint => write!(f, "hint"),
+        }
+    }
+}
+
+/// Extra classification of a violation that editors render specially: unnecessary code
+/// is faded out, deprecated code struck through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tag {
+    /// The flagged code does nothing and can be removed (an unused fixture, a no-op).
+    Unnecessary,
+    /// The flagged code uses an API that is deprecated or removed in newer versions.
+    Deprecated,
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unnecessary => write!(f, "unnecessary"),
+            Self::Deprecated => write!(f, "deprecated"),
         }
     }
 }
@@ -53,8 +92,66 @@ pub struct Violation {
     pub file_path: PathBuf,
     pub line: usize,
     pub col: Option<usize>,
+    /// End of the highlighted span (1-based, exclusive column), when the rule knows it.
+    pub end_line: Option<usize>,
+    pub end_col: Option<usize>,
     pub suggestion: Option<String>,
     pub test_name: Option<String>,
+    /// Machine-applicable edits that resolve the violation, applied by `--fix`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+    /// Rule-specific measurements behind the diagnostic (e.g. `call_targets` of the eager
+    /// test rule), exposed in JSON output.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// How editors should render the span, e.g. faded when the code is unnecessary.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+    /// Other places the diagnostic refers to, e.g. the first definition of a duplicated
+    /// test; shown as notes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedLocation>,
+}
+
+/// A second location of a diagnostic, with a message saying what is there. Lines and
+/// columns are as in [`Violation`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RelatedLocation {
+    pub file_path: PathBuf,
+    pub line: usize,
+    pub col: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_col: Option<usize>,
+    pub message: String,
+}
+
+/// Replacement of the source bytes `start_byte..end_byte` with `replacement`. An empty
+/// range is an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub replacement: String,
+}
+
+/// An automatic fix: edits that are applied together or not at all.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// A fix consisting of a single edit.
+    #[must_use]
+    pub fn replace(start_byte: usize, end_byte: usize, replacement: impl Into<String>) -> Self {
+        Self {
+            edits: vec![TextEdit {
+                start_byte,
+                end_byte,
+                replacement: replacement.into(),
+            }],
+        }
+    }
 }
 
 /// Metadata about an assert statement found in a test.
@@ -62,7 +159,12 @@ pub struct Violation {
 pub struct AssertionInfo {
     pub is_magic: bool,
     pub is_suboptimal: bool,
+    /// True when the assert cannot fail: constant comparisons and truthy literals.
+    #[serde(default)]
+    pub is_tautological: bool,
     pub has_comparison: bool,
+    /// True when the assert carries a failure message (`assert x, "why"`).
+    pub has_message: bool,
     pub expression_text: String,
     pub line: usize,
 }
@@ -79,6 +181,8 @@ pub struct TestFunction {
     pub parametrize_count: Option<usize>,
     pub has_assertions: bool,
     pub assertion_count: usize,
+    /// Distinct assertion sites: assert statements plus assertion-helper calls.
+    pub assertion_site_count: usize,
     pub has_mock_verifications: bool,
     pub has_state_assertions: bool,
     pub fixture_deps: Vec<String>,
@@ -108,6 +212,10 @@ pub struct TestFunction {
     pub mock_count: usize,
     pub uses_shutil_copy: bool,
     pub end_line: usize,
+    /// A `test_*` method of a `unittest.TestCase` subclass; `self.assert*()` calls count as
+    /// its assertions.
+    #[serde(default)]
+    pub is_unittest: bool,
 }
 
 /// Scope of a pytest fixture, from narrowest (function) to widest (session).
@@ -152,6 +260,39 @@ pub struct Fixture {
     pub used_by: Vec<String>,
 }
 
+/// A source comment surfaced by the parser (used for inline suppression directives).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub line: usize,
+    pub col: usize,
+    pub text: String,
+    /// True when the comment follows code on the same line.
+    pub is_trailing: bool,
+}
+
+/// Code the parser could not make sense of. Tree-sitter recovers around it, so the rest of
+/// the file is still analyzed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyntaxError {
+    pub message: String,
+    /// Span of the unparsable code (1-based, exclusive end column).
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    /// First and last line of the function around the error (the error itself at module
+    /// level): the region whose analysis cannot be trusted.
+    pub region: (usize, usize),
+}
+
+impl SyntaxError {
+    /// Whether `line` is in the broken region.
+    #[must_use]
+    pub fn covers(&self, line: usize) -> bool {
+        (self.region.0..=self.region.1).contains(&line)
+    }
+}
+
 /// Result of parsing a single Python test file: imports, tests, and fixtures.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedModule {
@@ -160,6 +301,10 @@ pub struct ParsedModule {
     pub imports: Vec<String>,
     pub test_functions: Vec<TestFunction>,
     pub fixtures: Vec<Fixture>,
+    pub comments: Vec<Comment>,
+    /// Empty when the file parsed cleanly.
+    #[serde(default)]
+    pub syntax_errors: Vec<SyntaxError>,
 }
 
 impl PartialEq for Violation {
@@ -250,8 +395,14 @@ mod tests {
             file_path: PathBuf::from("a.py"),
             line: 1,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = Violation {
             rule_id: "PYTEST-002".to_string(),
@@ -262,8 +413,14 @@ mod tests {
             file_path: PathBuf::from("a.py"),
             line: 2,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         assert!(v1 < v2);
     }
@@ -279,8 +436,14 @@ mod tests {
             file_path: PathBuf::from("a.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = Violation {
             rule_id: "A".to_string(),
@@ -291,8 +454,14 @@ mod tests {
             file_path: PathBuf::from("b.py"),
             line: 1,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         assert!(v1 < v2);
     }
@@ -322,8 +491,14 @@ mod tests {
             file_path: PathBuf::from("test.py"),
             line: 10,
             col: Some(5),
+            end_line: None,
+            end_col: None,
             suggestion: Some("fix it".to_string()),
             test_name: Some("test_foo".to_string()),
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let v2 = v.clone();
         assert_eq!(v, v2);
@@ -0,0 +1,84 @@
+//! Checkstyle XML output (`--format checkstyle`), as read by Jenkins warnings-ng and most
+//! CI dashboards: one `<file>` per linted file with an `<error>` per diagnostic.
+
+use super::{escape_xml, group_by_file};
+use crate::models::{Severity, Violation};
+
+fn checkstyle_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "info",
+    }
+}
+
+/// Render the report. With no violations this is an empty `<checkstyle>` element.
+#[must_use]
+pub fn format_checkstyle(violations: &[Violation]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<checkstyle version=\"4.3\">\n");
+    for (path, group) in group_by_file(violations) {
+        out.push_str(&format!("  <file name=\"{}\">\n", escape_xml(&path)));
+        for v in group {
+            let column = v
+                .col
+                .map(|c| format!(" column=\"{c}\""))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "    <error line=\"{}\"{column} severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+                v.line,
+                checkstyle_severity(v.severity),
+                escape_xml(&v.message),
+                escape_xml(&v.rule_id)
+            ));
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</checkstyle>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::make_violation;
+    use crate::models::Category;
+    use std::path::PathBuf;
+
+    fn violation(path: &str, line: usize, message: &str) -> Violation {
+        let mut v = make_violation(
+            "PYTEST-FLK-001",
+            "TimeSleepRule",
+            Severity::Warning,
+            Category::Flakiness,
+            message.to_string(),
+            PathBuf::from(path),
+            line,
+            None,
+            None,
+        );
+        v.col = Some(5);
+        v
+    }
+
+    #[test]
+    fn test_checkstyle_groups_per_file_and_escapes() {
+        let out = format_checkstyle(&[
+            violation("tests/test_a.py", 3, "uses <sleep> & \"waits\""),
+            violation("tests/test_b.py", 1, "b"),
+            violation("tests/test_a.py", 9, "a2"),
+        ]);
+        assert_eq!(out.matches("<file ").count(), 2);
+        assert!(out.contains(
+            "    <error line=\"3\" column=\"5\" severity=\"warning\" message=\"uses &lt;sleep&gt; &amp; &quot;waits&quot;\" source=\"PYTEST-FLK-001\"/>\n    <error line=\"9\""
+        ));
+    }
+
+    #[test]
+    fn test_checkstyle_empty_document() {
+        assert_eq!(
+            format_checkstyle(&[]),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n</checkstyle>\n"
+        );
+    }
+}
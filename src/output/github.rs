@@ -0,0 +1,108 @@
+//! GitHub Actions output (`--format github`): one workflow command per diagnostic, which
+//! the runner turns into inline annotations on the pull request.
+
+use std::path::Path;
+
+use crate::models::{Severity, Violation};
+use crate::rules::rule_slug;
+
+/// Escape the message part of a workflow command.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value (`file=`, `title=`), where `:` and `,` are separators too.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Path as GitHub expects it: relative to the working directory (the checkout) when possible.
+fn display_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    let text = relative.to_string_lossy().replace('\\', "/");
+    text.strip_prefix("./").map_or(text.clone(), str::to_string)
+}
+
+/// One `::error` / `::warning` / `::notice` command per violation.
+#[must_use]
+pub fn format_github(violations: &[Violation]) -> String {
+    let mut out = String::new();
+    for v in violations {
+        let command = match v.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info | Severity::Hint => "notice",
+        };
+        let mut properties = vec![
+            format!("file={}", escape_property(&display_path(&v.file_path))),
+            format!("line={}", v.line),
+        ];
+        if let Some(end_line) = v.end_line {
+            properties.push(format!("endLine={end_line}"));
+        }
+        if let Some(col) = v.col {
+            properties.push(format!("col={col}"));
+            // Our end column is exclusive; GitHub's is the last annotated column.
+            if let Some(end_col) = v.end_col.filter(|_| v.end_line == Some(v.line)) {
+                properties.push(format!("endColumn={}", end_col.saturating_sub(1).max(col)));
+            }
+        }
+        properties.push(format!(
+            "title={}",
+            escape_property(&format!("{} ({})", rule_slug(&v.rule_name), v.rule_id))
+        ));
+        out.push_str(&format!(
+            "::{command} {}::{}\n",
+            properties.join(","),
+            escape_data(&v.message)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::make_violation;
+    use crate::models::Category;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_format_github_maps_severity_span_and_escapes() {
+        let mut error = make_violation(
+            "PYTEST-MNT-004",
+            "NoAssertionRule",
+            Severity::Error,
+            Category::Maintenance,
+            "Test has no assertions".to_string(),
+            PathBuf::from("./tests/test_x.py"),
+            12,
+            None,
+            None,
+        );
+        error.end_line = Some(14);
+        error.col = Some(5);
+        error.end_col = Some(9);
+        let mut info = error.clone();
+        info.severity = Severity::Info;
+        info.message = "100% sure\r\nnext, line".to_string();
+        info.end_line = Some(12);
+        info.file_path = PathBuf::from("tests/a,b:c.py");
+
+        let out = format_github(&[error, info]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            "::error file=tests/test_x.py,line=12,endLine=14,col=5,title=no-assertion (PYTEST-MNT-004)::Test has no assertions"
+        );
+        assert_eq!(
+            lines[1],
+            "::notice file=tests/a%2Cb%3Ac.py,line=12,endLine=12,col=5,endColumn=8,title=no-assertion (PYTEST-MNT-004)::100%25 sure%0D%0Anext, line"
+        );
+    }
+}
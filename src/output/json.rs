@@ -0,0 +1,411 @@
+//! JSON output: a single versioned report document (`--format json`) or one diagnostic per
+//! line (`--format json-lines`).
+//!
+//! The field names here are a stable interface for dashboards and scripts; bump
+//! [`SCHEMA_VERSION`] when changing them incompatibly. Spans are the library's [`Span`],
+//! so the report and the API describe positions the same way.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::api::Span;
+use crate::config::{Config, RuleConfig};
+use crate::models::{Fix, Severity, Tag, Violation};
+use crate::output::summary::{FileCounts, Summary};
+use crate::output::SourceCache;
+use crate::rules::all_rules;
+use crate::span::LineIndex;
+
+/// Version of the JSON report layout. 2: spans are [`Span`]s, as in the library API.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Resolve a violation-style position into a [`Span`] with every field set, taking byte
+/// offsets from `index` when given. Positions without a column cover their whole line.
+fn resolve_span(
+    line: usize,
+    col: Option<usize>,
+    end: (Option<usize>, Option<usize>),
+    index: Option<&LineIndex>,
+) -> Span {
+    let line_len = |line: usize| Some(index?.line_text(line)?.len());
+    let offset = |line: usize, col: usize| index?.offset(line, col);
+
+    let start_line = line.max(1);
+    let (start_col, end_line, end_col) = match col {
+        Some(col) => (col, end.0.unwrap_or(start_line), end.1.unwrap_or(col)),
+        None => (1, start_line, line_len(start_line).map_or(1, |len| len + 1)),
+    };
+    let mut span = Span::new(start_line, Some(start_col), Some(end_line), Some(end_col));
+    span.start_byte = offset(start_line, start_col);
+    span.end_byte = offset(end_line, end_col);
+    span
+}
+
+/// Another location a diagnostic refers to, see [`Violation::related`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonRelated {
+    pub path: String,
+    /// Byte offsets are resolved only for locations in the diagnostic's own file.
+    pub span: Span,
+    pub message: String,
+}
+
+/// A single diagnostic as it appears in JSON output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonDiagnostic {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub path: String,
+    pub span: Span,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
+    /// Byte-range edits applied by `--fix`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+    /// Rule-specific measurements, see [`Violation::metadata`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// `unnecessary` / `deprecated`, see [`Violation::tags`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<JsonRelated>,
+}
+
+impl JsonDiagnostic {
+    /// Convert a violation, resolving byte offsets against the file contents when given.
+    ///
+    /// Violations without a column cover their whole line.
+    #[must_use]
+    pub fn from_violation(v: &Violation, source: Option<&str>) -> Self {
+        let index = source.map(LineIndex::new);
+        Self {
+            rule_id: v.rule_id.clone(),
+            severity: v.severity,
+            message: v.message.clone(),
+            path: v.file_path.display().to_string(),
+            span: resolve_span(v.line, v.col, (v.end_line, v.end_col), index.as_ref()),
+            suggestion: v.suggestion.clone(),
+            test_name: v.test_name.clone(),
+            fix: v.fix.clone(),
+            metadata: v.metadata.clone(),
+            tags: v.tags.clone(),
+            related: v
+                .related
+                .iter()
+                .map(|r| JsonRelated {
+                    path: r.file_path.display().to_string(),
+                    span: resolve_span(
+                        r.line,
+                        r.col,
+                        (r.end_line, r.end_col),
+                        index.as_ref().filter(|_| r.file_path == v.file_path),
+                    ),
+                    message: r.message.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Effective settings of one rule after configuration is applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRuleSettings {
+    pub enabled: bool,
+    pub severity: Severity,
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+/// An override section as configured.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonOverride {
+    pub path: String,
+    pub rules: BTreeMap<String, RuleConfig>,
+}
+
+/// The configuration a run used, with defaults filled in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonConfig {
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    pub excludes: Vec<String>,
+    pub rules: BTreeMap<String, JsonRuleSettings>,
+    pub overrides: Vec<JsonOverride>,
+}
+
+impl JsonConfig {
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        let rules = all_rules()
+            .iter()
+            .map(|rule| {
+                let id = rule.id();
+                let settings = JsonRuleSettings {
+                    enabled: config.is_rule_enabled(id),
+                    severity: config.rule_severity(id, rule.severity()),
+                    options: config
+                        .rules
+                        .get(id)
+                        .map(|rc| rc.options.clone())
+                        .unwrap_or_default(),
+                };
+                (id.to_string(), settings)
+            })
+            .collect();
+        Self {
+            format: config.format.clone(),
+            output: config.output.clone(),
+            baseline: config.baseline.clone(),
+            excludes: config.excludes.clone(),
+            rules,
+            overrides: config
+                .overrides
+                .iter()
+                .map(|o| JsonOverride {
+                    path: o.path.clone(),
+                    rules: o
+                        .rules
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The document written by `--format json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonReport {
+    pub schema_version: u32,
+    pub version: String,
+    pub config: JsonConfig,
+    pub diagnostics: Vec<JsonDiagnostic>,
+    /// Counts over `diagnostics`, see [`Summary`].
+    pub summary: Summary,
+}
+
+/// Build the report document for a run, resolving byte offsets against `sources`. `files`
+/// describes the files the run covered, when the violations came from one.
+#[must_use]
+pub fn build_report(
+    violations: &[Violation],
+    config: &Config,
+    files: Option<FileCounts>,
+    sources: &mut SourceCache,
+) -> JsonReport {
+    JsonReport {
+        schema_version: SCHEMA_VERSION,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config: JsonConfig::from_config(config),
+        diagnostics: violations
+            .iter()
+            .map(|v| JsonDiagnostic::from_violation(v, sources.get(&v.file_path)))
+            .collect(),
+        summary: Summary::new(violations, files),
+    }
+}
+
+/// Format violations as a pretty-printed JSON report.
+pub fn format_json(
+    violations: &[Violation],
+    config: &Config,
+    files: Option<FileCounts>,
+    sources: &mut SourceCache,
+) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&build_report(
+        violations, config, files, sources,
+    ))?)
+}
+
+/// Write one compact JSON diagnostic per line, without building the whole document.
+pub fn write_json_lines(
+    violations: &[Violation],
+    writer: &mut dyn Write,
+    sources: &mut SourceCache,
+) -> Result<()> {
+    for v in violations {
+        let diagnostic = JsonDiagnostic::from_violation(v, sources.get(&v.file_path));
+        serde_json::to_writer(&mut *writer, &diagnostic)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Category, RelatedLocation};
+
+    fn violation(line: usize, col: Option<usize>, end: Option<(usize, usize)>) -> Violation {
+        Violation {
+            rule_id: "PYTEST-FLK-001".to_string(),
+            rule_name: "TimeSleepRule".to_string(),
+            severity: Severity::Warning,
+            category: Category::Flakiness,
+            message: "slow".to_string(),
+            file_path: PathBuf::from("test_a.py"),
+            line,
+            col,
+            end_line: end.map(|e| e.0),
+            end_col: end.map(|e| e.1),
+            suggestion: Some("use an event".to_string()),
+            test_name: Some("test_a".to_string()),
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_round_trip() {
+        let source = "def test_a():\n    time.sleep(1)\n";
+        let d = JsonDiagnostic::from_violation(&violation(2, Some(5), Some((2, 18))), Some(source));
+        let parsed: JsonDiagnostic =
+            serde_json::from_str(&serde_json::to_string(&d).unwrap()).unwrap();
+        assert_eq!(parsed, d);
+    }
+
+    #[test]
+    fn test_span_byte_offsets() {
+        let source = "def test_a():\n    time.sleep(1)\n";
+        let d = JsonDiagnostic::from_violation(&violation(2, Some(5), Some((2, 18))), Some(source));
+        let (start, end) = (d.span.start_byte.unwrap(), d.span.end_byte.unwrap());
+        assert_eq!(&source[start..end], "time.sleep(1)");
+    }
+
+    #[test]
+    fn test_span_without_column_covers_line() {
+        let source = "def test_a():\n    pass\n";
+        let d = JsonDiagnostic::from_violation(&violation(1, None, None), Some(source));
+        assert_eq!((d.span.column, d.span.end_column), (Some(1), Some(14)));
+        assert_eq!(d.span.start_byte, Some(0));
+        assert_eq!(d.span.end_byte, Some(13));
+
+        let d = JsonDiagnostic::from_violation(&violation(1, None, None), None);
+        assert_eq!(d.span.start_byte, None);
+    }
+
+    #[test]
+    fn test_optional_fields_omitted() {
+        let mut v = violation(1, None, None);
+        v.suggestion = None;
+        v.test_name = None;
+        let json = serde_json::to_value(JsonDiagnostic::from_violation(&v, None)).unwrap();
+        assert!(json.get("suggestion").is_none());
+        assert!(json.get("test_name").is_none());
+        assert!(json.get("tags").is_none());
+        assert!(json["span"].get("start_byte").unwrap().is_null());
+    }
+
+    #[test]
+    fn test_hint_severity_and_tags_round_trip() {
+        let mut v = violation(1, None, None);
+        v.severity = Severity::Hint;
+        v.tags = vec![Tag::Unnecessary, Tag::Deprecated];
+        let d = JsonDiagnostic::from_violation(&v, None);
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(json["severity"], "hint");
+        assert_eq!(
+            json["tags"],
+            serde_json::json!(["unnecessary", "deprecated"])
+        );
+        let parsed: JsonDiagnostic = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, d);
+    }
+
+    #[test]
+    fn test_diagnostic_without_tags_still_parses() {
+        // Reports written before tags and related locations existed have neither key
+        let json = serde_json::json!({
+            "rule_id": "PYTEST-FLK-001",
+            "severity": "warning",
+            "message": "slow",
+            "path": "test_a.py",
+            "span": {"line": 1, "column": 1, "end_line": 1, "end_column": 5}
+        });
+        let parsed: JsonDiagnostic = serde_json::from_value(json).unwrap();
+        assert!(parsed.tags.is_empty());
+        assert!(parsed.related.is_empty());
+    }
+
+    #[test]
+    fn test_related_locations_round_trip() {
+        let source = "def test_a():\n    time.sleep(1)\n";
+        let mut v = violation(2, Some(5), Some((2, 18)));
+        let at = |file: &str| RelatedLocation {
+            file_path: PathBuf::from(file),
+            line: 1,
+            col: Some(5),
+            end_line: Some(1),
+            end_col: Some(11),
+            message: "defined here".to_string(),
+        };
+        v.related = vec![at("test_a.py"), at("test_b.py")];
+        let d = JsonDiagnostic::from_violation(&v, Some(source));
+        let json = serde_json::to_value(&d).unwrap();
+        assert_eq!(json["related"][0]["path"], "test_a.py");
+        assert_eq!(json["related"][0]["message"], "defined here");
+        assert_eq!(json["related"][0]["span"]["start_byte"], 4);
+        assert_eq!(json["related"][0]["span"]["end_byte"], 10);
+        // Offsets in other files would need their contents
+        assert!(json["related"][1]["span"]["start_byte"].is_null());
+        let parsed: JsonDiagnostic = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, d);
+
+        let json = serde_json::to_value(JsonDiagnostic::from_violation(
+            &violation(1, None, None),
+            None,
+        ))
+        .unwrap();
+        assert!(json.get("related").is_none());
+    }
+
+    #[test]
+    fn test_report_includes_resolved_config() {
+        let mut config = Config::default();
+        config
+            .rules
+            .entry("PYTEST-MNT-017".to_string())
+            .or_default()
+            .options
+            .insert("max_length".to_string(), toml::Value::Integer(120));
+        let report = build_report(&[], &config, None, &mut SourceCache::default());
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert_eq!(report.config.rules.len(), all_rules().len());
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["config"]["rules"]["PYTEST-MNT-017"]["max_length"], 120);
+        assert_eq!(
+            json["config"]["rules"]["PYTEST-MNT-004"]["severity"],
+            "error"
+        );
+    }
+
+    #[test]
+    fn test_json_lines_one_diagnostic_per_line() {
+        let mut out = Vec::new();
+        write_json_lines(
+            &[violation(1, None, None), violation(2, Some(1), None)],
+            &mut out,
+            &mut SourceCache::default(),
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let d: JsonDiagnostic = serde_json::from_str(line).unwrap();
+            assert_eq!(d.rule_id, "PYTEST-FLK-001");
+        }
+    }
+}
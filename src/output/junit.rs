@@ -0,0 +1,96 @@
+//! JUnit XML output (`--format junit`) for CI systems that only understand test reports
+//! (GitLab, Azure Pipelines): a `<testsuite>` per file and a failed `<testcase>` named
+//! `rule-id.path.line` per diagnostic.
+
+use super::{escape_xml, group_by_file};
+use crate::models::{Severity, Violation};
+
+fn failure_type(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+        Severity::Hint => "hint",
+    }
+}
+
+/// Render the report. With no violations this is an empty `<testsuites>` element.
+#[must_use]
+pub fn format_junit(violations: &[Violation]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"pytest-linter\" tests=\"{0}\" failures=\"{0}\">\n",
+        violations.len()
+    ));
+    for (path, group) in group_by_file(violations) {
+        let path = escape_xml(&path);
+        out.push_str(&format!(
+            "  <testsuite name=\"{path}\" tests=\"{0}\" failures=\"{0}\">\n",
+            group.len()
+        ));
+        for v in group {
+            let location = match v.col {
+                Some(col) => format!("{path}:{}:{col}", v.line),
+                None => format!("{path}:{}", v.line),
+            };
+            let message = escape_xml(&v.message);
+            out.push_str(&format!(
+                "    <testcase name=\"{}.{path}.{}\" classname=\"{path}\">\n",
+                escape_xml(&v.rule_id),
+                v.line
+            ));
+            out.push_str(&format!(
+                "      <failure message=\"{message}\" type=\"{}\">{location}: {message}</failure>\n",
+                failure_type(v.severity)
+            ));
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::make_violation;
+    use crate::models::Category;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_junit_testcase_per_violation_suite_per_file() {
+        let violation = |path: &str, line: usize| {
+            make_violation(
+                "PYTEST-MNT-004",
+                "NoAssertionRule",
+                Severity::Error,
+                Category::Maintenance,
+                "expected a < b & 'c'".to_string(),
+                PathBuf::from(path),
+                line,
+                None,
+                None,
+            )
+        };
+        let out = format_junit(&[
+            violation("tests/test_a.py", 2),
+            violation("tests/test_a.py", 7),
+            violation("tests/test_b.py", 4),
+        ]);
+        assert!(out.contains("<testsuites name=\"pytest-linter\" tests=\"3\" failures=\"3\">"));
+        assert!(out.contains("<testsuite name=\"tests/test_a.py\" tests=\"2\" failures=\"2\">"));
+        assert!(out.contains("<testcase name=\"PYTEST-MNT-004.tests/test_b.py.4\""));
+        assert!(out.contains(
+            "<failure message=\"expected a &lt; b &amp; &apos;c&apos;\" type=\"error\">tests/test_a.py:7: expected"
+        ));
+    }
+
+    #[test]
+    fn test_junit_empty_document() {
+        assert_eq!(
+            format_junit(&[]),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"pytest-linter\" tests=\"0\" failures=\"0\">\n</testsuites>\n"
+        );
+    }
+}
@@ -1,16 +1,130 @@
+use crate::config::Config;
 use crate::models::Violation;
+use crate::span::LineIndex;
 use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 // Re-export SARIF formatter module
+pub mod checkstyle;
+pub mod github;
+pub mod json;
+pub mod junit;
 pub mod sarif;
+pub mod summary;
+pub mod terminal;
 
-/// Public API: Format violations as SARIF.
+/// Public API: Format violations as SARIF, with character columns.
 pub fn format_sarif(violations: &[Violation]) -> Result<String> {
-    sarif::format_sarif(violations)
+    sarif::format_sarif(&char_columns(violations, &mut SourceCache::default()))
 }
 
-/// Public API: Format violations as pretty JSON (raw violations).
-pub fn format_json(violations: &[Violation]) -> Result<String> {
-    // Move simple JSON wrapping from engine into output layer.
-    serde_json::to_string_pretty(violations).map_err(|e| e.into())
+/// Public API: Format violations as a pretty JSON report (see [`json::JsonReport`]).
+pub fn format_json(violations: &[Violation], config: &Config) -> Result<String> {
+    json::format_json(violations, config, None, &mut SourceCache::default())
+}
+
+/// Public API: Format violations as GitHub Actions workflow commands (annotations).
+#[must_use]
+pub fn format_github(violations: &[Violation]) -> String {
+    github::format_github(&char_columns(violations, &mut SourceCache::default()))
+}
+
+/// Public API: Format violations as a Checkstyle XML report.
+#[must_use]
+pub fn format_checkstyle(violations: &[Violation]) -> String {
+    checkstyle::format_checkstyle(&char_columns(violations, &mut SourceCache::default()))
+}
+
+/// Public API: Format violations as a JUnit XML report (one failed test case each).
+#[must_use]
+pub fn format_junit(violations: &[Violation]) -> String {
+    junit::format_junit(&char_columns(violations, &mut SourceCache::default()))
+}
+
+/// Violations with their byte columns converted to 1-based character columns, as people
+/// and CI annotations count them. Columns of files that cannot be read are kept as they
+/// are. Only JSON output and the LSP server work from the byte columns.
+#[must_use]
+pub fn char_columns(violations: &[Violation], sources: &mut SourceCache) -> Vec<Violation> {
+    violations
+        .iter()
+        .map(|v| {
+            let mut v = v.clone();
+            if let Some(index) = sources.get(&v.file_path).map(LineIndex::new) {
+                let end_line = v.end_line.unwrap_or(v.line);
+                v.col = v.col.map(|c| index.char_col(v.line, c).unwrap_or(c));
+                v.end_col = v.end_col.map(|c| index.char_col(end_line, c).unwrap_or(c));
+            }
+            for r in &mut v.related {
+                if let Some(index) = sources.get(&r.file_path).map(LineIndex::new) {
+                    let end_line = r.end_line.unwrap_or(r.line);
+                    r.col = r.col.map(|c| index.char_col(r.line, c).unwrap_or(c));
+                    r.end_col = r.end_col.map(|c| index.char_col(end_line, c).unwrap_or(c));
+                }
+            }
+            v
+        })
+        .collect()
+}
+
+/// File contents for reporters that quote source or resolve byte offsets. Files are read
+/// from disk on first use unless their contents were provided up front, e.g. a buffer
+/// linted from stdin under a virtual path.
+#[derive(Debug, Default)]
+pub struct SourceCache {
+    sources: HashMap<PathBuf, Option<String>>,
+}
+
+impl SourceCache {
+    /// A cache that answers `path` with `source` instead of reading the file.
+    #[must_use]
+    pub fn with_source(path: &Path, source: &str) -> Self {
+        let mut cache = Self::default();
+        cache
+            .sources
+            .insert(path.to_path_buf(), Some(source.to_string()));
+        cache
+    }
+
+    /// Contents of `path`, or `None` if it cannot be read.
+    pub fn get(&mut self, path: &Path) -> Option<&str> {
+        self.sources
+            .entry(path.to_path_buf())
+            .or_insert_with(|| std::fs::read_to_string(path).ok())
+            .as_deref()
+    }
+}
+
+/// Escape text for an XML attribute or element. Characters XML 1.0 cannot carry at all
+/// (most control characters) are dropped.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push_str(&format!("&#{};", u32::from(ch))),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Violations grouped by file, files in order of first appearance.
+pub(crate) fn group_by_file(violations: &[Violation]) -> Vec<(String, Vec<&Violation>)> {
+    let mut groups: Vec<(String, Vec<&Violation>)> = Vec::new();
+    let mut index: HashMap<&Path, usize> = HashMap::new();
+    for v in violations {
+        let i = *index.entry(v.file_path.as_path()).or_insert_with(|| {
+            groups.push((v.file_path.to_string_lossy().into_owned(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[i].1.push(v);
+    }
+    groups
 }
@@ -1,4 +1,4 @@
-use crate::models::{Severity, Violation};
+use crate::models::{Severity, Tag, Violation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -17,6 +17,9 @@ pub struct SarifLog {
 #[serde(rename_all = "camelCase")]
 pub struct Run {
     pub tool: Tool,
+    /// `unicodeCodePoints`: region columns count characters, see
+    /// [`crate::output::char_columns`].
+    pub column_kind: String,
     pub results: Vec<SarifResult>,
 }
 
@@ -62,16 +65,35 @@ pub struct ReportingConfiguration {
 pub struct SarifResult {
     #[serde(rename = "ruleId")]
     pub rule_id: String,
+    #[serde(rename = "ruleIndex")]
+    pub rule_index: usize,
     pub level: String,
     pub message: Message,
     pub locations: Vec<Location>,
+    /// Other places the message refers to, e.g. the first definition of a duplicate.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<PropertyBag>,
+}
+
+/// SARIF property bag; only `tags` is used (`unnecessary`, `deprecated`).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyBag {
+    pub tags: Vec<Tag>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Location {
+    /// Set on related locations, numbered from 0 within the result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
     #[serde(rename = "physicalLocation")]
     pub physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,8 +115,12 @@ pub struct ArtifactLocation {
 pub struct Region {
     #[serde(rename = "startLine")]
     pub start_line: usize,
-    #[serde(rename = "startColumn")]
+    #[serde(rename = "startColumn", skip_serializing_if = "Option::is_none")]
     pub start_column: Option<usize>,
+    #[serde(rename = "endLine", skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(rename = "endColumn", skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<usize>,
 }
 
 /// Convert a file path to an RFC 3986 file URI.
@@ -110,60 +136,130 @@ fn path_to_file_uri(path: &std::path::Path) -> String {
     }
 }
 
-/// Convert violations into a SARIF log structure.
-pub fn violations_to_sarif(violations: &[Violation]) -> SarifLog {
-    // Collect unique rules for the driver
-    let mut rules_map: HashMap<String, Rule> = HashMap::new();
-    let mut results: Vec<SarifResult> = Vec::new();
+fn physical_location(
+    path: &std::path::Path,
+    line: usize,
+    col: Option<usize>,
+    end_line: Option<usize>,
+    end_col: Option<usize>,
+) -> PhysicalLocation {
+    PhysicalLocation {
+        artifact_location: ArtifactLocation {
+            uri: path_to_file_uri(path),
+        },
+        region: Region {
+            start_line: line.max(1),
+            start_column: col,
+            end_line,
+            end_column: end_col,
+        },
+    }
+}
 
-    for v in violations {
-        let level = match v.severity {
-            Severity::Error => "error",
-            Severity::Warning => "warning",
-            Severity::Info => "note",
-        }
-        .to_string();
+fn sarif_level(severity: Severity) -> String {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "note",
+    }
+    .to_string()
+}
+
+fn help_uri(rule_id: &str) -> Option<String> {
+    Some(crate::rules::rule_docs_url(rule_id))
+}
 
-        // Ensure the rule exists in the driver metadata
-        rules_map.entry(v.rule_id.clone()).or_insert_with(|| Rule {
-            id: v.rule_id.clone(),
-            name: v.rule_name.clone(),
+/// Convert violations into a SARIF log structure.
+///
+/// The driver lists every registered rule (sorted by ID) with its default severity, so
+/// the log is complete even when no violations were found.
+///
+/// Columns are written as given; pass the violations through
+/// [`crate::output::char_columns`] first, as the run declares character columns.
+pub fn violations_to_sarif(violations: &[Violation]) -> SarifLog {
+    let mut rules: Vec<Rule> = crate::rules::all_rules()
+        .iter()
+        .map(|r| Rule {
+            id: r.id().to_string(),
+            name: r.name().to_string(),
             short_description: Message {
-                text: v.rule_name.clone(),
+                text: r.description(),
             },
-            help_uri: Some(format!(
-                "https://github.com/Jonathangadeaharder/pytest-linter/blob/main/docs/rules/{}.md",
-                v.rule_id
-            )),
+            help_uri: help_uri(r.id()),
             default_configuration: ReportingConfiguration {
-                level: level.clone(),
+                level: sarif_level(r.severity()),
             },
-        });
+        })
+        .collect();
+
+    // Violations from outside the registry still need a rule entry to index into
+    for v in violations {
+        if !rules.iter().any(|r| r.id == v.rule_id) {
+            rules.push(Rule {
+                id: v.rule_id.clone(),
+                name: v.rule_name.clone(),
+                short_description: Message {
+                    text: v.rule_name.clone(),
+                },
+                help_uri: None,
+                default_configuration: ReportingConfiguration {
+                    level: sarif_level(v.severity),
+                },
+            });
+        }
+    }
 
-        let result = SarifResult {
+    // Sort rules by ID for deterministic output
+    rules.sort_by(|a, b| a.id.cmp(&b.id));
+    let rule_index: HashMap<&str, usize> = rules
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.id.as_str(), i))
+        .collect();
+
+    let results: Vec<SarifResult> = violations
+        .iter()
+        .map(|v| SarifResult {
             rule_id: v.rule_id.clone(),
-            level,
+            rule_index: rule_index[v.rule_id.as_str()],
+            level: sarif_level(v.severity),
             message: Message {
                 text: v.message.clone(),
             },
             locations: vec![Location {
-                physical_location: PhysicalLocation {
-                    artifact_location: ArtifactLocation {
-                        uri: path_to_file_uri(&v.file_path),
-                    },
-                    region: Region {
-                        start_line: v.line,
-                        start_column: v.col,
-                    },
-                },
+                id: None,
+                physical_location: physical_location(
+                    &v.file_path,
+                    v.line,
+                    v.col,
+                    v.end_line,
+                    v.end_col,
+                ),
+                message: None,
             }],
-        };
-        results.push(result);
-    }
-
-    // Sort rules by ID for deterministic output
-    let mut rules: Vec<Rule> = rules_map.into_values().collect();
-    rules.sort_by(|a, b| a.id.cmp(&b.id));
+            related_locations: v
+                .related
+                .iter()
+                .enumerate()
+                .map(|(id, r)| Location {
+                    id: Some(id),
+                    physical_location: physical_location(
+                        &r.file_path,
+                        r.line,
+                        r.col,
+                        r.end_line,
+                        r.end_col,
+                    ),
+                    message: Some(Message {
+                        text: r.message.clone(),
+                    }),
+                })
+                .collect(),
+            properties: (!v.tags.is_empty()).then(|| PropertyBag {
+                tags: v.tags.clone(),
+            }),
+        })
+        .collect();
 
     let run = Run {
         tool: Tool {
@@ -173,6 +269,7 @@ pub fn violations_to_sarif(violations: &[Violation]) -> SarifLog {
                 rules,
             },
         },
+        column_kind: "unicodeCodePoints".to_string(),
         results,
     };
 
@@ -193,7 +290,7 @@ pub fn format_sarif(violations: &[Violation]) -> anyhow::Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Category, Severity};
+    use crate::models::{Category, RelatedLocation, Severity};
     use std::path::PathBuf;
 
     #[test]
@@ -207,8 +304,14 @@ mod tests {
             file_path: PathBuf::from("a.py"),
             line: 10,
             col: Some(2),
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         };
         let log = violations_to_sarif(&[v]);
         assert_eq!(log.version, "2.1.0");
@@ -231,8 +334,14 @@ mod tests {
                 file_path: PathBuf::from("b.py"),
                 line: 5,
                 col: None,
+                end_line: None,
+                end_col: None,
                 suggestion: None,
                 test_name: None,
+                fix: None,
+                metadata: Default::default(),
+                tags: Vec::new(),
+                related: Vec::new(),
             },
             Violation {
                 rule_id: "R1".to_string(),
@@ -243,14 +352,159 @@ mod tests {
                 file_path: PathBuf::from("a.py"),
                 line: 1,
                 col: None,
+                end_line: None,
+                end_col: None,
                 suggestion: None,
                 test_name: None,
+                fix: None,
+                metadata: Default::default(),
+                tags: Vec::new(),
+                related: Vec::new(),
             },
         ];
         let log = violations_to_sarif(&violations);
         let rules = &log.runs[0].tool.driver.rules;
-        assert_eq!(rules[0].id, "R1");
-        assert_eq!(rules[1].id, "R2");
+        let ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        assert_eq!(ids, sorted);
+        let r1 = ids.iter().position(|id| *id == "R1").unwrap();
+        let r2 = ids.iter().position(|id| *id == "R2").unwrap();
+        assert!(r1 < r2);
+    }
+
+    fn violation(rule_id: &str, line: usize, col: Option<usize>) -> Violation {
+        Violation {
+            rule_id: rule_id.to_string(),
+            rule_name: "NoAssertionRule".to_string(),
+            severity: Severity::Warning,
+            category: Category::Maintenance,
+            message: "Test has no assertions".to_string(),
+            file_path: PathBuf::from("tests/test_a.py"),
+            line,
+            col,
+            end_line: None,
+            end_col: None,
+            suggestion: None,
+            test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sarif_rules_come_from_registry_with_default_level() {
+        let log = violations_to_sarif(&[violation("PYTEST-MNT-004", 3, None)]);
+        let rules = &log.runs[0].tool.driver.rules;
+        assert_eq!(rules.len(), crate::rules::all_rules().len());
+        let rule = rules.iter().find(|r| r.id == "PYTEST-MNT-004").unwrap();
+        // Default severity, not the (possibly overridden) severity of the result
+        assert_eq!(rule.default_configuration.level, "error");
+        assert_eq!(rule.short_description.text, "No assertion");
+        assert_eq!(log.runs[0].results[0].level, "warning");
+    }
+
+    #[test]
+    fn test_sarif_empty_run_keeps_results_array() {
+        let json = format_sarif(&[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let run = &value["runs"][0];
+        assert_eq!(run["results"].as_array().unwrap().len(), 0);
+        assert!(!run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sarif_same_rule_twice_in_one_file() {
+        let log = violations_to_sarif(&[
+            violation("PYTEST-MNT-004", 3, Some(1)),
+            violation("PYTEST-MNT-004", 9, Some(5)),
+        ]);
+        let run = &log.runs[0];
+        assert_eq!(run.results.len(), 2);
+        assert_eq!(run.results[0].rule_index, run.results[1].rule_index);
+        let indexed = &run.tool.driver.rules[run.results[0].rule_index];
+        assert_eq!(indexed.id, "PYTEST-MNT-004");
+        assert_eq!(
+            run.tool
+                .driver
+                .rules
+                .iter()
+                .filter(|r| r.id == "PYTEST-MNT-004")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_sarif_region_omits_unknown_column() {
+        let json = format_sarif(&[violation("PYTEST-MNT-004", 3, None)]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let region = &value["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 3);
+        assert!(region.get("startColumn").is_none());
+        assert!(region.get("endLine").is_none());
+    }
+
+    #[test]
+    fn test_sarif_hint_is_a_note_with_tags() {
+        let mut v = violation("PYTEST-FIX-005", 3, Some(1));
+        v.severity = Severity::Hint;
+        v.tags = vec![Tag::Unnecessary];
+        let json = format_sarif(&[v, violation("PYTEST-MNT-004", 9, None)]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let results = &value["runs"][0]["results"];
+        assert_eq!(results[0]["level"], "note");
+        assert_eq!(
+            results[0]["properties"]["tags"],
+            serde_json::json!(["unnecessary"])
+        );
+        assert!(results[1].get("properties").is_none());
+    }
+
+    #[test]
+    fn test_sarif_related_locations() {
+        let mut v = violation("PYTEST-MNT-022", 9, Some(5));
+        v.related = vec![RelatedLocation {
+            file_path: PathBuf::from("tests/test_a.py"),
+            line: 3,
+            col: Some(5),
+            end_line: Some(3),
+            end_col: Some(11),
+            message: "previous definition of 'test_a', which never runs".to_string(),
+        }];
+        let json = format_sarif(&[v, violation("PYTEST-MNT-004", 12, None)]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let results = &value["runs"][0]["results"];
+        let related = &results[0]["relatedLocations"][0];
+        assert_eq!(related["id"], 0);
+        assert_eq!(
+            related["message"]["text"],
+            "previous definition of 'test_a', which never runs"
+        );
+        assert_eq!(related["physicalLocation"]["region"]["startLine"], 3);
+        assert!(results[0]["locations"][0].get("id").is_none());
+        assert!(results[1].get("relatedLocations").is_none());
+
+        let log: SarifLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string_pretty(&log).unwrap(), json);
+    }
+
+    #[test]
+    fn test_sarif_round_trip() {
+        let json = format_sarif(&[
+            violation("PYTEST-MNT-004", 3, Some(1)),
+            violation("PYTEST-MNT-004", 9, None),
+        ])
+        .unwrap();
+        let log: SarifLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(log.version, "2.1.0");
+        assert_eq!(log.runs[0].results.len(), 2);
+        assert_eq!(serde_json::to_string_pretty(&log).unwrap(), json);
     }
 
     #[test]
@@ -0,0 +1,284 @@
+//! Run summary behind `--statistics` and `--top-files`, also embedded in JSON reports under
+//! `summary`. It is computed from the violations that are reported, i.e. after inline
+//! suppressions, the changed-lines filter and the baseline.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::CacheStats;
+use crate::models::{Severity, Violation};
+
+/// What happened to the files a run discovered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCounts {
+    /// Files parsed and checked (including those served from the cache).
+    pub analyzed: usize,
+    /// Files that could not be read or parsed.
+    pub skipped: usize,
+    /// Analyzed files whose diagnostics came from the cache.
+    pub cached: usize,
+}
+
+/// Reported diagnostics per severity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+    /// Absent in reports written before the `hint` severity existed.
+    #[serde(default)]
+    pub hint: usize,
+}
+
+/// Diagnostics of one rule and the number of files they are spread over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCount {
+    pub rule_id: String,
+    pub count: usize,
+    pub files: usize,
+}
+
+/// Diagnostics in one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCount {
+    pub path: String,
+    pub count: usize,
+}
+
+/// Counts over the reported diagnostics of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub total: usize,
+    pub by_severity: SeverityCounts,
+    /// Rules with at least one diagnostic, most frequent first.
+    pub by_rule: Vec<RuleCount>,
+    /// Files with at least one diagnostic, most diagnostics first.
+    pub by_file: Vec<FileCount>,
+    /// Absent when the violations did not come from a run over files (e.g. the LSP).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files: Option<FileCounts>,
+}
+
+impl Summary {
+    #[must_use]
+    pub fn new(violations: &[Violation], files: Option<FileCounts>) -> Self {
+        let mut by_severity = SeverityCounts::default();
+        let mut rules: BTreeMap<&str, (usize, BTreeSet<&Path>)> = BTreeMap::new();
+        let mut per_file: BTreeMap<&Path, usize> = BTreeMap::new();
+        for v in violations {
+            match v.severity {
+                Severity::Error => by_severity.error += 1,
+                Severity::Warning => by_severity.warning += 1,
+                Severity::Info => by_severity.info += 1,
+                Severity::Hint => by_severity.hint += 1,
+            }
+            let rule = rules.entry(&v.rule_id).or_default();
+            rule.0 += 1;
+            rule.1.insert(&v.file_path);
+            *per_file.entry(&v.file_path).or_default() += 1;
+        }
+
+        let mut by_rule: Vec<RuleCount> = rules
+            .into_iter()
+            .map(|(rule_id, (count, files))| RuleCount {
+                rule_id: rule_id.to_string(),
+                count,
+                files: files.len(),
+            })
+            .collect();
+        // Stable sorts over the ID- and path-ordered maps break ties alphabetically
+        by_rule.sort_by_key(|r| std::cmp::Reverse(r.count));
+        let mut by_file: Vec<FileCount> = per_file
+            .into_iter()
+            .map(|(path, count)| FileCount {
+                path: path.display().to_string(),
+                count,
+            })
+            .collect();
+        by_file.sort_by_key(|f| std::cmp::Reverse(f.count));
+
+        Self {
+            total: violations.len(),
+            by_severity,
+            by_rule,
+            by_file,
+            files,
+        }
+    }
+}
+
+fn plural(n: usize, singular: &str, plural: &str) -> String {
+    format!("{n} {}", if n == 1 { singular } else { plural })
+}
+
+/// The `--statistics` table: diagnostics per rule, totals by severity, file counts and the
+/// cache, if enabled.
+#[must_use]
+pub fn format_statistics(summary: &Summary, cache: Option<CacheStats>) -> String {
+    let mut out = String::new();
+    if !summary.by_rule.is_empty() {
+        let width = summary
+            .by_rule
+            .iter()
+            .map(|r| r.rule_id.len())
+            .max()
+            .unwrap_or(0)
+            .max("Rule".len());
+        let _ = writeln!(out, "{:<width$}  {:>6}  {:>6}", "Rule", "Count", "Files");
+        for r in &summary.by_rule {
+            let _ = writeln!(out, "{:<width$}  {:>6}  {:>6}", r.rule_id, r.count, r.files);
+        }
+    }
+    let s = summary.by_severity;
+    let _ = writeln!(
+        out,
+        "Total: {} ({}, {}, {} info{})",
+        plural(summary.total, "diagnostic", "diagnostics"),
+        plural(s.error, "error", "errors"),
+        plural(s.warning, "warning", "warnings"),
+        s.info,
+        if s.hint > 0 {
+            format!(", {}", plural(s.hint, "hint", "hints"))
+        } else {
+            String::new()
+        }
+    );
+    if let Some(files) = summary.files {
+        let _ = writeln!(
+            out,
+            "Files: {} analyzed, {} skipped, {} cached",
+            files.analyzed, files.skipped, files.cached
+        );
+    }
+    match cache {
+        Some(stats) => {
+            let _ = writeln!(out, "Cache: {} hits, {} misses", stats.hits, stats.misses);
+        }
+        None => out.push_str("Cache: disabled\n"),
+    }
+    out
+}
+
+/// The `--top-files` table: the `n` files with the most diagnostics.
+#[must_use]
+pub fn format_top_files(summary: &Summary, n: usize) -> String {
+    let shown = &summary.by_file[..n.min(summary.by_file.len())];
+    let width = shown
+        .iter()
+        .map(|f| f.count.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let mut out = format!(
+        "Top {} by diagnostics:\n",
+        plural(shown.len(), "file", "files")
+    );
+    for f in shown {
+        let _ = writeln!(out, "  {:>width$}  {}", f.count, f.path);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Category;
+    use std::path::PathBuf;
+
+    fn violation(rule_id: &str, severity: Severity, file: &str) -> Violation {
+        Violation {
+            rule_id: rule_id.to_string(),
+            rule_name: "Rule".to_string(),
+            severity,
+            category: Category::Flakiness,
+            message: "m".to_string(),
+            file_path: PathBuf::from(file),
+            line: 1,
+            col: None,
+            end_line: None,
+            end_col: None,
+            suggestion: None,
+            test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
+        }
+    }
+
+    fn sample() -> Vec<Violation> {
+        vec![
+            violation("PYTEST-MNT-004", Severity::Error, "test_b.py"),
+            violation("PYTEST-FLK-001", Severity::Warning, "test_a.py"),
+            violation("PYTEST-FLK-001", Severity::Warning, "test_b.py"),
+            violation("PYTEST-FLK-001", Severity::Warning, "test_b.py"),
+            violation("PYTEST-MNT-024", Severity::Info, "test_c.py"),
+        ]
+    }
+
+    #[test]
+    fn test_summary_counts_rules_files_and_severities() {
+        let files = FileCounts {
+            analyzed: 4,
+            skipped: 1,
+            cached: 2,
+        };
+        let summary = Summary::new(&sample(), Some(files));
+        assert_eq!(summary.total, 5);
+        assert_eq!(
+            summary.by_severity,
+            SeverityCounts {
+                error: 1,
+                warning: 3,
+                info: 1,
+                hint: 0
+            }
+        );
+        let rules: Vec<_> = summary
+            .by_rule
+            .iter()
+            .map(|r| (r.rule_id.as_str(), r.count, r.files))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("PYTEST-FLK-001", 3, 2),
+                ("PYTEST-MNT-004", 1, 1),
+                ("PYTEST-MNT-024", 1, 1)
+            ]
+        );
+        let worst: Vec<_> = summary.by_file.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(worst, ["test_b.py", "test_a.py", "test_c.py"]);
+    }
+
+    #[test]
+    fn test_format_statistics_and_top_files() {
+        let files = FileCounts {
+            analyzed: 4,
+            skipped: 1,
+            cached: 0,
+        };
+        let summary = Summary::new(&sample(), Some(files));
+        assert_eq!(
+            format_statistics(&summary, None),
+            "Rule             Count   Files\n\
+             PYTEST-FLK-001       3       2\n\
+             PYTEST-MNT-004       1       1\n\
+             PYTEST-MNT-024       1       1\n\
+             Total: 5 diagnostics (1 error, 3 warnings, 1 info)\n\
+             Files: 4 analyzed, 1 skipped, 0 cached\n\
+             Cache: disabled\n"
+        );
+        assert_eq!(
+            format_top_files(&summary, 2),
+            "Top 2 files by diagnostics:\n  3  test_b.py\n  1  test_a.py\n"
+        );
+        let empty = Summary::new(&[], None);
+        assert_eq!(
+            format_statistics(&empty, Some(CacheStats { hits: 2, misses: 0 })),
+            "Total: 0 diagnostics (0 errors, 0 warnings, 0 info)\nCache: 2 hits, 0 misses\n"
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Terminal renderers: the rich, rustc-style default with source snippets, and the terse
+//! one-line-per-violation `short` format.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use colored::{ColoredString, Colorize};
+
+use crate::models::{Severity, Violation};
+use crate::output::SourceCache;
+use crate::span::LineIndex;
+
+/// Display columns a tab advances to (tab stops every `TAB_WIDTH` columns).
+const TAB_WIDTH: usize = 4;
+
+/// Lines of a multi-line span shown before eliding the middle.
+const MAX_SPAN_LINES: usize = 6;
+
+fn severity_label(severity: Severity) -> ColoredString {
+    match severity {
+        Severity::Error => "error".red().bold(),
+        Severity::Warning => "warning".yellow().bold(),
+        Severity::Info => "info".blue().bold(),
+        Severity::Hint => "hint".cyan().bold(),
+    }
+}
+
+/// Approximate terminal width of a character: 0 for combining marks, 2 for wide East Asian
+/// characters and emoji, 1 otherwise.
+fn char_width(c: char) -> usize {
+    match u32::from(c) {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Expand tabs so the rendered line and its underline agree on column positions.
+fn expand_tabs(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let pad = TAB_WIDTH - width % TAB_WIDTH;
+            out.extend(std::iter::repeat_n(' ', pad));
+            width += pad;
+        } else {
+            out.push(c);
+            width += char_width(c);
+        }
+    }
+    out
+}
+
+/// Display width of the first `byte_col` bytes of `line` (violation columns are byte offsets).
+fn display_width(line: &str, byte_col: usize) -> usize {
+    let mut end = byte_col.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut width = 0;
+    for c in line[..end].chars() {
+        if c == '\t' {
+            width += TAB_WIDTH - width % TAB_WIDTH;
+        } else {
+            width += char_width(c);
+        }
+    }
+    width
+}
+
+/// Underline segment `(start, len)` in display columns for one line of a span.
+fn underline_for_line(
+    line: &str,
+    line_no: usize,
+    v: &Violation,
+    end_line: usize,
+) -> Option<(usize, usize)> {
+    let col = v.col?;
+    let line_width = display_width(line, line.len());
+    let indent = display_width(line, line.len() - line.trim_start().len());
+    let start = if line_no == v.line {
+        display_width(line, col - 1)
+    } else {
+        indent
+    };
+    let end = match v.end_col {
+        Some(end_col) if line_no == end_line => display_width(line, end_col - 1),
+        _ if line_no == end_line && v.end_line.is_none() => start + 1,
+        _ => line_width,
+    };
+    Some((start, end.max(start + 1) - start))
+}
+
+/// Render one violation with its source snippet. `source` is the full file contents.
+#[must_use]
+pub fn render_diagnostic(v: &Violation, source: Option<&str>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}{}: {}",
+        severity_label(v.severity),
+        format!("[{}]", v.rule_id).bold(),
+        v.message.bold()
+    );
+
+    let index = source.map(LineIndex::new);
+    let location = format_location(&v.file_path, v.line, v.col, index.as_ref());
+
+    let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+    let end_line = v.end_line.unwrap_or(v.line).max(v.line);
+    let shown: Vec<usize> = if end_line - v.line < MAX_SPAN_LINES {
+        (v.line..=end_line).collect()
+    } else {
+        (v.line..v.line + 2)
+            .chain(end_line - 1..=end_line)
+            .collect()
+    };
+    let gutter = end_line.to_string().len();
+    let bar = "|".blue().bold();
+    let _ = writeln!(out, "{:gutter$}{} {location}", "", "-->".blue().bold());
+
+    if v.line <= lines.len() {
+        let _ = writeln!(out, "{:gutter$} {bar}", "");
+        let mut previous = None;
+        for &line_no in &shown {
+            let Some(line) = lines.get(line_no - 1) else {
+                break;
+            };
+            if previous.is_some_and(|p| line_no > p + 1) {
+                let _ = writeln!(out, "{}", "...".blue().bold());
+            }
+            previous = Some(line_no);
+            let _ = writeln!(
+                out,
+                "{} {bar} {}",
+                format!("{line_no:>gutter$}").blue().bold(),
+                expand_tabs(line)
+            );
+            if let Some((start, len)) = underline_for_line(line, line_no, v, end_line) {
+                let marks = "^".repeat(len);
+                let marks = match v.severity {
+                    Severity::Error => marks.red().bold(),
+                    Severity::Warning => marks.yellow().bold(),
+                    Severity::Info => marks.blue().bold(),
+                    Severity::Hint => marks.cyan().bold(),
+                };
+                let _ = writeln!(out, "{:gutter$} {bar} {:start$}{marks}", "", "");
+            }
+        }
+        let _ = writeln!(out, "{:gutter$} {bar}", "");
+    }
+
+    if let Some(ref test_name) = v.test_name {
+        let _ = writeln!(
+            out,
+            "{:gutter$} {} note: in test `{test_name}`",
+            "",
+            "=".blue().bold()
+        );
+    }
+    for related in &v.related {
+        // Columns in other files are shown as bytes, their contents are not at hand
+        let index = index.as_ref().filter(|_| related.file_path == v.file_path);
+        let _ = writeln!(
+            out,
+            "{:gutter$} {} note: {}: {}",
+            "",
+            "=".blue().bold(),
+            format_location(&related.file_path, related.line, related.col, index),
+            related.message
+        );
+    }
+    if let Some(ref suggestion) = v.suggestion {
+        let _ = writeln!(
+            out,
+            "{:gutter$} {} {}: {suggestion}",
+            "",
+            "=".blue().bold(),
+            "help".bold()
+        );
+    }
+    out
+}
+
+/// `path:line:col`, with the column counted in characters when the file is indexed, as
+/// editors count it.
+fn format_location(
+    path: &Path,
+    line: usize,
+    col: Option<usize>,
+    index: Option<&LineIndex>,
+) -> String {
+    match col {
+        Some(col) => {
+            let col = index.and_then(|i| i.char_col(line, col)).unwrap_or(col);
+            format!("{}:{line}:{col}", path.display())
+        }
+        None => format!("{}:{line}", path.display()),
+    }
+}
+
+/// Render violations with source snippets, reading each file once.
+#[must_use]
+pub fn render_pretty(violations: &[Violation]) -> String {
+    render_pretty_with(violations, &mut SourceCache::default())
+}
+
+/// Render violations with source snippets taken from `sources`.
+#[must_use]
+pub fn render_pretty_with(violations: &[Violation], sources: &mut SourceCache) -> String {
+    let mut out = String::new();
+    for v in violations {
+        out.push_str(&render_diagnostic(v, sources.get(&v.file_path)));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render violations one per line: `SEVERITY [RULE] message (file:line:col)`.
+#[must_use]
+pub fn render_short(violations: &[Violation]) -> String {
+    let mut out = String::new();
+    for v in violations {
+        let severity_str = match v.severity {
+            Severity::Error => "ERROR".red().bold(),
+            Severity::Warning => "WARNING".yellow().bold(),
+            Severity::Info => "INFO".blue().bold(),
+            Severity::Hint => "HINT".cyan().bold(),
+        };
+        let location = format!(
+            "{}:{}:{}",
+            v.file_path.display(),
+            v.line,
+            v.col.map_or_else(|| "-".to_string(), |c| c.to_string())
+        );
+        let _ = writeln!(
+            out,
+            "{} [{}] {} ({})",
+            severity_str, v.rule_id, v.message, location
+        );
+        if let Some(ref suggestion) = v.suggestion {
+            let _ = writeln!(out, "  {} {}", "→".cyan(), suggestion);
+        }
+        if let Some(ref test_name) = v.test_name {
+            let _ = writeln!(out, "  {} test: {}", "→".dimmed(), test_name);
+        }
+    }
+    out
+}
+
+/// Whether colored output should be used for the given destination.
+///
+/// Colors are off with `--no-color`, when `NO_COLOR` is set to a non-empty value, when
+/// writing to a file, and when stdout is not a terminal.
+#[must_use]
+pub fn use_color(no_color: bool, output: Option<&Path>) -> bool {
+    use std::io::IsTerminal;
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color && !no_color_env && output.is_none() && std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Category, RelatedLocation};
+    use std::path::PathBuf;
+
+    fn violation(line: usize, col: Option<usize>, end: Option<(usize, usize)>) -> Violation {
+        Violation {
+            rule_id: "PYTEST-FLK-001".to_string(),
+            rule_name: "TimeSleepRule".to_string(),
+            severity: Severity::Warning,
+            category: Category::Flakiness,
+            message: "slow".to_string(),
+            file_path: PathBuf::from("test_a.py"),
+            line,
+            col,
+            end_line: end.map(|e| e.0),
+            end_col: end.map(|e| e.1),
+            suggestion: Some("use an event".to_string()),
+            test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
+        }
+    }
+
+    fn render(v: &Violation, source: &str) -> String {
+        colored::control::set_override(false);
+        render_diagnostic(v, Some(source))
+    }
+
+    #[test]
+    fn test_render_underlines_exact_span() {
+        let source = "def test_a():\n    time.sleep(1)\n";
+        let out = render(&violation(2, Some(5), Some((2, 18))), source);
+        assert_eq!(
+            out,
+            "warning[PYTEST-FLK-001]: slow\n \
+             --> test_a.py:2:5\n  \
+             |\n\
+             2 |     time.sleep(1)\n  \
+             |     ^^^^^^^^^^^^^\n  \
+             |\n  \
+             = help: use an event\n"
+        );
+    }
+
+    #[test]
+    fn test_render_aligns_after_tabs_and_wide_chars() {
+        let source = "x = \"日本\"\t; time.sleep(1)\n";
+        let out = render(&violation(1, Some(16), Some((1, 29))), source);
+        let lines: Vec<&str> = out.lines().collect();
+        // Byte column 16 is the 12th character
+        assert!(lines[1].ends_with("test_a.py:1:12"), "{}", lines[1]);
+        let code = lines[3].split_once("| ").unwrap().1;
+        let marks = lines[4].split_once("| ").unwrap().1;
+        let caret = marks.find('^').unwrap();
+        // 'x = "' (5) + two wide chars (4) + '"' (1) = 10, tab to 12, '; ' = 14
+        assert_eq!(caret, 14);
+        assert_eq!(&code[code.len() - 13..], "time.sleep(1)");
+        assert_eq!(marks.trim_start().len(), 13);
+    }
+
+    #[test]
+    fn test_render_multiline_span_underlines_each_line() {
+        let source = "def test_a():\n    for x in xs:\n        assert x\n";
+        let out = render(&violation(2, Some(5), Some((3, 17))), source);
+        assert!(out.contains("2 |     for x in xs:\n  |     ^^^^^^^^^^^^\n"));
+        assert!(out.contains("3 |         assert x\n  |         ^^^^^^^^\n"));
+    }
+
+    #[test]
+    fn test_render_without_column_has_no_underline() {
+        let out = render(&violation(1, None, None), "def test_a():\n");
+        assert!(out.contains("--> test_a.py:1\n"));
+        assert!(!out.contains('^'));
+    }
+
+    #[test]
+    fn test_render_related_locations_as_notes() {
+        let source = "def test_日本():\n    pass\n\ndef test_日本():\n    pass\n";
+        let mut v = violation(4, Some(5), Some((4, 15)));
+        v.related = vec![RelatedLocation {
+            file_path: PathBuf::from("test_a.py"),
+            line: 1,
+            col: Some(16),
+            end_line: Some(1),
+            end_col: Some(17),
+            message: "previous definition".to_string(),
+        }];
+        let out = render(&v, source);
+        // Byte column 16 is the 12th character
+        assert!(
+            out.contains("  = note: test_a.py:1:12: previous definition\n  = help: use an event\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_render_short_is_one_line_per_violation() {
+        colored::control::set_override(false);
+        let out = render_short(&[violation(2, None, None)]);
+        assert!(out.starts_with("WARNING [PYTEST-FLK-001] slow (test_a.py:2:-)\n"));
+    }
+}
@@ -1,27 +1,124 @@
-use crate::models::{Fixture, FixtureScope, ParsedModule, TestFunction};
+use crate::models::{Comment, Fixture, FixtureScope, ParsedModule, SyntaxError, TestFunction};
 use anyhow::Result;
 use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
 use std::path::Path;
-use tree_sitter::Parser;
+use tree_sitter::{Parser, Tree};
 
 struct DecoratorInfo<'a> {
     text: String,
     node: Option<tree_sitter::Node<'a>>,
 }
 
+/// Base classes that make a class a `unittest`-style test case.
+const UNITTEST_BASES: &[&str] = &[
+    "unittest.TestCase",
+    "unittest.IsolatedAsyncioTestCase",
+    "django.test.TestCase",
+    "django.test.SimpleTestCase",
+    "django.test.TransactionTestCase",
+    "django.test.LiveServerTestCase",
+    "rest_framework.test.APITestCase",
+    "asynctest.TestCase",
+];
+
+/// Statements and clauses that own a `block`, the only places an empty suite can hide.
+const COMPOUND_KINDS: &[&str] = &[
+    "if_statement",
+    "elif_clause",
+    "else_clause",
+    "for_statement",
+    "while_statement",
+    "try_statement",
+    "except_clause",
+    "except_group_clause",
+    "finally_clause",
+    "with_statement",
+    "function_definition",
+    "class_definition",
+    "decorated_definition",
+    "match_statement",
+    "case_clause",
+];
+
+/// Whether `block` holds no statement, only comments if anything.
+fn is_empty_block(block: tree_sitter::Node) -> bool {
+    let mut cursor = block.walk();
+    let empty = block
+        .named_children(&mut cursor)
+        .all(|n| n.kind() == "comment");
+    empty
+}
+
+/// The syntax errors of `source`, as reported by `PYTEST-SYN-001`.
+#[must_use]
+pub fn syntax_errors(source: &str) -> Vec<SyntaxError> {
+    crate::rules::ast::parse_python(source).map_or_else(Vec::new, |tree| {
+        PythonParser::extract_syntax_errors(tree.root_node(), source.as_bytes())
+    })
+}
+
+/// Settings that change what the parser extracts. They are part of the parse cache key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Extra base classes (dotted or bare names) that make a class a `unittest.TestCase`,
+    /// for project base classes defined in another module.
+    pub unittest_base_classes: Vec<String>,
+}
+
+impl ParseOptions {
+    #[must_use]
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            unittest_base_classes: config.unittest_base_classes.clone(),
+        }
+    }
+
+    /// Text mixed into the parse cache key: empty for the defaults, so existing cache
+    /// entries stay valid.
+    #[must_use]
+    pub fn cache_salt(&self) -> String {
+        if self.unittest_base_classes.is_empty() {
+            String::new()
+        } else {
+            format!("unittest_base_classes={:?}\n", self.unittest_base_classes)
+        }
+    }
+
+    /// Whether the fully resolved base `name` is a `TestCase` base.
+    fn is_unittest_base(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('.');
+        let matches = |base: &str| {
+            base == name
+                || base.ends_with(&format!(".{name}"))
+                || name.ends_with(&format!(".{base}"))
+        };
+        UNITTEST_BASES.contains(&name)
+            || self
+                .unittest_base_classes
+                .iter()
+                .any(|base| matches(base.as_str()))
+    }
+}
+
 /// Tree-sitter based Python test file parser that extracts test functions and fixtures.
 pub struct PythonParser {
     parser: Parser,
+    options: ParseOptions,
 }
 
 impl PythonParser {
     /// Create a new parser with the Python grammar loaded.
     #[allow(clippy::missing_errors_doc)]
     pub fn new() -> Result<Self> {
+        Self::with_options(ParseOptions::default())
+    }
+
+    /// Create a parser with non-default [`ParseOptions`].
+    #[allow(clippy::missing_errors_doc)]
+    pub fn with_options(options: ParseOptions) -> Result<Self> {
         let mut parser = Parser::new();
         parser.set_language(&tree_sitter_python::LANGUAGE.into())?;
-        Ok(Self { parser })
+        Ok(Self { parser, options })
     }
 
     /// Parse a Python file and extract its test functions, fixtures, and imports.
@@ -33,6 +130,17 @@ impl PythonParser {
 
     #[allow(clippy::missing_errors_doc)]
     pub fn parse_source(&mut self, source: &str, path: &Path) -> Result<ParsedModule> {
+        Ok(self.parse_source_with_tree(source, path)?.0)
+    }
+
+    /// Like [`Self::parse_source`], also returning the syntax tree for callers that walk it
+    /// afterwards; `None` when tree-sitter could not parse the source.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn parse_source_with_tree(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> Result<(ParsedModule, Option<Tree>)> {
         let tree = self.parser.parse(source, None);
         let file_path = path.to_path_buf();
 
@@ -40,27 +148,118 @@ impl PythonParser {
             let root = tree.root_node();
             let source_bytes = source.as_bytes();
             let imports = Self::extract_imports(&root, source_bytes);
-            let test_functions = Self::extract_test_functions(&root, source_bytes, &file_path);
+            let unittest_classes = Self::unittest_classes(&root, source_bytes, &self.options);
+            let test_functions =
+                Self::extract_test_functions(&root, source_bytes, &file_path, &unittest_classes);
             let fixtures = Self::extract_fixtures(&root, source_bytes, &file_path);
-            Ok(ParsedModule {
+            let comments = Self::extract_comments(&root, source_bytes);
+            let syntax_errors = Self::extract_syntax_errors(root, source_bytes);
+            let module = ParsedModule {
                 file_path,
                 source: source.to_string(),
                 imports,
                 test_functions,
                 fixtures,
-            })
+                comments,
+                syntax_errors,
+            };
+            Ok((module, Some(tree)))
         } else {
             eprintln!(
                 "Warning: tree-sitter failed to parse {}",
                 file_path.display()
             );
-            Ok(ParsedModule {
+            let module = ParsedModule {
                 file_path,
                 source: source.to_string(),
                 imports: vec![],
                 test_functions: vec![],
                 fixtures: vec![],
-            })
+                comments: vec![],
+                syntax_errors: vec![],
+            };
+            Ok((module, None))
+        }
+    }
+
+    /// The code tree-sitter skipped (`ERROR` nodes) or had to make up (`MISSING` tokens),
+    /// and the compound statements left without a body, outermost first in source order.
+    /// Tree-sitter accepts `if x:` followed by a dedent as an empty `block`, which Python
+    /// rejects.
+    fn extract_syntax_errors(root: tree_sitter::Node, source: &[u8]) -> Vec<SyntaxError> {
+        let mut errors = Vec::new();
+        let mut to_visit = vec![root];
+        while let Some(node) = to_visit.pop() {
+            if node.is_error() || node.is_missing() {
+                let message = if node.is_missing() {
+                    format!("expected `{}`", node.kind())
+                } else {
+                    let text = Self::node_text(node, source);
+                    let first_line = text.lines().next().unwrap_or_default().trim();
+                    let shown: String = first_line.chars().take(40).collect();
+                    let ellipsis = if shown.len() < first_line.len() {
+                        "…"
+                    } else {
+                        ""
+                    };
+                    format!("cannot parse `{shown}{ellipsis}`")
+                };
+                errors.push(Self::syntax_error(node, message));
+                continue;
+            }
+            // An empty block next to an error is recovery debris, already reported
+            let next_to_error = node.parent().is_some_and(|p| p.has_error());
+            if node.kind() == "block" && !next_to_error && is_empty_block(node) {
+                let keyword = node.parent().and_then(|parent| {
+                    let mut cursor = parent.walk();
+                    let keyword = parent
+                        .children(&mut cursor)
+                        .find(|c| !c.is_named() && c.kind() != "async")
+                        .map(|c| c.kind());
+                    keyword
+                });
+                let message = match keyword {
+                    Some(keyword) => format!("expected an indented block after `{keyword}`"),
+                    None => "expected an indented block".to_string(),
+                };
+                errors.push(Self::syntax_error(node, message));
+                continue;
+            }
+            if node.has_error()
+                || matches!(node.kind(), "module" | "block")
+                || COMPOUND_KINDS.contains(&node.kind())
+            {
+                let mut cursor = node.walk();
+                let children: Vec<_> = node.children(&mut cursor).collect();
+                to_visit.extend(children.into_iter().rev());
+            }
+        }
+        errors
+    }
+
+    /// A syntax error spanning `node`, whose region is the function around it.
+    fn syntax_error(node: tree_sitter::Node, message: String) -> SyntaxError {
+        let (start, end) = (node.start_position(), node.end_position());
+        // The function around the error, whose body may be cut short
+        let mut region = (start.row + 1, end.row + 1);
+        let mut parent = node.parent();
+        while let Some(p) = parent {
+            if p.kind() == "function_definition" {
+                region = (
+                    region.0.min(p.start_position().row + 1),
+                    region.1.max(p.end_position().row + 1),
+                );
+                break;
+            }
+            parent = p.parent();
+        }
+        SyntaxError {
+            message,
+            line: start.row + 1,
+            col: start.column + 1,
+            end_line: end.row + 1,
+            end_col: end.column + 1,
+            region,
         }
     }
 
@@ -83,6 +282,36 @@ impl PythonParser {
         imports
     }
 
+    /// Collect every comment token in the file, noting whether it trails code.
+    fn extract_comments(root: &tree_sitter::Node, source: &[u8]) -> Vec<Comment> {
+        let mut comments = Vec::new();
+        let mut to_visit = vec![*root];
+        while let Some(node) = to_visit.pop() {
+            if node.kind() == "comment" {
+                let line_start = source[..node.start_byte()]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |p| p + 1);
+                let is_trailing = source[line_start..node.start_byte()]
+                    .iter()
+                    .any(|b| !b.is_ascii_whitespace());
+                comments.push(Comment {
+                    line: node.start_position().row + 1,
+                    col: node.start_position().column + 1,
+                    text: Self::node_text(node, source),
+                    is_trailing,
+                });
+                continue;
+            }
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                to_visit.push(child);
+            }
+        }
+        comments.sort_by_key(|c| (c.line, c.col));
+        comments
+    }
+
     fn collect_function_nodes<'tree>(
         root: &'tree tree_sitter::Node<'tree>,
     ) -> Vec<tree_sitter::Node<'tree>> {
@@ -115,19 +344,110 @@ impl PythonParser {
         nodes
     }
 
+    /// Classes that derive from `unittest.TestCase` (or another known test case base):
+    /// directly, through classes defined in the same file, or through a configured project
+    /// base class.
+    fn unittest_classes<'tree>(
+        root: &tree_sitter::Node<'tree>,
+        source: &[u8],
+        options: &ParseOptions,
+    ) -> Vec<tree_sitter::Node<'tree>> {
+        let bindings = crate::rules::ast::import_bindings(*root, source);
+        let resolve = |base: &str| -> String {
+            let (head, rest) = base.split_once('.').unwrap_or((base, ""));
+            match bindings.get(head) {
+                Some(dotted) if rest.is_empty() => dotted.clone(),
+                Some(dotted) => format!("{dotted}.{rest}"),
+                None => base.to_string(),
+            }
+        };
+
+        let mut classes: Vec<(tree_sitter::Node<'tree>, String, Vec<String>)> = Vec::new();
+        let mut to_visit = vec![*root];
+        while let Some(node) = to_visit.pop() {
+            if node.kind() == "class_definition" {
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| Self::node_text(n, source))
+                    .unwrap_or_default();
+                let mut bases = Vec::new();
+                if let Some(args) = node.child_by_field_name("superclasses") {
+                    let mut cursor = args.walk();
+                    for arg in args.named_children(&mut cursor) {
+                        if matches!(arg.kind(), "identifier" | "attribute") {
+                            bases.push(Self::node_text(arg, source));
+                        }
+                    }
+                }
+                classes.push((node, name, bases));
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        classes.sort_by_key(|(node, _, _)| node.start_byte());
+
+        let mut found: Vec<tree_sitter::Node<'tree>> = Vec::new();
+        let mut names: HashSet<String> = HashSet::new();
+        loop {
+            let before = found.len();
+            for (node, name, bases) in &classes {
+                let derives = bases
+                    .iter()
+                    .any(|b| names.contains(b) || options.is_unittest_base(&resolve(b)));
+                if derives && !found.contains(node) {
+                    found.push(*node);
+                    names.insert(name.clone());
+                }
+            }
+            if found.len() == before {
+                found.sort_by_key(tree_sitter::Node::start_byte);
+                return found;
+            }
+        }
+    }
+
+    /// Methods defined directly in a class body.
+    fn class_methods<'tree>(class: &tree_sitter::Node<'tree>) -> Vec<tree_sitter::Node<'tree>> {
+        let Some(body) = class.child_by_field_name("body") else {
+            return vec![];
+        };
+        let mut cursor = body.walk();
+        body.named_children(&mut cursor)
+            .filter_map(|stmt| match stmt.kind() {
+                "function_definition" => Some(stmt),
+                "decorated_definition" => stmt.child_by_field_name("definition"),
+                _ => None,
+            })
+            .filter(|def| def.kind() == "function_definition")
+            .collect()
+    }
+
     fn extract_test_functions(
         root: &tree_sitter::Node,
         source: &[u8],
         file_path: &Path,
+        unittest_classes: &[tree_sitter::Node],
     ) -> Vec<TestFunction> {
         let mut tests = Vec::new();
-        for func_node in Self::collect_function_nodes(root) {
+        let module_functions = Self::collect_function_nodes(root)
+            .into_iter()
+            .map(|f| (f, false));
+        // unittest runs every `test_*` method of a TestCase, whatever the class is called
+        let unittest_methods = unittest_classes
+            .iter()
+            .flat_map(|class| Self::class_methods(class))
+            .map(|f| (f, true));
+        for (func_node, is_unittest) in module_functions.chain(unittest_methods) {
             let name_node = func_node.child_by_field_name("name");
             if let Some(nn) = name_node {
                 let name = Self::node_text(nn, source);
                 if name.starts_with("test_") {
                     tests.push(Self::build_test_function(
-                        &func_node, source, file_path, &name,
+                        &func_node,
+                        source,
+                        file_path,
+                        &name,
+                        is_unittest,
                     ));
                 }
             }
@@ -140,6 +460,7 @@ impl PythonParser {
         source: &[u8],
         file_path: &Path,
         name: &str,
+        is_unittest: bool,
     ) -> TestFunction {
         let line = func_node.start_position().row + 1;
         let body = func_node.child_by_field_name("body");
@@ -155,12 +476,20 @@ impl PythonParser {
             has_async
         };
         let (is_parametrized, parametrize_count) = Self::detect_parametrize(&decorators);
-        let assertion_count = Self::count_assertions(body.as_ref());
+        // `self.assert*()` / `self.fail()` are the assertions of unittest-style tests
+        let unittest_assertions = if is_unittest {
+            Self::count_unittest_assertions(body.as_ref(), source)
+        } else {
+            0
+        };
+        let assertion_count = Self::count_assertions(body.as_ref()) + unittest_assertions;
+        let assertion_site_count = Self::count_assertion_sites(body.as_ref(), source);
         let has_assertions = assertion_count > 0;
         let has_mock_verifications = body_text.contains(".assert_called")
             || body_text.contains(".called")
             || body_text.contains(".call_count");
-        let has_state_assertions = has_assertions && !has_mock_verifications_only(&body_text);
+        let has_state_assertions =
+            has_assertions && (unittest_assertions > 0 || !has_mock_verifications_only(&body_text));
         let fixture_deps = Self::extract_fixture_deps(func_node, source);
         let uses_time_sleep = Self::detect_time_sleep(body.as_ref(), source);
         let sleep_value = Self::detect_sleep_value(body.as_ref(), source);
@@ -188,12 +517,7 @@ impl PythonParser {
         let uses_shutil_copy = Self::detect_shutil_copy(body.as_ref(), source);
 
         let end_line = func_node.end_position().row + 1;
-        let body_hash = body.map(|b| {
-            let text = Self::node_text(b, source);
-            let mut hasher = std::collections::hash_map::DefaultHasher::new();
-            text.hash(&mut hasher);
-            hasher.finish()
-        });
+        let body_hash = body.map(|b| crate::rules::ast::body_fingerprint(b, source));
 
         TestFunction {
             name: name.to_string(),
@@ -205,6 +529,7 @@ impl PythonParser {
             parametrize_count,
             has_assertions,
             assertion_count,
+            assertion_site_count,
             has_mock_verifications,
             has_state_assertions,
             fixture_deps,
@@ -233,6 +558,7 @@ impl PythonParser {
             has_magic_mock,
             mock_count,
             uses_shutil_copy,
+            is_unittest,
         }
     }
 
@@ -417,6 +743,89 @@ impl PythonParser {
         }
     }
 
+    /// Count `self.assert*()` and `self.fail*()` calls (including deprecated aliases such
+    /// as `failUnless`), the assertions of `unittest.TestCase` methods.
+    fn count_unittest_assertions(body: Option<&tree_sitter::Node>, source: &[u8]) -> usize {
+        let Some(body) = body else {
+            return 0;
+        };
+        let mut count = 0;
+        let mut to_visit = vec![*body];
+        while let Some(node) = to_visit.pop() {
+            if node.kind() == "call" {
+                let func = node.child_by_field_name("function");
+                let is_self_assertion = func.filter(|f| f.kind() == "attribute").is_some_and(|f| {
+                    let on_self = f
+                        .child_by_field_name("object")
+                        .is_some_and(|o| Self::node_text(o, source) == "self");
+                    on_self
+                        && f.child_by_field_name("attribute").is_some_and(|a| {
+                            let name = Self::node_text(a, source);
+                            name.starts_with("assert") || name.starts_with("fail")
+                        })
+                });
+                if is_self_assertion {
+                    count += 1;
+                }
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        count
+    }
+
+    /// Count distinct assertion sites: `assert` statements plus calls to assertion helpers
+    /// (`assert_all_valid(...)`, `self.assertEqual(...)`). Each site counts once, however
+    /// often a loop or parametrization would execute it; mock verifications are left to
+    /// the mock rules.
+    fn count_assertion_sites(body: Option<&tree_sitter::Node>, source: &[u8]) -> usize {
+        body.map_or(0, |b| {
+            let mut count = 0;
+            Self::count_assertion_sites_recursive(*b, source, &mut count);
+            count
+        })
+    }
+
+    fn count_assertion_sites_recursive(node: tree_sitter::Node, source: &[u8], count: &mut usize) {
+        if node.kind() == "assert_statement" {
+            // Helper calls inside the assert expression belong to this site
+            *count += 1;
+            return;
+        }
+        if node.kind() == "call" && Self::is_assertion_helper_call(node, source) {
+            *count += 1;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::count_assertion_sites_recursive(child, source, count);
+        }
+    }
+
+    fn is_assertion_helper_call(call: tree_sitter::Node, source: &[u8]) -> bool {
+        let Some(func) = call.child_by_field_name("function") else {
+            return false;
+        };
+        let name_node = if func.kind() == "attribute" {
+            func.child_by_field_name("attribute")
+        } else {
+            Some(func)
+        };
+        let Some(name_node) = name_node else {
+            return false;
+        };
+        let name = Self::node_text(name_node, source);
+        const MOCK_VERIFICATIONS: &[&str] = &[
+            "assert_called",
+            "assert_not_called",
+            "assert_any_call",
+            "assert_has_calls",
+            "assert_awaited",
+            "assert_not_awaited",
+            "assert_has_awaits",
+        ];
+        name.starts_with("assert") && !MOCK_VERIFICATIONS.iter().any(|m| name.starts_with(m))
+    }
+
     fn detect_conditionals(body: Option<&tree_sitter::Node>) -> bool {
         body.is_some_and(|b| Self::has_node_kind(*b, "if_statement"))
     }
@@ -478,10 +887,18 @@ impl PythonParser {
                 !has_comparison && kind == "identifier"
             });
             let is_suboptimal = expr_node.is_some_and(|n| Self::is_suboptimal_assertion(n, source));
+            let is_tautological = expr_node.is_some_and(|n| Self::is_tautological(n, source));
+            let has_message = {
+                let mut cursor = node.walk();
+                let found = node.children(&mut cursor).any(|c| c.kind() == ",");
+                found
+            };
             infos.push(crate::models::AssertionInfo {
                 is_magic,
                 is_suboptimal,
+                is_tautological,
                 has_comparison,
+                has_message,
                 expression_text,
                 line,
             });
@@ -493,6 +910,63 @@ impl PythonParser {
         }
     }
 
+    /// A pure literal or literal arithmetic: numbers, strings without interpolation,
+    /// `True`/`False`/`None`, and operators, parentheses and tuples/lists over those. Names
+    /// are never constant, even when bound to a literal earlier in the test.
+    fn is_constant_expr(node: tree_sitter::Node) -> bool {
+        match node.kind() {
+            "integer" | "float" | "true" | "false" | "none" => true,
+            "string" => !Self::has_node_kind_recursive(node, "interpolation"),
+            "concatenated_string"
+            | "unary_operator"
+            | "binary_operator"
+            | "parenthesized_expression"
+            | "tuple"
+            | "list" => {
+                let mut cursor = node.walk();
+                let all = node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() != "comment")
+                    .all(|c| Self::is_constant_expr(c));
+                all
+            }
+            _ => false,
+        }
+    }
+
+    /// An assert that cannot fail: a comparison between constants (`1 == 1`,
+    /// `2 + 2 == 4`) or a truthy literal (`True`, `"yes"`, `42`, `(1,)`).
+    fn is_tautological(expr: tree_sitter::Node, source: &[u8]) -> bool {
+        match expr.kind() {
+            "comparison_operator" => {
+                let mut cursor = expr.walk();
+                let all = expr
+                    .named_children(&mut cursor)
+                    .all(|c| Self::is_constant_expr(c));
+                all
+            }
+            "true" => true,
+            "integer" | "float" => {
+                let text = Self::node_text(expr, source);
+                text.parse::<f64>().is_ok_and(|v| v != 0.0)
+            }
+            "string" | "concatenated_string" => {
+                Self::is_constant_expr(expr) && {
+                    let mut cursor = expr.walk();
+                    let has_content = expr.named_children(&mut cursor).any(|c| {
+                        matches!(c.kind(), "string_content" | "escape_sequence" | "string")
+                    });
+                    has_content
+                }
+            }
+            "tuple" | "list" => expr.named_child_count() > 0 && Self::is_constant_expr(expr),
+            "parenthesized_expression" => expr
+                .named_child(0)
+                .is_some_and(|inner| Self::is_tautological(inner, source)),
+            _ => false,
+        }
+    }
+
     fn has_node_kind_recursive(node: tree_sitter::Node, kind: &str) -> bool {
         if node.kind() == kind {
             return true;
@@ -1376,7 +1850,7 @@ impl PythonParser {
                     if let Some(first_char) = name.chars().next() {
                         if first_char.is_uppercase() {
                             // Check if it's a frozen dataclass
-                            let class_name = name.split('.').last().unwrap_or(&name);
+                            let class_name = name.split('.').next_back().unwrap_or(&name);
                             if frozen_classes.contains(class_name) {
                                 return false;
                             }
@@ -1772,12 +2246,12 @@ fn extract_patch_target(text: &str) -> Option<String> {
 
 fn extract_first_string_arg(text: &str) -> Option<String> {
     let trimmed = text.trim_start();
-    if trimmed.starts_with('"') {
-        let end = trimmed[1..].find('"')?;
-        Some(trimmed[1..1 + end].to_string())
-    } else if trimmed.starts_with('\'') {
-        let end = trimmed[1..].find('\'')?;
-        Some(trimmed[1..1 + end].to_string())
+    if let Some(rest) = trimmed.strip_prefix('"') {
+        let end = rest.find('"')?;
+        Some(rest[..end].to_string())
+    } else if let Some(rest) = trimmed.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        Some(rest[..end].to_string())
     } else {
         None
     }
@@ -1794,6 +2268,61 @@ fn has_mock_verifications_only(body_text: &str) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unittest_methods_are_tests_with_self_assertions() {
+        let source = r#"
+import unittest as ut
+from unittest import IsolatedAsyncioTestCase as Async
+from tests.base import ApiTestCase
+
+class Base(ut.TestCase):
+    def helper(self):
+        pass
+
+class CheckThing(Base):
+    def test_a(self):
+        self.assertEqual(1, 1)
+        self.failUnless(True)
+
+class AsyncCase(Async):
+    async def test_b(self):
+        self.fail("boom")
+
+class TestApi(ApiTestCase):
+    def test_c(self):
+        self.assertTrue(True)
+
+class TestPlain:
+    def test_d(self):
+        self.assertEqual(1, 1)
+"#;
+        let path = Path::new("test_ut.py");
+        let module = PythonParser::new()
+            .unwrap()
+            .parse_source(source, path)
+            .unwrap();
+        let names: Vec<(&str, usize)> = module
+            .test_functions
+            .iter()
+            .map(|t| (t.name.as_str(), t.assertion_count))
+            .collect();
+        assert_eq!(names, [("test_a", 2), ("test_b", 1)]);
+        assert!(module
+            .test_functions
+            .iter()
+            .all(|t| t.is_unittest && t.has_assertions));
+
+        let options = ParseOptions {
+            unittest_base_classes: vec!["tests.base.ApiTestCase".to_string()],
+        };
+        let module = PythonParser::with_options(options)
+            .unwrap()
+            .parse_source(source, path)
+            .unwrap();
+        assert_eq!(module.test_functions.len(), 3);
+        assert_eq!(module.test_functions[2].name, "test_c");
+    }
+
     #[test]
     fn test_parse_simple_file() {
         let dir = tempfile::tempdir().unwrap();
@@ -2360,6 +2889,36 @@ def test_magic_false():
         assert!(info[0].is_magic);
     }
 
+    #[test]
+    fn test_assertion_info_tautological() {
+        let module = parse_source(
+            r#"
+def test_constants():
+    expected = 4
+    assert True
+    assert 1 == 1
+    assert 2 + 2 == 4
+    assert "a" "b" == "ab"
+    assert "non-empty"
+    assert (1, 2)
+    assert f"{expected}" == "4"
+    assert expected == 4
+    assert False
+    assert ""
+    assert compute() == 4
+"#,
+        );
+        let flags: Vec<bool> = module.test_functions[0]
+            .assertions
+            .iter()
+            .map(|a| a.is_tautological)
+            .collect();
+        assert_eq!(
+            flags,
+            [true, true, true, true, true, true, false, false, false, false, false]
+        );
+    }
+
     #[test]
     fn test_assertion_info_true_keyword() {
         let module = parse_source(
@@ -4600,4 +5159,60 @@ def test_body_nonstdlib():
             "non-stdlib body patch should not be added to targets"
         );
     }
+
+    #[test]
+    fn test_assertion_sites_count_loop_body_once() {
+        let module = parse_source(
+            r#"
+def test_table():
+    cases = [(1, 2), (2, 3), (3, 4)]
+    for given, expected in cases:
+        assert inc(given) == expected
+        assert expected > given
+"#,
+        );
+        assert_eq!(module.test_functions[0].assertion_site_count, 2);
+    }
+
+    #[test]
+    fn test_assertion_sites_include_helpers_but_not_mock_verifications() {
+        let module = parse_source(
+            r#"
+def test_helpers(mock_api):
+    results = run()
+    assert_all_valid(results)
+    self.assertEqual(len(results), 3)
+    assert check_all(results)
+    mock_api.assert_called_once_with(1)
+"#,
+        );
+        assert_eq!(module.test_functions[0].assertion_site_count, 3);
+    }
+
+    #[test]
+    fn test_assertion_has_message() {
+        let module = parse_source(
+            r#"
+def test_msgs():
+    assert x == 1, "x should be one"
+    assert y == 2
+"#,
+        );
+        let assertions = &module.test_functions[0].assertions;
+        assert!(assertions[0].has_message);
+        assert!(!assertions[1].has_message);
+    }
+
+    #[test]
+    fn test_syntax_errors_cover_the_enclosing_function() {
+        let module =
+            parse_source("def test_ok():\n    assert 1\n\ndef test_cut():\n    x = call(\n");
+        assert_eq!(module.syntax_errors.len(), 1, "{:?}", module.syntax_errors);
+        let error = &module.syntax_errors[0];
+        assert!(error.covers(4), "{error:?}");
+        assert!(!error.covers(2), "{error:?}");
+        assert!(parse_source("def test_ok():\n    assert 1\n")
+            .syntax_errors
+            .is_empty());
+    }
 }
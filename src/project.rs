@@ -0,0 +1,370 @@
+//! `--project-mode`: a project-wide index built before the rules run, so that rules can see
+//! fixtures, tests and helpers in files that are not being linted, most importantly the
+//! `conftest.py` files of parent directories.
+//!
+//! The index covers every test file and `conftest.py` under the project roots of the linted
+//! paths. A root is the nearest ancestor holding a project marker (`.git`, `pyproject.toml`,
+//! `pytest.ini`, `setup.cfg`, `tox.ini` or a pytest-linter configuration), or else the
+//! outermost ancestor holding a `conftest.py`. Entries are keyed by the file content hash
+//! and stored in the analysis cache, so a warm run only re-reads files that changed.
+//!
+//! Rules query the index through [`ProjectContext`], which resolves fixture names the way
+//! pytest does: a module's own fixtures first, then the `conftest.py` of its directory and
+//! of each parent directory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Tree;
+
+use crate::cache::Cache;
+use crate::config::STANDALONE_CONFIG_FILES;
+use crate::discovery::FileDiscovery;
+use crate::models::{FixtureScope, ParsedModule};
+use crate::parser::{ParseOptions, PythonParser};
+use crate::rules::ast::node_text;
+
+/// Files whose directory is the root of a project.
+const PROJECT_MARKERS: &[&str] = &[
+    ".git",
+    "pyproject.toml",
+    "pytest.ini",
+    "setup.cfg",
+    "tox.ini",
+];
+
+/// A fixture definition as recorded in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedFixture {
+    pub name: String,
+    pub line: usize,
+    pub scope: FixtureScope,
+    pub autouse: bool,
+}
+
+/// What the index knows about one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIndex {
+    /// Hash of the content the entry was built from, see [`Cache::content_hash`].
+    pub content_hash: String,
+    pub fixtures: Vec<IndexedFixture>,
+    /// Names of the module-level test functions.
+    pub tests: Vec<String>,
+    /// Fixture names requested by the file's tests and fixtures.
+    pub requested: BTreeSet<String>,
+    /// Fixtures that request the definition they override (`def db(db): ...`).
+    pub extends: BTreeSet<String>,
+    /// Module-level functions that are neither tests nor fixtures.
+    pub helpers: Vec<String>,
+}
+
+impl FileIndex {
+    fn new(module: &ParsedModule, tree: Option<&Tree>, content_hash: String) -> Self {
+        let mut requested = BTreeSet::new();
+        let mut extends = BTreeSet::new();
+        for test in &module.test_functions {
+            requested.extend(test.fixture_deps.iter().cloned());
+        }
+        for fixture in &module.fixtures {
+            for dep in &fixture.dependencies {
+                if *dep == fixture.name {
+                    extends.insert(dep.clone());
+                } else {
+                    requested.insert(dep.clone());
+                }
+            }
+        }
+        let fixture_lines: BTreeSet<usize> = module.fixtures.iter().map(|f| f.line).collect();
+        let mut tests = Vec::new();
+        let mut helpers = Vec::new();
+        if let Some(tree) = tree {
+            let source = module.source.as_bytes();
+            let root = tree.root_node();
+            let mut cursor = root.walk();
+            for stmt in root.named_children(&mut cursor) {
+                let definition = if stmt.kind() == "decorated_definition" {
+                    stmt.child_by_field_name("definition")
+                } else {
+                    Some(stmt)
+                };
+                let Some(function) = definition.filter(|d| d.kind() == "function_definition")
+                else {
+                    continue;
+                };
+                let Some(name) = function.child_by_field_name("name") else {
+                    continue;
+                };
+                let line = stmt.start_position().row + 1;
+                let def_line = function.start_position().row + 1;
+                let name = node_text(name, source).to_string();
+                if fixture_lines.contains(&line) || fixture_lines.contains(&def_line) {
+                    continue;
+                }
+                if module.test_functions.iter().any(|t| t.name == name) {
+                    tests.push(name);
+                } else {
+                    helpers.push(name);
+                }
+            }
+        }
+        Self {
+            content_hash,
+            fixtures: module
+                .fixtures
+                .iter()
+                .map(|f| IndexedFixture {
+                    name: f.name.clone(),
+                    line: f.line,
+                    scope: f.scope,
+                    autouse: f.is_autouse,
+                })
+                .collect(),
+            tests,
+            requested,
+            extends,
+            helpers,
+        }
+    }
+
+    fn fixture(&self, name: &str) -> Option<&IndexedFixture> {
+        self.fixtures.iter().find(|f| f.name == name)
+    }
+}
+
+/// The project index: every test file and `conftest.py` under the project roots, by
+/// canonical path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    pub files: BTreeMap<PathBuf, FileIndex>,
+}
+
+/// A fixture definition found through the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixtureDefinition<'a> {
+    pub file: &'a Path,
+    pub fixture: &'a IndexedFixture,
+}
+
+/// The canonical form of `path` used as index key. Files that do not exist keep their path.
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn is_conftest(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == "conftest.py")
+}
+
+/// The project root of a linted path; see the module documentation.
+#[must_use]
+pub fn project_root(path: &Path) -> PathBuf {
+    let path = normalize(path);
+    let start = if path.is_file() {
+        path.parent()
+            .map_or_else(|| path.clone(), Path::to_path_buf)
+    } else {
+        path
+    };
+    let mut root = start.clone();
+    for dir in start.ancestors() {
+        let has_marker = PROJECT_MARKERS
+            .iter()
+            .chain(STANDALONE_CONFIG_FILES)
+            .any(|marker| dir.join(marker).exists());
+        if has_marker {
+            return dir.to_path_buf();
+        }
+        if dir.join("conftest.py").is_file() {
+            root = dir.to_path_buf();
+        }
+    }
+    root
+}
+
+impl ProjectIndex {
+    /// Index the test files and `conftest.py` files under the project roots of `paths`.
+    /// Entries of `previous` whose content hash still matches are reused; files that cannot
+    /// be read or parsed are left out.
+    #[must_use]
+    pub fn build(
+        paths: &[PathBuf],
+        discovery: &FileDiscovery,
+        options: &ParseOptions,
+        previous: Option<&ProjectIndex>,
+    ) -> Self {
+        let mut roots: Vec<PathBuf> = paths.iter().map(|p| project_root(p)).collect();
+        roots.sort();
+        roots.dedup();
+        let files = discovery.discover(&roots);
+        let salt = options.cache_salt();
+        let files = files
+            .par_iter()
+            .filter_map(|file| {
+                let source = std::fs::read_to_string(file).ok()?;
+                let content_hash = Cache::content_hash(&format!("{salt}{source}"));
+                let key = normalize(file);
+                if let Some(entry) = previous
+                    .and_then(|p| p.files.get(&key))
+                    .filter(|e| e.content_hash == content_hash)
+                {
+                    return Some((key, entry.clone()));
+                }
+                let (module, tree) = PythonParser::with_options(options.clone())
+                    .ok()?
+                    .parse_source_with_tree(&source, file)
+                    .ok()?;
+                Some((key, FileIndex::new(&module, tree.as_ref(), content_hash)))
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Hash of the whole index, part of the cache key of every file's diagnostics.
+    #[must_use]
+    pub fn fingerprint(&self) -> String {
+        Cache::content_hash(&serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+/// Handle through which rules query the project index.
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectContext<'a> {
+    index: &'a ProjectIndex,
+}
+
+impl<'a> ProjectContext<'a> {
+    #[must_use]
+    pub fn new(index: &'a ProjectIndex) -> Self {
+        Self { index }
+    }
+
+    fn entry(&self, file: &Path) -> Option<(&'a Path, &'a FileIndex)> {
+        self.index
+            .files
+            .get_key_value(&normalize(file))
+            .map(|(path, entry)| (path.as_path(), entry))
+    }
+
+    /// The first `conftest.py` definition of `name` in `dir` or one of its parents.
+    fn conftest_fixture(&self, dir: Option<&Path>, name: &str) -> Option<FixtureDefinition<'a>> {
+        dir?.ancestors().find_map(|dir| {
+            let (file, entry) = self.index.files.get_key_value(&dir.join("conftest.py"))?;
+            entry.fixture(name).map(|fixture| FixtureDefinition {
+                file: file.as_path(),
+                fixture,
+            })
+        })
+    }
+
+    /// The definition of `name` a test or fixture in `file` receives: the file's own, or
+    /// the nearest `conftest.py` one.
+    #[must_use]
+    pub fn fixture(&self, file: &Path, name: &str) -> Option<FixtureDefinition<'a>> {
+        let file = normalize(file);
+        if let Some((path, entry)) = self.entry(&file) {
+            if let Some(fixture) = entry.fixture(name) {
+                return Some(FixtureDefinition {
+                    file: path,
+                    fixture,
+                });
+            }
+        }
+        self.conftest_fixture(file.parent(), name)
+    }
+
+    /// The definition that `file`'s own fixture `name` overrides: for a module the nearest
+    /// `conftest.py` one, for a `conftest.py` the one in a parent directory.
+    #[must_use]
+    pub fn overridden(&self, file: &Path, name: &str) -> Option<FixtureDefinition<'a>> {
+        let file = normalize(file);
+        let dir = file.parent();
+        let start = if is_conftest(&file) {
+            dir.and_then(Path::parent)
+        } else {
+            dir
+        };
+        self.conftest_fixture(start, name)
+    }
+
+    /// Whether some test or fixture receives `file`'s definition of `name`: for a
+    /// `conftest.py` any file below its directory, for a module the module itself.
+    #[must_use]
+    pub fn is_requested(&self, file: &Path, name: &str) -> bool {
+        let file = normalize(file);
+        let Some(dir) = file.parent() else {
+            return false;
+        };
+        let scope: Vec<_> = if is_conftest(&file) {
+            self.index
+                .files
+                .iter()
+                .filter(|(path, _)| path.starts_with(dir))
+                .collect()
+        } else {
+            self.index.files.get_key_value(&file).into_iter().collect()
+        };
+        scope.into_iter().any(|(path, entry)| {
+            let receives = |definition: Option<FixtureDefinition>| {
+                definition.is_some_and(|d| d.file == file.as_path())
+            };
+            (entry.requested.contains(name) && receives(self.fixture(path, name)))
+                || (entry.extends.contains(name) && receives(self.overridden(path, name)))
+        })
+    }
+
+    /// Other modules in the directory of `file` that define a module-level test `name`.
+    #[must_use]
+    pub fn tests_named(&self, file: &Path, name: &str) -> Vec<&'a Path> {
+        let file = normalize(file);
+        let Some(dir) = file.parent() else {
+            return vec![];
+        };
+        self.index
+            .files
+            .iter()
+            .filter(|(path, entry)| {
+                path.parent() == Some(dir)
+                    && **path != file
+                    && !is_conftest(path)
+                    && entry.tests.iter().any(|t| t == name)
+            })
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+
+    /// The file defining a module-level helper function `name` that `file` can see: its
+    /// own, or one in a `conftest.py` of its directory or a parent.
+    #[must_use]
+    pub fn helper(&self, file: &Path, name: &str) -> Option<&'a Path> {
+        let file = normalize(file);
+        let defines = |entry: &FileIndex| entry.helpers.iter().any(|h| h == name);
+        if let Some((path, _)) = self.entry(&file).filter(|(_, e)| defines(e)) {
+            return Some(path);
+        }
+        file.parent()?.ancestors().find_map(|dir| {
+            let (path, entry) = self.index.files.get_key_value(&dir.join("conftest.py"))?;
+            defines(entry).then_some(path.as_path())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_root_is_the_marker_or_outermost_conftest() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let nested = root.join("tests").join("unit");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(project_root(&nested), nested);
+
+        std::fs::write(root.join("tests").join("conftest.py"), "").unwrap();
+        assert_eq!(project_root(&nested), root.join("tests"));
+
+        std::fs::write(root.join("pyproject.toml"), "").unwrap();
+        assert_eq!(project_root(&nested), root);
+    }
+}
@@ -0,0 +1,341 @@
+//! Syntax-tree helpers shared by rules that inspect the Python tree directly.
+
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::{Node, Tree};
+
+use crate::models::{Fix, RelatedLocation, Violation};
+
+/// Parse Python source for rules that need the syntax tree.
+#[must_use]
+pub fn parse_python(source: &str) -> Option<Tree> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_python::LANGUAGE.into())
+        .ok()?;
+    parser.parse(source, None)
+}
+
+/// Find the `function_definition` whose `def` is on the given 1-indexed line, at any depth.
+#[must_use]
+pub fn function_at_line(root: Node, target_line: usize) -> Option<Node> {
+    let mut to_visit = vec![root];
+    while let Some(node) = to_visit.pop() {
+        if node.kind() == "function_definition" && node.start_position().row + 1 == target_line {
+            return Some(node);
+        }
+        if node.start_position().row + 1 > target_line || node.end_position().row + 1 < target_line
+        {
+            continue;
+        }
+        let mut cursor = node.walk();
+        to_visit.extend(node.named_children(&mut cursor));
+    }
+    None
+}
+
+/// Source text of a node (empty on invalid UTF-8).
+#[must_use]
+pub fn node_text<'a>(node: Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+/// One token of a normalized function body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyToken<'a> {
+    pub text: &'a str,
+    /// A number or string literal (without interpolation).
+    pub literal: bool,
+    /// 1-based line of the token.
+    pub line: usize,
+}
+
+/// Tokens of a function body for structural comparison, with formatting, comments and a
+/// leading docstring dropped. Strings are single tokens.
+#[must_use]
+pub fn body_tokens<'a>(body: Node, source: &'a [u8]) -> Vec<BodyToken<'a>> {
+    fn push_tokens<'a>(node: Node, source: &'a [u8], out: &mut Vec<BodyToken<'a>>) {
+        if node.kind() == "comment" {
+            return;
+        }
+        if node.child_count() == 0 || node.kind() == "string" {
+            let literal = match node.kind() {
+                "integer" | "float" => true,
+                "string" => {
+                    let mut cursor = node.walk();
+                    let interpolated = node
+                        .children(&mut cursor)
+                        .any(|c| c.kind() == "interpolation");
+                    !interpolated
+                }
+                _ => false,
+            };
+            out.push(BodyToken {
+                text: node_text(node, source),
+                literal,
+                line: node.start_position().row + 1,
+            });
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            push_tokens(child, source, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut cursor = body.walk();
+    for (i, stmt) in body.named_children(&mut cursor).enumerate() {
+        let is_docstring = i == 0
+            && stmt.kind() == "expression_statement"
+            && stmt.named_child(0).is_some_and(|n| n.kind() == "string");
+        if !is_docstring {
+            push_tokens(stmt, source, &mut out);
+        }
+    }
+    out
+}
+
+/// Canonical form of a function body: its [`body_tokens`] separated by single spaces.
+#[must_use]
+pub fn normalized_body(body: Node, source: &[u8]) -> String {
+    let tokens: Vec<&str> = body_tokens(body, source).iter().map(|t| t.text).collect();
+    tokens.join(" ")
+}
+
+/// Stable hash of [`normalized_body`], for grouping structurally identical bodies.
+#[must_use]
+pub fn body_fingerprint(body: Node, source: &[u8]) -> u64 {
+    crate::baseline::fnv1a(&normalized_body(body, source))
+}
+
+/// Local names bound by module-level imports, mapped to the dotted names they refer to
+/// (`from unittest import TestCase as TC` binds `TC` to `unittest.TestCase`).
+#[must_use]
+pub fn import_bindings(root: Node, source: &[u8]) -> HashMap<String, String> {
+    let text = |n: Node| node_text(n, source).to_string();
+    let mut bindings = HashMap::new();
+    let mut cursor = root.walk();
+    for stmt in root.named_children(&mut cursor) {
+        let module = match stmt.kind() {
+            "import_statement" => None,
+            "import_from_statement" => stmt.child_by_field_name("module_name").map(text),
+            _ => continue,
+        };
+        let mut names = stmt.walk();
+        for name in stmt.children_by_field_name("name", &mut names) {
+            let (target, local) = if name.kind() == "aliased_import" {
+                let target = name
+                    .child_by_field_name("name")
+                    .map(text)
+                    .unwrap_or_default();
+                let alias = name
+                    .child_by_field_name("alias")
+                    .map(text)
+                    .unwrap_or_default();
+                (target, alias)
+            } else {
+                let target = text(name);
+                // `import a.b` binds `a`
+                let local = match module {
+                    Some(_) => target.clone(),
+                    None => target.split('.').next().unwrap_or_default().to_string(),
+                };
+                (target, local)
+            };
+            let dotted = match (&module, name.kind()) {
+                (Some(module), _) => format!("{module}.{target}"),
+                (None, "aliased_import") => target,
+                (None, _) => local.clone(),
+            };
+            bindings.insert(local, dotted);
+        }
+    }
+    bindings
+}
+
+/// Dotted name a callee refers to given the module's [`import_bindings`]:
+/// `sp.run` is `subprocess.run` after `import subprocess as sp`. Names that are not
+/// imported resolve to themselves.
+#[must_use]
+pub fn resolve_callee(func: Node, source: &[u8], bindings: &HashMap<String, String>) -> String {
+    let text = node_text(func, source);
+    let (head, rest) = text
+        .split_once('.')
+        .map_or((text, None), |(h, r)| (h, Some(r)));
+    let head = bindings.get(head).map_or(head, String::as_str);
+    match rest {
+        Some(rest) => format!("{head}.{rest}"),
+        None => head.to_string(),
+    }
+}
+
+/// Names bound by a module's top-level statements, for rules that follow state shared
+/// across the functions of a file.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleSymbols {
+    /// Variables assigned at module level, with the line of their first assignment.
+    pub variables: HashMap<String, usize>,
+    /// Classes defined at module level.
+    pub classes: HashSet<String>,
+}
+
+impl ModuleSymbols {
+    #[must_use]
+    pub fn new(root: Node, source: &[u8]) -> Self {
+        let mut symbols = Self::default();
+        let mut cursor = root.walk();
+        for stmt in root.named_children(&mut cursor) {
+            match stmt.kind() {
+                "expression_statement" => {
+                    let mut inner = stmt.walk();
+                    for expr in stmt.named_children(&mut inner) {
+                        symbols.add_assignment(expr, source);
+                    }
+                }
+                "class_definition" => {
+                    if let Some(name) = stmt.child_by_field_name("name") {
+                        symbols.classes.insert(node_text(name, source).to_string());
+                    }
+                }
+                "decorated_definition" => {
+                    if let Some(name) = stmt
+                        .child_by_field_name("definition")
+                        .filter(|d| d.kind() == "class_definition")
+                        .and_then(|d| d.child_by_field_name("name"))
+                    {
+                        symbols.classes.insert(node_text(name, source).to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        symbols
+    }
+
+    /// Record the names bound by `a = b = value` and `a, b = value`.
+    fn add_assignment(&mut self, expr: Node, source: &[u8]) {
+        if expr.kind() != "assignment" {
+            return;
+        }
+        if let Some(left) = expr.child_by_field_name("left") {
+            let mut to_visit = vec![left];
+            while let Some(target) = to_visit.pop() {
+                match target.kind() {
+                    "identifier" => {
+                        self.variables
+                            .entry(node_text(target, source).to_string())
+                            .or_insert(target.start_position().row + 1);
+                    }
+                    "pattern_list" | "tuple_pattern" | "list_pattern" => {
+                        let mut cursor = target.walk();
+                        to_visit.extend(target.named_children(&mut cursor));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(right) = expr.child_by_field_name("right") {
+            self.add_assignment(right, source);
+        }
+    }
+}
+
+/// Fix that deletes a statement: the whole line when the statement is alone on it, the
+/// statement and its `;` when it shares the line. With `becomes_pass` (the statement is the
+/// last one left in its block) it is replaced by `pass` so the block stays valid.
+#[must_use]
+pub fn delete_statement(stmt: Node, source: &str, becomes_pass: bool) -> Option<Fix> {
+    let (start, end) = (stmt.start_byte(), stmt.end_byte());
+    if becomes_pass {
+        return Some(Fix::replace(start, end, "pass"));
+    }
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..]
+        .find('\n')
+        .map_or(source.len(), |i| end + i + 1);
+    let before = &source[line_start..start];
+    let after = &source[end..line_end];
+    if before.trim().is_empty() && after.trim().is_empty() {
+        return Some(Fix::replace(line_start, line_end, ""));
+    }
+    let after_trimmed = after.trim_start_matches([' ', '\t']);
+    if let Some(rest) = after_trimmed.strip_prefix(';') {
+        let next = line_end - rest.trim_start_matches([' ', '\t']).len();
+        return Some(Fix::replace(start, next, ""));
+    }
+    let before_trimmed = before.trim_end_matches([' ', '\t']);
+    if let Some(kept) = before_trimmed.strip_suffix(';') {
+        let kept = kept.trim_end_matches([' ', '\t']);
+        return Some(Fix::replace(line_start + kept.len(), end, ""));
+    }
+    None
+}
+
+/// Point a violation at a node: its start line/column and the end of its span (1-based).
+pub fn set_span(v: &mut Violation, node: Node) {
+    let (start, end) = (node.start_position(), node.end_position());
+    v.line = start.row + 1;
+    v.col = Some(start.column + 1);
+    v.end_line = Some(end.row + 1);
+    v.end_col = Some(end.column + 1);
+}
+
+/// Attach a note at another node of the violation's file, e.g. the definition it conflicts with.
+pub fn add_related(v: &mut Violation, node: Node, message: impl Into<String>) {
+    let (start, end) = (node.start_position(), node.end_position());
+    v.related.push(RelatedLocation {
+        file_path: v.file_path.clone(),
+        line: start.row + 1,
+        col: Some(start.column + 1),
+        end_line: Some(end.row + 1),
+        end_col: Some(end.column + 1),
+        message: message.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_at_line_finds_methods() {
+        let source = "class TestX:\n    def test_a(self):\n        pass\n";
+        let tree = parse_python(source).unwrap();
+        assert!(function_at_line(tree.root_node(), 2).is_some());
+        assert!(function_at_line(tree.root_node(), 3).is_none());
+    }
+
+    #[test]
+    fn test_normalized_body_ignores_layout_comments_and_docstring() {
+        let body = |source: &str| {
+            let tree = parse_python(source).unwrap();
+            let func = function_at_line(tree.root_node(), 1).unwrap();
+            let body = func.child_by_field_name("body").unwrap();
+            normalized_body(body, source.as_bytes())
+        };
+        let a = body(
+            "def test_a():\n    \"\"\"Doc.\"\"\"\n    x = f( 1 )  # note\n\n    assert x == 1\n",
+        );
+        let b = body("def test_b():\n    x = f(1)\n    assert x==1\n");
+        assert_eq!(a, "x = f ( 1 ) assert x == 1");
+        assert_eq!(a, b);
+        assert_ne!(b, body("def test_c():\n    x = f(2)\n    assert x == 1\n"));
+    }
+
+    #[test]
+    fn test_module_symbols_collect_top_level_bindings() {
+        let source = "import os\nA = B = []\nx, (y, z) = 1, (2, 3)\nT: dict = {}\n\n\
+                      def f():\n    local = 1\n\nclass C:\n    attr = 1\n\n\
+                      @dataclass\nclass D:\n    pass\n";
+        let tree = parse_python(source).unwrap();
+        let symbols = ModuleSymbols::new(tree.root_node(), source.as_bytes());
+        let mut variables: Vec<_> = symbols.variables.keys().map(String::as_str).collect();
+        variables.sort_unstable();
+        assert_eq!(variables, ["A", "B", "T", "x", "y", "z"]);
+        assert_eq!(symbols.variables["T"], 4);
+        let mut classes: Vec<_> = symbols.classes.iter().map(String::as_str).collect();
+        classes.sort_unstable();
+        assert_eq!(classes, ["C", "D"]);
+    }
+}
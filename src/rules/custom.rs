@@ -0,0 +1,491 @@
+//! Project-specific rules declared in a rules file rather than in code. Each rule flags
+//! the calls, imports or decorators whose dotted name matches a pattern, and reports them
+//! under an ID in the `custom/` namespace:
+//!
+//! ```toml
+//! [[rule]]
+//! id = "custom/no-direct-db-session"
+//! call = "db.session.{method}"
+//! in = "tests"
+//! message = "Test '{function}' calls db.session.{method} directly"
+//! suggestion = "Use the `db` fixture"
+//! severity = "error"
+//! ```
+//!
+//! Once loaded, a custom rule is configured, suppressed and baselined like a built-in one.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Node, Tree};
+
+use crate::models::{Category, ParsedModule, Severity, Tag, Violation};
+use crate::rules::ast::{import_bindings, node_text, resolve_callee, set_span};
+
+/// Namespace of custom rule IDs, so they never collide with built-in ones.
+pub const CUSTOM_RULE_PREFIX: &str = "custom/";
+
+/// Placeholders every message may use besides the captures of its pattern.
+const BUILTIN_CAPTURES: &[&str] = &["match", "function"];
+
+/// Whether `key` is a well-formed custom rule ID: `custom/` and a lowercase slug.
+#[must_use]
+pub fn is_custom_rule_id(key: &str) -> bool {
+    key.strip_prefix(CUSTOM_RULE_PREFIX).is_some_and(|slug| {
+        !slug.is_empty()
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    })
+}
+
+/// Where a custom rule looks for matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchScope {
+    /// Inside test functions, including functions nested in them.
+    Tests,
+    /// Inside fixtures.
+    Fixtures,
+    /// Anywhere in a test file or `conftest.py`.
+    #[default]
+    Anywhere,
+}
+
+fn default_severity() -> Severity {
+    Severity::Warning
+}
+
+fn default_category() -> Category {
+    Category::Maintenance
+}
+
+/// A custom rule as written in the rules file. Exactly one of `call`, `import` and
+/// `decorator` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRuleDef {
+    /// `custom/<slug>`.
+    pub id: String,
+    /// Pattern matched against the dotted name of each called function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call: Option<String>,
+    /// Pattern matched against each imported module or name, and the packages above it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import: Option<String>,
+    /// Pattern matched against the dotted name of each decorator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decorator: Option<String>,
+    #[serde(default, rename = "in")]
+    pub scope: MatchScope,
+    /// Message of each diagnostic; `{capture}` placeholders are filled in.
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    #[serde(default = "default_category")]
+    pub category: Category,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+}
+
+/// Top-level structure of a rules file: a `[[rule]]` array.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<CustomRuleDef>,
+}
+
+/// Read and check the custom rules of a rules file.
+///
+/// # Errors
+///
+/// When the file cannot be read or parsed, or one of its rules is invalid (see
+/// [`compile_rules`]).
+pub fn load_rules_file(path: &Path) -> Result<Vec<CustomRuleDef>> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let file: RulesFile =
+        toml::from_str(&contents).with_context(|| format!("parse {}", path.display()))?;
+    compile_rules(&file.rule)
+        .with_context(|| format!("invalid custom rule in {}", path.display()))?;
+    Ok(file.rule)
+}
+
+/// Compile custom rule definitions for checking.
+///
+/// # Errors
+///
+/// When an ID is malformed or defined twice, a rule sets no matcher or several, a pattern
+/// is malformed, or a message uses a placeholder that is not one of its captures.
+pub fn compile_rules(defs: &[CustomRuleDef]) -> Result<Vec<CustomRule>> {
+    let mut seen = HashSet::new();
+    defs.iter()
+        .enumerate()
+        .map(|(idx, def)| {
+            if !seen.insert(def.id.as_str()) {
+                bail!("custom rule `{}` is defined twice", def.id);
+            }
+            CustomRule::compile(def).with_context(|| {
+                if def.id.is_empty() {
+                    format!("rule[{idx}]")
+                } else {
+                    format!("rule `{}`", def.id)
+                }
+            })
+        })
+        .collect()
+}
+
+/// What a custom rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Call,
+    Import,
+    Decorator,
+}
+
+/// A checked, ready-to-run custom rule.
+#[derive(Debug, Clone)]
+pub struct CustomRule {
+    def: CustomRuleDef,
+    target: Target,
+    pattern: Regex,
+}
+
+impl CustomRule {
+    fn compile(def: &CustomRuleDef) -> Result<Self> {
+        if !is_custom_rule_id(&def.id) {
+            bail!(
+                "`id = \"{}\"` must be `{CUSTOM_RULE_PREFIX}` followed by lowercase letters, digits, `-` or `_`",
+                def.id
+            );
+        }
+        let (target, pattern) = match (&def.call, &def.import, &def.decorator) {
+            (Some(p), None, None) => (Target::Call, p),
+            (None, Some(p), None) => (Target::Import, p),
+            (None, None, Some(p)) => (Target::Decorator, p),
+            (None, None, None) => bail!("set one of `call`, `import` or `decorator`"),
+            _ => bail!("set only one of `call`, `import` or `decorator`"),
+        };
+        let (pattern, captures) = pattern_regex(pattern)?;
+        for text in std::iter::once(&def.message).chain(&def.suggestion) {
+            for name in placeholders(text) {
+                if !captures.iter().any(|c| c == name) && !BUILTIN_CAPTURES.contains(&name) {
+                    bail!(
+                        "`{{{name}}}` in `{text}` is not a capture of the pattern; use one of {}",
+                        captures
+                            .iter()
+                            .map(String::as_str)
+                            .chain(BUILTIN_CAPTURES.iter().copied())
+                            .map(|c| format!("{{{c}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            def: def.clone(),
+            target,
+            pattern,
+        })
+    }
+
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.def.id
+    }
+
+    /// Flag every match in `module`, whose syntax tree is `tree`.
+    #[must_use]
+    pub fn check(&self, module: &ParsedModule, tree: Option<&Tree>) -> Vec<Violation> {
+        let Some(tree) = tree else {
+            return vec![];
+        };
+        let source = module.source.as_bytes();
+        let root = tree.root_node();
+        let bindings = import_bindings(root, source);
+        let mut violations = Vec::new();
+        let mut to_visit = vec![root];
+        while let Some(node) = to_visit.pop() {
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+            let candidates: Vec<(Node, String)> = match (self.target, node.kind()) {
+                (Target::Call, "call") => node
+                    .child_by_field_name("function")
+                    .map(|func| (node, resolve_callee(func, source, &bindings)))
+                    .into_iter()
+                    .collect(),
+                (Target::Decorator, "decorator") => node
+                    .named_child(0)
+                    .map(|expr| {
+                        let func = if expr.kind() == "call" {
+                            expr.child_by_field_name("function").unwrap_or(expr)
+                        } else {
+                            expr
+                        };
+                        (node, resolve_callee(func, source, &bindings))
+                    })
+                    .into_iter()
+                    .collect(),
+                (Target::Import, "import_statement" | "import_from_statement") => {
+                    imported_names(node, source)
+                }
+                _ => continue,
+            };
+            for (site, name) in candidates {
+                if let Some(v) = self.check_site(module, site, &name) {
+                    violations.push(v);
+                }
+            }
+        }
+        violations.sort_by_key(|v| (v.line, v.col));
+        violations
+    }
+
+    fn check_site(&self, module: &ParsedModule, site: Node, name: &str) -> Option<Violation> {
+        if !name.split('.').all(is_identifier) {
+            return None;
+        }
+        let captures = match self.target {
+            // `import a.b.c` is matched by a pattern for `a.b.c`, `a.b` or `a`
+            Target::Import => {
+                let mut prefixes = name.match_indices('.').map(|(i, _)| &name[..i]);
+                prefixes
+                    .find_map(|prefix| self.pattern.captures(prefix))
+                    .or_else(|| self.pattern.captures(name))?
+            }
+            Target::Call | Target::Decorator => self.pattern.captures(name)?,
+        };
+        let owner = owner(module, site);
+        let in_scope = match self.def.scope {
+            MatchScope::Tests => matches!(owner, Owner::Test(_)),
+            MatchScope::Fixtures => matches!(owner, Owner::Fixture(_)),
+            MatchScope::Anywhere => true,
+        };
+        if !in_scope {
+            return None;
+        }
+        let function = match &owner {
+            Owner::Test(name) | Owner::Fixture(name) | Owner::Function(name) => name.clone(),
+            Owner::Module => "<module>".to_string(),
+        };
+        let fill = |text: &str| {
+            placeholder_regex()
+                .replace_all(text, |caps: &regex::Captures| match &caps[1] {
+                    "match" => captures[0].to_string(),
+                    "function" => function.clone(),
+                    capture => captures
+                        .name(capture)
+                        .map_or_else(String::new, |m| m.as_str().to_string()),
+                })
+                .into_owned()
+        };
+        let mut v = Violation {
+            rule_id: self.def.id.clone(),
+            rule_name: self.def.id.clone(),
+            severity: self.def.severity,
+            category: self.def.category,
+            message: fill(&self.def.message),
+            file_path: module.file_path.clone(),
+            line: 0,
+            col: None,
+            end_line: None,
+            end_col: None,
+            suggestion: self.def.suggestion.as_deref().map(fill),
+            test_name: match owner {
+                Owner::Test(name) => Some(name),
+                _ => None,
+            },
+            fix: None,
+            metadata: Default::default(),
+            tags: self.def.tags.clone(),
+            related: Vec::new(),
+        };
+        set_span(&mut v, site);
+        Some(v)
+    }
+}
+
+/// The function a match sits in, as far as scopes go.
+enum Owner {
+    Test(String),
+    Fixture(String),
+    /// A function that is neither a test nor a fixture (a helper, a class method).
+    Function(String),
+    Module,
+}
+
+/// The test or fixture enclosing `node`, looking through nested functions; a decorator
+/// belongs to the function it decorates.
+fn owner(module: &ParsedModule, node: Node) -> Owner {
+    let source = module.source.as_bytes();
+    let mut innermost = None;
+    let mut current = Some(node);
+    while let Some(n) = current {
+        let function = match n.kind() {
+            "function_definition" => Some(n),
+            "decorated_definition" => n
+                .child_by_field_name("definition")
+                .filter(|d| d.kind() == "function_definition"),
+            _ => None,
+        };
+        if let Some(function) = function {
+            let line = function.start_position().row + 1;
+            let name = function
+                .child_by_field_name("name")
+                .map(|n| node_text(n, source).to_string())
+                .unwrap_or_default();
+            if module
+                .test_functions
+                .iter()
+                .any(|t| t.line == line && t.name == name)
+            {
+                return Owner::Test(name);
+            }
+            if module
+                .fixtures
+                .iter()
+                .any(|f| f.line == line && f.name == name)
+            {
+                return Owner::Fixture(name);
+            }
+            innermost.get_or_insert(name);
+        }
+        current = n.parent();
+    }
+    innermost.map_or(Owner::Module, Owner::Function)
+}
+
+/// Dotted names brought in by an import statement, with the node to report each at:
+/// `from a import b, c` imports `a.b` and `a.c`. Relative imports are skipped.
+fn imported_names<'t>(stmt: Node<'t>, source: &[u8]) -> Vec<(Node<'t>, String)> {
+    let module = match stmt.kind() {
+        "import_from_statement" => match stmt.child_by_field_name("module_name") {
+            Some(m) if m.kind() == "dotted_name" => Some(node_text(m, source)),
+            _ => return vec![],
+        },
+        _ => None,
+    };
+    let mut names = Vec::new();
+    let mut cursor = stmt.walk();
+    for name in stmt.children_by_field_name("name", &mut cursor) {
+        let target = if name.kind() == "aliased_import" {
+            name.child_by_field_name("name").unwrap_or(name)
+        } else {
+            name
+        };
+        let target = node_text(target, source);
+        names.push((
+            name,
+            match module {
+                Some(module) => format!("{module}.{target}"),
+                None => target.to_string(),
+            },
+        ));
+    }
+    if let (Some(module), true) = (module, names.is_empty()) {
+        // `from a import *`
+        names.push((stmt, module.to_string()));
+    }
+    names
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid regex"))
+}
+
+/// Names of the `{placeholder}`s in a message.
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    placeholder_regex()
+        .captures_iter(text)
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str()))
+}
+
+/// Regex matching whole dotted names for a pattern, and the names of its captures.
+/// `*` matches within one segment, `**` across segments, and `{name}` captures one segment.
+fn pattern_regex(pattern: &str) -> Result<(Regex, Vec<String>)> {
+    if pattern.trim().is_empty() {
+        bail!("the pattern is empty");
+    }
+    let mut re = String::from("^");
+    let mut captures: Vec<String> = Vec::new();
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**") {
+            re.push_str(".+");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('*') {
+            re.push_str("[^.]*");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let end = after
+                .find('}')
+                .ok_or_else(|| anyhow!("unclosed `{{` in pattern `{pattern}`"))?;
+            let name = &after[..end];
+            if !name.is_ascii() || !is_identifier(name) {
+                bail!(
+                    "capture `{{{name}}}` in pattern `{pattern}` must be a name like `{{method}}`"
+                );
+            }
+            if BUILTIN_CAPTURES.contains(&name) || captures.iter().any(|c| c == name) {
+                bail!("capture `{{{name}}}` in pattern `{pattern}` is already defined");
+            }
+            re.push_str(&format!("(?P<{name}>[^.]+)"));
+            captures.push(name.to_string());
+            rest = &after[end + 1..];
+        } else {
+            re.push_str(&regex::escape(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    re.push('$');
+    let regex = Regex::new(&re).with_context(|| format!("invalid pattern `{pattern}`"))?;
+    Ok((regex, captures))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_wildcards_and_captures() {
+        let (re, captures) = pattern_regex("db.session.{method}").unwrap();
+        assert_eq!(captures, ["method"]);
+        assert_eq!(&re.captures("db.session.query").unwrap()["method"], "query");
+        assert!(!re.is_match("db.session.query.filter"));
+        assert!(!re.is_match("mydb.session.query"));
+
+        let (re, _) = pattern_regex("mock.assert_*").unwrap();
+        assert!(re.is_match("mock.assert_called_once"));
+        assert!(!re.is_match("mock.calls.assert_x"));
+        let (re, _) = pattern_regex("app.**.commit").unwrap();
+        assert!(re.is_match("app.db.session.commit"));
+        assert!(!re.is_match("app.commit"));
+
+        assert!(pattern_regex("a.{b").is_err());
+        assert!(pattern_regex("a.{match}").is_err());
+        assert!(pattern_regex("a.{x}.{x}").is_err());
+    }
+
+    #[test]
+    fn test_custom_rule_ids_are_namespaced() {
+        assert!(is_custom_rule_id("custom/no-db-session"));
+        assert!(!is_custom_rule_id("custom/"));
+        assert!(!is_custom_rule_id("custom/No-Db"));
+        assert!(!is_custom_rule_id("PYTEST-FLK-001"));
+    }
+}
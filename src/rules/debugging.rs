@@ -0,0 +1,274 @@
+//! Debugging leftovers in tests: debugger entry points (`breakpoint()`, `pdb.set_trace()`)
+//! and prints. Both rules carry a fix that deletes the statement.
+
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, Fix, ParsedModule, Severity, Violation};
+use crate::rules::ast::{
+    delete_statement, function_at_line, import_bindings, resolve_callee, set_span,
+};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
+
+/// Calls that stop at a debugger prompt or wait for a debugger to attach.
+const DEBUGGER_CALLS: &[&str] = &[
+    "breakpoint",
+    "pdb.set_trace",
+    "pdb.post_mortem",
+    "pdb.pm",
+    "ipdb.set_trace",
+    "ipdb.post_mortem",
+    "pudb.set_trace",
+    "rpdb.set_trace",
+    "remote_pdb.set_trace",
+    "web_pdb.set_trace",
+    "IPython.embed",
+    "debugpy.breakpoint",
+    "debugpy.wait_for_client",
+];
+
+/// Calls that print to the terminal.
+const PRINT_CALLS: &[&str] = &[
+    "print",
+    "pprint.pprint",
+    "pprint.pp",
+    "rich.print",
+    "rich.inspect",
+    "icecream.ic",
+    "devtools.debug",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArtifactKind {
+    Debugger,
+    Print,
+}
+
+/// One debugging call inside a test.
+struct Artifact<'t> {
+    kind: ArtifactKind,
+    call: Node<'t>,
+    callee: String,
+    test: usize,
+    fix: Option<Fix>,
+}
+
+/// Debugging calls in the module's tests, in source order. Both kinds are collected
+/// together so that when every statement of a block is a leftover, exactly one of them
+/// becomes `pass` and the block still parses after `--fix`, whichever rules are enabled.
+fn artifacts<'t>(root: Node<'t>, module: &ParsedModule) -> Vec<Artifact<'t>> {
+    let source = module.source.as_bytes();
+    let bindings = import_bindings(root, source);
+    let mut found = Vec::new();
+    for (test, function) in module.test_functions.iter().enumerate() {
+        let Some(body) =
+            function_at_line(root, function.line).and_then(|f| f.child_by_field_name("body"))
+        else {
+            continue;
+        };
+        let mut to_visit = vec![body];
+        while let Some(node) = to_visit.pop() {
+            if node.kind() == "call" {
+                if let Some((kind, callee)) = classify(node, source, &bindings) {
+                    found.push(Artifact {
+                        kind,
+                        call: node,
+                        callee,
+                        test,
+                        fix: None,
+                    });
+                }
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+    }
+    found.sort_by_key(|a| a.call.start_byte());
+
+    // Statements that are nothing but a debugging call, grouped by their block
+    let statement = |call: Node<'t>| {
+        call.parent()
+            .filter(|p| p.kind() == "expression_statement" && p.named_child_count() == 1)
+    };
+    let removable: HashSet<usize> = found
+        .iter()
+        .filter_map(|a| statement(a.call).map(|s| s.id()))
+        .collect();
+    let mut pass_given = HashSet::new();
+    for artifact in &mut found {
+        let Some(stmt) = statement(artifact.call) else {
+            continue;
+        };
+        let Some(block) = stmt.parent() else {
+            continue;
+        };
+        let mut cursor = block.walk();
+        let all_removable = block
+            .named_children(&mut cursor)
+            .filter(|n| n.kind() != "comment")
+            .all(|n| removable.contains(&n.id()));
+        let becomes_pass = all_removable && pass_given.insert(block.id());
+        artifact.fix = delete_statement(stmt, &module.source, becomes_pass);
+    }
+    found
+}
+
+fn classify(
+    call: Node,
+    source: &[u8],
+    bindings: &HashMap<String, String>,
+) -> Option<(ArtifactKind, String)> {
+    let func = call.child_by_field_name("function")?;
+    if !matches!(func.kind(), "identifier" | "attribute") {
+        return None;
+    }
+    let callee = resolve_callee(func, source, bindings);
+    if DEBUGGER_CALLS.contains(&callee.as_str()) {
+        Some((ArtifactKind::Debugger, callee))
+    } else if PRINT_CALLS.contains(&callee.as_str()) {
+        Some((ArtifactKind::Print, callee))
+    } else {
+        None
+    }
+}
+
+fn report(
+    rule: &dyn Rule,
+    module: &ParsedModule,
+    artifact: Artifact,
+    message: String,
+    suggestion: &str,
+) -> Violation {
+    let test = &module.test_functions[artifact.test];
+    let mut v = make_violation(
+        rule.id(),
+        rule.name(),
+        rule.severity(),
+        rule.category(),
+        message,
+        module.file_path.clone(),
+        test.line,
+        Some(suggestion.to_string()),
+        Some(test.name.clone()),
+    );
+    set_span(&mut v, artifact.call);
+    v.fix = artifact.fix;
+    v
+}
+
+/// Rule that detects debugger calls (`breakpoint()`, `pdb.set_trace()`) left in tests,
+/// which hang a CI run waiting for input.
+pub struct DebuggerCallRule;
+
+impl Rule for DebuggerCallRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-026"
+    }
+    fn name(&self) -> &'static str {
+        "DebuggerCallRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn fixable(&self) -> bool {
+        true
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        artifacts(tree.root_node(), module)
+            .into_iter()
+            .filter(|a| a.kind == ArtifactKind::Debugger)
+            .map(|a| {
+                let message = format!(
+                    "Test '{}' calls {}(), which stops at a debugger prompt",
+                    module.test_functions[a.test].name, a.callee
+                );
+                report(self, module, a, message, "Remove the debugger call")
+            })
+            .collect()
+    }
+}
+
+/// Rule that detects `print()` / `pprint()` calls left in tests. Output meant to stay
+/// belongs in `logging` or in the assertion message.
+pub struct PrintInTestRule;
+
+impl Rule for PrintInTestRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-027"
+    }
+    fn name(&self) -> &'static str {
+        "PrintInTestRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn fixable(&self) -> bool {
+        true
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "allowed_loggers",
+            kind: OptionKind::StringList,
+            description: "Print functions or modules the project uses as test loggers, e.g. \
+                          [\"rich\"] or [\"icecream.ic\"] (default [])",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let allowed = ctx.options.string_list("allowed_loggers");
+        let is_allowed = |callee: &str| {
+            allowed.iter().any(|a| {
+                callee == a
+                    || callee
+                        .strip_prefix(a.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        };
+        artifacts(tree.root_node(), module)
+            .into_iter()
+            .filter(|a| a.kind == ArtifactKind::Print && !is_allowed(&a.callee))
+            .map(|a| {
+                let message = format!(
+                    "Test '{}' calls {}(); debugging output left in the test",
+                    module.test_functions[a.test].name, a.callee
+                );
+                report(
+                    self,
+                    module,
+                    a,
+                    message,
+                    "Remove the print, or use logging or an assertion message",
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,537 @@
+//! Tests that depend on the machine and the moment they run: environment variables, the
+//! current time, and the locale or time zone. A read is fine when the test controls what it
+//! returns, or when the value never reaches an assertion.
+
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, ParsedModule, Severity, Violation};
+use crate::rules::ast::{function_at_line, import_bindings, node_text, resolve_callee, set_span};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Dependency {
+    Env,
+    Clock,
+    Locale,
+}
+
+impl Dependency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Env => "env",
+            Self::Clock => "clock",
+            Self::Locale => "locale",
+        }
+    }
+}
+
+/// Calls whose result depends on the machine or the time, by the dotted name they resolve to.
+const READ_CALLS: &[(&str, Dependency)] = &[
+    ("os.getenv", Dependency::Env),
+    ("os.getenvb", Dependency::Env),
+    ("os.environ.get", Dependency::Env),
+    ("datetime.datetime.now", Dependency::Clock),
+    ("datetime.datetime.utcnow", Dependency::Clock),
+    ("datetime.datetime.today", Dependency::Clock),
+    ("datetime.date.today", Dependency::Clock),
+    ("time.time", Dependency::Clock),
+    ("time.time_ns", Dependency::Clock),
+    ("time.localtime", Dependency::Clock),
+    ("locale.getlocale", Dependency::Locale),
+    ("locale.getdefaultlocale", Dependency::Locale),
+    ("locale.getencoding", Dependency::Locale),
+    ("locale.getpreferredencoding", Dependency::Locale),
+    ("locale.localeconv", Dependency::Locale),
+    ("tzlocal.get_localzone", Dependency::Locale),
+];
+
+/// Module attributes read directly (`os.environ["HOME"]`, `time.tzname`).
+const READ_ATTRIBUTES: &[(&str, Dependency)] = &[
+    ("os.environ", Dependency::Env),
+    ("time.timezone", Dependency::Locale),
+    ("time.altzone", Dependency::Locale),
+    ("time.tzname", Dependency::Locale),
+];
+
+/// `os.environ` methods that change it rather than read it.
+const ENVIRON_WRITES: &[&str] = &["pop", "popitem", "update", "setdefault", "clear"];
+
+/// Fixtures of pytest plugins that freeze the clock.
+const CLOCK_FIXTURES: &[&str] = &["freezer", "time_machine"];
+
+/// What freezes the clock, as a decorator, a context manager or a plain call: freezegun,
+/// time-machine, and the marker of pytest-freezegun.
+const CLOCK_CONTROLS: &[&str] = &[
+    "freezegun.freeze_time",
+    "time_machine.travel",
+    "pytest.mark.freeze_time",
+];
+
+/// A read of the environment, clock or locale in a test.
+struct Read<'t> {
+    dependency: Dependency,
+    node: Node<'t>,
+    /// The read as written, e.g. `os.environ` or `datetime.now()`.
+    what: String,
+}
+
+/// Reads and controls in one test body.
+struct Scan<'a, 't> {
+    source: &'t [u8],
+    bindings: &'a HashMap<String, String>,
+    reads: Vec<Read<'t>>,
+    controlled: HashSet<Dependency>,
+}
+
+impl<'t> Scan<'_, 't> {
+    fn resolve(&self, node: Node) -> String {
+        resolve_callee(node, self.source, self.bindings)
+    }
+
+    /// Record what a call controls: `monkeypatch.setenv`, `mock.patch.dict(os.environ)`,
+    /// `freeze_time(...)`, `locale.setlocale(...)`, `monkeypatch.setattr(time, ...)`.
+    fn control(&mut self, call: Node<'t>) {
+        let Some(func) = call.child_by_field_name("function") else {
+            return;
+        };
+        let callee = self.resolve(func);
+        let last = callee.rsplit('.').next().unwrap_or(&callee);
+        let first_arg = call
+            .child_by_field_name("arguments")
+            .and_then(|a| a.named_child(0))
+            .map(|a| {
+                let resolved = self.resolve(a);
+                resolved.trim_matches(|c| c == '"' || c == '\'').to_string()
+            })
+            .unwrap_or_default();
+        let target_module = first_arg.split('.').next().unwrap_or_default();
+        match last {
+            "setenv" | "delenv" => {
+                self.controlled.insert(Dependency::Env);
+                self.controlled.insert(Dependency::Locale);
+            }
+            "dict" if callee.ends_with("patch.dict") && first_arg == "os.environ" => {
+                self.controlled.insert(Dependency::Env);
+                self.controlled.insert(Dependency::Locale);
+            }
+            "setlocale" if callee == "locale.setlocale" => {
+                self.controlled.insert(Dependency::Locale);
+            }
+            "setattr" | "patch" | "object" => match target_module {
+                "time" | "datetime" => {
+                    self.controlled.insert(Dependency::Clock);
+                }
+                "locale" | "tzlocal" => {
+                    self.controlled.insert(Dependency::Locale);
+                }
+                "os" => {
+                    self.controlled.insert(Dependency::Env);
+                }
+                _ => {}
+            },
+            _ if CLOCK_CONTROLS.contains(&callee.as_str()) => {
+                self.controlled.insert(Dependency::Clock);
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, dependency: Dependency, node: Node<'t>, what: String) {
+        self.reads.push(Read {
+            dependency,
+            node,
+            what,
+        });
+    }
+
+    fn visit(&mut self, node: Node<'t>) {
+        match node.kind() {
+            // Nested functions run later, if at all
+            "function_definition" | "class_definition" => return,
+            // Deleting a variable is not reading it
+            "delete_statement" => return,
+            "assignment" | "augmented_assignment" => {
+                if let Some(right) = node.child_by_field_name("right") {
+                    self.visit(right);
+                }
+                if let Some(left) = node.child_by_field_name("left") {
+                    if left.kind() != "subscript" {
+                        self.visit(left);
+                    } else if left
+                        .child_by_field_name("value")
+                        .is_some_and(|v| self.resolve(v) == "os.environ")
+                    {
+                        // `os.environ["X"] = "1"` sets the environment
+                        self.controlled.insert(Dependency::Env);
+                    }
+                }
+                return;
+            }
+            "call" => {
+                self.control(node);
+                let callee = node
+                    .child_by_field_name("function")
+                    .map(|f| (f, self.resolve(f)));
+                if let Some((func, callee)) = &callee {
+                    let method = callee.strip_prefix("os.environ.");
+                    if let Some(&(_, dependency)) = READ_CALLS.iter().find(|(c, _)| c == callee) {
+                        let what = format!("{}()", node_text(*func, self.source));
+                        self.read(dependency, node, what);
+                    } else if method.is_some_and(|m| ENVIRON_WRITES.contains(&m)) {
+                        self.controlled.insert(Dependency::Env);
+                    } else {
+                        self.visit(*func);
+                    }
+                }
+                // `patch.dict(os.environ, ...)` names the environment to replace it
+                let patches_dict = callee
+                    .as_ref()
+                    .is_some_and(|(_, c)| c.ends_with("patch.dict"));
+                if let Some(args) = node.child_by_field_name("arguments") {
+                    let mut cursor = args.walk();
+                    for (i, arg) in args.named_children(&mut cursor).enumerate() {
+                        if !(patches_dict && i == 0) {
+                            self.visit(arg);
+                        }
+                    }
+                }
+                return;
+            }
+            "attribute" | "identifier" => {
+                let resolved = self.resolve(node);
+                if let Some(&(_, dependency)) = READ_ATTRIBUTES.iter().find(|(a, _)| *a == resolved)
+                {
+                    let what = node_text(node, self.source).to_string();
+                    self.read(dependency, node, what);
+                    return;
+                }
+                // Only the object of `a.b` can be a read; `b` is just a name
+                if let Some(object) = node.child_by_field_name("object") {
+                    self.visit(object);
+                }
+                return;
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node<'t>> = node.named_children(&mut cursor).collect();
+        for child in children {
+            self.visit(child);
+        }
+    }
+}
+
+/// Decorators of `func` and of the classes around it.
+fn enclosing_decorators(func: Node) -> Vec<Node> {
+    let mut decorators = Vec::new();
+    let mut node = Some(func);
+    while let Some(n) = node {
+        if n == func || n.kind() == "class_definition" {
+            if let Some(decorated) = n.parent().filter(|p| p.kind() == "decorated_definition") {
+                let mut cursor = decorated.walk();
+                decorators.extend(
+                    decorated
+                        .named_children(&mut cursor)
+                        .filter(|c| c.kind() == "decorator"),
+                );
+            }
+        }
+        node = n.parent();
+    }
+    decorators
+}
+
+/// Names of a function's parameters.
+fn parameter_names<'t>(func: Node<'t>, source: &'t [u8]) -> Vec<&'t str> {
+    let Some(params) = func.child_by_field_name("parameters") else {
+        return vec![];
+    };
+    let mut cursor = params.walk();
+    let names = params
+        .named_children(&mut cursor)
+        .filter_map(|param| match param.kind() {
+            "identifier" => Some(param),
+            _ => param
+                .child_by_field_name("name")
+                .or_else(|| param.named_child(0))
+                .filter(|n| n.kind() == "identifier"),
+        })
+        .map(|name| node_text(name, source))
+        .collect();
+    names
+}
+
+/// Identifiers under `node`.
+fn names_in<'t>(node: Node, source: &'t [u8]) -> HashSet<&'t str> {
+    let mut names = HashSet::new();
+    let mut to_visit = vec![node];
+    while let Some(n) = to_visit.pop() {
+        if n.kind() == "identifier" {
+            names.insert(node_text(n, source));
+        }
+        let mut cursor = n.walk();
+        to_visit.extend(n.named_children(&mut cursor));
+    }
+    names
+}
+
+/// Identifiers bound by an assignment target (`a`, `a, b`, `[a, *b]`).
+fn target_names<'t>(target: Node, source: &'t [u8]) -> Vec<&'t str> {
+    match target.kind() {
+        "identifier" => vec![node_text(target, source)],
+        "pattern_list" | "tuple_pattern" | "list_pattern" | "list_splat_pattern"
+        | "as_pattern_target" => {
+            let mut cursor = target.walk();
+            let names = target
+                .named_children(&mut cursor)
+                .flat_map(|t| target_names(t, source))
+                .collect();
+            names
+        }
+        _ => vec![],
+    }
+}
+
+/// Where values flow inside a test body: assignments (as target names and the value) and
+/// assertions.
+struct Flow<'t> {
+    source: &'t [u8],
+    assignments: Vec<(Vec<&'t str>, Node<'t>)>,
+    assertions: Vec<Node<'t>>,
+}
+
+impl<'t> Flow<'t> {
+    fn new(body: Node<'t>, source: &'t [u8]) -> Self {
+        let mut flow = Self {
+            source,
+            assignments: Vec::new(),
+            assertions: Vec::new(),
+        };
+        let mut to_visit = vec![body];
+        while let Some(node) = to_visit.pop() {
+            let (target, value) = match node.kind() {
+                "function_definition" | "class_definition" => continue,
+                "assert_statement" => {
+                    flow.assertions.push(node);
+                    (None, None)
+                }
+                "assignment" | "augmented_assignment" | "for_statement" => (
+                    node.child_by_field_name("left"),
+                    node.child_by_field_name("right"),
+                ),
+                "named_expression" => (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("value"),
+                ),
+                "as_pattern" => (node.child_by_field_name("alias"), node.named_child(0)),
+                "call" => {
+                    let func = node.child_by_field_name("function");
+                    let callee = func.map_or("", |f| node_text(f, source));
+                    let method = callee.rsplit('.').next().unwrap_or(callee);
+                    if method.starts_with("assert") {
+                        // `self.assertEqual(...)`, `mock.assert_called_with(...)`
+                        flow.assertions.push(node);
+                        (None, None)
+                    } else {
+                        // `results.append(value)` puts the value into `results`
+                        let object = func
+                            .filter(|f| f.kind() == "attribute")
+                            .and_then(|f| f.child_by_field_name("object"))
+                            .filter(|o| o.kind() == "identifier");
+                        (object, node.child_by_field_name("arguments"))
+                    }
+                }
+                _ => (None, None),
+            };
+            if let (Some(target), Some(value)) = (target, value) {
+                let names = target_names(target, source);
+                if !names.is_empty() {
+                    flow.assignments.push((names, value));
+                }
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        flow
+    }
+
+    /// Whether the value read at `read` reaches an assertion: the read is inside one, or is
+    /// assigned to a name that, through further assignments, is used in one.
+    fn reaches_assertion(&self, read: Node) -> bool {
+        let contains = |outer: Node, inner: Node| {
+            outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+        };
+        if self.assertions.iter().any(|a| contains(*a, read)) {
+            return true;
+        }
+        let mut tainted: HashSet<&str> = HashSet::new();
+        for (targets, value) in &self.assignments {
+            if contains(*value, read) {
+                tainted.extend(targets.iter().copied());
+            }
+        }
+        loop {
+            let before = tainted.len();
+            for (targets, value) in &self.assignments {
+                if targets.iter().all(|t| tainted.contains(t)) {
+                    continue;
+                }
+                if !names_in(*value, self.source).is_disjoint(&tainted) {
+                    tainted.extend(targets.iter().copied());
+                }
+            }
+            if tainted.len() == before {
+                break;
+            }
+        }
+        !tainted.is_empty()
+            && self
+                .assertions
+                .iter()
+                .any(|a| !names_in(*a, self.source).is_disjoint(&tainted))
+    }
+}
+
+/// Rule that flags tests reading environment variables, the current time or the locale
+/// without controlling them (monkeypatch, freezegun, time-machine), when the value reaches
+/// an assertion.
+pub struct EnvironmentDependentRule;
+
+impl Rule for EnvironmentDependentRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FLK-016"
+    }
+    fn name(&self) -> &'static str {
+        "EnvironmentDependentRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Flakiness
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "controlled_fixtures",
+            kind: OptionKind::StringList,
+            description: "Project fixtures that pin the environment, clock and locale, e.g. \
+                          [\"frozen_time\"] (default [])",
+        }]
+    }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let bindings = import_bindings(root, source);
+        let controlled_fixtures = ctx.options.string_list("controlled_fixtures");
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(func) = function_at_line(root, test.line) else {
+                continue;
+            };
+            let Some(body) = func.child_by_field_name("body") else {
+                continue;
+            };
+            let params = parameter_names(func, source);
+            if params
+                .iter()
+                .any(|p| controlled_fixtures.iter().any(|f| f == p))
+            {
+                continue;
+            }
+            let mut scan = Scan {
+                source,
+                bindings: &bindings,
+                reads: Vec::new(),
+                controlled: HashSet::new(),
+            };
+            if params.iter().any(|p| CLOCK_FIXTURES.contains(p)) {
+                scan.controlled.insert(Dependency::Clock);
+            }
+            for decorator in enclosing_decorators(func) {
+                let mut to_visit = vec![decorator];
+                while let Some(node) = to_visit.pop() {
+                    if node.kind() == "call" {
+                        scan.control(node);
+                    } else if node.kind() == "attribute"
+                        && CLOCK_CONTROLS
+                            .contains(&resolve_callee(node, source, &bindings).as_str())
+                    {
+                        // `@pytest.mark.freeze_time` without arguments
+                        scan.controlled.insert(Dependency::Clock);
+                    }
+                    let mut cursor = node.walk();
+                    to_visit.extend(node.named_children(&mut cursor));
+                }
+            }
+            scan.visit(body);
+            if scan.reads.is_empty() {
+                continue;
+            }
+
+            let flow = Flow::new(body, source);
+            for read in &scan.reads {
+                if scan.controlled.contains(&read.dependency) || !flow.reaches_assertion(read.node)
+                {
+                    continue;
+                }
+                let (message, suggestion) = match read.dependency {
+                    Dependency::Env => (
+                        format!(
+                            "Test '{}' reads the environment ({}) without setting it; the result depends on the machine",
+                            test.name, read.what
+                        ),
+                        "Set the variable in the test with monkeypatch.setenv() or mock.patch.dict(os.environ, ...)",
+                    ),
+                    Dependency::Clock => (
+                        format!(
+                            "Test '{}' reads the current time ({}) without freezing it; the result depends on when the test runs",
+                            test.name, read.what
+                        ),
+                        "Freeze the clock with freezegun's freeze_time or time-machine's travel",
+                    ),
+                    Dependency::Locale => (
+                        format!(
+                            "Test '{}' reads the locale or time zone ({}) without setting it; the result depends on the machine",
+                            test.name, read.what
+                        ),
+                        "Set the locale with locale.setlocale() or the time zone with monkeypatch.setenv(\"TZ\", ...)",
+                    ),
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    message,
+                    module.file_path.clone(),
+                    read.node.start_position().row + 1,
+                    Some(suggestion.to_string()),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, read.node);
+                v.metadata
+                    .insert("dependency".to_string(), read.dependency.as_str().into());
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
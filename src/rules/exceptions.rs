@@ -0,0 +1,311 @@
+//! Exception-assertion detection (`pytest.raises`, `assertRaises`, ...) and the rule that
+//! flags unreachable code inside `pytest.raises` blocks.
+
+use std::collections::{HashMap, HashSet};
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, ParsedModule, Severity, Violation};
+use crate::rules::ast::{function_at_line, node_text};
+use crate::rules::{Rule, RuleContext};
+
+/// What an exception-assertion helper expects the code under test to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    /// `pytest.raises`, `assertRaises`: the block stops at the raising statement.
+    Raises,
+    /// `pytest.warns`, `pytest.deprecated_call`, `assertWarns`: execution continues.
+    Warns,
+}
+
+/// Names under which pytest and its exception helpers are reachable in a module,
+/// so `import pytest as pt` and `from pytest import raises as expect` are recognised.
+#[derive(Debug, Default)]
+pub struct PytestAliases {
+    modules: HashSet<String>,
+    functions: HashMap<String, Expectation>,
+}
+
+fn pytest_helper(name: &str) -> Option<Expectation> {
+    match name {
+        "raises" => Some(Expectation::Raises),
+        "warns" | "deprecated_call" => Some(Expectation::Warns),
+        _ => None,
+    }
+}
+
+fn unittest_helper(name: &str) -> Option<Expectation> {
+    match name {
+        "assertRaises" | "assertRaisesRegex" | "assertRaisesRegexp" => Some(Expectation::Raises),
+        "assertWarns" | "assertWarnsRegex" => Some(Expectation::Warns),
+        _ => None,
+    }
+}
+
+impl PytestAliases {
+    /// Collect pytest aliases from the module-level imports under `root`.
+    #[must_use]
+    pub fn from_root(root: Node, source: &[u8]) -> Self {
+        let mut aliases = Self::default();
+        let mut cursor = root.walk();
+        for stmt in root.children(&mut cursor) {
+            match stmt.kind() {
+                "import_statement" => {
+                    let mut inner = stmt.walk();
+                    for name in stmt.children_by_field_name("name", &mut inner) {
+                        match name.kind() {
+                            "dotted_name" if node_text(name, source) == "pytest" => {
+                                aliases.modules.insert("pytest".to_string());
+                            }
+                            "aliased_import" => {
+                                let module = name.child_by_field_name("name");
+                                let alias = name.child_by_field_name("alias");
+                                if let (Some(m), Some(a)) = (module, alias) {
+                                    if node_text(m, source) == "pytest" {
+                                        aliases.modules.insert(node_text(a, source).to_string());
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "import_from_statement" => {
+                    let from_pytest = stmt
+                        .child_by_field_name("module_name")
+                        .is_some_and(|m| node_text(m, source) == "pytest");
+                    if !from_pytest {
+                        continue;
+                    }
+                    let mut inner = stmt.walk();
+                    for name in stmt.children_by_field_name("name", &mut inner) {
+                        let (imported, local) = if name.kind() == "aliased_import" {
+                            (
+                                name.child_by_field_name("name"),
+                                name.child_by_field_name("alias"),
+                            )
+                        } else {
+                            (Some(name), Some(name))
+                        };
+                        if let (Some(i), Some(l)) = (imported, local) {
+                            if let Some(kind) = pytest_helper(node_text(i, source)) {
+                                aliases
+                                    .functions
+                                    .insert(node_text(l, source).to_string(), kind);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        aliases
+    }
+
+    /// Classify a call node as an exception assertion, in context-manager or callable form.
+    #[must_use]
+    pub fn expectation(&self, call: Node, source: &[u8]) -> Option<Expectation> {
+        if call.kind() != "call" {
+            return None;
+        }
+        let func = call.child_by_field_name("function")?;
+        match func.kind() {
+            "identifier" => self.functions.get(node_text(func, source)).copied(),
+            "attribute" => {
+                let attr = node_text(func.child_by_field_name("attribute")?, source);
+                let object = node_text(func.child_by_field_name("object")?, source);
+                if self.modules.contains(object) {
+                    pytest_helper(attr)
+                } else {
+                    unittest_helper(attr)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// True if the subtree contains a `pytest.raises`-style exception assertion.
+#[must_use]
+pub fn contains_exception_assertion(node: Node, source: &[u8], aliases: &PytestAliases) -> bool {
+    if aliases.expectation(node, source).is_some() {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .any(|c| contains_exception_assertion(c, source, aliases));
+    found
+}
+
+/// Collect `with` statements whose context manager expects an exception to be raised.
+fn collect_raises_blocks<'tree>(
+    node: Node<'tree>,
+    source: &[u8],
+    aliases: &PytestAliases,
+    blocks: &mut Vec<(Node<'tree>, String)>,
+) {
+    if node.kind() == "with_statement" {
+        let raising_call = node
+            .named_children(&mut node.walk())
+            .find(|c| c.kind() == "with_clause")
+            .and_then(|clause| {
+                let mut items = Vec::new();
+                let mut cursor = clause.walk();
+                for item in clause.named_children(&mut cursor) {
+                    if let Some(value) = item.child_by_field_name("value") {
+                        // `with pytest.raises(E) as exc:` wraps the call in an as_pattern
+                        let call = if value.kind() == "as_pattern" {
+                            value.named_child(0).unwrap_or(value)
+                        } else {
+                            value
+                        };
+                        items.push(call);
+                    }
+                }
+                items
+                    .into_iter()
+                    .find(|call| aliases.expectation(*call, source) == Some(Expectation::Raises))
+            });
+        if let Some(call) = raising_call {
+            let helper = call
+                .child_by_field_name("function")
+                .map(|f| node_text(f, source).to_string())
+                .unwrap_or_default();
+            blocks.push((node, helper));
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_raises_blocks(child, source, aliases, blocks);
+    }
+}
+
+/// Rule that flags statements following the raising statement in a `pytest.raises` block.
+pub struct RaisesBlockTrailingCodeRule;
+
+impl Rule for RaisesBlockTrailingCodeRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-019"
+    }
+    fn name(&self) -> &'static str {
+        "RaisesBlockTrailingCodeRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let aliases = PytestAliases::from_root(root, source);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let mut blocks = Vec::new();
+            collect_raises_blocks(body, source, &aliases, &mut blocks);
+            for (with_stmt, helper) in blocks {
+                let Some(block) = with_stmt.child_by_field_name("body") else {
+                    continue;
+                };
+                let mut cursor = block.walk();
+                let Some(trailing) = block
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() != "comment")
+                    .nth(1)
+                else {
+                    continue;
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Code after the raising statement in a `{helper}` block in test '{}' is never reached",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    trailing.start_position().row + 1,
+                    Some(format!(
+                        "Keep only the raising call inside `{helper}` and move the rest after the block"
+                    )),
+                    Some(test.name.clone()),
+                );
+                v.col = Some(trailing.start_position().column + 1);
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::ast::parse_python;
+    use tree_sitter::Tree;
+
+    fn aliases_for(source: &str) -> (Tree, PytestAliases) {
+        let tree = parse_python(source).unwrap();
+        let aliases = PytestAliases::from_root(tree.root_node(), source.as_bytes());
+        (tree, aliases)
+    }
+
+    #[test]
+    fn test_aliases_from_imports() {
+        let (_, aliases) = aliases_for(
+            "import pytest as pt\nfrom pytest import raises as expect_error, warns\nimport os\n",
+        );
+        assert!(aliases.modules.contains("pt"));
+        assert_eq!(
+            aliases.functions.get("expect_error"),
+            Some(&Expectation::Raises)
+        );
+        assert_eq!(aliases.functions.get("warns"), Some(&Expectation::Warns));
+        assert!(!aliases.modules.contains("os"));
+    }
+
+    #[test]
+    fn test_contains_exception_assertion_callable_form() {
+        let source = "import pytest\n\ndef test_a():\n    pytest.raises(ValueError, int, 'x')\n";
+        let (tree, aliases) = aliases_for(source);
+        let func = function_at_line(tree.root_node(), 3).unwrap();
+        assert!(contains_exception_assertion(
+            func,
+            source.as_bytes(),
+            &aliases
+        ));
+    }
+
+    #[test]
+    fn test_raises_requires_pytest_import() {
+        let source = "def test_a():\n    with raises(ValueError):\n        int('x')\n";
+        let (tree, aliases) = aliases_for(source);
+        let func = function_at_line(tree.root_node(), 1).unwrap();
+        assert!(!contains_exception_assertion(
+            func,
+            source.as_bytes(),
+            &aliases
+        ));
+    }
+}
@@ -1,10 +1,16 @@
-//! Rules that detect fixture anti-patterns: autouse, scope issues, mutations, missing cleanup.
+//! Rules that detect fixture anti-patterns: autouse, scope issues, mutations, missing cleanup,
+//! unused fixture parameters.
 
 use std::collections::{HashMap, HashSet};
 
+use tree_sitter::Node;
+
 use crate::engine::{fixture_scope_by_name, make_violation};
-use crate::models::{Category, Fixture, FixtureScope, ParsedModule, Severity, Violation};
-use crate::rules::{Rule, RuleContext};
+use crate::models::{Category, Fix, Fixture, FixtureScope, ParsedModule, Severity, Tag, Violation};
+use crate::rules::ast::{function_at_line, import_bindings, node_text, resolve_callee, set_span};
+use crate::rules::exceptions::{contains_exception_assertion, PytestAliases};
+use crate::rules::flakiness::SleepFinder;
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
 
 /// Rule that detects autouse fixtures which implicitly affect all tests.
 pub struct AutouseFixtureRule;
@@ -74,7 +80,18 @@ impl Rule for InvalidScopeRule {
 
         for fixture in &module.fixtures {
             for dep_name in &fixture.dependencies {
-                if let Some(dep_scope) = fixture_scope_by_name(ctx.fixture_map, dep_name) {
+                // In project mode the dependency is the definition pytest would inject
+                let visible = ctx.project.and_then(|project| {
+                    if *dep_name == fixture.name {
+                        project.overridden(&module.file_path, dep_name)
+                    } else {
+                        project.fixture(&module.file_path, dep_name)
+                    }
+                });
+                let dep_scope = visible
+                    .map(|d| d.fixture.scope)
+                    .or_else(|| fixture_scope_by_name(ctx.fixture_map, dep_name));
+                if let Some(dep_scope) = dep_scope {
                     if fixture.scope > dep_scope {
                         violations.push(make_violation(
                             self.id(),
@@ -125,6 +142,43 @@ impl Rule for ShadowedFixtureRule {
     ) -> Vec<Violation> {
         let mut violations = Vec::new();
 
+        if let Some(project) = ctx.project {
+            for fixture in &module.fixtures {
+                // `def db(db)` extends the outer definition on purpose
+                if fixture.dependencies.contains(&fixture.name) {
+                    continue;
+                }
+                let Some(outer) = project.overridden(&module.file_path, &fixture.name) else {
+                    continue;
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Fixture '{}' shadows the definition in {}:{}",
+                        fixture.name,
+                        outer.file.display(),
+                        outer.fixture.line
+                    ),
+                    module.file_path.clone(),
+                    fixture.line,
+                    Some(
+                        "Rename the fixture, or request the outer one by its own name to extend it"
+                            .to_string(),
+                    ),
+                    None,
+                );
+                v.metadata.insert(
+                    "shadowed".to_string(),
+                    format!("{}:{}", outer.file.display(), outer.fixture.line).into(),
+                );
+                violations.push(v);
+            }
+            return violations;
+        }
+
         for fixture in &module.fixtures {
             if let Some(locations) = ctx.fixture_locations.get(&fixture.name) {
                 if locations.len() > 1 {
@@ -166,6 +220,12 @@ impl Rule for UnusedFixtureRule {
     fn category(&self) -> Category {
         Category::Fixture
     }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn tags(&self) -> &'static [Tag] {
+        &[Tag::Unnecessary]
+    }
     fn check(
         &self,
         module: &ParsedModule,
@@ -177,7 +237,10 @@ impl Rule for UnusedFixtureRule {
             if fixture.is_autouse {
                 continue;
             }
-            if !ctx.used_fixture_names.contains(&fixture.name) {
+            let requested = ctx
+                .project
+                .is_some_and(|p| p.is_requested(&module.file_path, &fixture.name));
+            if !ctx.used_fixture_names.contains(&fixture.name) && !requested {
                 violations.push(make_violation(
                     self.id(),
                     self.name(),
@@ -403,26 +466,34 @@ impl Rule for AutouseCascadeDepthRule {
     fn category(&self) -> Category {
         Category::Fixture
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_depth",
+            kind: OptionKind::Integer,
+            description: "Maximum dependency depth below an autouse fixture (default 3)",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
         ctx: &RuleContext,
     ) -> Vec<Violation> {
+        let max_depth = ctx.options.usize("max_depth", 3);
         let mut violations = Vec::new();
         for fixture in &module.fixtures {
             if fixture.is_autouse {
                 let mut visited = HashSet::new();
                 let depth = compute_cascade_depth(fixture, ctx.fixture_map, &mut visited);
-                if depth > 3 {
+                if depth > max_depth {
                     violations.push(make_violation(
                         self.id(),
                         self.name(),
                         self.severity(),
                         self.category(),
                         format!(
-                            "Autouse fixture '{}' has dependency cascade depth of {} (> 3)",
-                            fixture.name, depth
+                            "Autouse fixture '{}' has dependency cascade depth of {} (> {})",
+                            fixture.name, depth, max_depth
                         ),
                         module.file_path.clone(),
                         fixture.line,
@@ -641,17 +712,26 @@ impl Rule for NoContractHintRule {
         "NoContractHintRule"
     }
     fn severity(&self) -> Severity {
-        Severity::Info
+        Severity::Hint
     }
     fn category(&self) -> Category {
         Category::Enhancement
     }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        // assertRaises and aliased pytest.raises are exception assertions too
+        let root = ctx.tree.map(tree_sitter::Tree::root_node);
+        let source = module.source.as_bytes();
+        let aliases = root
+            .map(|r| PytestAliases::from_root(r, source))
+            .unwrap_or_default();
         let mut violations = Vec::new();
         for test in &module.test_functions {
             if test.has_assertions
@@ -659,6 +739,12 @@ impl Rule for NoContractHintRule {
                 && !test.has_try_except
                 && !test.is_parametrized
             {
+                let expects_exception = root
+                    .and_then(|r| function_at_line(r, test.line))
+                    .is_some_and(|f| contains_exception_assertion(f, source, &aliases));
+                if expects_exception {
+                    continue;
+                }
                 violations.push(make_violation(
                     self.id(),
                     self.name(),
@@ -675,3 +761,463 @@ impl Rule for NoContractHintRule {
         violations
     }
 }
+
+/// Builtin fixtures that are commonly requested only for their side effects
+/// (capturing output or warnings, setting up a test database).
+const SIDE_EFFECT_FIXTURES: &[&str] = &[
+    "capsys",
+    "capsysbinary",
+    "capfd",
+    "capfdbinary",
+    "recwarn",
+    "db",
+    "transactional_db",
+];
+
+/// Argument names declared by `@pytest.mark.parametrize` decorators on a function.
+fn parametrize_argnames(func: Node, source: &[u8]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let Some(decorated) = func.parent().filter(|p| p.kind() == "decorated_definition") else {
+        return names;
+    };
+    let mut cursor = decorated.walk();
+    for decorator in decorated.named_children(&mut cursor) {
+        let Some(call) = decorator.named_child(0).filter(|c| c.kind() == "call") else {
+            continue;
+        };
+        let is_parametrize = call
+            .child_by_field_name("function")
+            .is_some_and(|f| node_text(f, source).ends_with("parametrize"));
+        let first_arg = call
+            .child_by_field_name("arguments")
+            .and_then(|args| args.named_child(0));
+        let Some(first_arg) = first_arg.filter(|_| is_parametrize) else {
+            continue;
+        };
+        let strings: Vec<Node> = match first_arg.kind() {
+            "string" => vec![first_arg],
+            "list" | "tuple" => {
+                let mut c = first_arg.walk();
+                first_arg
+                    .named_children(&mut c)
+                    .filter(|n| n.kind() == "string")
+                    .collect()
+            }
+            _ => vec![],
+        };
+        for s in strings {
+            let text = node_text(s, source).trim_matches(|c| c == '"' || c == '\'');
+            names.extend(
+                text.split(',')
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+    names
+}
+
+/// Whether any identifier under `node` is spelled `name`.
+fn references_name(node: Node, source: &[u8], name: &str) -> bool {
+    if node.kind() == "identifier" && node_text(node, source) == name {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .any(|child| references_name(child, source, name));
+    found
+}
+
+/// Rule that detects test parameters requesting fixtures the test body never uses.
+pub struct UnusedFixtureParameterRule;
+
+impl Rule for UnusedFixtureParameterRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FIX-014"
+    }
+    fn name(&self) -> &'static str {
+        "UnusedFixtureParameterRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Fixture
+    }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn tags(&self) -> &'static [Tag] {
+        &[Tag::Unnecessary]
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "side_effect_fixtures",
+            kind: OptionKind::StringList,
+            description: "Additional fixtures that may be requested only for their side effects \
+                          (capsys, capfd, recwarn, db and their variants are always allowed)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let extra_exempt = ctx.options.string_list("side_effect_fixtures");
+        let bindings = import_bindings(root, source);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(func) = function_at_line(root, test.line) else {
+                continue;
+            };
+            let (Some(params), Some(body)) = (
+                func.child_by_field_name("parameters"),
+                func.child_by_field_name("body"),
+            ) else {
+                continue;
+            };
+            // `@given` and `@patch` inject positional arguments themselves
+            let injects_arguments = func
+                .parent()
+                .filter(|p| p.kind() == "decorated_definition")
+                .is_some_and(|d| {
+                    let mut cursor = d.walk();
+                    let found = d
+                        .named_children(&mut cursor)
+                        .filter(|c| c.kind() == "decorator")
+                        .any(|dec| injects_positional_arguments(dec, source, &bindings));
+                    found
+                });
+            if injects_arguments {
+                continue;
+            }
+            let argnames = parametrize_argnames(func, source);
+
+            let mut cursor = params.walk();
+            for param in params.named_children(&mut cursor) {
+                // Parameters with defaults, `*args` and `**kwargs` are never fixtures.
+                let name_node = match param.kind() {
+                    "identifier" => param,
+                    "typed_parameter" => match param.named_child(0) {
+                        Some(n) if n.kind() == "identifier" => n,
+                        _ => continue,
+                    },
+                    _ => continue,
+                };
+                let name = node_text(name_node, source);
+                if name == "self"
+                    || name == "cls"
+                    || argnames.contains(name)
+                    || SIDE_EFFECT_FIXTURES.contains(&name)
+                    || extra_exempt.iter().any(|e| e == name)
+                    || references_name(body, source, name)
+                {
+                    continue;
+                }
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' requests fixture '{}' but never uses it",
+                        test.name, name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(format!(
+                        "Remove the parameter, or use @pytest.mark.usefixtures(\"{name}\") if only its side effects are needed"
+                    )),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, param);
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
+/// Whether a decorator passes arguments to the test itself: hypothesis' `@given`, and
+/// `@patch` / `@patch.object` / `@patch.multiple` from `unittest.mock` or `mock`.
+fn injects_positional_arguments(
+    decorator: Node,
+    source: &[u8],
+    bindings: &HashMap<String, String>,
+) -> bool {
+    const INJECTING: &[&str] = &[
+        "hypothesis.given",
+        "unittest.mock.patch",
+        "unittest.mock.patch.object",
+        "unittest.mock.patch.multiple",
+        "mock.patch",
+        "mock.patch.object",
+        "mock.patch.multiple",
+    ];
+    let Some(expr) = decorator.named_child(0) else {
+        return false;
+    };
+    let callee = match expr.kind() {
+        "call" => match expr.child_by_field_name("function") {
+            Some(function) => function,
+            None => return false,
+        },
+        _ => expr,
+    };
+    INJECTING.contains(&resolve_callee(callee, source, bindings).as_str())
+}
+
+/// File and network I/O calls that an autouse fixture would run before every test.
+fn first_io_call<'t>(node: Node<'t>, source: &[u8]) -> Option<Node<'t>> {
+    const IO_METHODS: &[&str] = &[
+        "open",
+        "read_text",
+        "write_text",
+        "read_bytes",
+        "write_bytes",
+        "mkdir",
+        "makedirs",
+        "rmtree",
+        "unlink",
+        "remove",
+        "touch",
+        "urlopen",
+    ];
+    const IO_MODULES: &[&str] = &["requests", "httpx", "socket", "urllib", "aiohttp", "shutil"];
+    if node.kind() == "call" {
+        if let Some(func) = node.child_by_field_name("function") {
+            let is_io = match func.kind() {
+                "identifier" => ["open", "urlopen"].contains(&node_text(func, source)),
+                "attribute" => {
+                    let method = func
+                        .child_by_field_name("attribute")
+                        .map(|a| node_text(a, source));
+                    let object = func
+                        .child_by_field_name("object")
+                        .map(|o| node_text(o, source));
+                    method.is_some_and(|m| IO_METHODS.contains(&m))
+                        || object
+                            .is_some_and(|o| IO_MODULES.contains(&o.split('.').next().unwrap_or(o)))
+                }
+                _ => false,
+            };
+            if is_io {
+                return Some(node);
+            }
+        }
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .find_map(|child| first_io_call(child, source));
+    found
+}
+
+/// Rule that detects autouse fixtures in `conftest.py` that do I/O or sleep, slowing down
+/// every test in the directory.
+pub struct AutouseConftestSideEffectRule;
+
+impl Rule for AutouseConftestSideEffectRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FIX-015"
+    }
+    fn name(&self) -> &'static str {
+        "AutouseConftestSideEffectRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Fixture
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let is_conftest = module
+            .file_path
+            .file_name()
+            .is_some_and(|n| n == "conftest.py");
+        if !is_conftest || !module.fixtures.iter().any(|f| f.is_autouse) {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let sleeps = SleepFinder::new(root, source, &[]);
+
+        let mut violations = Vec::new();
+        for fixture in module.fixtures.iter().filter(|f| f.is_autouse) {
+            let Some(body) =
+                function_at_line(root, fixture.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let sleep = sleeps.sleeps_in(body).into_iter().next();
+            let io = first_io_call(body, source);
+            let sleep_first = match (&sleep, io) {
+                (Some(s), Some(call)) => {
+                    (s.line, s.col)
+                        < (
+                            call.start_position().row + 1,
+                            call.start_position().column + 1,
+                        )
+                }
+                (Some(_), None) => true,
+                _ => false,
+            };
+            let (what, line) = if sleep_first {
+                let s = sleep.as_ref().expect("sleep_first implies a sleep site");
+                (format!("sleeps ({}())", s.callee), s.line)
+            } else if let Some(call) = io {
+                let callee = call
+                    .child_by_field_name("function")
+                    .map_or("", |f| node_text(f, source));
+                (
+                    format!("performs I/O ({callee}())"),
+                    call.start_position().row + 1,
+                )
+            } else {
+                continue;
+            };
+            let mut v = make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Autouse fixture '{}' in conftest.py {} before every test it applies to",
+                    fixture.name, what
+                ),
+                module.file_path.clone(),
+                line,
+                Some(
+                    "Drop autouse=True and request the fixture only from the tests that need it"
+                        .to_string(),
+                ),
+                None,
+            );
+            if let (true, Some(s)) = (sleep_first, &sleep) {
+                v.col = Some(s.col);
+                v.end_line = Some(s.end.0);
+                v.end_col = Some(s.end.1);
+            } else if let Some(call) = io {
+                set_span(&mut v, call);
+            }
+            violations.push(v);
+        }
+        violations
+    }
+}
+
+/// Rule that detects generator fixtures whose `yield` is the last statement, so there is no
+/// teardown and `return` would do.
+pub struct PointlessYieldFixtureRule;
+
+impl Rule for PointlessYieldFixtureRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FIX-016"
+    }
+    fn name(&self) -> &'static str {
+        "PointlessYieldFixtureRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Fixture
+    }
+    fn tags(&self) -> &'static [Tag] {
+        &[Tag::Unnecessary]
+    }
+    fn fixable(&self) -> bool {
+        true
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if !module.fixtures.iter().any(|f| f.has_yield) {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+
+        let mut violations = Vec::new();
+        for fixture in module.fixtures.iter().filter(|f| f.has_yield) {
+            let Some(body) =
+                function_at_line(root, fixture.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let Some(last) = body.named_child(body.named_child_count().saturating_sub(1)) else {
+                continue;
+            };
+            // Only a plain `yield` / `yield value` statement; `x = yield` and `yield from`
+            // are real generator use, and a yield nested in `with`/`try` has teardown.
+            let Some(yield_node) = last
+                .named_child(0)
+                .filter(|n| last.kind() == "expression_statement" && n.kind() == "yield")
+            else {
+                continue;
+            };
+            let mut cursor = yield_node.walk();
+            let is_yield_from = yield_node.children(&mut cursor).any(|c| c.kind() == "from");
+            if is_yield_from || count_yields(body) != 1 {
+                continue;
+            }
+            let mut v = make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Fixture '{}' yields with no teardown code after the yield",
+                    fixture.name
+                ),
+                module.file_path.clone(),
+                fixture.line,
+                Some(
+                    "Use return instead of yield when the fixture has nothing to clean up"
+                        .to_string(),
+                ),
+                None,
+            );
+            set_span(&mut v, last);
+            let keyword_end = yield_node.start_byte() + "yield".len();
+            v.fix = Some(Fix::replace(yield_node.start_byte(), keyword_end, "return"));
+            violations.push(v);
+        }
+        violations
+    }
+}
+
+/// Number of `yield` expressions in a body, not counting nested functions and lambdas.
+fn count_yields(node: Node) -> usize {
+    if matches!(node.kind(), "function_definition" | "lambda") {
+        return 0;
+    }
+    let mut cursor = node.walk();
+    let nested: usize = node.children(&mut cursor).map(count_yields).sum();
+    nested + usize::from(node.is_named() && node.kind() == "yield")
+}
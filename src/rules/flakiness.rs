@@ -1,11 +1,16 @@
-//! Rules that detect test flakiness patterns: time.sleep, file I/O, network, random, subprocess.
+//! Rules that detect test flakiness patterns: time.sleep, file I/O, network, random, subprocess,
+//! exact float comparisons.
+
+use std::collections::{HashMap, HashSet};
 
 use crate::engine::make_violation;
 use crate::models::{Category, ParsedModule, Severity, Violation};
-use crate::rules::{Rule, RuleContext};
+use crate::rules::ast::{function_at_line, node_text, set_span};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
 use tree_sitter::Node;
 
-/// Rule that detects use of `time.sleep` in tests, which causes flaky behavior.
+/// Rule that detects time-based waits (`time.sleep`, `asyncio.sleep`, ...) in tests, which
+/// cause slow and flaky behavior. Also reachable as `sleepy-test`.
 pub struct TimeSleepRule;
 
 impl Rule for TimeSleepRule {
@@ -15,41 +20,688 @@ impl Rule for TimeSleepRule {
     fn name(&self) -> &'static str {
         "TimeSleepRule"
     }
+    fn aliases(&self) -> &'static [&'static str] {
+        &["sleepy-test"]
+    }
     fn severity(&self) -> Severity {
         Severity::Warning
     }
     fn category(&self) -> Category {
         Category::Flakiness
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "allowed_functions",
+            kind: OptionKind::StringList,
+            description: "Retry/backoff helpers whose sleeps are ignored; matched as a \
+                          case-insensitive substring of the function name (default [\"retry\", \"backoff\"])",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+
+        let mut allowed = ctx.options.string_list("allowed_functions");
+        if allowed.is_empty() {
+            allowed = vec!["retry".to_string(), "backoff".to_string()];
+        }
+        let finder = SleepFinder::new(root, source, &allowed);
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.uses_time_sleep {
-                violations.push(make_violation(
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            for site in finder.sleeps_in(body) {
+                let message = match &site.via {
+                    Some(inner) => format!(
+                        "Test '{}' waits via helper '{}()', which calls {}() — time-based waits cause flaky tests",
+                        test.name, site.callee, inner
+                    ),
+                    None => format!(
+                        "Test '{}' calls {}() — time-based waits cause flaky tests",
+                        test.name, site.callee
+                    ),
+                };
+                let mut v = make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
-                    format!(
-                        "Test '{}' uses time.sleep which causes flaky tests",
-                        test.name
-                    ),
+                    message,
                     module.file_path.clone(),
-                    test.line,
-                    Some("Use pytest's time mocking or wait for a condition instead".to_string()),
+                    site.line,
+                    Some(
+                        "Wait for an event or condition instead, or use a fake clock (e.g. freezegun, time-machine)"
+                            .to_string(),
+                    ),
                     Some(test.name.clone()),
-                ));
+                );
+                v.col = Some(site.col);
+                v.end_line = Some(site.end.0);
+                v.end_col = Some(site.end.1);
+                violations.push(v);
             }
         }
         violations
     }
 }
 
+/// Modules whose `sleep` is a real wall-clock wait.
+const SLEEP_MODULES: &[&str] = &["time", "asyncio", "anyio", "trio", "gevent", "eventlet"];
+
+/// A sleep call found in a test, either direct or through a same-file helper.
+pub(crate) struct SleepSite {
+    pub(crate) line: usize,
+    pub(crate) col: usize,
+    /// End of the call expression (line, exclusive column), 1-based.
+    pub(crate) end: (usize, usize),
+    /// The callee as written (`time.sleep`, `wait`, or the helper's name).
+    pub(crate) callee: String,
+    /// For helper calls, the sleep the helper ends up making.
+    pub(crate) via: Option<String>,
+}
+
+/// Resolves sleep calls in one module: import aliases (`from time import sleep as wait`,
+/// `import asyncio as aio`) and module-level helper functions that sleep.
+pub(crate) struct SleepFinder<'s> {
+    source: &'s [u8],
+    modules: HashSet<String>,
+    functions: HashSet<String>,
+    allowed: Vec<String>,
+    /// Helper name -> the sleep call it makes (directly or through other helpers).
+    helpers: HashMap<String, String>,
+}
+
+impl<'s> SleepFinder<'s> {
+    pub(crate) fn new(root: Node, source: &'s [u8], allowed: &[String]) -> Self {
+        let mut finder = Self {
+            source,
+            modules: SLEEP_MODULES.iter().map(|m| (*m).to_string()).collect(),
+            functions: HashSet::from(["sleep".to_string()]),
+            allowed: allowed.iter().map(|a| a.to_lowercase()).collect(),
+            helpers: HashMap::new(),
+        };
+        finder.collect_aliases(root);
+        finder.collect_helpers(root);
+        finder
+    }
+
+    fn is_allowed(&self, function_name: &str) -> bool {
+        let name = function_name.to_lowercase();
+        self.allowed.iter().any(|a| name.contains(a.as_str()))
+    }
+
+    fn collect_aliases(&mut self, node: Node) {
+        match node.kind() {
+            "import_statement" => {
+                let mut cursor = node.walk();
+                for name in node.children_by_field_name("name", &mut cursor) {
+                    if name.kind() == "aliased_import" {
+                        let module = name.child_by_field_name("name");
+                        let alias = name.child_by_field_name("alias");
+                        if let (Some(m), Some(a)) = (module, alias) {
+                            if SLEEP_MODULES.contains(&node_text(m, self.source)) {
+                                self.modules.insert(node_text(a, self.source).to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            "import_from_statement" => {
+                let from_sleep_module = node
+                    .child_by_field_name("module_name")
+                    .is_some_and(|m| SLEEP_MODULES.contains(&node_text(m, self.source)));
+                if from_sleep_module {
+                    let mut cursor = node.walk();
+                    for name in node.children_by_field_name("name", &mut cursor) {
+                        if name.kind() != "aliased_import" {
+                            continue;
+                        }
+                        let imported = name.child_by_field_name("name");
+                        let alias = name.child_by_field_name("alias");
+                        if let (Some(i), Some(a)) = (imported, alias) {
+                            if node_text(i, self.source) == "sleep" {
+                                self.functions.insert(node_text(a, self.source).to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.collect_aliases(child);
+                }
+            }
+        }
+    }
+
+    /// The sleep callee text if `call` is a direct sleep call.
+    fn direct_sleep(&self, call: Node) -> Option<String> {
+        let func = call.child_by_field_name("function")?;
+        let is_sleep = match func.kind() {
+            "identifier" => self.functions.contains(node_text(func, self.source)),
+            "attribute" => {
+                let attr = func.child_by_field_name("attribute")?;
+                let object = func.child_by_field_name("object")?;
+                node_text(attr, self.source) == "sleep"
+                    && self.modules.contains(node_text(object, self.source))
+            }
+            _ => false,
+        };
+        is_sleep.then(|| node_text(func, self.source).to_string())
+    }
+
+    /// Record every non-test module-level function that sleeps, resolving helper chains.
+    fn collect_helpers(&mut self, root: Node) {
+        let mut direct: HashMap<String, Option<String>> = HashMap::new();
+        let mut calls: HashMap<String, Vec<String>> = HashMap::new();
+        let mut cursor = root.walk();
+        for child in root.children(&mut cursor) {
+            let func = match child.kind() {
+                "function_definition" => Some(child),
+                "decorated_definition" => child.child_by_field_name("definition"),
+                _ => None,
+            };
+            let Some(func) = func.filter(|f| f.kind() == "function_definition") else {
+                continue;
+            };
+            let Some(name) = func.child_by_field_name("name") else {
+                continue;
+            };
+            let name = node_text(name, self.source).to_string();
+            if name.starts_with("test_") || self.is_allowed(&name) {
+                continue;
+            }
+            let Some(body) = func.child_by_field_name("body") else {
+                continue;
+            };
+            let mut sites = Vec::new();
+            let mut called = Vec::new();
+            self.walk(body, &mut sites, &mut called);
+            direct.insert(name.clone(), sites.into_iter().next().map(|s| s.callee));
+            calls.insert(name, called);
+        }
+
+        // Propagate through helper -> helper calls until nothing changes
+        let mut resolved: HashMap<String, String> = direct
+            .iter()
+            .filter_map(|(n, s)| s.clone().map(|s| (n.clone(), s)))
+            .collect();
+        loop {
+            let mut changed = false;
+            for (name, callees) in &calls {
+                if resolved.contains_key(name) {
+                    continue;
+                }
+                if let Some(inner) = callees.iter().find_map(|c| resolved.get(c).cloned()) {
+                    resolved.insert(name.clone(), inner);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        self.helpers = resolved;
+    }
+
+    /// Collect direct sleep calls and the plain names of other calls under `node`,
+    /// skipping nested functions whose names are on the allowlist.
+    fn walk(&self, node: Node, sites: &mut Vec<SleepSite>, called: &mut Vec<String>) {
+        if node.kind() == "function_definition" {
+            let allowed = node
+                .child_by_field_name("name")
+                .is_some_and(|n| self.is_allowed(node_text(n, self.source)));
+            if allowed {
+                return;
+            }
+        }
+        if node.kind() == "call" {
+            if let Some(callee) = self.direct_sleep(node) {
+                sites.push(SleepSite {
+                    line: node.start_position().row + 1,
+                    col: node.start_position().column + 1,
+                    end: (node.end_position().row + 1, node.end_position().column + 1),
+                    callee,
+                    via: None,
+                });
+            } else if let Some(func) = node
+                .child_by_field_name("function")
+                .filter(|f| f.kind() == "identifier")
+            {
+                called.push(node_text(func, self.source).to_string());
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.walk(child, sites, called);
+        }
+    }
+
+    /// Sleep sites in a test body, including calls to helpers that sleep.
+    pub(crate) fn sleeps_in(&self, body: Node) -> Vec<SleepSite> {
+        let mut sites = Vec::new();
+        let mut called = Vec::new();
+        self.walk(body, &mut sites, &mut called);
+        self.walk_helper_calls(body, &mut sites);
+        sites.sort_by_key(|s| (s.line, s.col));
+        sites
+    }
+
+    fn walk_helper_calls(&self, node: Node, sites: &mut Vec<SleepSite>) {
+        if node.kind() == "call" {
+            if let Some(func) = node
+                .child_by_field_name("function")
+                .filter(|f| f.kind() == "identifier")
+            {
+                let name = node_text(func, self.source);
+                if let Some(inner) = self.helpers.get(name) {
+                    sites.push(SleepSite {
+                        line: node.start_position().row + 1,
+                        col: node.start_position().column + 1,
+                        end: (node.end_position().row + 1, node.end_position().column + 1),
+                        callee: name.to_string(),
+                        via: Some(inner.clone()),
+                    });
+                }
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.walk_helper_calls(child, sites);
+        }
+    }
+
+    /// The sleep `call` makes, directly or through a same-file helper: the callee as written.
+    pub(crate) fn sleep_callee(&self, call: Node) -> Option<String> {
+        self.direct_sleep(call).or_else(|| {
+            let func = call
+                .child_by_field_name("function")
+                .filter(|f| f.kind() == "identifier")?;
+            let name = node_text(func, self.source);
+            self.helpers.contains_key(name).then(|| name.to_string())
+        })
+    }
+}
+
+/// What stops a polling loop from running forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopBound {
+    Attempts,
+    Deadline,
+    /// A `pytest-timeout`-style limit on the whole test.
+    TestTimeout,
+}
+
+impl LoopBound {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Attempts => "attempts",
+            Self::Deadline => "deadline",
+            Self::TestTimeout => "test-timeout",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Attempts => "an attempt count",
+            Self::Deadline => "a deadline",
+            Self::TestTimeout => "the test's timeout",
+        }
+    }
+}
+
+/// Name fragments of variables that hold a time limit.
+const DEADLINE_NAMES: &[&str] = &["deadline", "timeout", "time_limit", "expire", "elapsed"];
+
+/// Clock functions (last segment of the callee) whose value in a condition makes it a deadline.
+const CLOCK_FUNCTIONS: &[&str] = &[
+    "time",
+    "time_ns",
+    "monotonic",
+    "monotonic_ns",
+    "perf_counter",
+    "perf_counter_ns",
+    "now",
+    "utcnow",
+];
+
+/// Rule that flags loops that sleep between polls, by whether anything bounds them: an
+/// unbounded loop is an error, one bounded by attempts or a deadline a warning.
+pub struct SleepPollingLoopRule;
+
+impl Rule for SleepPollingLoopRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FLK-015"
+    }
+    fn name(&self) -> &'static str {
+        "SleepPollingLoopRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+    fn category(&self) -> Category {
+        Category::Flakiness
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() && module.fixtures.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let finder = SleepFinder::new(root, source, &[]);
+        let module_timeout = has_module_timeout(root, source);
+
+        let functions = module
+            .test_functions
+            .iter()
+            .map(|t| ("Test", &t.name, t.line))
+            .chain(module.fixtures.iter().map(|f| ("Fixture", &f.name, f.line)));
+        let mut violations = Vec::new();
+        for (kind, name, line) in functions {
+            let Some(func) = function_at_line(root, line) else {
+                continue;
+            };
+            let Some(body) = func.child_by_field_name("body") else {
+                continue;
+            };
+            let test_timeout =
+                kind == "Test" && (module_timeout || has_timeout_decorator(func, source));
+            let mut loops = Vec::new();
+            polling_loops(body, None, &finder, &mut loops);
+            loops.sort_by_key(|(l, _, _)| l.start_byte());
+            for (loop_node, sleep, callee) in loops {
+                let bound = loop_bound(loop_node, source)
+                    .or(test_timeout.then_some(LoopBound::TestTimeout));
+                let sleep_line = sleep.start_position().row + 1;
+                let (severity, message, suggestion) = match bound {
+                    None => (
+                        Severity::Error,
+                        format!(
+                            "{kind} '{name}' polls in a loop that sleeps ({callee}() at line {sleep_line}) with no timeout or attempt limit — it can hang CI forever"
+                        ),
+                        "Bound the loop with a deadline or an attempt limit, or wait with a helper such as tenacity",
+                    ),
+                    Some(bound) => (
+                        Severity::Warning,
+                        format!(
+                            "{kind} '{name}' polls in a loop that sleeps ({callee}() at line {sleep_line}), bounded by {}",
+                            bound.describe()
+                        ),
+                        "Use a wait helper (e.g. tenacity, polling2) or wait on an event the code under test signals",
+                    ),
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    severity,
+                    self.category(),
+                    message,
+                    module.file_path.clone(),
+                    loop_node.start_position().row + 1,
+                    Some(suggestion.to_string()),
+                    (kind == "Test").then(|| name.clone()),
+                );
+                let mut cursor = loop_node.walk();
+                let keyword = loop_node
+                    .children(&mut cursor)
+                    .find(|c| matches!(c.kind(), "while" | "for"))
+                    .unwrap_or(loop_node);
+                set_span(&mut v, keyword);
+                v.metadata.insert(
+                    "bound".to_string(),
+                    bound.map_or("none", LoopBound::as_str).into(),
+                );
+                v.metadata
+                    .insert("sleep_line".to_string(), sleep_line.into());
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
+/// Loops under `node` that sleep, each with its first sleep call. A sleep belongs to the
+/// innermost loop around it; nested functions and lambdas are not followed.
+fn polling_loops<'t>(
+    node: Node<'t>,
+    innermost: Option<Node<'t>>,
+    finder: &SleepFinder,
+    out: &mut Vec<(Node<'t>, Node<'t>, String)>,
+) {
+    if matches!(node.kind(), "function_definition" | "lambda") {
+        return;
+    }
+    let innermost = if matches!(node.kind(), "while_statement" | "for_statement") {
+        Some(node)
+    } else {
+        innermost
+    };
+    if node.kind() == "call" {
+        if let (Some(loop_node), Some(callee)) = (innermost, finder.sleep_callee(node)) {
+            if !out.iter().any(|(l, _, _)| *l == loop_node) {
+                out.push((loop_node, node, callee));
+            }
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        polling_loops(child, innermost, finder, out);
+    }
+}
+
+/// What bounds a loop: its condition or iterable, or an exit (`break`, `return`, `raise`,
+/// a failing `assert`) guarded by a deadline or attempt check.
+fn loop_bound(loop_node: Node, source: &[u8]) -> Option<LoopBound> {
+    let body = loop_node.child_by_field_name("body")?;
+    let head = match loop_node.kind() {
+        "for_statement" => {
+            let iterable = loop_node.child_by_field_name("right")?;
+            if !is_endless_iterator(iterable, source) {
+                return Some(LoopBound::Attempts);
+            }
+            None
+        }
+        _ => loop_node.child_by_field_name("condition"),
+    };
+    if let Some(bound) = head.and_then(|c| condition_bound(c, body, source)) {
+        return Some(bound);
+    }
+    let mut guards = Vec::new();
+    exit_guards(body, &mut guards);
+    guards
+        .into_iter()
+        .find_map(|guard| condition_bound(guard, body, source))
+}
+
+/// `itertools.count()`, `itertools.cycle(...)`, `itertools.repeat(x)` and two-argument
+/// `iter(...)` never run out.
+fn is_endless_iterator(iterable: Node, source: &[u8]) -> bool {
+    if iterable.kind() != "call" {
+        return false;
+    }
+    let Some(func) = iterable.child_by_field_name("function") else {
+        return false;
+    };
+    let callee = node_text(func, source);
+    let last = callee.rsplit('.').next().unwrap_or(callee);
+    let args = iterable
+        .child_by_field_name("arguments")
+        .map_or(0, |a| a.named_child_count());
+    match last {
+        "count" | "cycle" => true,
+        "repeat" => args == 1,
+        "iter" => args == 2,
+        _ => false,
+    }
+}
+
+/// Conditions guarding an exit from the loop, not looking into nested loops or functions.
+fn exit_guards<'t>(node: Node<'t>, out: &mut Vec<Node<'t>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "while_statement" | "for_statement" | "function_definition" | "lambda" => {}
+            "if_statement" | "elif_clause" => {
+                let exits = child
+                    .child_by_field_name("consequence")
+                    .is_some_and(contains_exit);
+                if let (true, Some(condition)) = (exits, child.child_by_field_name("condition")) {
+                    out.push(condition);
+                }
+                exit_guards(child, out);
+            }
+            "assert_statement" => {
+                if let Some(condition) = child.named_child(0) {
+                    out.push(condition);
+                }
+            }
+            _ => exit_guards(child, out),
+        }
+    }
+}
+
+/// Whether a block leaves the loop: `break`, `return` or `raise` outside nested loops.
+fn contains_exit(node: Node) -> bool {
+    if matches!(
+        node.kind(),
+        "break_statement" | "return_statement" | "raise_statement"
+    ) {
+        return true;
+    }
+    if matches!(
+        node.kind(),
+        "while_statement" | "for_statement" | "function_definition" | "lambda"
+    ) {
+        return false;
+    }
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).any(contains_exit);
+    found
+}
+
+/// Whether a condition checks a deadline (a clock call or a timeout-like name) or a counter
+/// the loop body increments or decrements.
+fn condition_bound(condition: Node, body: Node, source: &[u8]) -> Option<LoopBound> {
+    let mut identifiers = Vec::new();
+    let mut to_visit = vec![condition];
+    while let Some(node) = to_visit.pop() {
+        match node.kind() {
+            "identifier" => identifiers.push(node_text(node, source)),
+            "call" => {
+                let callee = node
+                    .child_by_field_name("function")
+                    .map_or("", |f| node_text(f, source));
+                let last = callee.rsplit('.').next().unwrap_or(callee);
+                if CLOCK_FUNCTIONS.contains(&last) {
+                    return Some(LoopBound::Deadline);
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        to_visit.extend(node.named_children(&mut cursor));
+    }
+    if identifiers.iter().any(|name| {
+        let name = name.to_lowercase();
+        DEADLINE_NAMES.iter().any(|d| name.contains(d))
+    }) {
+        return Some(LoopBound::Deadline);
+    }
+    let counters = stepped_names(body, source);
+    identifiers
+        .iter()
+        .any(|name| counters.contains(name))
+        .then_some(LoopBound::Attempts)
+}
+
+/// Names the target of an augmented assignment (`attempts += 1`) under `node`.
+fn stepped_names<'s>(node: Node, source: &'s [u8]) -> HashSet<&'s str> {
+    let mut names = HashSet::new();
+    let mut to_visit = vec![node];
+    while let Some(n) = to_visit.pop() {
+        if n.kind() == "augmented_assignment" {
+            if let Some(left) = n
+                .child_by_field_name("left")
+                .filter(|l| l.kind() == "identifier")
+            {
+                names.insert(node_text(left, source));
+            }
+        }
+        let mut cursor = n.walk();
+        to_visit.extend(n.named_children(&mut cursor));
+    }
+    names
+}
+
+/// A `@pytest.mark.timeout(...)`-style decorator (pytest-timeout, timeout-decorator) on a
+/// test.
+fn has_timeout_decorator(func: Node, source: &[u8]) -> bool {
+    let Some(decorated) = func.parent().filter(|p| p.kind() == "decorated_definition") else {
+        return false;
+    };
+    let mut cursor = decorated.walk();
+    let found = decorated
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "decorator")
+        .any(|d| is_timeout_marker(d, source));
+    found
+}
+
+fn is_timeout_marker(decorator: Node, source: &[u8]) -> bool {
+    let Some(expr) = decorator.named_child(0) else {
+        return false;
+    };
+    let func = if expr.kind() == "call" {
+        expr.child_by_field_name("function").unwrap_or(expr)
+    } else {
+        expr
+    };
+    node_text(func, source).rsplit('.').next() == Some("timeout")
+}
+
+/// `pytestmark = pytest.mark.timeout(...)` (or a list holding it) at module level.
+fn has_module_timeout(root: Node, source: &[u8]) -> bool {
+    let mut cursor = root.walk();
+    let found = root.named_children(&mut cursor).any(|stmt| {
+        stmt.named_child(0)
+            .filter(|e| e.kind() == "assignment")
+            .is_some_and(|assignment| {
+                let target = assignment
+                    .child_by_field_name("left")
+                    .map_or("", |l| node_text(l, source));
+                let value = assignment
+                    .child_by_field_name("right")
+                    .map_or("", |r| node_text(r, source));
+                target == "pytestmark" && value.contains("mark.timeout")
+            })
+    });
+    found
+}
+
 /// Rule that detects file I/O without temporary fixtures.
 pub struct FileIoRule;
 
@@ -101,6 +753,19 @@ impl Rule for FileIoRule {
     }
 }
 
+/// HTTP mocking libraries; importing one means network calls in the file hit a test double.
+const MOCK_LAYER_LIBS: &[&str] = &[
+    "pytest_httpx",
+    "respx",
+    "aioresponses",
+    "responses",
+    "requests_mock",
+    "pytest_mock",
+    "vcrpy",
+    "betamax",
+    "httmock",
+];
+
 /// Rule that detects imports of network libraries in test files.
 pub struct NetworkImportRule;
 
@@ -135,18 +800,6 @@ impl Rule for NetworkImportRule {
             "grpc",
             "aiogrpc",
         ];
-        let mock_layer_libs = [
-            "pytest_httpx",
-            "respx",
-            "aioresponses",
-            "responses",
-            "requests_mock",
-            "pytest_mock",
-            "vcrpy",
-            "betamax",
-            "httmock",
-        ];
-
         let has_network = module
             .imports
             .iter()
@@ -155,7 +808,7 @@ impl Rule for NetworkImportRule {
         let has_mock_layer = module
             .imports
             .iter()
-            .any(|imp| mock_layer_libs.iter().any(|ml| imp.contains(ml)));
+            .any(|imp| MOCK_LAYER_LIBS.iter().any(|ml| imp.contains(ml)));
 
         if has_network && !has_mock_layer {
             vec![make_violation(
@@ -220,7 +873,8 @@ impl Rule for CwdDependencyRule {
     }
 }
 
-/// Rule that detects mystery guest anti-pattern: file I/O without explicit temp fixtures.
+/// Rule that detects the mystery guest anti-pattern: tests reaching real files, the network,
+/// or hard-coded absolute paths instead of explicit, temporary test data.
 pub struct MysteryGuestRule;
 
 impl Rule for MysteryGuestRule {
@@ -236,43 +890,354 @@ impl Rule for MysteryGuestRule {
     fn category(&self) -> Category {
         Category::Flakiness
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[
+            RuleOption {
+                name: "allowed_fixtures",
+                kind: OptionKind::StringList,
+                description: "Fixtures that provide safe test doubles; tests requesting one are not flagged",
+            },
+            RuleOption {
+                name: "allowed_modules",
+                kind: OptionKind::StringList,
+                description: "Module prefixes considered safe doubles, matched against the call and the module it was imported from",
+            },
+        ]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+
+        let allowed_fixtures = ctx.options.string_list("allowed_fixtures");
+        let allowed_modules = ctx.options.string_list("allowed_modules");
+        let finder = GuestFinder::new(root, source, &allowed_modules);
+        let network_mocked = module
+            .imports
+            .iter()
+            .any(|imp| MOCK_LAYER_LIBS.iter().any(|ml| imp.contains(ml)));
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.uses_file_io {
-                let has_tmp = test
-                    .fixture_deps
-                    .iter()
-                    .any(|d| d == "tmp_path" || d == "tmpdir" || d == "tmp_path_factory");
-                if !has_tmp {
-                    violations.push(make_violation(
-                        self.id(),
-                        self.name(),
-                        self.severity(),
-                        self.category(),
-                        format!(
-                            "Test '{}' may be a Mystery Guest — uses file I/O without temp fixtures",
-                            test.name
-                        ),
-                        module.file_path.clone(),
-                        test.line,
-                        Some(
-                            "Use tmp_path fixture and make test data explicit".to_string(),
-                        ),
-                        Some(test.name.clone()),
-                    ));
+            if test
+                .fixture_deps
+                .iter()
+                .any(|d| allowed_fixtures.contains(d))
+            {
+                continue;
+            }
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let uses_temp_files = test
+                .fixture_deps
+                .iter()
+                .any(|d| TEMP_FIXTURES.contains(&d.as_str()))
+                || finder.mentions_tempfile(body);
+
+            let mut guests = Vec::new();
+            finder.collect(body, &mut guests);
+            for (node, guest) in guests {
+                let skip = match guest {
+                    Guest::File(_) => uses_temp_files,
+                    Guest::Network(_) => network_mocked,
+                    Guest::AbsolutePath(_) => false,
+                };
+                if skip {
+                    continue;
                 }
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' may be a Mystery Guest — {}",
+                        test.name,
+                        guest.describe()
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(guest.suggestion().to_string()),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, node);
+                violations.push(v);
             }
         }
         violations
     }
 }
 
+/// Fixtures whose presence means the test's file access targets temporary, test-owned files.
+const TEMP_FIXTURES: &[&str] = &[
+    "tmp_path",
+    "tmpdir",
+    "tmp_path_factory",
+    "tmpdir_factory",
+    "fs",
+];
+
+/// Modules whose calls reach the network.
+const NETWORK_MODULES: &[&str] = &[
+    "requests", "httpx", "urllib", "urllib3", "socket", "aiohttp", "http",
+];
+
+/// `pathlib.Path` methods that touch the file system.
+const PATH_IO_METHODS: &[&str] = &[
+    "read_text",
+    "read_bytes",
+    "write_text",
+    "write_bytes",
+    "open",
+];
+
+/// Well-known roots of hard-coded POSIX paths (URL paths such as `/api/users` are not flagged).
+const ABSOLUTE_PATH_ROOTS: &[&str] = &[
+    "/tmp", "/var", "/etc", "/home", "/Users", "/usr", "/opt", "/mnt", "/srv", "/root", "/data",
+];
+
+/// Something a test reaches for outside of its own setup.
+enum Guest {
+    File(String),
+    Network(String),
+    AbsolutePath(String),
+}
+
+impl Guest {
+    fn describe(&self) -> String {
+        match self {
+            Self::File(callee) => format!("reads or writes a real file via {callee}()"),
+            Self::Network(callee) => format!("makes a network call via {callee}()"),
+            Self::AbsolutePath(path) => format!("hard-codes the absolute path '{path}'"),
+        }
+    }
+
+    fn suggestion(&self) -> &'static str {
+        match self {
+            Self::File(_) | Self::AbsolutePath(_) => {
+                "Use tmp_path fixture and make test data explicit"
+            }
+            Self::Network(_) => {
+                "Mock the network layer (e.g. responses, respx) or use a local test server"
+            }
+        }
+    }
+}
+
+/// A hard-coded absolute POSIX path under a well-known root, or a Windows drive path.
+fn is_absolute_path_literal(text: &str) -> bool {
+    let posix = ABSOLUTE_PATH_ROOTS.iter().any(|root| {
+        text.strip_prefix(root)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    let bytes = text.as_bytes();
+    let windows = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    posix || windows
+}
+
+/// Finds mystery-guest call sites, resolving network names imported under aliases.
+struct GuestFinder<'s> {
+    source: &'s [u8],
+    allowed_modules: &'s [String],
+    /// Local name -> fully qualified module/function it refers to.
+    network_names: HashMap<String, String>,
+}
+
+impl<'s> GuestFinder<'s> {
+    fn new(root: Node, source: &'s [u8], allowed_modules: &'s [String]) -> Self {
+        let mut finder = Self {
+            source,
+            allowed_modules,
+            network_names: NETWORK_MODULES
+                .iter()
+                .map(|m| ((*m).to_string(), (*m).to_string()))
+                .collect(),
+        };
+        finder.collect_imports(root);
+        finder
+    }
+
+    fn is_network_module(name: &str) -> bool {
+        let top = name.split('.').next().unwrap_or(name);
+        NETWORK_MODULES.contains(&top)
+    }
+
+    fn is_allowed(&self, qualified: &str) -> bool {
+        self.allowed_modules
+            .iter()
+            .any(|prefix| qualified.starts_with(prefix.as_str()))
+    }
+
+    fn collect_imports(&mut self, root: Node) {
+        let mut cursor = root.walk();
+        for stmt in root.children(&mut cursor) {
+            match stmt.kind() {
+                "import_statement" => {
+                    let mut inner = stmt.walk();
+                    for name in stmt.children_by_field_name("name", &mut inner) {
+                        if name.kind() != "aliased_import" {
+                            continue;
+                        }
+                        let module = name.child_by_field_name("name");
+                        let alias = name.child_by_field_name("alias");
+                        if let (Some(m), Some(a)) = (module, alias) {
+                            let module = node_text(m, self.source);
+                            if Self::is_network_module(module) {
+                                self.network_names.insert(
+                                    node_text(a, self.source).to_string(),
+                                    module.to_string(),
+                                );
+                            }
+                        }
+                    }
+                }
+                "import_from_statement" => {
+                    let Some(module) = stmt.child_by_field_name("module_name") else {
+                        continue;
+                    };
+                    let module = node_text(module, self.source).to_string();
+                    let mut inner = stmt.walk();
+                    for name in stmt.children_by_field_name("name", &mut inner) {
+                        let (imported, local) = if name.kind() == "aliased_import" {
+                            (
+                                name.child_by_field_name("name"),
+                                name.child_by_field_name("alias"),
+                            )
+                        } else {
+                            (Some(name), Some(name))
+                        };
+                        let (Some(i), Some(l)) = (imported, local) else {
+                            continue;
+                        };
+                        let local = node_text(l, self.source).to_string();
+                        let qualified = format!("{module}.{}", node_text(i, self.source));
+                        if Self::is_network_module(&module) {
+                            self.network_names.insert(local, qualified);
+                        } else if self.network_names.contains_key(&local) {
+                            // e.g. `from tests.fakes import requests` shadows the real module
+                            self.network_names.insert(local, qualified);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// True if the body refers to the `tempfile` module.
+    fn mentions_tempfile(&self, node: Node) -> bool {
+        if node.kind() == "identifier" && node_text(node, self.source) == "tempfile" {
+            return true;
+        }
+        let mut cursor = node.walk();
+        let found = node
+            .children(&mut cursor)
+            .any(|c| self.mentions_tempfile(c));
+        found
+    }
+
+    /// True if the receiver of a method call is built from `Path(...)`.
+    fn is_path_object(&self, node: Node) -> bool {
+        match node.kind() {
+            "call" => node.child_by_field_name("function").is_some_and(|f| {
+                matches!(
+                    node_text(f, self.source),
+                    "Path" | "pathlib.Path" | "PurePath" | "pathlib.PurePath"
+                )
+            }),
+            "binary_operator" => node
+                .child_by_field_name("left")
+                .is_some_and(|l| self.is_path_object(l)),
+            "parenthesized_expression" => node
+                .named_child(0)
+                .is_some_and(|inner| self.is_path_object(inner)),
+            _ => false,
+        }
+    }
+
+    fn classify_call(&self, call: Node) -> Option<Guest> {
+        let func = call.child_by_field_name("function")?;
+        let callee = node_text(func, self.source);
+        match func.kind() {
+            "identifier" => {
+                if callee == "open" {
+                    return Some(Guest::File(callee.to_string()));
+                }
+                let qualified = self.network_names.get(callee)?;
+                (Self::is_network_module(qualified) && !self.is_allowed(qualified))
+                    .then(|| Guest::Network(callee.to_string()))
+            }
+            "attribute" => {
+                let object = func.child_by_field_name("object")?;
+                let method = node_text(func.child_by_field_name("attribute")?, self.source);
+                if PATH_IO_METHODS.contains(&method) && self.is_path_object(object) {
+                    return Some(Guest::File(format!("Path.{method}")));
+                }
+                if matches!(callee, "io.open" | "os.open" | "codecs.open") {
+                    return Some(Guest::File(callee.to_string()));
+                }
+                // Root identifier of a dotted chain: `urllib` in `urllib.request.urlopen`
+                let mut root = object;
+                while root.kind() == "attribute" {
+                    root = root.child_by_field_name("object")?;
+                }
+                if root.kind() != "identifier" {
+                    return None;
+                }
+                let root_name = node_text(root, self.source);
+                let qualified = self.network_names.get(root_name)?;
+                let full = format!("{qualified}{}", &callee[root_name.len()..]);
+                (Self::is_network_module(qualified) && !self.is_allowed(&full))
+                    .then(|| Guest::Network(callee.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    fn collect<'tree>(&self, node: Node<'tree>, guests: &mut Vec<(Node<'tree>, Guest)>) {
+        match node.kind() {
+            "call" => {
+                if let Some(guest) = self.classify_call(node) {
+                    guests.push((node, guest));
+                }
+            }
+            "string" => {
+                let mut cursor = node.walk();
+                let content: String = node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() == "string_content")
+                    .map(|c| node_text(c, self.source))
+                    .collect();
+                if is_absolute_path_literal(&content) {
+                    guests.push((node, Guest::AbsolutePath(content)));
+                }
+                return;
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect(child, guests);
+        }
+    }
+}
+
 /// Rule that detects session-scoped fixtures returning mutable state modified by tests.
 pub struct XdistSharedStateRule;
 
@@ -425,6 +1390,194 @@ impl Rule for DatetimeInAssertionRule {
     }
 }
 
+/// `TestCase` methods that compare for exact equality.
+const UNITTEST_EQUALITY_METHODS: &[&str] = &[
+    "assertEqual",
+    "assertNotEqual",
+    "assertEquals",
+    "assertNotEquals",
+    "failUnlessEqual",
+    "failIfEqual",
+];
+
+/// Whether an expression is a float literal or arithmetic built from one (`0.1 + 0.2`,
+/// `-1.5`, `x * 0.5`). Calls are not looked into: `pytest.approx(0.3)` is not a float.
+fn involves_float_literal(node: Node, source: &[u8], allow_zero: bool) -> bool {
+    match node.kind() {
+        "float" => !(allow_zero && is_zero_literal(node_text(node, source))),
+        "unary_operator" | "binary_operator" | "parenthesized_expression" => {
+            let mut cursor = node.walk();
+            let found = node
+                .named_children(&mut cursor)
+                .any(|c| involves_float_literal(c, source, allow_zero));
+            found
+        }
+        _ => false,
+    }
+}
+
+/// `0.0`, `0.`, `.0`, `0e0` and the like.
+fn is_zero_literal(text: &str) -> bool {
+    let mantissa = text
+        .split(['e', 'E'])
+        .next()
+        .unwrap_or(text)
+        .replace('_', "");
+    mantissa.chars().all(|c| c == '0' || c == '.')
+}
+
+/// Whether an expression is a `pytest.approx(...)` / `approx(...)` call.
+fn is_approx_call(node: Node, source: &[u8]) -> bool {
+    node.kind() == "call"
+        && node
+            .child_by_field_name("function")
+            .is_some_and(|f| matches!(node_text(f, source), "approx" | "pytest.approx"))
+}
+
+/// Whether one side of an equality is a float expression and neither side is `approx`.
+fn is_float_equality(left: Node, right: Node, source: &[u8], allow_zero: bool) -> bool {
+    !is_approx_call(left, source)
+        && !is_approx_call(right, source)
+        && (involves_float_literal(left, source, allow_zero)
+            || involves_float_literal(right, source, allow_zero))
+}
+
+/// Exact float comparisons in a test body: `assert a == 0.3` comparisons (`false`) and
+/// `self.assertEqual(a, 0.3)` calls (`true`).
+fn float_equalities<'t>(body: Node<'t>, source: &[u8], allow_zero: bool) -> Vec<(Node<'t>, bool)> {
+    let mut found = Vec::new();
+    let mut to_visit = vec![body];
+    while let Some(node) = to_visit.pop() {
+        match node.kind() {
+            "function_definition" | "class_definition" | "lambda" => continue,
+            "assert_statement" => {
+                if let Some(cmp) = node
+                    .named_child(0)
+                    .filter(|c| c.kind() == "comparison_operator")
+                {
+                    let mut cursor = cmp.walk();
+                    let children: Vec<Node> = cmp.children(&mut cursor).collect();
+                    let exact = children.windows(3).any(|w| {
+                        matches!(w[1].kind(), "==" | "!=")
+                            && is_float_equality(w[0], w[2], source, allow_zero)
+                    });
+                    if exact {
+                        found.push((cmp, false));
+                    }
+                }
+            }
+            "call" => {
+                let func = node.child_by_field_name("function");
+                let is_equality_method =
+                    func.filter(|f| f.kind() == "attribute").is_some_and(|f| {
+                        f.child_by_field_name("object")
+                            .is_some_and(|o| node_text(o, source) == "self")
+                            && f.child_by_field_name("attribute").is_some_and(|a| {
+                                UNITTEST_EQUALITY_METHODS.contains(&node_text(a, source))
+                            })
+                    });
+                let args: Vec<Node> = node
+                    .child_by_field_name("arguments")
+                    .map(|a| {
+                        let mut cursor = a.walk();
+                        a.named_children(&mut cursor)
+                            .filter(|c| c.kind() != "comment")
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if is_equality_method
+                    && args.len() >= 2
+                    && args[..2].iter().all(|a| a.kind() != "keyword_argument")
+                    && is_float_equality(args[0], args[1], source, allow_zero)
+                {
+                    found.push((node, true));
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        to_visit.extend(node.named_children(&mut cursor));
+    }
+    found.sort_by_key(|(n, _)| n.start_byte());
+    found
+}
+
+/// Rule that detects exact equality assertions on floating-point values
+/// (`assert total == 0.1 + 0.2`), which fail on rounding error.
+pub struct FloatEqualityRule;
+
+impl Rule for FloatEqualityRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FLK-013"
+    }
+    fn name(&self) -> &'static str {
+        "FloatEqualityRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Flakiness
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "allow_zero",
+            kind: OptionKind::Boolean,
+            description: "Permit exact comparison against 0.0 (default true)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let allow_zero = ctx.options.bool("allow_zero", true);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            for (node, unittest_call) in float_equalities(body, source, allow_zero) {
+                let suggestion = if unittest_call {
+                    "Use self.assertAlmostEqual(a, b) (or places=/delta=) instead"
+                } else {
+                    "Compare with pytest.approx(expected) or math.isclose()"
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' compares floats for exact equality: {}",
+                        test.name,
+                        node_text(node, source)
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(suggestion.to_string()),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, node);
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
 /// Rule that detects session-scoped fixtures performing file I/O.
 pub struct XdistFixtureIoRule;
 
@@ -971,4 +2124,14 @@ mod mutation_tests {
             "non-subprocess call should not be detected"
         );
     }
+
+    #[test]
+    fn test_absolute_path_literals() {
+        assert!(is_absolute_path_literal("/tmp/out.json"));
+        assert!(is_absolute_path_literal("/etc"));
+        assert!(is_absolute_path_literal("C:\\data\\in.csv"));
+        assert!(!is_absolute_path_literal("/api/users"));
+        assert!(!is_absolute_path_literal("/tmpfile"));
+        assert!(!is_absolute_path_literal("relative/path"));
+    }
 }
@@ -0,0 +1,325 @@
+//! Test isolation: module-level state that tests modify, so the outcome of one test depends
+//! on which tests ran before it.
+
+use std::collections::{BTreeMap, HashSet};
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, FixtureScope, ParsedModule, Severity, Violation};
+use crate::rules::ast::{function_at_line, node_text, set_span, ModuleSymbols};
+use crate::rules::{Rule, RuleContext};
+
+/// Methods that modify a list, dict, set or deque in place.
+const MUTATING_METHODS: &[&str] = &[
+    "add",
+    "append",
+    "appendleft",
+    "clear",
+    "discard",
+    "extend",
+    "extendleft",
+    "insert",
+    "pop",
+    "popitem",
+    "popleft",
+    "remove",
+    "reverse",
+    "rotate",
+    "setdefault",
+    "sort",
+    "update",
+];
+
+/// How one function uses module-level symbols: variables (`CACHE`) and class attributes
+/// (`Config.debug`).
+#[derive(Default)]
+struct Access<'t> {
+    /// Symbols modified, with the first statement or call doing it.
+    writes: BTreeMap<String, Node<'t>>,
+    /// Symbols rebound to a fresh value (`global CACHE; CACHE = []`, `Config.debug = False`).
+    resets: HashSet<String>,
+    /// Symbols read or written.
+    refs: HashSet<String>,
+}
+
+struct Scan<'a, 't> {
+    source: &'t [u8],
+    symbols: &'a ModuleSymbols,
+    /// Names local to the function: parameters and names it assigns without `global`.
+    locals: HashSet<&'t str>,
+    access: Access<'t>,
+}
+
+impl<'t> Scan<'_, 't> {
+    /// The module-level symbol an expression refers to: `CACHE`, `CACHE["k"]` and
+    /// `CACHE.attr` are `CACHE`; `Config.debug` is the class attribute `Config.debug`.
+    fn symbol(&self, expr: Node<'t>) -> Option<String> {
+        match expr.kind() {
+            "identifier" => {
+                let name = node_text(expr, self.source);
+                (self.symbols.variables.contains_key(name) && !self.locals.contains(name))
+                    .then(|| name.to_string())
+            }
+            "attribute" => {
+                let object = expr.child_by_field_name("object")?;
+                let class = node_text(object, self.source);
+                if object.kind() == "identifier"
+                    && self.symbols.classes.contains(class)
+                    && !self.locals.contains(class)
+                {
+                    let attr = expr.child_by_field_name("attribute")?;
+                    return Some(format!("{class}.{}", node_text(attr, self.source)));
+                }
+                self.symbol(object)
+            }
+            "subscript" => self.symbol(expr.child_by_field_name("value")?),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, target: Node<'t>, at: Node<'t>, reset: bool) {
+        let Some(symbol) = self.symbol(target) else {
+            return;
+        };
+        // Only rebinding the name itself replaces the old value
+        if reset && symbol == node_text(target, self.source) {
+            self.access.resets.insert(symbol.clone());
+        }
+        self.access.writes.entry(symbol).or_insert(at);
+    }
+
+    fn visit(&mut self, node: Node<'t>) {
+        match node.kind() {
+            // Nested scopes have their own locals
+            "function_definition" | "lambda" | "class_definition" => return,
+            "identifier" | "attribute" => {
+                if let Some(symbol) = self.symbol(node) {
+                    self.access.refs.insert(symbol);
+                }
+                if node.kind() == "identifier" {
+                    return;
+                }
+            }
+            "assignment" | "augmented_assignment" => {
+                if let Some(left) = node.child_by_field_name("left") {
+                    self.write(left, node, node.kind() == "assignment");
+                }
+            }
+            "delete_statement" => {
+                let mut cursor = node.walk();
+                for target in node.named_children(&mut cursor) {
+                    if target.kind() != "identifier" {
+                        self.write(target, node, false);
+                    }
+                }
+            }
+            "call" => {
+                let method = node
+                    .child_by_field_name("function")
+                    .filter(|f| f.kind() == "attribute");
+                if let Some(method) = method {
+                    let name = method
+                        .child_by_field_name("attribute")
+                        .map(|a| node_text(a, self.source));
+                    if let (Some(name), Some(object)) = (name, method.child_by_field_name("object"))
+                    {
+                        if MUTATING_METHODS.contains(&name) {
+                            self.write(object, node, false);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node<'t>> = if node.kind() == "attribute" {
+            node.child_by_field_name("object").into_iter().collect()
+        } else if node.kind() == "keyword_argument" {
+            node.child_by_field_name("value").into_iter().collect()
+        } else {
+            node.named_children(&mut cursor).collect()
+        };
+        for child in children {
+            self.visit(child);
+        }
+    }
+}
+
+/// Names a function binds locally: its parameters, and names it assigns or loops over
+/// without declaring them `global`.
+fn local_names<'t>(function: Node<'t>, source: &'t [u8]) -> HashSet<&'t str> {
+    let mut globals = HashSet::new();
+    let mut locals = HashSet::new();
+    if let Some(params) = function.child_by_field_name("parameters") {
+        let mut cursor = params.walk();
+        for param in params.named_children(&mut cursor) {
+            let name = match param.kind() {
+                "identifier" => Some(param),
+                _ => param
+                    .child_by_field_name("name")
+                    .or_else(|| param.named_child(0)),
+            };
+            if let Some(name) = name.filter(|n| n.kind() == "identifier") {
+                locals.insert(node_text(name, source));
+            }
+        }
+    }
+    let Some(body) = function.child_by_field_name("body") else {
+        return locals;
+    };
+    let mut to_visit = vec![body];
+    while let Some(node) = to_visit.pop() {
+        let target = match node.kind() {
+            "function_definition" | "lambda" | "class_definition" => continue,
+            "global_statement" => {
+                let mut cursor = node.walk();
+                for name in node.named_children(&mut cursor) {
+                    globals.insert(node_text(name, source));
+                }
+                None
+            }
+            "assignment" | "augmented_assignment" | "for_statement" => {
+                node.child_by_field_name("left")
+            }
+            "as_pattern" => node.child_by_field_name("alias"),
+            "named_expression" => node.child_by_field_name("name"),
+            _ => None,
+        };
+        let mut targets = target.into_iter().collect::<Vec<_>>();
+        while let Some(target) = targets.pop() {
+            match target.kind() {
+                "identifier" => {
+                    locals.insert(node_text(target, source));
+                }
+                "pattern_list" | "tuple_pattern" | "list_pattern" | "as_pattern_target" => {
+                    let mut cursor = target.walk();
+                    targets.extend(target.named_children(&mut cursor));
+                }
+                _ => {}
+            }
+        }
+        let mut cursor = node.walk();
+        to_visit.extend(node.named_children(&mut cursor));
+    }
+    locals.retain(|name| !globals.contains(name));
+    locals
+}
+
+fn scan<'t>(function: Node<'t>, source: &'t [u8], symbols: &ModuleSymbols) -> Access<'t> {
+    let mut scan = Scan {
+        source,
+        symbols,
+        locals: local_names(function, source),
+        access: Access::default(),
+    };
+    if let Some(body) = function.child_by_field_name("body") {
+        scan.visit(body);
+    }
+    scan.access
+}
+
+/// Rule that detects tests modifying module-level variables or class attributes, which
+/// makes them depend on test order. State reset by every test using it, or by a
+/// function-scoped fixture, is not reported.
+pub struct SharedModuleStateRule;
+
+impl Rule for SharedModuleStateRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FLK-014"
+    }
+    fn name(&self) -> &'static str {
+        "SharedModuleStateRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Flakiness
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let symbols = ModuleSymbols::new(root, source);
+        if symbols.variables.is_empty() && symbols.classes.is_empty() {
+            return vec![];
+        }
+
+        // Function-scoped fixtures that set up or tear down a symbol manage it
+        let managed: HashSet<String> = module
+            .fixtures
+            .iter()
+            .filter(|f| f.scope == FixtureScope::Function)
+            .filter_map(|f| function_at_line(root, f.line))
+            .flat_map(|f| scan(f, source, &symbols).writes.into_keys())
+            .collect();
+        let tests: Vec<_> = module
+            .test_functions
+            .iter()
+            .filter_map(|test| {
+                let function = function_at_line(root, test.line)?;
+                Some((test, scan(function, source, &symbols)))
+            })
+            .collect();
+
+        let mut violations = Vec::new();
+        for (test, access) in &tests {
+            for (symbol, &at) in &access.writes {
+                if managed.contains(symbol) {
+                    continue;
+                }
+                let users: Vec<_> = tests
+                    .iter()
+                    .filter(|(_, other)| other.refs.contains(symbol))
+                    .collect();
+                if users.iter().all(|(_, other)| other.resets.contains(symbol)) {
+                    continue;
+                }
+                let others: Vec<&str> = users
+                    .iter()
+                    .filter(|(other, _)| other.name != test.name)
+                    .map(|(other, _)| other.name.as_str())
+                    .collect();
+                let shared = if others.is_empty() {
+                    "; the change outlives the test".to_string()
+                } else {
+                    format!(", also used by {}", others.join(", "))
+                };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' modifies module-level '{symbol}'{shared}",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Build the state in a function-scoped fixture, or reset it in every test that uses it"
+                            .to_string(),
+                    ),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, at);
+                v.metadata
+                    .insert("symbol".to_string(), symbol.as_str().into());
+                v.metadata.insert("shared_with".to_string(), others.into());
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
@@ -2,10 +2,18 @@
 
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
 
 use crate::engine::make_violation;
-use crate::models::{Category, ParsedModule, Severity, Violation};
-use crate::rules::{Rule, RuleContext};
+use crate::models::{Category, Fix, ParsedModule, Severity, Tag, TestFunction, Violation};
+use crate::project::ProjectContext;
+use crate::rules::ast::{
+    add_related, delete_statement, function_at_line, import_bindings, node_text, resolve_callee,
+    set_span,
+};
+use crate::rules::exceptions::{contains_exception_assertion, PytestAliases};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
+use tree_sitter::Node;
 
 fn stable_hash(content: &str) -> u64 {
     use std::collections::hash_map::DefaultHasher;
@@ -14,9 +22,82 @@ fn stable_hash(content: &str) -> u64 {
     hasher.finish()
 }
 
-/// Rule that detects conditional logic inside test functions.
+/// Control-flow constructs `TestLogicRule` can flag, with the names used by its `allowed` option.
+const LOGIC_CONSTRUCTS: &[(&str, &str, &str)] = &[
+    ("if_statement", "if", "`if` statement"),
+    ("for_statement", "for", "`for` loop"),
+    ("while_statement", "while", "`while` loop"),
+    ("try_statement", "try", "`try` block"),
+    ("match_statement", "match", "`match` statement"),
+];
+
+/// Rule that detects conditional logic (`if`, loops, `try`, `match`, ternaries in
+/// assertions) directly inside test functions.
 pub struct TestLogicRule;
 
+impl TestLogicRule {
+    /// True for a loop whose body is a single `assert` statement (and which has no `else`).
+    fn is_single_assert_loop(node: Node) -> bool {
+        if node.child_by_field_name("alternative").is_some() {
+            return false;
+        }
+        let Some(body) = node.child_by_field_name("body") else {
+            return false;
+        };
+        let mut cursor = body.walk();
+        let stmts: Vec<Node> = body
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() != "comment")
+            .collect();
+        matches!(stmts.as_slice(), [only] if only.kind() == "assert_statement")
+    }
+
+    /// The keyword token that introduces a construct (`for` in `async for`, `if` in a ternary).
+    fn keyword<'tree>(node: Node<'tree>, keyword: &str) -> Node<'tree> {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find(|c| c.kind() == keyword);
+        found.unwrap_or(node)
+    }
+
+    fn collect<'tree>(
+        node: Node<'tree>,
+        allowed: &[String],
+        allow_single_assert_loops: bool,
+        in_assert: bool,
+        found: &mut Vec<(Node<'tree>, &'static str)>,
+    ) {
+        match node.kind() {
+            // Helpers, fixtures and classes defined inside the test are not the test body
+            "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                return;
+            }
+            "conditional_expression" if in_assert && !allowed.iter().any(|a| a == "ternary") => {
+                found.push((
+                    Self::keyword(node, "if"),
+                    "conditional expression in an assertion",
+                ));
+            }
+            kind => {
+                if let Some((_, name, label)) = LOGIC_CONSTRUCTS.iter().find(|(k, ..)| *k == kind) {
+                    let is_loop = matches!(*name, "for" | "while");
+                    let exempt = allowed.iter().any(|a| a == name)
+                        || (is_loop
+                            && allow_single_assert_loops
+                            && Self::is_single_assert_loop(node));
+                    if !exempt {
+                        found.push((Self::keyword(node, name), label));
+                    }
+                }
+            }
+        }
+        let in_assert = in_assert || node.kind() == "assert_statement";
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect(child, allowed, allow_single_assert_loops, in_assert, found);
+        }
+    }
+}
+
 impl Rule for TestLogicRule {
     fn id(&self) -> &'static str {
         "PYTEST-MNT-001"
@@ -30,29 +111,59 @@ impl Rule for TestLogicRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[
+            RuleOption {
+                name: "allowed",
+                kind: OptionKind::StringList,
+                description: "Constructs to permit: any of \"if\", \"for\", \"while\", \"try\", \"match\", \"ternary\"",
+            },
+            RuleOption {
+                name: "allow_single_assert_loops",
+                kind: OptionKind::Boolean,
+                description: "Permit loops whose body is a single assert statement (default true)",
+            },
+        ]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let allowed = ctx.options.string_list("allowed");
+        let allow_single_assert_loops = ctx.options.bool("allow_single_assert_loops", true);
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.has_conditional_logic {
-                violations.push(make_violation(
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let mut found = Vec::new();
+            Self::collect(body, &allowed, allow_single_assert_loops, false, &mut found);
+            for (keyword, label) in found {
+                let mut v = make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
-                    format!(
-                        "Test '{}' contains conditional logic (if statements)",
-                        test.name
-                    ),
+                    format!("Test '{}' contains conditional logic ({label})", test.name),
                     module.file_path.clone(),
                     test.line,
                     Some("Split into separate tests or use parametrize".to_string()),
                     Some(test.name.clone()),
-                ));
+                );
+                set_span(&mut v, keyword);
+                violations.push(v);
             }
         }
         violations
@@ -75,17 +186,20 @@ impl Rule for MagicAssertRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn fixable(&self) -> bool {
+        true
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
         let mut violations = Vec::new();
         for test in &module.test_functions {
             for assertion in &test.assertions {
                 if assertion.is_magic {
-                    violations.push(make_violation(
+                    let mut v = make_violation(
                         self.id(),
                         self.name(),
                         self.severity(),
@@ -98,6 +212,74 @@ impl Rule for MagicAssertRule {
                         assertion.line,
                         Some("Replace with a meaningful comparison".to_string()),
                         Some(test.name.clone()),
+                    );
+                    if matches!(assertion.expression_text.as_str(), "True" | "1") {
+                        v.fix = ctx.tree.and_then(|t| {
+                            remove_always_true_assert(t.root_node(), assertion.line, &module.source)
+                        });
+                    }
+                    violations.push(v);
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// True when every assert in the test is tautological and nothing else asserts, so the
+/// test verifies nothing.
+fn only_tautological_assertions(test: &crate::models::TestFunction) -> bool {
+    !test.assertions.is_empty()
+        && test.assertions.iter().all(|a| a.is_tautological)
+        && test.assertion_site_count == test.assertions.len()
+        && !test.has_mock_verifications
+}
+
+/// Rule that detects assertions that cannot fail: comparisons between literal constants
+/// and truthy literals. `assert True` and `assert 1` are left to `MagicAssertRule`.
+pub struct TautologicalAssertionRule;
+
+impl Rule for TautologicalAssertionRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-021"
+    }
+    fn name(&self) -> &'static str {
+        "TautologicalAssertionRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn tags(&self) -> &'static [Tag] {
+        &[Tag::Unnecessary]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        _ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            for assertion in &test.assertions {
+                if assertion.is_tautological && !assertion.is_magic {
+                    violations.push(make_violation(
+                        self.id(),
+                        self.name(),
+                        self.severity(),
+                        self.category(),
+                        format!(
+                            "Tautological assertion in test '{}': '{}' only involves literal constants and cannot fail",
+                            test.name, assertion.expression_text
+                        ),
+                        module.file_path.clone(),
+                        assertion.line,
+                        Some(
+                            "Assert on a value produced by the code under test".to_string(),
+                        ),
+                        Some(test.name.clone()),
                     ));
                 }
             }
@@ -106,6 +288,41 @@ impl Rule for MagicAssertRule {
     }
 }
 
+/// The `assert` statement starting on `line` (1-based), if any.
+fn assert_at_line(node: Node, line: usize) -> Option<Node> {
+    let row = line.checked_sub(1)?;
+    if node.start_position().row > row || node.end_position().row < row {
+        return None;
+    }
+    if node.kind() == "assert_statement" && node.start_position().row == row {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .find_map(|child| assert_at_line(child, line));
+    found
+}
+
+/// Fix that removes an assert that can never fail (`assert True`, `assert 1`); see
+/// [`delete_statement`].
+fn remove_always_true_assert(root: Node, line: usize, source: &str) -> Option<Fix> {
+    let stmt = assert_at_line(root, line)?;
+    let expr = stmt.named_child(0)?;
+    let always_true =
+        expr.kind() == "true" || (expr.kind() == "integer" && &source[expr.byte_range()] == "1");
+    if !always_true {
+        return None;
+    }
+    let block = stmt.parent()?;
+    let mut cursor = block.walk();
+    let siblings = block
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() != "comment")
+        .count();
+    delete_statement(stmt, source, siblings == 1)
+}
+
 /// Rule that detects suboptimal assertion patterns.
 pub struct SuboptimalAssertRule;
 
@@ -169,6 +386,89 @@ impl Rule for NoAssertionRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let unasserted: Vec<_> = module
+            .test_functions
+            .iter()
+            .filter(|t| !t.has_assertions || only_tautological_assertions(t))
+            .collect();
+        if unasserted.is_empty() {
+            return vec![];
+        }
+        // pytest.raises / assertRaises and friends are assertions too; detect them on the
+        // syntax tree so aliased imports are resolved
+        let root = ctx.tree.map(tree_sitter::Tree::root_node);
+        let source = module.source.as_bytes();
+        let aliases = root
+            .map(|r| PytestAliases::from_root(r, source))
+            .unwrap_or_default();
+
+        let mut violations = Vec::new();
+        for test in unasserted {
+            let expects_exception = root
+                .and_then(|r| function_at_line(r, test.line))
+                .is_some_and(|f| contains_exception_assertion(f, source, &aliases));
+            if !expects_exception {
+                let message = if test.has_assertions {
+                    format!(
+                        "Test '{}' has no assertions that can fail (all are tautological)",
+                        test.name
+                    )
+                } else {
+                    format!("Test '{}' has no assertions", test.name)
+                };
+                violations.push(make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    message,
+                    module.file_path.clone(),
+                    test.line,
+                    Some("Add assertions to verify expected behavior".to_string()),
+                    Some(test.name.clone()),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Whether `test` verifies mock calls and checks no state, the mock-only check of
+/// [`MockOnlyVerifyRule`].
+pub(crate) fn only_verifies_mocks(test: &TestFunction) -> bool {
+    test.has_mock_verifications && !test.has_state_assertions
+}
+
+/// Rule that detects tests that only verify mock calls without real assertions, tests
+/// mocking standard-library modules, and weak assertions that only check types, existence
+/// or key presence.
+pub struct MockOnlyVerifyRule;
+
+impl Rule for MockOnlyVerifyRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-005"
+    }
+    fn name(&self) -> &'static str {
+        "MockOnlyVerifyRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
     fn check(
         &self,
         module: &ParsedModule,
@@ -177,36 +477,1058 @@ impl Rule for NoAssertionRule {
     ) -> Vec<Violation> {
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if !test.has_assertions {
+            if only_verifies_mocks(test) {
                 violations.push(make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
-                    format!("Test '{}' has no assertions", test.name),
+                    format!(
+                        "Test '{}' only verifies mocks without checking state",
+                        test.name
+                    ),
                     module.file_path.clone(),
                     test.line,
-                    Some("Add assertions to verify expected behavior".to_string()),
+                    Some("Add state assertions to verify actual outcomes".to_string()),
+                    Some(test.name.clone()),
+                ));
+            }
+            if test.mocks_stdlib_module {
+                violations.push(make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' mocks stdlib module(s): {} — use dependency injection or test doubles instead",
+                        test.name,
+                        test.mocked_stdlib_targets.join(", ")
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some("Refactor to inject dependencies instead of patching stdlib internals".to_string()),
+                    Some(test.name.clone()),
+                ));
+            }
+            if test.has_weak_assertions && !test.weak_assertion_details.is_empty() {
+                violations.push(make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' uses weak assertion patterns: {}",
+                        test.name,
+                        test.weak_assertion_details.join(", ")
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Use value-level assertions instead of type/existence/key-presence checks"
+                            .to_string(),
+                    ),
+                    Some(test.name.clone()),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Rule that detects assertion roulette: too many assertions without messages.
+pub struct AssertionRouletteRule;
+
+impl Rule for AssertionRouletteRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-006"
+    }
+    fn name(&self) -> &'static str {
+        "AssertionRouletteRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[
+            RuleOption {
+                name: "max",
+                kind: OptionKind::Integer,
+                description: "Assertions allowed before messages are required (default 3)",
+            },
+            RuleOption {
+                name: "min_message_ratio",
+                kind: OptionKind::Float,
+                description: "Fraction of assertions that must carry a message (default 0.5)",
+            },
+        ]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        // A single assertion is never roulette, whatever the configured limit
+        let max = ctx.options.usize("max", 3).max(1);
+        let min_ratio = ctx.options.f64("min_message_ratio", 0.5);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let total = test.assertions.len();
+            if total <= max || test.is_parametrized {
+                continue;
+            }
+            let bare: Vec<usize> = test
+                .assertions
+                .iter()
+                .filter(|a| !a.has_message)
+                .map(|a| a.line)
+                .collect();
+            #[allow(clippy::cast_precision_loss)]
+            let ratio = (total - bare.len()) as f64 / total as f64;
+            if ratio >= min_ratio {
+                continue;
+            }
+            let lines = bare
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            violations.push(make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' has {} assertions, {} without messages (assertion roulette) — lines {}",
+                    test.name,
+                    total,
+                    bare.len(),
+                    lines
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some("Add a message to each assertion or split into focused tests".to_string()),
+                Some(test.name.clone()),
+            ));
+        }
+        violations
+    }
+}
+
+/// Rule that flags tests with more assertion sites than the configured limit.
+pub struct TooManyAssertionsRule;
+
+impl Rule for TooManyAssertionsRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-018"
+    }
+    fn name(&self) -> &'static str {
+        "TooManyAssertionsRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max",
+            kind: OptionKind::Integer,
+            description: "Maximum assertion sites per test (default 5)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let max = ctx.options.usize("max", 5);
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            // Sites are counted statically, so loops and parametrize never multiply them
+            if test.assertion_site_count > max {
+                violations.push(make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' has {} assertions (limit {})",
+                        test.name, test.assertion_site_count, max
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some("Split into smaller, focused tests".to_string()),
+                    Some(test.name.clone()),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Statement counts of a test body, as measured by [`ObscureTestRule`].
+#[derive(Default)]
+struct BodyShape {
+    /// Statements in the body; the contents of `with` blocks count, the `with` itself does
+    /// not.
+    statements: usize,
+    /// Index of the first statement that asserts.
+    first_assertion: Option<usize>,
+    /// Whether that assertion is a `pytest.raises()`-style block, whose contents are the
+    /// action.
+    wraps_action: bool,
+    /// Index of the first statement that is a bare call (`client.post(...)`).
+    first_call: Option<usize>,
+}
+
+impl BodyShape {
+    fn measure(body: Node, source: &[u8], aliases: &PytestAliases) -> Self {
+        let mut shape = Self::default();
+        shape.walk(body, source, aliases, true);
+        shape
+    }
+
+    /// Statements before the action: those before the first assertion, less the statement
+    /// just before it, which is taken to be the action. Without assertions, the action is
+    /// the first bare call, or else the last statement.
+    fn arrange(&self) -> usize {
+        match (self.first_assertion, self.first_call) {
+            (Some(first), _) if self.wraps_action => first,
+            (Some(first), _) => first.saturating_sub(1),
+            (None, Some(call)) => call,
+            (None, None) => self.statements.saturating_sub(1),
+        }
+    }
+
+    fn walk(&mut self, block: Node, source: &[u8], aliases: &PytestAliases, top: bool) {
+        let mut cursor = block.walk();
+        for (idx, stmt) in block.named_children(&mut cursor).enumerate() {
+            match stmt.kind() {
+                "comment" => {}
+                "expression_statement"
+                    if top
+                        && idx == 0
+                        && stmt.named_child(0).is_some_and(|c| c.kind() == "string") => {}
+                "with_statement" => {
+                    let raises = stmt
+                        .named_children(&mut stmt.walk())
+                        .find(|c| c.kind() == "with_clause")
+                        .is_some_and(|c| contains_exception_assertion(c, source, aliases));
+                    if raises && self.first_assertion.is_none() {
+                        self.first_assertion = Some(self.statements);
+                        self.wraps_action = true;
+                    }
+                    if let Some(body) = stmt.child_by_field_name("body") {
+                        self.walk(body, source, aliases, false);
+                    }
+                }
+                "function_definition" | "decorated_definition" | "class_definition" => {
+                    self.statements += 1;
+                }
+                // `case` clauses of a `match`
+                kind if kind.ends_with("_clause") => self.walk_blocks(stmt, source, aliases),
+                _ => {
+                    let compound = matches!(
+                        stmt.kind(),
+                        "if_statement"
+                            | "for_statement"
+                            | "while_statement"
+                            | "try_statement"
+                            | "match_statement"
+                    );
+                    if !compound
+                        && self.first_assertion.is_none()
+                        && (first_assertion(stmt, source).is_some()
+                            || is_mock_assertion(stmt, source))
+                    {
+                        self.first_assertion = Some(self.statements);
+                    }
+                    let bare_call = stmt.kind() == "expression_statement"
+                        && stmt.named_child(0).is_some_and(|c| c.kind() == "call");
+                    if bare_call && self.first_call.is_none() {
+                        self.first_call = Some(self.statements);
+                    }
+                    self.statements += 1;
+                    if compound {
+                        self.walk_blocks(stmt, source, aliases);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Walk the blocks of a compound statement or clause, including those of its `elif`,
+    /// `else`, `except`, `finally` and `case` clauses.
+    fn walk_blocks(&mut self, node: Node, source: &[u8], aliases: &PytestAliases) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "block" {
+                self.walk(child, source, aliases, false);
+            } else if child.kind().ends_with("_clause") {
+                self.walk_blocks(child, source, aliases);
+            }
+        }
+    }
+}
+
+/// Whether a statement is a mock assertion call (`mock.assert_called_once_with(...)`).
+fn is_mock_assertion(stmt: Node, source: &[u8]) -> bool {
+    stmt.named_child(0)
+        .filter(|c| c.kind() == "call")
+        .and_then(|c| c.child_by_field_name("function"))
+        .filter(|f| f.kind() == "attribute")
+        .and_then(|f| f.child_by_field_name("attribute"))
+        .is_some_and(|a| node_text(a, source).starts_with("assert_"))
+}
+
+/// Rule that flags obscure tests: bodies with too many statements, or a long arrange
+/// section before the first action or assertion.
+pub struct ObscureTestRule;
+
+impl Rule for ObscureTestRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-033"
+    }
+    fn name(&self) -> &'static str {
+        "ObscureTestRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[
+            RuleOption {
+                name: "max_statements",
+                kind: OptionKind::Integer,
+                description: "Maximum statements in a test body (default 25)",
+            },
+            RuleOption {
+                name: "max_arrange",
+                kind: OptionKind::Integer,
+                description: "Maximum statements before the first action or assertion \
+                              (default 15)",
+            },
+        ]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let aliases = PytestAliases::from_root(root, source);
+        let max_statements = ctx.options.usize("max_statements", 25);
+        let max_arrange = ctx.options.usize("max_arrange", 15);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            // Decorators, and so parametrize tables, are outside the body
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let shape = BodyShape::measure(body, source, &aliases);
+            let arrange = shape.arrange();
+            let long_arrange = arrange > max_arrange;
+            if shape.statements <= max_statements && !long_arrange {
+                continue;
+            }
+            let suggestion = if long_arrange {
+                "Extract the setup into a fixture or a builder function"
+            } else {
+                "Split the test into focused tests, or move shared setup into fixtures"
+            };
+            let mut v = make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' has {} statements (limit {max_statements}), {arrange} of them \
+                     before the first action (limit {max_arrange})",
+                    test.name, shape.statements
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some(suggestion.to_string()),
+                Some(test.name.clone()),
+            );
+            v.metadata
+                .insert("statements".to_string(), shape.statements.into());
+            v.metadata
+                .insert("arrange_statements".to_string(), arrange.into());
+            violations.push(v);
+        }
+        violations
+    }
+}
+
+/// How a snapshot is taken in `assert value == snapshot...`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnapshotKind {
+    /// The `snapshot` fixture of syrupy compared without `name=`: snapshots are told apart
+    /// only by their index in the test.
+    Unnamed,
+    /// A named fixture snapshot: `snapshot(name=...)`, or pytest-snapshot's
+    /// `snapshot.assert_match(value, "name")`.
+    Named,
+    /// An inline-snapshot `snapshot(...)` kept in the source.
+    Inline,
+}
+
+/// Snapshot comparisons and other assertions of a test body.
+#[derive(Default)]
+struct SnapshotUse<'a> {
+    snapshots: Vec<(SnapshotKind, Node<'a>)>,
+    others: usize,
+}
+
+/// Rule that flags snapshot tests whose snapshots carry the whole check: tests with no
+/// assertion besides the snapshot, several unnamed snapshots in one test, and inline
+/// snapshots too long to review. Covers syrupy, pytest-snapshot and inline-snapshot.
+pub struct SnapshotOveruseRule;
+
+impl SnapshotOveruseRule {
+    /// Walk a test body (not nested functions) for snapshot comparisons and other
+    /// assertions. `fixture` is whether the test takes the `snapshot` fixture, `inline`
+    /// whether `snapshot` is imported from inline-snapshot.
+    fn scan<'a>(
+        body: Node<'a>,
+        source: &[u8],
+        aliases: &PytestAliases,
+        fixture: bool,
+        inline: bool,
+    ) -> SnapshotUse<'a> {
+        let mut found = SnapshotUse::default();
+        let mut to_visit = vec![body];
+        while let Some(node) = to_visit.pop() {
+            match node.kind() {
+                "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                    continue;
+                }
+                "assert_statement" => {
+                    let snapshot = node
+                        .named_child(0)
+                        .filter(|c| c.kind() == "comparison_operator")
+                        .and_then(|c| Self::compared_snapshot(c, source, fixture, inline));
+                    match snapshot {
+                        Some(snapshot) => found.snapshots.push(snapshot),
+                        None => found.others += 1,
+                    }
+                    continue;
+                }
+                "with_statement" => {
+                    let raises = node
+                        .named_children(&mut node.walk())
+                        .find(|c| c.kind() == "with_clause")
+                        .is_some_and(|c| contains_exception_assertion(c, source, aliases));
+                    if raises {
+                        found.others += 1;
+                    }
+                }
+                "call" => {
+                    let func = node.child_by_field_name("function");
+                    let text = func.map_or("", |f| node_text(f, source));
+                    if fixture
+                        && matches!(text, "snapshot.assert_match" | "snapshot.assert_match_dir")
+                    {
+                        found.snapshots.push((SnapshotKind::Named, node));
+                    } else {
+                        let name = text.rsplit('.').next().unwrap_or(text);
+                        if name.starts_with("assert") || name == "fail" {
+                            found.others += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        found.snapshots.sort_by_key(|(_, n)| n.start_byte());
+        found
+    }
+
+    /// The snapshot side of `value == snapshot` / `snapshot(...) == value`, if any.
+    fn compared_snapshot<'a>(
+        comparison: Node<'a>,
+        source: &[u8],
+        fixture: bool,
+        inline: bool,
+    ) -> Option<(SnapshotKind, Node<'a>)> {
+        let mut cursor = comparison.walk();
+        let operands: Vec<Node> = comparison.named_children(&mut cursor).collect();
+        if operands.len() != 2 || node_text(comparison, source).contains("!=") {
+            return None;
+        }
+        operands.into_iter().find_map(|operand| {
+            let callee = (operand.kind() == "call")
+                .then(|| operand.child_by_field_name("function"))
+                .flatten();
+            match (operand.kind(), callee.map(|f| node_text(f, source))) {
+                ("call", Some("snapshot")) if inline => Some((SnapshotKind::Inline, operand)),
+                ("call", Some("snapshot")) if fixture => {
+                    let named = operand
+                        .child_by_field_name("arguments")
+                        .is_some_and(|args| {
+                            args.named_children(&mut args.walk()).any(|a| {
+                                a.kind() == "keyword_argument"
+                                    && a.child_by_field_name("name")
+                                        .is_some_and(|n| node_text(n, source) == "name")
+                            })
+                        });
+                    Some((
+                        if named {
+                            SnapshotKind::Named
+                        } else {
+                            SnapshotKind::Unnamed
+                        },
+                        operand,
+                    ))
+                }
+                ("identifier", _)
+                    if fixture && !inline && node_text(operand, source) == "snapshot" =>
+                {
+                    Some((SnapshotKind::Unnamed, operand))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Lines spanned by the value of an inline snapshot, `snapshot(<value>)`.
+    fn inline_lines(call: Node) -> usize {
+        call.child_by_field_name("arguments")
+            .and_then(|args| args.named_child(0))
+            .map_or(0, |value| {
+                value.end_position().row - value.start_position().row + 1
+            })
+    }
+}
+
+impl Rule for SnapshotOveruseRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-034"
+    }
+    fn name(&self) -> &'static str {
+        "SnapshotOveruseRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_inline_lines",
+            kind: OptionKind::Integer,
+            description: "Maximum lines of an inline snapshot value (default 30)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if !module.source.contains("snapshot") {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let aliases = PytestAliases::from_root(root, source);
+        let inline = import_bindings(root, source)
+            .get("snapshot")
+            .is_some_and(|target| target == "inline_snapshot.snapshot");
+        let max_inline_lines = ctx.options.usize("max_inline_lines", 30);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let fixture = test.fixture_deps.iter().any(|d| d == "snapshot");
+            if !fixture && !inline {
+                continue;
+            }
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let found = Self::scan(body, source, &aliases, fixture, inline);
+            if found.snapshots.is_empty() {
+                continue;
+            }
+            if found.others == 0 {
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    Severity::Info,
+                    self.category(),
+                    format!(
+                        "Test '{}' checks nothing but snapshots: it passes whenever the output \
+                         matches what it was last time",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Assert the behavior that matters explicitly, and keep the snapshot for \
+                         the rest"
+                            .to_string(),
+                    ),
+                    Some(test.name.clone()),
+                );
+                v.metadata
+                    .insert("snapshots".to_string(), found.snapshots.len().into());
+                violations.push(v);
+            }
+            let unnamed: Vec<Node> = found
+                .snapshots
+                .iter()
+                .filter(|(kind, _)| *kind == SnapshotKind::Unnamed)
+                .map(|(_, node)| *node)
+                .collect();
+            if let [_, second, ..] = unnamed[..] {
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' takes {} unnamed snapshots; a failure does not say which \
+                         one changed",
+                        test.name,
+                        unnamed.len()
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some("Name each snapshot, e.g. `snapshot(name=\"after_login\")`".to_string()),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, second);
+                v.metadata
+                    .insert("unnamed_snapshots".to_string(), unnamed.len().into());
+                violations.push(v);
+            }
+            for &(kind, call) in &found.snapshots {
+                let lines = Self::inline_lines(call);
+                if kind != SnapshotKind::Inline || lines <= max_inline_lines {
+                    continue;
+                }
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Inline snapshot in test '{}' is {lines} lines long (limit \
+                         {max_inline_lines}); nobody reviews a change to it",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Snapshot the relevant part of the output, or store it with \
+                         `external()`"
+                            .to_string(),
+                    ),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, call);
+                v.metadata.insert("lines".to_string(), lines.into());
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
+/// Rule that detects bare except or overly broad exception handling.
+pub struct RawExceptionHandlingRule;
+
+impl Rule for RawExceptionHandlingRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-007"
+    }
+    fn name(&self) -> &'static str {
+        "RawExceptionHandlingRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        _ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            if test.has_try_except {
+                violations.push(make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' uses try/except instead of pytest.raises",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some("Use pytest.raises() for exception testing".to_string()),
+                    Some(test.name.clone()),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Whether `node` is an assertion: an `assert` statement or a `self.assert*()` call.
+fn is_assertion_node(node: Node, source: &[u8]) -> bool {
+    match node.kind() {
+        "assert_statement" => true,
+        "call" => node
+            .child_by_field_name("function")
+            .filter(|f| f.kind() == "attribute")
+            .is_some_and(|f| {
+                f.child_by_field_name("object")
+                    .is_some_and(|o| node_text(o, source) == "self")
+                    && f.child_by_field_name("attribute")
+                        .is_some_and(|a| node_text(a, source).starts_with("assert"))
+            }),
+        _ => false,
+    }
+}
+
+/// First assertion under `node`, not looking into nested functions and classes.
+fn first_assertion<'t>(node: Node<'t>, source: &[u8]) -> Option<Node<'t>> {
+    if is_assertion_node(node, source) {
+        return Some(node);
+    }
+    if matches!(
+        node.kind(),
+        "function_definition" | "decorated_definition" | "class_definition" | "lambda"
+    ) {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .named_children(&mut cursor)
+        .find_map(|c| first_assertion(c, source));
+    found
+}
+
+/// Calls that wrap an iterable without changing whether it is empty.
+const ITERABLE_WRAPPERS: &[&str] = &[
+    "enumerate",
+    "sorted",
+    "reversed",
+    "list",
+    "tuple",
+    "set",
+    "zip",
+    "iter",
+];
+
+/// Whether a loop's iterable is known to be non-empty: a non-empty literal or
+/// `range(<positive int>)`.
+fn is_non_empty_literal(iterable: Node, source: &[u8]) -> bool {
+    match iterable.kind() {
+        "list" | "tuple" | "set" | "dictionary" => iterable.named_child_count() > 0,
+        "string" => node_text(iterable, source).len() > 2,
+        "call" => {
+            let func = iterable
+                .child_by_field_name("function")
+                .map_or("", |f| node_text(f, source));
+            let mut args = iterable
+                .child_by_field_name("arguments")
+                .map(|a| {
+                    let mut cursor = a.walk();
+                    a.named_children(&mut cursor).collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            match func {
+                "range" if args.len() == 1 => node_text(args[0], source)
+                    .replace('_', "")
+                    .parse::<u64>()
+                    .is_ok_and(|n| n > 0),
+                f if ITERABLE_WRAPPERS.contains(&f) && !args.is_empty() => {
+                    args.drain(..).all(|a| is_non_empty_literal(a, source))
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// The collections a loop iterates over: `items` for `enumerate(items)` or `items.values()`.
+fn loop_subjects<'a>(iterable: Node, source: &'a [u8], out: &mut Vec<&'a str>) {
+    if iterable.kind() == "call" {
+        if let Some(func) = iterable.child_by_field_name("function") {
+            let name = node_text(func, source);
+            if ITERABLE_WRAPPERS.contains(&name) {
+                if let Some(args) = iterable.child_by_field_name("arguments") {
+                    let mut cursor = args.walk();
+                    for arg in args.named_children(&mut cursor) {
+                        loop_subjects(arg, source, out);
+                    }
+                }
+                return;
+            }
+            if func.kind() == "attribute"
+                && func
+                    .child_by_field_name("attribute")
+                    .is_some_and(|a| matches!(node_text(a, source), "items" | "keys" | "values"))
+            {
+                if let Some(object) = func.child_by_field_name("object") {
+                    loop_subjects(object, source, out);
+                    return;
+                }
+            }
+        }
+    }
+    out.push(node_text(iterable, source));
+}
+
+/// Whether `text` mentions `subject` as a whole expression (`len(items)`, `assert items`),
+/// not as part of a longer name.
+fn mentions(text: &str, subject: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(subject).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + subject.len()..].chars().next();
+        !before.is_some_and(|c| is_ident(c) || c == '.') && !after.is_some_and(is_ident)
+    })
+}
+
+/// Rule that detects assertions inside `for` loops over collections that may be empty, in
+/// which case the test passes without checking anything.
+pub struct AssertInLoopRule;
+
+impl AssertInLoopRule {
+    /// Loops in `block` that hold an assertion and whose collection is neither a non-empty
+    /// literal nor asserted on by an earlier statement of the same block. Loops nested in
+    /// another loop are not looked at.
+    fn collect<'t>(block: Node<'t>, source: &[u8], found: &mut Vec<Node<'t>>) {
+        let mut cursor = block.walk();
+        let stmts: Vec<Node> = block.named_children(&mut cursor).collect();
+        for (i, stmt) in stmts.iter().enumerate() {
+            match stmt.kind() {
+                "function_definition" | "decorated_definition" | "class_definition" => {}
+                "for_statement" => {
+                    let Some(body) = stmt.child_by_field_name("body") else {
+                        continue;
+                    };
+                    if first_assertion(body, source).is_none() {
+                        continue;
+                    }
+                    let Some(iterable) = stmt.child_by_field_name("right") else {
+                        continue;
+                    };
+                    if is_non_empty_literal(iterable, source) {
+                        continue;
+                    }
+                    let mut subjects = Vec::new();
+                    loop_subjects(iterable, source, &mut subjects);
+                    let guarded = stmts[..i].iter().any(|prev| {
+                        first_assertion(*prev, source).is_some_and(|a| {
+                            let text = node_text(a, source);
+                            subjects.iter().any(|s| mentions(text, s))
+                        })
+                    });
+                    if !guarded {
+                        found.push(*stmt);
+                    }
+                }
+                _ => Self::collect_in(*stmt, source, found),
+            }
+        }
+    }
+
+    /// Descend into the blocks of a compound statement (`if`, `with`, `try` and their clauses).
+    fn collect_in<'t>(node: Node<'t>, source: &[u8], found: &mut Vec<Node<'t>>) {
+        if node.kind() == "block" {
+            Self::collect(node, source, found);
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "block" || child.kind().ends_with("_clause") {
+                Self::collect_in(child, source, found);
+            }
+        }
+    }
+}
+
+impl Rule for AssertInLoopRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-024"
+    }
+    fn name(&self) -> &'static str {
+        "AssertInLoopRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let mut loops = Vec::new();
+            Self::collect(body, source, &mut loops);
+            for stmt in loops {
+                let iterable = stmt
+                    .child_by_field_name("right")
+                    .map_or("", |r| node_text(r, source));
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' asserts inside a loop over '{iterable}'; if it is empty, no assertion runs",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Assert the collection's length before the loop, or collect the results and assert once"
+                            .to_string(),
+                    ),
                     Some(test.name.clone()),
-                ));
+                );
+                set_span(&mut v, TestLogicRule::keyword(stmt, "for"));
+                violations.push(v);
             }
         }
         violations
     }
 }
 
-/// Rule that detects tests that only verify mock calls without real assertions.
-pub struct MockOnlyVerifyRule;
+/// Exception types whose handler also catches a failed assertion.
+const ASSERTION_CATCHING: &[&str] = &["Exception", "BaseException", "AssertionError"];
 
-impl Rule for MockOnlyVerifyRule {
+/// The exception type an `except` clause catches that swallows assertion failures
+/// (`"bare except"` for `except:`), if any.
+fn catches_assertion_error(clause: Node, source: &[u8]) -> Option<String> {
+    let mut cursor = clause.walk();
+    let Some(caught) = clause
+        .named_children(&mut cursor)
+        .find(|c| c.kind() != "block" && c.kind() != "comment")
+    else {
+        return Some("bare except".to_string());
+    };
+    let caught = if caught.kind() == "as_pattern" {
+        caught.named_child(0).unwrap_or(caught)
+    } else {
+        caught
+    };
+    let types: Vec<Node> = match caught.kind() {
+        "tuple" | "parenthesized_expression" => {
+            let mut inner = caught.walk();
+            caught.named_children(&mut inner).collect()
+        }
+        _ => vec![caught],
+    };
+    types.into_iter().find_map(|t| {
+        let text = node_text(t, source);
+        ASSERTION_CATCHING
+            .contains(&text.rsplit('.').next().unwrap_or(text))
+            .then(|| format!("except {text}"))
+    })
+}
+
+/// Whether an `except` handler propagates the failure: re-raises or calls `pytest.fail()`
+/// / `self.fail()`.
+fn handler_propagates(node: Node, source: &[u8]) -> bool {
+    match node.kind() {
+        "raise_statement" => return true,
+        "call" => {
+            let func = node
+                .child_by_field_name("function")
+                .map_or("", |f| node_text(f, source));
+            if matches!(func, "pytest.fail" | "fail" | "self.fail") {
+                return true;
+            }
+        }
+        "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+            return false;
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .named_children(&mut cursor)
+        .any(|c| handler_propagates(c, source));
+    found
+}
+
+/// Rule that detects assertions inside `try` blocks whose `except` handler catches
+/// `AssertionError` (directly or through `Exception` / a bare `except`) without re-raising,
+/// so the test cannot fail.
+pub struct SwallowedAssertionRule;
+
+impl Rule for SwallowedAssertionRule {
     fn id(&self) -> &'static str {
-        "PYTEST-MNT-005"
+        "PYTEST-MNT-025"
     }
     fn name(&self) -> &'static str {
-        "MockOnlyVerifyRule"
+        "SwallowedAssertionRule"
     }
     fn severity(&self) -> Severity {
-        Severity::Warning
+        Severity::Error
     }
     fn category(&self) -> Category {
         Category::Maintenance
@@ -215,77 +1537,255 @@ impl Rule for MockOnlyVerifyRule {
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if !module.test_functions.iter().any(|t| t.has_try_except) {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.has_mock_verifications && !test.has_state_assertions {
-                violations.push(make_violation(
-                    self.id(),
-                    self.name(),
-                    self.severity(),
-                    self.category(),
-                    format!(
-                        "Test '{}' only verifies mocks without checking state",
-                        test.name
-                    ),
-                    module.file_path.clone(),
-                    test.line,
-                    Some("Add state assertions to verify actual outcomes".to_string()),
-                    Some(test.name.clone()),
-                ));
-            }
-            if test.mocks_stdlib_module {
-                violations.push(make_violation(
-                    self.id(),
-                    self.name(),
-                    self.severity(),
-                    self.category(),
-                    format!(
-                        "Test '{}' mocks stdlib module(s): {} — use dependency injection or test doubles instead",
-                        test.name,
-                        test.mocked_stdlib_targets.join(", ")
-                    ),
-                    module.file_path.clone(),
-                    test.line,
-                    Some("Refactor to inject dependencies instead of patching stdlib internals".to_string()),
-                    Some(test.name.clone()),
-                ));
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let mut to_visit = vec![body];
+            let mut found = Vec::new();
+            while let Some(node) = to_visit.pop() {
+                if matches!(
+                    node.kind(),
+                    "function_definition" | "decorated_definition" | "class_definition" | "lambda"
+                ) {
+                    continue;
+                }
+                if node.kind() == "try_statement"
+                    && node
+                        .child_by_field_name("body")
+                        .and_then(|b| first_assertion(b, source))
+                        .is_some()
+                {
+                    let mut cursor = node.walk();
+                    let swallowing = node.named_children(&mut cursor).find_map(|clause| {
+                        if clause.kind() != "except_clause" || handler_propagates(clause, source) {
+                            return None;
+                        }
+                        catches_assertion_error(clause, source).map(|caught| (clause, caught))
+                    });
+                    if let Some(found_clause) = swallowing {
+                        found.push(found_clause);
+                    }
+                }
+                let mut cursor = node.walk();
+                to_visit.extend(node.named_children(&mut cursor));
             }
-            if test.has_weak_assertions && !test.weak_assertion_details.is_empty() {
-                violations.push(make_violation(
+            found.sort_by_key(|(clause, _)| clause.start_byte());
+            for (clause, caught) in found {
+                let mut v = make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
                     format!(
-                        "Test '{}' uses weak assertion patterns: {}",
-                        test.name,
-                        test.weak_assertion_details.join(", ")
+                        "Test '{}' asserts inside a try block whose {caught} handler swallows the failure; the test cannot fail",
+                        test.name
                     ),
                     module.file_path.clone(),
                     test.line,
                     Some(
-                        "Use value-level assertions instead of type/existence/key-presence checks"
+                        "Remove the try/except, or re-raise / call pytest.fail() in the handler"
                             .to_string(),
                     ),
                     Some(test.name.clone()),
-                ));
+                );
+                set_span(&mut v, TestLogicRule::keyword(clause, "except"));
+                violations.push(v);
             }
         }
         violations
     }
 }
 
-/// Rule that detects assertion roulette: too many assertions without messages.
-pub struct AssertionRouletteRule;
+/// Builtins, standard-library modules and test tooling: call roots that are not the code
+/// under test.
+const NON_PROJECT_ROOTS: &[&str] = &[
+    // builtins
+    "abs",
+    "all",
+    "any",
+    "bool",
+    "bytes",
+    "callable",
+    "dict",
+    "dir",
+    "enumerate",
+    "filter",
+    "float",
+    "format",
+    "frozenset",
+    "getattr",
+    "hasattr",
+    "hash",
+    "id",
+    "int",
+    "isinstance",
+    "issubclass",
+    "iter",
+    "len",
+    "list",
+    "map",
+    "max",
+    "min",
+    "next",
+    "object",
+    "open",
+    "print",
+    "range",
+    "repr",
+    "reversed",
+    "round",
+    "set",
+    "setattr",
+    "sorted",
+    "str",
+    "sum",
+    "super",
+    "tuple",
+    "type",
+    "vars",
+    "zip",
+    // standard library and test tooling
+    "asyncio",
+    "builtins",
+    "collections",
+    "contextlib",
+    "copy",
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "enum",
+    "functools",
+    "glob",
+    "hashlib",
+    "http",
+    "io",
+    "itertools",
+    "json",
+    "logging",
+    "math",
+    "mock",
+    "os",
+    "pathlib",
+    "platform",
+    "pytest",
+    "random",
+    "re",
+    "shutil",
+    "socket",
+    "sqlite3",
+    "string",
+    "subprocess",
+    "sys",
+    "tempfile",
+    "textwrap",
+    "threading",
+    "time",
+    "typing",
+    "unittest",
+    "urllib",
+    "uuid",
+];
 
-impl Rule for AssertionRouletteRule {
+/// Whether a dotted module path belongs to the code under test: a relative import, a module
+/// under one of `packages`, or, when no packages are configured, anything outside the
+/// standard library and test tooling.
+pub(crate) fn is_project_module(dotted: &str, packages: &[String]) -> bool {
+    if dotted.starts_with('.') {
+        return true;
+    }
+    if packages.is_empty() {
+        let top = dotted.split('.').next().unwrap_or(dotted);
+        return !NON_PROJECT_ROOTS.contains(&top);
+    }
+    packages.iter().any(|p| {
+        dotted == p
+            || dotted
+                .strip_prefix(p.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Root of a call target with attribute chains collapsed: `order.items.add()` is `order`,
+/// `self.client.get()` is `self.client`, `Builder().build()` is `Builder`.
+fn call_root(func: Node, source: &[u8]) -> Option<String> {
+    match func.kind() {
+        "identifier" => Some(node_text(func, source).to_string()),
+        "attribute" | "subscript" => {
+            let object = func
+                .child_by_field_name("object")
+                .or_else(|| func.child_by_field_name("value"))?;
+            if object.kind() == "identifier" && node_text(object, source) == "self" {
+                return Some(
+                    node_text(func, source)
+                        .split(['.', '['])
+                        .take(2)
+                        .collect::<Vec<_>>()
+                        .join("."),
+                );
+            }
+            call_root(object, source)
+        }
+        "call" => call_root(func.child_by_field_name("function")?, source),
+        "parenthesized_expression" => call_root(func.named_child(0)?, source),
+        _ => None,
+    }
+}
+
+/// Rule that detects eager tests: tests that exercise many distinct production call targets
+/// at once, so a failure says little about which behaviour broke.
+pub struct EagerTestRule;
+
+impl EagerTestRule {
+    /// Action call roots in `node` and how often each is called, in order of first use.
+    /// Assertions, including calls in their arguments, are verification and are skipped.
+    fn collect(node: Node, source: &[u8], roots: &mut Vec<(String, usize)>) {
+        match node.kind() {
+            "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                return;
+            }
+            "assert_statement" => return,
+            "call" if is_assertion_node(node, source) => return,
+            "call" => {
+                if let Some(root) = node
+                    .child_by_field_name("function")
+                    .and_then(|f| call_root(f, source))
+                {
+                    match roots.iter_mut().find(|(r, _)| *r == root) {
+                        Some((_, count)) => *count += 1,
+                        None => roots.push((root, 1)),
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            Self::collect(child, source, roots);
+        }
+    }
+}
+
+impl Rule for EagerTestRule {
     fn id(&self) -> &'static str {
-        "PYTEST-MNT-006"
+        "PYTEST-MNT-028"
     }
     fn name(&self) -> &'static str {
-        "AssertionRouletteRule"
+        "EagerTestRule"
     }
     fn severity(&self) -> Severity {
         Severity::Warning
@@ -293,74 +1793,296 @@ impl Rule for AssertionRouletteRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[
+            RuleOption {
+                name: "max",
+                kind: OptionKind::Integer,
+                description: "Maximum distinct call targets per test (default 6)",
+            },
+            RuleOption {
+                name: "ignored",
+                kind: OptionKind::StringList,
+                description: "Extra call roots (names or modules) that do not count, on top of \
+                              the builtin and standard-library defaults",
+            },
+        ]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let max = ctx.options.usize("max", 6);
+        let ignored = ctx.options.string_list("ignored");
+        let bindings = import_bindings(root, source);
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.assertion_count > 3 && !test.is_parametrized {
-                violations.push(make_violation(
-                    self.id(),
-                    self.name(),
-                    self.severity(),
-                    self.category(),
-                    format!(
-                        "Test '{}' has {} assertions (assertion roulette)",
-                        test.name, test.assertion_count
-                    ),
-                    module.file_path.clone(),
-                    test.line,
-                    Some("Split into smaller, focused tests".to_string()),
-                    Some(test.name.clone()),
-                ));
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            let mut roots = Vec::new();
+            Self::collect(body, source, &mut roots);
+            roots.retain(|(name, _)| {
+                // Calls on fixtures (the test's parameters) set the scene rather than act
+                let head = name.split('.').next().unwrap_or(name);
+                let module_root = bindings
+                    .get(head)
+                    .map_or(head, |dotted| dotted.split('.').next().unwrap_or(dotted));
+                !test.fixture_deps.iter().any(|f| f == head)
+                    && !NON_PROJECT_ROOTS.contains(&module_root)
+                    && !ignored.iter().any(|i| i == head || i == module_root)
+            });
+            if roots.len() <= max {
+                continue;
             }
+            let mut top = roots.clone();
+            top.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            let listed: Vec<String> = top
+                .iter()
+                .take(5)
+                .map(|(name, count)| {
+                    if *count > 1 {
+                        format!("{name} ({count}×)")
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect();
+            let more = if roots.len() > 5 { ", ..." } else { "" };
+            let mut v = make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' exercises {} distinct call targets (max {max}): {}{more}",
+                    test.name,
+                    roots.len(),
+                    listed.join(", ")
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some("Split the test so each one exercises a single behaviour".to_string()),
+                Some(test.name.clone()),
+            );
+            v.metadata
+                .insert("call_targets".to_string(), roots.len().into());
+            violations.push(v);
         }
         violations
     }
 }
 
-/// Rule that detects bare except or overly broad exception handling.
-pub struct RawExceptionHandlingRule;
+/// Whether an expression is built from literals only (no names, no calls).
+fn is_constant(node: Node) -> bool {
+    if matches!(node.kind(), "identifier" | "call" | "attribute") {
+        return false;
+    }
+    let mut cursor = node.walk();
+    let all = node.named_children(&mut cursor).all(is_constant);
+    all
+}
 
-impl Rule for RawExceptionHandlingRule {
+/// Rule that detects equality assertions whose expected side is computed by project code
+/// (`assert encode(x) == encode_reference(x)`): the assertion repeats the logic under test
+/// and cannot catch a regression in it.
+pub struct ComputedExpectedValueRule;
+
+impl ComputedExpectedValueRule {
+    /// First call to project code in `expr`, looking through local variables assigned in
+    /// the test body. Class instantiations (`Money(10)`) are values, not computations.
+    fn project_call<'t>(
+        expr: Node<'t>,
+        source: &[u8],
+        is_project: &dyn Fn(&str) -> bool,
+        locals: &HashMap<&str, Node<'t>>,
+        depth: usize,
+    ) -> Option<String> {
+        match expr.kind() {
+            "lambda" => return None,
+            "identifier" if depth < 4 => {
+                let value = locals.get(node_text(expr, source))?;
+                return Self::project_call(*value, source, is_project, locals, depth + 1);
+            }
+            "call" => {
+                if let Some(func) = expr
+                    .child_by_field_name("function")
+                    .filter(|f| matches!(f.kind(), "identifier" | "attribute"))
+                {
+                    let text = node_text(func, source);
+                    let last = text.rsplit('.').next().unwrap_or(text);
+                    if is_project(text) && !last.starts_with(char::is_uppercase) {
+                        return Some(text.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut cursor = expr.walk();
+        let found = expr
+            .named_children(&mut cursor)
+            .find_map(|c| Self::project_call(c, source, is_project, locals, depth));
+        found
+    }
+
+    /// `(actual, expected)` operand pairs of the equality assertions in `body`.
+    fn equalities<'t>(body: Node<'t>, source: &[u8]) -> Vec<(Node<'t>, Node<'t>)> {
+        let mut found = Vec::new();
+        let mut to_visit = vec![body];
+        while let Some(node) = to_visit.pop() {
+            match node.kind() {
+                "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                    continue;
+                }
+                "assert_statement" => {
+                    if let Some(cmp) = node
+                        .named_child(0)
+                        .filter(|c| c.kind() == "comparison_operator")
+                    {
+                        let mut cursor = cmp.walk();
+                        let children: Vec<Node> = cmp.children(&mut cursor).collect();
+                        if let [left, op, right] = children.as_slice() {
+                            if op.kind() == "==" {
+                                found.push((*left, *right));
+                            }
+                        }
+                    }
+                }
+                "call" if is_assertion_node(node, source) => {
+                    let method = node
+                        .child_by_field_name("function")
+                        .and_then(|f| f.child_by_field_name("attribute"))
+                        .map_or("", |a| node_text(a, source));
+                    let args: Vec<Node> = node
+                        .child_by_field_name("arguments")
+                        .map(|a| {
+                            let mut cursor = a.walk();
+                            a.named_children(&mut cursor)
+                                .filter(|c| !matches!(c.kind(), "comment" | "keyword_argument"))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    if matches!(method, "assertEqual" | "assertEquals") && args.len() >= 2 {
+                        found.push((args[0], args[1]));
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        found.sort_by_key(|(left, _)| left.start_byte());
+        found
+    }
+}
+
+impl Rule for ComputedExpectedValueRule {
     fn id(&self) -> &'static str {
-        "PYTEST-MNT-007"
+        "PYTEST-MNT-029"
     }
     fn name(&self) -> &'static str {
-        "RawExceptionHandlingRule"
+        "ComputedExpectedValueRule"
     }
     fn severity(&self) -> Severity {
-        Severity::Warning
+        Severity::Info
     }
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "project_packages",
+            kind: OptionKind::StringList,
+            description: "Module prefixes of the code under test, e.g. [\"shop\"]; by default any \
+                          import outside the standard library and test tooling counts",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let packages = ctx.options.string_list("project_packages");
+        let bindings = import_bindings(root, source);
+        let is_project = |callee: &str| {
+            let head = callee.split('.').next().unwrap_or(callee);
+            bindings
+                .get(head)
+                .is_some_and(|dotted| is_project_module(dotted, &packages))
+        };
+
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.has_try_except {
-                violations.push(make_violation(
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            // Simple `name = value` assignments directly in the body; the last one wins
+            let mut locals: HashMap<&str, Node> = HashMap::new();
+            let mut cursor = body.walk();
+            for stmt in body.named_children(&mut cursor) {
+                let assignment = stmt
+                    .named_child(0)
+                    .filter(|a| stmt.kind() == "expression_statement" && a.kind() == "assignment");
+                if let Some(assignment) = assignment {
+                    if let (Some(left), Some(right)) = (
+                        assignment.child_by_field_name("left"),
+                        assignment.child_by_field_name("right"),
+                    ) {
+                        if left.kind() == "identifier" {
+                            locals.insert(node_text(left, source), right);
+                        }
+                    }
+                }
+            }
+            for (actual, expected) in Self::equalities(body, source) {
+                // `assert 3 == add(1, 2)` puts the expected value first
+                if is_constant(actual) {
+                    continue;
+                }
+                let Some(callee) = Self::project_call(expected, source, &is_project, &locals, 0)
+                else {
+                    continue;
+                };
+                let mut v = make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
                     format!(
-                        "Test '{}' uses try/except instead of pytest.raises",
+                        "Test '{}' computes the expected value with project code ({callee}()); the assertion repeats the logic under test",
                         test.name
                     ),
                     module.file_path.clone(),
                     test.line,
-                    Some("Use pytest.raises() for exception testing".to_string()),
+                    Some("Compare against a literal or a hand-checked constant".to_string()),
                     Some(test.name.clone()),
-                ));
+                );
+                set_span(&mut v, expected);
+                violations.push(v);
             }
         }
         violations
@@ -432,7 +2154,7 @@ impl Rule for PropertyTestHintRule {
         "PropertyTestHintRule"
     }
     fn severity(&self) -> Severity {
-        Severity::Info
+        Severity::Hint
     }
     fn category(&self) -> Category {
         Category::Enhancement
@@ -592,16 +2314,24 @@ impl Rule for ParametrizeExplosionRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_cases",
+            kind: OptionKind::Integer,
+            description: "Maximum number of parametrized cases per test (default 20)",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        let max_cases = ctx.options.usize("max_cases", 20);
         let mut violations = Vec::new();
         for test in &module.test_functions {
             if let Some(count) = test.parametrize_count {
-                if count > 20 {
+                if count > max_cases {
                     violations.push(make_violation(
                         self.id(),
                         self.name(),
@@ -688,51 +2418,329 @@ impl Rule for DuplicateTestBodiesRule {
         _all_modules: &[ParsedModule],
         _ctx: &RuleContext,
     ) -> Vec<Violation> {
+        // Report each later copy against the first test with the same normalized body
+        let mut tests: Vec<_> = module.test_functions.iter().collect();
+        tests.sort_by_key(|t| t.line);
+        let mut originals: HashMap<u64, &crate::models::TestFunction> = HashMap::new();
         let mut violations = Vec::new();
-        let tests = &module.test_functions;
+        for test in tests {
+            let Some(hash) = test.body_hash else {
+                continue;
+            };
+            let Some(original) = originals.get(&hash) else {
+                originals.insert(hash, test);
+                continue;
+            };
+            violations.push(make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' has an identical body to '{}' (line {})",
+                    test.name, original.name, original.line
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some("Consolidate or differentiate the test bodies".to_string()),
+                Some(test.name.clone()),
+            ));
+        }
+        violations
+    }
+}
+
+/// Rule that detects a test function defined twice in the same module or class, where
+/// pytest silently keeps only the last definition.
+pub struct DuplicateTestNameRule;
+
+impl DuplicateTestNameRule {
+    /// In project mode: a module-level test whose name is also used by modules next to it.
+    fn across_modules(
+        &self,
+        module: &ParsedModule,
+        name: &str,
+        name_node: Node,
+        others: &[&Path],
+    ) -> Violation {
+        let files: Vec<String> = others
+            .iter()
+            .map(|p| {
+                p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        let mut v = make_violation(
+            self.id(),
+            self.name(),
+            self.severity(),
+            self.category(),
+            format!(
+                "Test '{name}' is also defined in {} in the same directory; `-k {name}` selects all of them",
+                files.join(", ")
+            ),
+            module.file_path.clone(),
+            name_node.start_position().row + 1,
+            Some("Give the tests names that say what each module checks".to_string()),
+            Some(name.to_string()),
+        );
+        set_span(&mut v, name_node);
+        v.metadata.insert("also_in".to_string(), files.into());
+        v
+    }
 
-        let mut hash_map: HashMap<u64, Vec<usize>> = HashMap::new();
-        for (i, test) in tests.iter().enumerate() {
-            if let Some(hash) = test.body_hash {
-                hash_map.entry(hash).or_default().push(i);
+    /// Check the definitions directly inside `scope` (a module or class body).
+    fn check_scope(
+        &self,
+        module: &ParsedModule,
+        scope: Node,
+        owner: Option<&str>,
+        project: Option<ProjectContext>,
+        violations: &mut Vec<Violation>,
+    ) {
+        let source = module.source.as_bytes();
+        let mut seen: HashMap<&str, Node> = HashMap::new();
+        let mut cursor = scope.walk();
+        for stmt in scope.named_children(&mut cursor) {
+            let definition = if stmt.kind() == "decorated_definition" {
+                stmt.child_by_field_name("definition")
+            } else {
+                Some(stmt)
+            };
+            let Some(definition) = definition else {
+                continue;
+            };
+            let Some(name_node) = definition.child_by_field_name("name") else {
+                continue;
+            };
+            let name = node_text(name_node, source);
+            match definition.kind() {
+                "class_definition" => {
+                    if let Some(body) = definition.child_by_field_name("body") {
+                        self.check_scope(module, body, Some(name), None, violations);
+                    }
+                }
+                "function_definition" if name.starts_with("test") => {
+                    let line = name_node.start_position().row + 1;
+                    let Some(&previous) = seen.get(name) else {
+                        seen.insert(name, name_node);
+                        if let Some(project) = project {
+                            let others = project.tests_named(&module.file_path, name);
+                            if !others.is_empty() {
+                                violations
+                                    .push(self.across_modules(module, name, name_node, &others));
+                            }
+                        }
+                        continue;
+                    };
+                    let original = previous.start_position().row + 1;
+                    let location = owner.map_or_else(String::new, |c| format!(" in class '{c}'"));
+                    let mut v = make_violation(
+                        self.id(),
+                        self.name(),
+                        self.severity(),
+                        self.category(),
+                        format!(
+                            "Test '{name}'{location} is defined again; pytest only runs this definition, so the one on line {original} never runs"
+                        ),
+                        module.file_path.clone(),
+                        line,
+                        Some("Rename one of the tests or remove the stale copy".to_string()),
+                        Some(name.to_string()),
+                    );
+                    set_span(&mut v, name_node);
+                    add_related(
+                        &mut v,
+                        previous,
+                        format!("previous definition of '{name}', which never runs"),
+                    );
+                    violations.push(v);
+                    seen.insert(name, name_node);
+                }
+                _ => {}
             }
         }
+    }
+}
 
-        let mut reported = std::collections::HashSet::new();
-        for indices in hash_map.values() {
-            if indices.len() < 2 {
+impl Rule for DuplicateTestNameRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-022"
+    }
+    fn name(&self) -> &'static str {
+        "DuplicateTestNameRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let mut violations = Vec::new();
+        self.check_scope(module, tree.root_node(), None, ctx.project, &mut violations);
+        violations
+    }
+}
+
+/// Rule that suggests `@pytest.mark.parametrize` for groups of tests whose bodies differ
+/// only in number or string literals at corresponding positions.
+pub struct SuggestParametrizeRule;
+
+impl Rule for SuggestParametrizeRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-PARAM-004"
+    }
+    fn name(&self) -> &'static str {
+        "SuggestParametrizeRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "min_group_size",
+            kind: OptionKind::Integer,
+            description: "Smallest group of near-identical tests to report (default 3)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let min_group_size = ctx.options.usize("min_group_size", 3).max(2);
+        if module.test_functions.len() < min_group_size {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let source = module.source.as_bytes();
+
+        // Group tests by body shape: their tokens with every literal blanked out
+        let mut tests: Vec<_> = module
+            .test_functions
+            .iter()
+            .filter(|t| !t.is_parametrized)
+            .collect();
+        tests.sort_by_key(|t| t.line);
+        let mut groups: Vec<(String, Vec<_>)> = Vec::new();
+        for test in tests {
+            let Some(body) = function_at_line(tree.root_node(), test.line)
+                .and_then(|f| f.child_by_field_name("body"))
+            else {
                 continue;
+            };
+            let tokens = crate::rules::ast::body_tokens(body, source);
+            let shape: Vec<&str> = tokens
+                .iter()
+                .map(|t| if t.literal { "\u{0}" } else { t.text })
+                .collect();
+            let shape = shape.join(" ");
+            match groups.iter_mut().find(|(s, _)| *s == shape) {
+                Some((_, members)) => members.push((test, tokens)),
+                None => groups.push((shape, vec![(test, tokens)])),
             }
-            let names: Vec<&str> = indices.iter().map(|i| tests[*i].name.as_str()).collect();
-            for &i in indices {
-                let test = &tests[i];
-                if reported.contains(&test.name) {
-                    continue;
-                }
-                let peers: Vec<&str> = names.iter().filter(|n| **n != test.name).copied().collect();
-                violations.push(make_violation(
-                    self.id(),
-                    self.name(),
-                    self.severity(),
-                    self.category(),
-                    format!(
-                        "Test '{}' has identical body to {} other test(s): {} (shared body hash)",
-                        test.name,
-                        peers.len(),
-                        peers.join(", ")
-                    ),
-                    module.file_path.clone(),
-                    test.line,
-                    Some("Consolidate or differentiate the test bodies".to_string()),
-                    Some(test.name.clone()),
-                ));
-                reported.insert(test.name.clone());
+        }
+
+        let mut violations = Vec::new();
+        for (_, members) in groups {
+            if members.len() < min_group_size {
+                continue;
+            }
+            let (first, first_tokens) = &members[0];
+            let differing: Vec<String> = first_tokens
+                .iter()
+                .enumerate()
+                .filter(|(i, t)| t.literal && members.iter().any(|(_, m)| m[*i].text != t.text))
+                .map(|(i, t)| {
+                    let values: Vec<&str> = members.iter().map(|(_, m)| m[i].text).collect();
+                    format!("line {}: {}", t.line, values.join(" / "))
+                })
+                .collect();
+            // Identical bodies are DuplicateTestBodiesRule's concern
+            if differing.is_empty() {
+                continue;
             }
+            let others: Vec<&str> = members[1..].iter().map(|(t, _)| t.name.as_str()).collect();
+            violations.push(make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' and {} similar test(s) ({}) differ only in literal values at {} position(s): {}",
+                    first.name,
+                    others.len(),
+                    others.join(", "),
+                    differing.len(),
+                    differing.join("; ")
+                ),
+                module.file_path.clone(),
+                first.line,
+                Some(
+                    "Consolidate them into one test with @pytest.mark.parametrize over the differing values"
+                        .to_string(),
+                ),
+                Some(first.name.clone()),
+            ));
         }
         violations
     }
 }
 
+/// Longest sleep, in seconds, that PYTEST-MNT-016 lets through.
+const SLEEP_THRESHOLD: f64 = 0.1;
+
+/// Dotted name, resolved through the module's imports, of the first sleep call under
+/// `node` that waits longer than [`SLEEP_THRESHOLD`]: `asyncio.sleep` for `aio.sleep(1)`
+/// after `import asyncio as aio`.
+fn long_sleep_callee(
+    node: Node,
+    source: &[u8],
+    bindings: &HashMap<String, String>,
+) -> Option<String> {
+    if let Some(func) = node
+        .child_by_field_name("function")
+        .filter(|_| node.kind() == "call")
+    {
+        let is_sleep = match func.kind() {
+            "identifier" => node_text(func, source) == "sleep",
+            "attribute" => func
+                .child_by_field_name("attribute")
+                .is_some_and(|a| node_text(a, source) == "sleep"),
+            _ => false,
+        };
+        let seconds = node
+            .child_by_field_name("arguments")
+            .and_then(|args| args.named_child(0))
+            .and_then(|arg| node_text(arg, source).parse::<f64>().ok());
+        if is_sleep && seconds.is_some_and(|s| s > SLEEP_THRESHOLD) {
+            return Some(resolve_callee(func, source, bindings));
+        }
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .find_map(|c| long_sleep_callee(c, source, bindings));
+    found
+}
+
 pub struct SleepWithValueRule;
 
 impl Rule for SleepWithValueRule {
@@ -752,20 +2760,27 @@ impl Rule for SleepWithValueRule {
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        let root = ctx.tree.map(tree_sitter::Tree::root_node);
+        let source = module.source.as_bytes();
+        let bindings = root.map(|r| import_bindings(r, source)).unwrap_or_default();
         let mut violations = Vec::new();
         for test in &module.test_functions {
             if test.uses_time_sleep {
-                let exceeds_threshold = test.sleep_value.is_some_and(|v| v > 0.1);
+                let exceeds_threshold = test.sleep_value.is_some_and(|v| v > SLEEP_THRESHOLD);
                 if exceeds_threshold {
+                    let callee = root
+                        .and_then(|r| function_at_line(r, test.line))
+                        .and_then(|f| long_sleep_callee(f, source, &bindings))
+                        .unwrap_or_else(|| "sleep".to_string());
                     violations.push(make_violation(
                         self.id(),
                         self.name(),
                         self.severity(),
                         self.category(),
                         format!(
-                            "Test '{}' uses time.sleep() with value > 0.1s — slows test suite",
+                            "Test '{}' uses {callee}() with value > 0.1s — slows test suite",
                             test.name
                         ),
                         module.file_path.clone(),
@@ -795,23 +2810,32 @@ impl Rule for TestNameLengthRule {
     fn category(&self) -> Category {
         Category::Maintenance
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_length",
+            kind: OptionKind::Integer,
+            description: "Maximum test name length in characters (default 80)",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        let max_length = ctx.options.usize("max_length", 80);
         let mut violations = Vec::new();
         for test in &module.test_functions {
-            if test.name.chars().count() > 80 {
+            if test.name.chars().count() > max_length {
                 violations.push(make_violation(
                     self.id(),
                     self.name(),
                     self.severity(),
                     self.category(),
                     format!(
-                        "Test name '{}' exceeds 80 characters ({} chars)",
+                        "Test name '{}' exceeds {} characters ({} chars)",
                         test.name,
+                        max_length,
                         test.name.chars().count()
                     ),
                     module.file_path.clone(),
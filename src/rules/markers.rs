@@ -0,0 +1,333 @@
+//! Skip-marker checks: `@pytest.mark.skip` / `skipif` applied without a `reason=`, whether as
+//! a decorator, through a variable, or via a `pytestmark` list.
+
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, Fix, ParsedModule, Severity, Violation};
+use crate::rules::ast::{node_text, set_span};
+use crate::rules::{Rule, RuleContext};
+
+/// Skip markers that silently drop tests from the run.
+const SKIP_MARKS: &[&str] = &["skip", "skipif"];
+
+/// The skip mark an expression applies (`skip`/`skipif`), if it lacks a reason.
+///
+/// `skip` accepts its reason positionally; `skipif` only as `reason=`, because its
+/// positional arguments are the conditions.
+fn reasonless_skip(expr: Node, source: &[u8]) -> Option<&'static str> {
+    let (func, args) = if expr.kind() == "call" {
+        (
+            expr.child_by_field_name("function")?,
+            expr.child_by_field_name("arguments"),
+        )
+    } else {
+        (expr, None)
+    };
+    let text = node_text(func, source);
+    let mark = SKIP_MARKS.iter().copied().find(|m| {
+        text.strip_suffix(m)
+            .is_some_and(|prefix| prefix == "mark." || prefix.ends_with(".mark."))
+    })?;
+
+    let has_reason = args.is_some_and(|args| {
+        let mut cursor = args.walk();
+        let found = args
+            .named_children(&mut cursor)
+            .any(|arg| match arg.kind() {
+                "keyword_argument" => arg
+                    .child_by_field_name("name")
+                    .is_some_and(|n| node_text(n, source) == "reason"),
+                "string" | "concatenated_string" => mark == "skip",
+                _ => false,
+            });
+        found
+    });
+    (!has_reason).then_some(mark)
+}
+
+/// Fix that adds a placeholder `reason="TODO"` to a reasonless skip mark expression.
+fn reason_fix(expr: Node, source: &str) -> Fix {
+    const REASON: &str = "reason=\"TODO\"";
+    let Some(args) = expr
+        .child_by_field_name("arguments")
+        .filter(|_| expr.kind() == "call")
+    else {
+        return Fix::replace(expr.end_byte(), expr.end_byte(), format!("({REASON})"));
+    };
+    // Insert before the closing parenthesis, after any existing arguments
+    let inner = &source[args.start_byte() + 1..args.end_byte() - 1];
+    let existing = inner.trim_end();
+    let at = args.start_byte() + 1 + existing.len();
+    let replacement = if existing.trim_start().is_empty() {
+        REASON.to_string()
+    } else if existing.ends_with(',') {
+        format!(" {REASON}")
+    } else {
+        format!(", {REASON}")
+    };
+    Fix::replace(at, at, replacement)
+}
+
+/// Module-level `name = pytest.mark.skip...` assignments, so `@name` can be resolved.
+fn skip_mark_variables<'tree>(root: Node<'tree>, source: &[u8]) -> HashMap<String, Node<'tree>> {
+    let mut vars = HashMap::new();
+    let mut cursor = root.walk();
+    for stmt in root.children(&mut cursor) {
+        let Some(assign) = stmt
+            .named_child(0)
+            .filter(|_| stmt.kind() == "expression_statement")
+            .filter(|a| a.kind() == "assignment")
+        else {
+            continue;
+        };
+        let (Some(left), Some(right)) = (
+            assign.child_by_field_name("left"),
+            assign.child_by_field_name("right"),
+        ) else {
+            continue;
+        };
+        if left.kind() == "identifier" && reasonless_skip(right, source).is_some() {
+            vars.insert(node_text(left, source).to_string(), right);
+        }
+    }
+    vars
+}
+
+/// Rule that flags skip markers applied without a reason.
+pub struct SkipWithoutReasonRule;
+
+impl SkipWithoutReasonRule {
+    #[allow(clippy::too_many_arguments)]
+    fn report(
+        &self,
+        module: &ParsedModule,
+        node: Node,
+        target: &str,
+        mark: &str,
+        via: Option<&str>,
+        fix: Fix,
+        test_name: Option<String>,
+        violations: &mut Vec<Violation>,
+    ) {
+        let applied = via.map_or_else(String::new, |var| format!(" (via `{var}`)"));
+        let mut v = make_violation(
+            self.id(),
+            self.name(),
+            self.severity(),
+            self.category(),
+            format!("`pytest.mark.{mark}`{applied} on {target} has no reason"),
+            module.file_path.clone(),
+            node.start_position().row + 1,
+            Some(format!(
+                "Pass reason=\"...\" to pytest.mark.{mark} explaining why and when it can be removed"
+            )),
+            test_name,
+        );
+        set_span(&mut v, node);
+        v.fix = Some(fix);
+        violations.push(v);
+    }
+
+    /// Check one marker expression (a decorator or a `pytestmark` element).
+    fn check_mark(
+        &self,
+        module: &ParsedModule,
+        expr: Node,
+        vars: &HashMap<String, Node>,
+        target: &str,
+        test_name: Option<&str>,
+        violations: &mut Vec<Violation>,
+    ) {
+        let source = module.source.as_bytes();
+        // A mark applied through a variable is fixed at its definition
+        let (mark, via, fixed) = if expr.kind() == "identifier" {
+            let name = node_text(expr, source);
+            match vars
+                .get(name)
+                .and_then(|value| reasonless_skip(*value, source).map(|mark| (mark, *value)))
+            {
+                Some((mark, value)) => (mark, Some(name), value),
+                None => return,
+            }
+        } else {
+            match reasonless_skip(expr, source) {
+                Some(mark) => (mark, None, expr),
+                None => return,
+            }
+        };
+        self.report(
+            module,
+            expr,
+            target,
+            mark,
+            via,
+            reason_fix(fixed, &module.source),
+            test_name.map(str::to_string),
+            violations,
+        );
+    }
+
+    fn walk(
+        &self,
+        module: &ParsedModule,
+        node: Node,
+        vars: &HashMap<String, Node>,
+        violations: &mut Vec<Violation>,
+    ) {
+        let source = module.source.as_bytes();
+        match node.kind() {
+            "decorated_definition" => {
+                let definition = node.child_by_field_name("definition");
+                let name = definition
+                    .and_then(|d| d.child_by_field_name("name"))
+                    .map(|n| node_text(n, source));
+                let target = match (definition.map(|d| d.kind()), name) {
+                    (Some("class_definition"), Some(n)) => format!("class '{n}'"),
+                    (_, Some(n)) => format!("test '{n}'"),
+                    _ => "definition".to_string(),
+                };
+                let mut cursor = node.walk();
+                for decorator in node.named_children(&mut cursor) {
+                    if decorator.kind() != "decorator" {
+                        continue;
+                    }
+                    if let Some(expr) = decorator.named_child(0) {
+                        self.check_mark(module, expr, vars, &target, name, violations);
+                    }
+                }
+            }
+            "assignment" => {
+                let is_pytestmark = node
+                    .child_by_field_name("left")
+                    .is_some_and(|l| node_text(l, source) == "pytestmark");
+                if let Some(right) = node.child_by_field_name("right").filter(|_| is_pytestmark) {
+                    let in_class = node
+                        .parent()
+                        .and_then(|p| p.parent())
+                        .is_some_and(|p| p.kind() == "block");
+                    let target = if in_class {
+                        "all tests in the class (pytestmark)"
+                    } else {
+                        "all tests in the module (pytestmark)"
+                    };
+                    if matches!(right.kind(), "list" | "tuple") {
+                        let mut cursor = right.walk();
+                        for element in right.named_children(&mut cursor) {
+                            self.check_mark(module, element, vars, target, None, violations);
+                        }
+                    } else {
+                        self.check_mark(module, right, vars, target, None, violations);
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.walk(module, child, vars, violations);
+        }
+    }
+}
+
+impl Rule for SkipWithoutReasonRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-020"
+    }
+    fn name(&self) -> &'static str {
+        "SkipWithoutReasonRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn fixable(&self) -> bool {
+        true
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let vars = skip_mark_variables(root, module.source.as_bytes());
+        let mut violations = Vec::new();
+        self.walk(module, root, &vars, &mut violations);
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::ast::parse_python;
+
+    fn first_expr_skip(source: &str) -> Option<&'static str> {
+        let tree = parse_python(source).unwrap();
+        let stmt = tree.root_node().named_child(0).unwrap();
+        reasonless_skip(stmt.named_child(0).unwrap(), source.as_bytes())
+    }
+
+    #[test]
+    fn test_reasonless_skip_detection() {
+        assert_eq!(first_expr_skip("pytest.mark.skip\n"), Some("skip"));
+        assert_eq!(first_expr_skip("pytest.mark.skip()\n"), Some("skip"));
+        assert_eq!(
+            first_expr_skip("mark.skipif(sys.platform == 'win32')\n"),
+            Some("skipif")
+        );
+        assert_eq!(first_expr_skip("pytest.mark.skip('flaky on CI')\n"), None);
+        assert_eq!(first_expr_skip("pytest.mark.skip(reason='x')\n"), None);
+        assert_eq!(
+            first_expr_skip("pytest.mark.skipif(True, reason='x')\n"),
+            None
+        );
+        // A positional string is a condition for skipif, not a reason
+        assert_eq!(
+            first_expr_skip("pytest.mark.skipif('sys.version_info < (3, 9)')\n"),
+            Some("skipif")
+        );
+        assert_eq!(first_expr_skip("pytest.mark.slow\n"), None);
+        assert_eq!(first_expr_skip("pytest.mark.skip_slow\n"), None);
+    }
+
+    #[test]
+    fn test_reason_fix_inserts_reason() {
+        let fixed = |source: &str| {
+            let tree = parse_python(source).unwrap();
+            let expr = tree
+                .root_node()
+                .named_child(0)
+                .unwrap()
+                .named_child(0)
+                .unwrap();
+            let fix = reason_fix(expr, source);
+            crate::fix::apply_fixes(source, &[&fix]).source
+        };
+        assert_eq!(
+            fixed("pytest.mark.skip\n"),
+            "pytest.mark.skip(reason=\"TODO\")\n"
+        );
+        assert_eq!(
+            fixed("pytest.mark.skip( )\n"),
+            "pytest.mark.skip(reason=\"TODO\" )\n"
+        );
+        assert_eq!(
+            fixed("mark.skipif(sys.platform == 'win32')\n"),
+            "mark.skipif(sys.platform == 'win32', reason=\"TODO\")\n"
+        );
+        assert_eq!(
+            fixed("mark.skipif(\n    WIN,\n)\n"),
+            "mark.skipif(\n    WIN, reason=\"TODO\"\n)\n"
+        );
+    }
+}
@@ -1,6 +1,13 @@
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
 use crate::engine::make_violation;
-use crate::models::{Category, ParsedModule, Severity, Violation};
-use crate::rules::{Rule, RuleContext};
+use crate::models::{Category, ParsedModule, Severity, TestFunction, Violation};
+use crate::rules::ast::{function_at_line, node_text, resolve_callee, set_span};
+use crate::rules::exceptions::{contains_exception_assertion, PytestAliases};
+use crate::rules::maintenance::{is_project_module, only_verifies_mocks};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
 
 pub struct PatchTargetingDefinitionModuleRule;
 
@@ -166,17 +173,25 @@ impl Rule for MockRatioBudgetRule {
     fn category(&self) -> Category {
         Category::Enhancement
     }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_ratio",
+            kind: OptionKind::Float,
+            description: "Maximum mocks per assertion (default 3.0)",
+        }]
+    }
     fn check(
         &self,
         module: &ParsedModule,
         _all_modules: &[ParsedModule],
-        _ctx: &RuleContext,
+        ctx: &RuleContext,
     ) -> Vec<Violation> {
+        let max_ratio = ctx.options.f64("max_ratio", 3.0);
         let mut violations = Vec::new();
         for test in &module.test_functions {
             if test.mock_count > 0 && test.assertion_count > 0 {
                 let ratio = test.mock_count as f64 / test.assertion_count as f64;
-                if ratio > 3.0 {
+                if ratio > max_ratio {
                     violations.push(make_violation(
                         self.id(),
                         self.name(),
@@ -212,3 +227,483 @@ impl Rule for MockRatioBudgetRule {
         violations
     }
 }
+
+/// Classes and helpers of `unittest.mock` (and `pytest-mock`'s `mocker`) that create a mock.
+const MOCK_FACTORIES: &[&str] = &[
+    "AsyncMock",
+    "MagicMock",
+    "Mock",
+    "NonCallableMagicMock",
+    "NonCallableMock",
+    "create_autospec",
+];
+
+/// Mock methods that verify how a mock was called.
+const INTERACTION_ASSERTIONS: &[&str] = &[
+    "assert_any_call",
+    "assert_awaited",
+    "assert_awaited_once",
+    "assert_awaited_once_with",
+    "assert_awaited_with",
+    "assert_called",
+    "assert_called_once",
+    "assert_called_once_with",
+    "assert_called_with",
+    "assert_has_awaits",
+    "assert_has_calls",
+    "assert_not_awaited",
+    "assert_not_called",
+];
+
+/// Mock attributes that record calls; an `assert` on them verifies an interaction.
+const CALL_RECORDS: &[&str] = &[
+    ".await_count",
+    ".call_args",
+    ".call_count",
+    ".called",
+    ".mock_calls",
+];
+
+/// Kind of `patch` call: `patch(...)`, `patch.object(...)` or `patch.multiple(...)`, from
+/// `unittest.mock`, `mock` or `mocker`. `patch.dict` creates no mock and is not one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Patch {
+    Target,
+    Object,
+    Multiple,
+}
+
+/// A test's function node with its decorators, which `@patch` stacks live in.
+struct TestNode<'t> {
+    function: Node<'t>,
+    decorators: Vec<Node<'t>>,
+}
+
+impl<'t> TestNode<'t> {
+    fn find(root: Node<'t>, test: &TestFunction) -> Option<Self> {
+        let function = function_at_line(root, test.line)?;
+        let decorators = function
+            .parent()
+            .filter(|p| p.kind() == "decorated_definition")
+            .map(|p| {
+                let mut cursor = p.walk();
+                p.named_children(&mut cursor)
+                    .filter(|c| c.kind() == "decorator")
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            function,
+            decorators,
+        })
+    }
+
+    /// Calls in the decorators and the body, not looking into nested functions and classes.
+    fn calls(&self) -> Vec<Node<'t>> {
+        let mut calls = Vec::new();
+        let mut to_visit: Vec<Node<'t>> = self.decorators.clone();
+        to_visit.extend(self.function.child_by_field_name("body"));
+        while let Some(node) = to_visit.pop() {
+            match node.kind() {
+                "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                    continue;
+                }
+                "call" => calls.push(node),
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        calls.sort_by_key(Node::start_byte);
+        calls
+    }
+}
+
+/// The dotted name a call's function refers to, e.g. `unittest.mock.patch.object`.
+fn callee(call: Node, source: &[u8], bindings: &HashMap<String, String>) -> Option<String> {
+    let func = call
+        .child_by_field_name("function")
+        .filter(|f| matches!(f.kind(), "identifier" | "attribute"))?;
+    Some(resolve_callee(func, source, bindings))
+}
+
+fn patch_kind(callee: &str) -> Option<Patch> {
+    let mut segments = callee.rsplit('.');
+    match (segments.next()?, segments.next()) {
+        ("patch", _) => Some(Patch::Target),
+        ("object", Some("patch")) => Some(Patch::Object),
+        ("multiple", Some("patch")) => Some(Patch::Multiple),
+        _ => None,
+    }
+}
+
+fn creates_mock(callee: &str) -> bool {
+    let last = callee.rsplit('.').next().unwrap_or(callee);
+    MOCK_FACTORIES.contains(&last) || patch_kind(callee).is_some()
+}
+
+/// Rule that flags tests creating more mocks than the configured limit: `Mock()`,
+/// `MagicMock()`, `patch()` / `mocker.patch()` calls and `@patch` decorators on the test.
+pub struct MockOveruseRule;
+
+impl Rule for MockOveruseRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MOC-005"
+    }
+    fn name(&self) -> &'static str {
+        "MockOveruseRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "max_mocks",
+            kind: OptionKind::Integer,
+            description: "Maximum mocks and patches created per test, decorators included \
+                          (default 5)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let bindings = crate::rules::ast::import_bindings(root, source);
+        let max = ctx.options.usize("max_mocks", 5);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(node) = TestNode::find(root, test) else {
+                continue;
+            };
+            let calls = node.calls();
+            let created: Vec<_> = calls
+                .iter()
+                .filter(|c| callee(**c, source, &bindings).is_some_and(|f| creates_mock(&f)))
+                .collect();
+            if created.len() <= max {
+                continue;
+            }
+            let decorated = created
+                .iter()
+                .filter(|c| c.start_byte() < node.function.start_byte())
+                .count();
+            let from_decorators = if decorated > 0 {
+                format!(", {decorated} of them patch decorators")
+            } else {
+                String::new()
+            };
+            let mut v = make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Test '{}' creates {} mocks (limit {max}){from_decorators}",
+                    test.name,
+                    created.len()
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some(
+                    "Test the unit through fewer seams: use real collaborators or fakes, or split the test"
+                        .to_string(),
+                ),
+                Some(test.name.clone()),
+            );
+            v.metadata.insert("mocks".to_string(), created.len().into());
+            violations.push(v);
+        }
+        violations
+    }
+}
+
+/// Rule that flags interaction-only tests: every check is a mock call verification
+/// (`assert_called`, `assert_called_once`, `assert_called_with`, ...), none is on a
+/// return value or on state. Tests the older mock-only check of PYTEST-MNT-005 reports are
+/// left to it, so that existing configurations and suppressions keep matching.
+pub struct InteractionOnlyTestRule;
+
+impl InteractionOnlyTestRule {
+    /// Counts of mock call verifications and of other assertions in a test body.
+    fn assertions(body: Node, source: &[u8], aliases: &PytestAliases) -> (usize, usize) {
+        let mut interactions = 0;
+        let mut others = 0;
+        let mut to_visit = vec![body];
+        while let Some(node) = to_visit.pop() {
+            match node.kind() {
+                "function_definition" | "decorated_definition" | "class_definition" | "lambda" => {
+                    continue;
+                }
+                "assert_statement" => {
+                    let text = node_text(node, source);
+                    if CALL_RECORDS.iter().any(|r| text.contains(r)) {
+                        interactions += 1;
+                    } else {
+                        others += 1;
+                    }
+                    continue;
+                }
+                "with_statement" => {
+                    let raises = node
+                        .named_children(&mut node.walk())
+                        .find(|c| c.kind() == "with_clause")
+                        .is_some_and(|c| contains_exception_assertion(c, source, aliases));
+                    if raises {
+                        others += 1;
+                    }
+                }
+                "call" => {
+                    let func = node.child_by_field_name("function");
+                    let name = match func.map(|f| (f.kind(), f)) {
+                        Some(("attribute", f)) => f
+                            .child_by_field_name("attribute")
+                            .map(|a| node_text(a, source)),
+                        Some(("identifier", f)) => Some(node_text(f, source)),
+                        _ => None,
+                    };
+                    if let Some(name) = name {
+                        if INTERACTION_ASSERTIONS.contains(&name) {
+                            interactions += 1;
+                        } else if name.starts_with("assert") || name == "fail" {
+                            // `self.assertEqual(...)`, `assert_frame_equal(...)`, helpers
+                            others += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        (interactions, others)
+    }
+}
+
+impl Rule for InteractionOnlyTestRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MOC-006"
+    }
+    fn name(&self) -> &'static str {
+        "InteractionOnlyTestRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn reliable_on_partial_input(&self) -> bool {
+        false
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let aliases = PytestAliases::from_root(root, source);
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(body) =
+                function_at_line(root, test.line).and_then(|f| f.child_by_field_name("body"))
+            else {
+                continue;
+            };
+            // PYTEST-MNT-005 already reports the tests it recognizes as mock-only
+            if only_verifies_mocks(test) {
+                continue;
+            }
+            let (interactions, others) = Self::assertions(body, source, &aliases);
+            if interactions == 0 || others > 0 {
+                continue;
+            }
+            violations.push(make_violation(
+                self.id(),
+                self.name(),
+                self.severity(),
+                self.category(),
+                format!(
+                    "Interaction-only test '{}': it checks which mock calls were made, never a result or state",
+                    test.name
+                ),
+                module.file_path.clone(),
+                test.line,
+                Some(
+                    "Assert on the return value or the resulting state as well as on the calls"
+                        .to_string(),
+                ),
+                Some(test.name.clone()),
+            ));
+        }
+        violations
+    }
+}
+
+/// Rule that flags `patch()` / `patch.object()` of project code without `autospec=True`
+/// (or a `spec`, or a replacement object): the `MagicMock` put in place accepts any call,
+/// so the test keeps passing when the real signature changes.
+pub struct SpeclessProjectPatchRule;
+
+impl SpeclessProjectPatchRule {
+    /// Module the patched object lives in: the target string of `patch("shop.cart.total")`,
+    /// or the imported module of `patch.object(cart, "total")`.
+    fn patched_module(
+        call: Node,
+        kind: Patch,
+        source: &[u8],
+        bindings: &HashMap<String, String>,
+    ) -> Option<String> {
+        let args = call.child_by_field_name("arguments")?;
+        let first = args
+            .named_child(0)
+            .filter(|a| a.kind() != "keyword_argument")?;
+        match kind {
+            Patch::Target if first.kind() == "string" => Some(
+                node_text(first, source)
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_string(),
+            ),
+            Patch::Object if matches!(first.kind(), "identifier" | "attribute") => {
+                let head = node_text(first, source).split('.').next()?;
+                bindings.get(head)?;
+                Some(resolve_callee(first, source, bindings))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the patch swaps in an object of its own or a mock with a spec.
+    fn has_spec(call: Node, kind: Patch, source: &[u8]) -> bool {
+        let Some(args) = call.child_by_field_name("arguments") else {
+            return true;
+        };
+        let mut cursor = args.walk();
+        let mut positional = 0;
+        for arg in args.named_children(&mut cursor) {
+            if arg.kind() != "keyword_argument" {
+                positional += 1;
+                continue;
+            }
+            let name = arg
+                .child_by_field_name("name")
+                .map_or("", |n| node_text(n, source));
+            let value = arg
+                .child_by_field_name("value")
+                .map_or("", |v| node_text(v, source));
+            match name {
+                "autospec" | "spec" | "spec_set" if !matches!(value, "False" | "None") => {
+                    return true;
+                }
+                "new" | "new_callable" => return true,
+                _ => {}
+            }
+        }
+        // `patch(target, new)` and `patch.object(obj, attr, new)`
+        let new_at = if kind == Patch::Object { 3 } else { 2 };
+        positional >= new_at
+    }
+}
+
+impl Rule for SpeclessProjectPatchRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MOC-007"
+    }
+    fn name(&self) -> &'static str {
+        "SpeclessProjectPatchRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "project_packages",
+            kind: OptionKind::StringList,
+            description: "Module prefixes of the code under test, e.g. [\"shop\"]; by default any \
+                          module outside the standard library and test tooling counts",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let bindings = crate::rules::ast::import_bindings(root, source);
+        let packages = ctx.options.string_list("project_packages");
+
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let Some(node) = TestNode::find(root, test) else {
+                continue;
+            };
+            for call in node.calls() {
+                let Some(kind) = callee(call, source, &bindings).and_then(|f| patch_kind(&f))
+                else {
+                    continue;
+                };
+                let Some(target) = Self::patched_module(call, kind, source, &bindings) else {
+                    continue;
+                };
+                if !is_project_module(&target, &packages) || Self::has_spec(call, kind, source) {
+                    continue;
+                }
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "Test '{}' patches '{target}' with a spec-less MagicMock, which accepts any call",
+                        test.name
+                    ),
+                    module.file_path.clone(),
+                    test.line,
+                    Some(
+                        "Pass autospec=True so calls are checked against the real signature"
+                            .to_string(),
+                    ),
+                    Some(test.name.clone()),
+                );
+                set_span(&mut v, call);
+                v.metadata
+                    .insert("target".to_string(), target.as_str().into());
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
@@ -1,6 +1,8 @@
 use crate::models::{Fixture, ParsedModule, Violation};
+use crate::project::ProjectContext;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use tree_sitter::Tree;
 
 /// Context passed to each rule containing cross-module fixture information.
 pub struct RuleContext<'a> {
@@ -8,6 +10,150 @@ pub struct RuleContext<'a> {
     pub used_fixture_names: &'a HashSet<String>,
     pub fixture_locations: &'a HashMap<String, Vec<PathBuf>>,
     pub session_mutable_fixtures: &'a HashSet<String>,
+    /// The project index, in `--project-mode` only.
+    pub project: Option<ProjectContext<'a>>,
+    /// Options configured for the rule currently being checked.
+    pub options: RuleOptions<'a>,
+    /// Syntax tree of the module being checked, parsed once for all rules; `None` if
+    /// tree-sitter could not parse it.
+    pub tree: Option<&'a Tree>,
+}
+
+/// Value type accepted by a rule option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    Integer,
+    Float,
+    Boolean,
+    String,
+    StringList,
+    /// A string holding a regular expression.
+    Regex,
+}
+
+impl OptionKind {
+    /// Whether a TOML value is acceptable for this kind. Integers are accepted for floats.
+    #[must_use]
+    pub fn accepts(self, value: &toml::Value) -> bool {
+        match self {
+            Self::Integer => value.as_integer().is_some_and(|i| i >= 0),
+            Self::Float => value.is_float() || value.is_integer(),
+            Self::Boolean => value.is_bool(),
+            Self::String => value.is_str(),
+            Self::Regex => value
+                .as_str()
+                .is_some_and(|pattern| regex::Regex::new(pattern).is_ok()),
+            Self::StringList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(toml::Value::is_str)),
+        }
+    }
+}
+
+impl OptionKind {
+    /// Short machine-readable name, as listed by `pytest-linter rules --format json`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Boolean => "boolean",
+            Self::String => "string",
+            Self::StringList => "string-list",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+impl std::fmt::Display for OptionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer => write!(f, "a non-negative integer"),
+            Self::Float => write!(f, "a number"),
+            Self::Boolean => write!(f, "a boolean"),
+            Self::String => write!(f, "a string"),
+            Self::StringList => write!(f, "a list of strings"),
+            Self::Regex => write!(f, "a valid regular expression"),
+        }
+    }
+}
+
+/// An option a rule accepts in its `[rules.<id>]` config table.
+#[derive(Debug, Clone, Copy)]
+pub struct RuleOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+    pub description: &'static str,
+}
+
+/// Read-only view over the options configured for one rule. Values have already been
+/// type-checked against the rule's declared options when the config was loaded, so the
+/// getters fall back to the default only when the option is unset.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleOptions<'a> {
+    values: Option<&'a toml::Table>,
+}
+
+impl<'a> RuleOptions<'a> {
+    #[must_use]
+    pub fn new(values: &'a toml::Table) -> Self {
+        Self {
+            values: Some(values),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&'a toml::Value> {
+        self.values.and_then(|v| v.get(name))
+    }
+
+    #[must_use]
+    pub fn usize(&self, name: &str, default: usize) -> usize {
+        self.get(name)
+            .and_then(toml::Value::as_integer)
+            .and_then(|i| usize::try_from(i).ok())
+            .unwrap_or(default)
+    }
+
+    #[must_use]
+    pub fn f64(&self, name: &str, default: f64) -> f64 {
+        match self.get(name) {
+            Some(toml::Value::Float(f)) => *f,
+            #[allow(clippy::cast_precision_loss)]
+            Some(toml::Value::Integer(i)) => *i as f64,
+            _ => default,
+        }
+    }
+
+    #[must_use]
+    pub fn bool(&self, name: &str, default: bool) -> bool {
+        self.get(name)
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(default)
+    }
+
+    #[must_use]
+    pub fn string_list(&self, name: &str) -> Vec<String> {
+        self.get(name)
+            .and_then(toml::Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn string(&self, name: &str) -> Option<&'a str> {
+        self.get(name).and_then(toml::Value::as_str)
+    }
+
+    /// A regex option compiled to match whole strings, or `None` when unset.
+    #[must_use]
+    pub fn regex(&self, name: &str) -> Option<regex::Regex> {
+        regex::Regex::new(&format!("^(?:{})$", self.string(name)?)).ok()
+    }
 }
 
 /// Trait implemented by all lint rules.
@@ -16,10 +162,42 @@ pub trait Rule: Send + Sync {
     fn id(&self) -> &'static str;
     /// Human-readable rule name.
     fn name(&self) -> &'static str;
-    /// Default severity for violations produced by this rule.
+    /// Default severity for violations produced by this rule. A rule may report some
+    /// violations at another severity; a severity set in the configuration replaces both.
     fn severity(&self) -> crate::models::Severity;
     /// Category this rule belongs to.
     fn category(&self) -> crate::models::Category;
+    /// Extra names accepted wherever a rule is referenced (config keys, suppressions).
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+    /// One-line description, derived from the rule name unless overridden.
+    fn description(&self) -> String {
+        let slug = rule_slug(self.name()).replace('-', " ");
+        let mut chars = slug.chars();
+        chars.next().map_or_else(String::new, |first| {
+            first.to_uppercase().chain(chars).collect()
+        })
+    }
+    /// Whether violations of this rule carry a machine-applicable fix (`--fix`).
+    fn fixable(&self) -> bool {
+        false
+    }
+    /// Options this rule accepts in its config table. Most rules take none.
+    fn options(&self) -> &'static [RuleOption] {
+        &[]
+    }
+    /// Tags given to violations that do not set their own, e.g. `Unnecessary` for rules
+    /// that flag dead code.
+    fn tags(&self) -> &'static [crate::models::Tag] {
+        &[]
+    }
+    /// Whether violations hold in code around a syntax error. Rules that report something
+    /// missing (an assertion, a use of a fixture) say no: what they look for may be in the
+    /// code that did not parse, so their violations in the broken region are dropped.
+    fn reliable_on_partial_input(&self) -> bool {
+        true
+    }
     /// Check a module and return any violations found.
     fn check(
         &self,
@@ -29,17 +207,29 @@ pub trait Rule: Send + Sync {
     ) -> Vec<Violation>;
 }
 
+pub mod ast;
+pub mod custom;
+pub mod debugging;
+pub mod environment;
+pub mod exceptions;
 pub mod fixtures;
 pub mod flakiness;
 pub mod infrastructure;
+pub mod isolation;
 pub mod maintenance;
+pub mod markers;
 pub mod mocking;
+pub mod naming;
+pub mod suppression;
+pub mod syntax;
+pub mod unittest;
 
 /// Return all available lint rules.
 #[must_use]
 pub fn all_rules() -> Vec<Box<dyn Rule>> {
     vec![
         Box::new(flakiness::TimeSleepRule),
+        Box::new(flakiness::SleepPollingLoopRule),
         Box::new(flakiness::FileIoRule),
         Box::new(flakiness::NetworkImportRule),
         Box::new(flakiness::CwdDependencyRule),
@@ -48,6 +238,9 @@ pub fn all_rules() -> Vec<Box<dyn Rule>> {
         Box::new(flakiness::XdistFixtureIoRule),
         Box::new(flakiness::RandomWithoutSeedRule),
         Box::new(flakiness::SubprocessWithoutTimeoutRule),
+        Box::new(flakiness::FloatEqualityRule),
+        Box::new(isolation::SharedModuleStateRule),
+        Box::new(environment::EnvironmentDependentRule),
         Box::new(maintenance::TestLogicRule),
         Box::new(maintenance::MagicAssertRule),
         Box::new(maintenance::SuboptimalAssertRule),
@@ -55,6 +248,12 @@ pub fn all_rules() -> Vec<Box<dyn Rule>> {
         Box::new(maintenance::MockOnlyVerifyRule),
         Box::new(maintenance::AssertionRouletteRule),
         Box::new(maintenance::RawExceptionHandlingRule),
+        Box::new(maintenance::AssertInLoopRule),
+        Box::new(maintenance::SwallowedAssertionRule),
+        Box::new(maintenance::EagerTestRule),
+        Box::new(maintenance::ComputedExpectedValueRule),
+        Box::new(debugging::DebuggerCallRule),
+        Box::new(debugging::PrintInTestRule),
         Box::new(maintenance::BddMissingScenarioRule),
         Box::new(maintenance::PropertyTestHintRule),
         Box::new(maintenance::ParametrizeEmptyRule),
@@ -78,27 +277,154 @@ pub fn all_rules() -> Vec<Box<dyn Rule>> {
         Box::new(maintenance::DuplicateTestBodiesRule),
         Box::new(maintenance::SleepWithValueRule),
         Box::new(maintenance::TestNameLengthRule),
+        Box::new(naming::UninformativeTestNameRule),
+        Box::new(naming::NumberedTestNameRule),
+        Box::new(naming::TestNamePatternRule),
         Box::new(fixtures::NoContractHintRule),
         Box::new(mocking::PatchTargetingDefinitionModuleRule),
         Box::new(mocking::MagicMockOnAsyncRule),
         Box::new(mocking::PatchInitBypassRule),
         Box::new(mocking::MockRatioBudgetRule),
+        Box::new(mocking::MockOveruseRule),
+        Box::new(mocking::InteractionOnlyTestRule),
+        Box::new(mocking::SpeclessProjectPatchRule),
         Box::new(infrastructure::NetworkBanMissingRule),
         Box::new(infrastructure::LiveSuiteUnmarkedRule),
         Box::new(infrastructure::NonIdiomaticMonkeyPatchRule),
         Box::new(infrastructure::MacOsCopyArtefactRule),
         Box::new(maintenance::InlineSchemaRedeclaredRule),
+        Box::new(suppression::UnknownSuppressionRule),
+        Box::new(maintenance::TooManyAssertionsRule),
+        Box::new(maintenance::ObscureTestRule),
+        Box::new(maintenance::SnapshotOveruseRule),
+        Box::new(exceptions::RaisesBlockTrailingCodeRule),
+        Box::new(markers::SkipWithoutReasonRule),
+        Box::new(maintenance::TautologicalAssertionRule),
+        Box::new(maintenance::DuplicateTestNameRule),
+        Box::new(maintenance::SuggestParametrizeRule),
+        Box::new(fixtures::UnusedFixtureParameterRule),
+        Box::new(fixtures::AutouseConftestSideEffectRule),
+        Box::new(fixtures::PointlessYieldFixtureRule),
+        Box::new(unittest::DeprecatedUnittestAliasRule),
+        Box::new(unittest::UnittestSetUpSideEffectRule),
+        Box::new(syntax::SyntaxErrorRule),
     ]
 }
 
+/// Kebab-case slug derived from a rule name (e.g. `NoAssertionRule` -> `no-assertion`).
+#[must_use]
+pub fn rule_slug(name: &str) -> String {
+    let base = name.strip_suffix("Rule").unwrap_or(name);
+    let mut slug = String::with_capacity(base.len() + 4);
+    for (i, ch) in base.chars().enumerate() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                slug.push('-');
+            }
+            slug.extend(ch.to_lowercase());
+        } else {
+            slug.push(ch);
+        }
+    }
+    slug
+}
+
+/// Resolve a user-supplied rule reference (ID, name, or slug) to its canonical rule ID.
+#[must_use]
+pub fn resolve_rule_id(key: &str) -> Option<&'static str> {
+    let key = key.trim();
+    all_rules()
+        .iter()
+        .find(|r| {
+            r.id().eq_ignore_ascii_case(key)
+                || r.name() == key
+                || rule_slug(r.name()) == key
+                || r.aliases().contains(&key)
+        })
+        .map(|r| r.id())
+}
+
+/// Resolve a rule reference like [`resolve_rule_id`], also accepting the ID of a custom
+/// rule ([`custom`]) as is. Custom IDs are not checked against the loaded rules files.
+#[must_use]
+pub fn resolve_rule_key(key: &str) -> Option<String> {
+    let key = key.trim();
+    resolve_rule_id(key)
+        .map(str::to_string)
+        .or_else(|| custom::is_custom_rule_id(key).then(|| key.to_string()))
+}
+
+/// Documentation page of a rule.
+#[must_use]
+pub fn rule_docs_url(rule_id: &str) -> String {
+    format!(
+        "https://github.com/Jonathangadeaharder/pytest-linter/blob/main/docs/rules/{rule_id}.md"
+    )
+}
+
+/// Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The rule reference (ID, slug or alias) closest to an unknown one, when it is close
+/// enough to be a plausible typo.
+#[must_use]
+pub fn suggest_rule(key: &str) -> Option<String> {
+    let key = key.trim().to_ascii_lowercase();
+    let max_distance = (key.chars().count() / 6).max(2);
+    all_rules()
+        .iter()
+        .flat_map(|r| {
+            [r.id().to_string(), rule_slug(r.name())]
+                .into_iter()
+                .chain(r.aliases().iter().map(|a| (*a).to_string()))
+        })
+        .map(|candidate| {
+            (
+                edit_distance(&key, &candidate.to_ascii_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// `unknown rule` message for `key`, with a suggestion when one is close.
+#[must_use]
+pub fn unknown_rule_message(key: &str) -> String {
+    match suggest_rule(key) {
+        Some(suggestion) => format!("unknown rule `{key}` (did you mean `{suggestion}`?)"),
+        None => format!("unknown rule `{key}`"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_default_description_from_name() {
+        assert_eq!(maintenance::NoAssertionRule.description(), "No assertion");
+        assert_eq!(flakiness::TimeSleepRule.description(), "Time sleep");
+    }
+
     #[test]
     fn test_all_rules_count() {
         let rules = all_rules();
-        assert_eq!(rules.len(), 49);
+        assert_eq!(rules.len(), 80);
     }
 
     #[test]
@@ -118,6 +444,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_suggest_rule_by_edit_distance() {
+        assert_eq!(
+            suggest_rule("PYTEST-FLK-01").as_deref(),
+            Some("PYTEST-FLK-001")
+        );
+        assert_eq!(suggest_rule("no-asertion").as_deref(), Some("no-assertion"));
+        assert_eq!(suggest_rule("sleepytest").as_deref(), Some("sleepy-test"));
+        assert_eq!(suggest_rule("completely-unrelated-words"), None);
+    }
+
+    #[test]
+    fn test_rule_slug_from_name() {
+        assert_eq!(rule_slug("NoAssertionRule"), "no-assertion");
+        assert_eq!(rule_slug("TimeSleepRule"), "time-sleep");
+    }
+
+    #[test]
+    fn test_resolve_rule_id_accepts_id_name_and_slug() {
+        assert_eq!(resolve_rule_id("PYTEST-MNT-004"), Some("PYTEST-MNT-004"));
+        assert_eq!(resolve_rule_id("pytest-mnt-004"), Some("PYTEST-MNT-004"));
+        assert_eq!(resolve_rule_id("NoAssertionRule"), Some("PYTEST-MNT-004"));
+        assert_eq!(resolve_rule_id("no-assertion"), Some("PYTEST-MNT-004"));
+        assert_eq!(resolve_rule_id("sleepy-test"), Some("PYTEST-FLK-001"));
+        assert_eq!(resolve_rule_id("no-such-rule"), None);
+    }
+
     #[test]
     fn test_expected_rule_ids_present() {
         let rules = all_rules();
@@ -0,0 +1,351 @@
+//! Test naming: names that say nothing about the behaviour under test, names that differ
+//! from a sibling only by a trailing number, and an opt-in naming convention. Name length is
+//! checked by [`TestNameLengthRule`](crate::rules::maintenance::TestNameLengthRule).
+//! Diagnostics sit on the name of the `def`, so an editor rename targets the right range.
+
+use std::collections::{BTreeMap, HashSet};
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, ParsedModule, Severity, TestFunction, Violation};
+use crate::rules::ast::{function_at_line, node_text, set_span};
+use crate::rules::{OptionKind, Rule, RuleContext, RuleOption};
+
+/// Words that carry no information about what a test checks.
+const STOPLIST: &[&str] = &[
+    "a",
+    "bar",
+    "baz",
+    "case",
+    "check",
+    "dummy",
+    "eggs",
+    "example",
+    "foo",
+    "func",
+    "function",
+    "ham",
+    "it",
+    "method",
+    "misc",
+    "new",
+    "ok",
+    "quux",
+    "qux",
+    "sample",
+    "should",
+    "something",
+    "spam",
+    "stuff",
+    "temp",
+    "test",
+    "testing",
+    "tests",
+    "the",
+    "thing",
+    "things",
+    "tmp",
+    "todo",
+    "work",
+    "working",
+    "works",
+    "xxx",
+];
+
+/// Words of a test name after its `test` prefix, lowercased. Splits on underscores and
+/// between letters and digits (`test_case1` -> `case`, `1`).
+fn name_words(name: &str) -> Vec<String> {
+    let rest = name.strip_prefix("test").unwrap_or(name);
+    let mut words = Vec::new();
+    for part in rest.split('_') {
+        let mut word = String::new();
+        let mut prev: Option<char> = None;
+        for ch in part.chars() {
+            let boundary = prev.is_some_and(|p| p.is_ascii_digit() != ch.is_ascii_digit());
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(ch.to_lowercase());
+            prev = Some(ch);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// The `def` name node of a test and the class it is defined in, if any.
+fn name_and_owner<'t>(
+    root: Node<'t>,
+    test: &TestFunction,
+    source: &'t [u8],
+) -> Option<(Node<'t>, Option<&'t str>)> {
+    let function = function_at_line(root, test.line)?;
+    let name = function.child_by_field_name("name")?;
+    let mut parent = function.parent();
+    while let Some(node) = parent {
+        match node.kind() {
+            "class_definition" => {
+                let owner = node
+                    .child_by_field_name("name")
+                    .map(|n| node_text(n, source));
+                return Some((name, owner));
+            }
+            "function_definition" => break,
+            _ => parent = node.parent(),
+        }
+    }
+    Some((name, None))
+}
+
+fn report(
+    rule: &dyn Rule,
+    module: &ParsedModule,
+    test: &TestFunction,
+    name: Option<Node>,
+    message: String,
+    suggestion: &str,
+) -> Violation {
+    let mut v = make_violation(
+        rule.id(),
+        rule.name(),
+        rule.severity(),
+        rule.category(),
+        message,
+        module.file_path.clone(),
+        test.line,
+        Some(suggestion.to_string()),
+        Some(test.name.clone()),
+    );
+    if let Some(name) = name {
+        set_span(&mut v, name);
+    }
+    v
+}
+
+/// Rule that detects test names made only of placeholder words and numbers (`test_1`,
+/// `test_foo`, `test_works`, `test_test`), which say nothing when they fail in a report.
+pub struct UninformativeTestNameRule;
+
+impl Rule for UninformativeTestNameRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-030"
+    }
+    fn name(&self) -> &'static str {
+        "UninformativeTestNameRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "forbidden_words",
+            kind: OptionKind::StringList,
+            description: "Words treated as placeholders on top of the built-in stoplist, \
+                          e.g. [\"scenario\", \"happy\"] (default [])",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.is_empty() {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let forbidden: HashSet<String> = ctx
+            .options
+            .string_list("forbidden_words")
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect();
+        let source = module.source.as_bytes();
+        let mut violations = Vec::new();
+        for test in &module.test_functions {
+            let uninformative = name_words(&test.name).iter().all(|w| {
+                w.chars().all(|c| c.is_ascii_digit())
+                    || STOPLIST.contains(&w.as_str())
+                    || forbidden.contains(w)
+            });
+            if !uninformative {
+                continue;
+            }
+            let name = name_and_owner(tree.root_node(), test, source).map(|(name, _)| name);
+            violations.push(report(
+                self,
+                module,
+                test,
+                name,
+                format!(
+                    "Test name '{}' does not say what behaviour is tested",
+                    test.name
+                ),
+                "Name the unit, the scenario and the expected outcome, e.g. test_login_with_expired_token_is_rejected",
+            ));
+        }
+        violations
+    }
+}
+
+/// The name with a trailing one- or two-digit number removed (`test_login2` ->
+/// `test_login`), or `None` when it does not end in such a number.
+fn strip_counter(name: &str) -> Option<&str> {
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if !(1..=2).contains(&(name.len() - stem.len())) {
+        return None;
+    }
+    Some(stem.trim_end_matches('_'))
+}
+
+/// Rule that detects tests whose name differs from a sibling's only by a trailing number
+/// (`test_login`, `test_login2`): copies that were never given a name of their own.
+pub struct NumberedTestNameRule;
+
+impl Rule for NumberedTestNameRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-031"
+    }
+    fn name(&self) -> &'static str {
+        "NumberedTestNameRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if module.test_functions.len() < 2 {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let source = module.source.as_bytes();
+        let located: Vec<_> = module
+            .test_functions
+            .iter()
+            .filter_map(|test| {
+                let (name, owner) = name_and_owner(tree.root_node(), test, source)?;
+                Some((test, name, owner))
+            })
+            .collect();
+        // Stems that some numbered test was derived from, per module or class
+        let numbered: HashSet<(Option<&str>, &str)> = located
+            .iter()
+            .filter_map(|(test, _, owner)| Some((*owner, strip_counter(&test.name)?)))
+            // `test_1`, `test_2` are left to UninformativeTestNameRule
+            .filter(|(_, stem)| !name_words(stem).is_empty())
+            .collect();
+        let mut groups: BTreeMap<(Option<&str>, &str), Vec<_>> = BTreeMap::new();
+        for (test, name, owner) in &located {
+            let stem = strip_counter(&test.name).unwrap_or(&test.name);
+            if numbered.contains(&(*owner, stem)) {
+                groups
+                    .entry((*owner, stem))
+                    .or_default()
+                    .push((*test, *name));
+            }
+        }
+
+        let mut violations = Vec::new();
+        for members in groups.values_mut() {
+            // Compare against the unnumbered original when there is one
+            members.sort_by_key(|(t, _)| (strip_counter(&t.name).is_some(), t.line));
+            let Some(((first, _), rest)) = members.split_first() else {
+                continue;
+            };
+            // Exact redefinitions are DuplicateTestNameRule's
+            for (test, name) in rest.iter().filter(|(t, _)| t.name != first.name) {
+                violations.push(report(
+                    self,
+                    module,
+                    test,
+                    Some(*name),
+                    format!(
+                        "Test '{}' differs from '{}' (line {}) only by a trailing number",
+                        test.name, first.name, first.line
+                    ),
+                    "Rename the test after what it checks differently, or merge the copies with @pytest.mark.parametrize",
+                ));
+            }
+        }
+        violations.sort_by_key(|v| v.line);
+        violations
+    }
+}
+
+/// Rule that enforces a project naming convention for tests, given as a regular expression
+/// in its `pattern` option. Without a pattern the rule reports nothing.
+pub struct TestNamePatternRule;
+
+impl Rule for TestNamePatternRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-032"
+    }
+    fn name(&self) -> &'static str {
+        "TestNamePatternRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn options(&self) -> &'static [RuleOption] {
+        &[RuleOption {
+            name: "pattern",
+            kind: OptionKind::Regex,
+            description: "Regular expression every test name must match in full, e.g. \
+                          \"test_[a-z0-9]+_[a-z0-9_]+\" (default: unset, rule off)",
+        }]
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let Some(pattern) = ctx.options.regex("pattern") else {
+            return vec![];
+        };
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let source = module.source.as_bytes();
+        let convention = ctx.options.string("pattern").unwrap_or_default();
+        module
+            .test_functions
+            .iter()
+            .filter(|test| !pattern.is_match(&test.name))
+            .map(|test| {
+                let name = name_and_owner(tree.root_node(), test, source).map(|(name, _)| name);
+                report(
+                    self,
+                    module,
+                    test,
+                    name,
+                    format!(
+                        "Test name '{}' does not match the naming convention `{convention}`",
+                        test.name
+                    ),
+                    "Rename the test to follow the project's naming convention",
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+//! Inline suppression directives (`# pytl: ignore[...]`) and the rule that validates them.
+
+use crate::engine::make_violation;
+use crate::models::{Category, Comment, ParsedModule, Severity, Violation};
+use crate::rules::{resolve_rule_key, suggest_rule, Rule, RuleContext};
+
+/// Where a suppression directive applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionScope {
+    /// `# pytl: ignore[...]` — the commented line (trailing) or the next line (own line).
+    Line,
+    /// `# pytl: ignore-file[...]` — the whole file; honoured up to the first statement.
+    File,
+}
+
+/// A parsed `# pytl: ...` directive. An empty `rules` list means "all rules".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuppressionDirective {
+    pub scope: SuppressionScope,
+    pub rules: Vec<String>,
+}
+
+/// Parse a `# pytl: ignore[...]` or `# pytl: ignore-file[...]` directive from a comment.
+#[must_use]
+pub fn parse_suppression_directive(comment: &str) -> Option<SuppressionDirective> {
+    let body = comment.trim_start_matches('#').trim_start();
+    let rest = body.strip_prefix("pytl:")?.trim_start();
+
+    let (scope, rest) = if let Some(r) = rest.strip_prefix("ignore-file") {
+        (SuppressionScope::File, r)
+    } else if let Some(r) = rest.strip_prefix("ignore") {
+        (SuppressionScope::Line, r)
+    } else {
+        return None;
+    };
+
+    let rules = if let Some(inner) = rest.strip_prefix('[') {
+        let end = inner.find(']')?;
+        inner[..end]
+            .split(',')
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty())
+            .collect()
+    } else if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        vec![]
+    } else {
+        return None;
+    };
+
+    Some(SuppressionDirective { scope, rules })
+}
+
+/// Line number of the first statement after the module docstring, ignoring comments and blank
+/// lines (file-level directives must be on or above it); `usize::MAX` when there is none.
+#[must_use]
+pub fn first_statement_line(source: &str) -> usize {
+    let is_code = |l: &str| {
+        let t = l.trim();
+        !t.is_empty() && !t.starts_with('#')
+    };
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(first) = lines.iter().position(|l| is_code(l)) else {
+        return usize::MAX;
+    };
+    let Some((end, rest)) = docstring_end(&lines, first) else {
+        return first + 1;
+    };
+    if is_code(rest) {
+        return end + 1;
+    }
+    lines[end + 1..]
+        .iter()
+        .position(|l| is_code(l))
+        .map_or(usize::MAX, |idx| end + idx + 2)
+}
+
+/// Where a string literal opening line `first` closes: the line index and the text after it.
+fn docstring_end<'a>(lines: &[&'a str], first: usize) -> Option<(usize, &'a str)> {
+    let body = lines[first]
+        .trim_start()
+        .trim_start_matches(['r', 'R', 'u', 'U']);
+    let quote = [r#"""""#, "'''", "\"", "'"]
+        .into_iter()
+        .find(|q| body.starts_with(q))?;
+    let mut rest = &body[quote.len()..];
+    let mut line = first;
+    loop {
+        if let Some(close) = rest.find(quote) {
+            return Some((line, &rest[close + quote.len()..]));
+        }
+        if quote.len() == 1 {
+            return None;
+        }
+        line += 1;
+        rest = lines.get(line)?;
+    }
+}
+
+/// Line a line-scoped directive applies to: its own line when trailing code, else the next line.
+#[must_use]
+pub fn directive_target_line(comment: &Comment) -> usize {
+    if comment.is_trailing {
+        comment.line
+    } else {
+        comment.line + 1
+    }
+}
+
+/// Rule that flags suppression directives naming unknown rules or misplaced file-level ignores.
+pub struct UnknownSuppressionRule;
+
+impl Rule for UnknownSuppressionRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-SUP-001"
+    }
+    fn name(&self) -> &'static str {
+        "UnknownSuppressionRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        _ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let first_stmt = first_statement_line(&module.source);
+        for comment in &module.comments {
+            let Some(directive) = parse_suppression_directive(&comment.text) else {
+                continue;
+            };
+            for rule in &directive.rules {
+                if resolve_rule_key(rule).is_none() {
+                    let mut v = make_violation(
+                        self.id(),
+                        self.name(),
+                        self.severity(),
+                        self.category(),
+                        format!("Suppression comment references unknown rule '{rule}'"),
+                        module.file_path.clone(),
+                        comment.line,
+                        Some(match suggest_rule(rule) {
+                            Some(suggestion) => format!("Did you mean '{suggestion}'?"),
+                            None => "Use a rule ID such as PYTEST-MNT-004 or its name".to_string(),
+                        }),
+                        None,
+                    );
+                    v.col = Some(comment.col);
+                    violations.push(v);
+                }
+            }
+            if directive.scope == SuppressionScope::File && comment.line > first_stmt {
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    "File-level suppression must appear before the first statement".to_string(),
+                    module.file_path.clone(),
+                    comment.line,
+                    Some(
+                        "Move the `# pytl: ignore-file` comment to the top of the file".to_string(),
+                    ),
+                    None,
+                );
+                v.col = Some(comment.col);
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_ignore() {
+        let d = parse_suppression_directive("# pytl: ignore").unwrap();
+        assert_eq!(d.scope, SuppressionScope::Line);
+        assert!(d.rules.is_empty());
+    }
+
+    #[test]
+    fn test_parse_ignore_with_rules() {
+        let d =
+            parse_suppression_directive("# pytl: ignore[PYTEST-MNT-004, no-assertion]").unwrap();
+        assert_eq!(d.rules, vec!["PYTEST-MNT-004", "no-assertion"]);
+    }
+
+    #[test]
+    fn test_parse_ignore_file() {
+        let d = parse_suppression_directive("# pytl: ignore-file[PYTEST-FLK-001]").unwrap();
+        assert_eq!(d.scope, SuppressionScope::File);
+        assert_eq!(d.rules, vec!["PYTEST-FLK-001"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_comments() {
+        assert!(parse_suppression_directive("# noqa: PYTEST-FLK-001").is_none());
+        assert!(parse_suppression_directive("# pytl: ignored").is_none());
+        assert!(parse_suppression_directive("# pytl: ignore[unterminated").is_none());
+    }
+
+    #[test]
+    fn test_parse_allows_trailing_text() {
+        let d = parse_suppression_directive("#pytl: ignore  -- flaky upstream").unwrap();
+        assert!(d.rules.is_empty());
+    }
+
+    #[test]
+    fn test_first_statement_line_skips_comments() {
+        assert_eq!(first_statement_line("# a\n\n# b\nimport os\n"), 4);
+        assert_eq!(first_statement_line("# only comments\n"), usize::MAX);
+    }
+
+    #[test]
+    fn test_first_statement_line_skips_the_docstring() {
+        assert_eq!(
+            first_statement_line("\"\"\"Tests.\"\"\"\n# c\nimport os\n"),
+            3
+        );
+        assert_eq!(
+            first_statement_line("r'''Tests\nof the API.\n'''\n\nimport os\n"),
+            5
+        );
+        assert_eq!(first_statement_line("\"Tests.\"; import os\n"), 1);
+        assert_eq!(
+            first_statement_line("\"\"\"Only a docstring.\"\"\"\n"),
+            usize::MAX
+        );
+        assert_eq!(first_statement_line("\"\"\"Unterminated\n"), 1);
+    }
+}
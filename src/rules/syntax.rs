@@ -0,0 +1,62 @@
+//! Syntax errors. Tree-sitter recovers around code it cannot parse, so a half-written test
+//! does not stop the run: the error is reported here, the rest of the file is still
+//! analyzed, and rules that cannot be trusted near broken code are silenced in its region.
+//! Empty suites, which tree-sitter accepts, are reported too.
+
+use crate::engine::make_violation;
+use crate::models::{Category, ParsedModule, Severity, Violation};
+use crate::rules::{Rule, RuleContext};
+
+/// Rule ID of [`SyntaxErrorRule`], which `--ignore-syntax-errors` leaves out of the exit
+/// policy.
+pub const SYNTAX_ERROR_RULE_ID: &str = "PYTEST-SYN-001";
+
+/// Rule that reports the code the parser could not make sense of.
+pub struct SyntaxErrorRule;
+
+impl Rule for SyntaxErrorRule {
+    fn id(&self) -> &'static str {
+        SYNTAX_ERROR_RULE_ID
+    }
+    fn name(&self) -> &'static str {
+        "SyntaxErrorRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        _ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        module
+            .syntax_errors
+            .iter()
+            .map(|error| {
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!("Syntax error: {}", error.message),
+                    module.file_path.clone(),
+                    error.line,
+                    Some(
+                        "Fix the syntax; until then, rules that look for something missing \
+                         skip the code around the error"
+                            .to_string(),
+                    ),
+                    None,
+                );
+                v.col = Some(error.col);
+                v.end_line = Some(error.end_line);
+                v.end_col = Some(error.end_col);
+                v
+            })
+            .collect()
+    }
+}
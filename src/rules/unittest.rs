@@ -0,0 +1,304 @@
+//! `unittest.TestCase` checks: deprecated assertion aliases and `setUp` methods that sleep
+//! or reach the network before every test of the class.
+
+use tree_sitter::Node;
+
+use crate::engine::make_violation;
+use crate::models::{Category, Fix, ParsedModule, Severity, Tag, Violation};
+use crate::rules::ast::{node_text, set_span};
+use crate::rules::flakiness::SleepFinder;
+use crate::rules::{Rule, RuleContext};
+
+/// Deprecated `TestCase` method aliases and their modern names. Most were removed in
+/// Python 3.12.
+const DEPRECATED_ALIASES: &[(&str, &str)] = &[
+    ("assertEquals", "assertEqual"),
+    ("assertNotEquals", "assertNotEqual"),
+    ("assertAlmostEquals", "assertAlmostEqual"),
+    ("assertNotAlmostEquals", "assertNotAlmostEqual"),
+    ("assert_", "assertTrue"),
+    ("assertRegexpMatches", "assertRegex"),
+    ("assertNotRegexpMatches", "assertNotRegex"),
+    ("assertRaisesRegexp", "assertRaisesRegex"),
+    ("failUnless", "assertTrue"),
+    ("failIf", "assertFalse"),
+    ("failUnlessEqual", "assertEqual"),
+    ("failIfEqual", "assertNotEqual"),
+    ("failUnlessAlmostEqual", "assertAlmostEqual"),
+    ("failIfAlmostEqual", "assertNotAlmostEqual"),
+    ("failUnlessRaises", "assertRaises"),
+];
+
+/// Methods `unittest` runs around the tests of a class.
+const SETUP_METHODS: &[&str] = &["setUp", "asyncSetUp", "setUpClass"];
+
+/// Modules whose calls reach the network.
+const NETWORK_MODULES: &[&str] = &["requests", "httpx", "aiohttp", "socket", "urllib", "http"];
+
+/// Calls to `self.<alias>(...)` for a deprecated alias: the attribute node and the
+/// modern name.
+fn deprecated_alias_calls<'t>(root: Node<'t>, source: &[u8]) -> Vec<(Node<'t>, &'static str)> {
+    let mut found = Vec::new();
+    let mut to_visit = vec![root];
+    while let Some(node) = to_visit.pop() {
+        if node.kind() == "call" {
+            let func = node
+                .child_by_field_name("function")
+                .filter(|f| f.kind() == "attribute");
+            let on_self = func
+                .and_then(|f| f.child_by_field_name("object"))
+                .is_some_and(|o| node_text(o, source) == "self");
+            if let Some(attr) = func
+                .and_then(|f| f.child_by_field_name("attribute"))
+                .filter(|_| on_self)
+            {
+                let name = node_text(attr, source);
+                if let Some((_, modern)) = DEPRECATED_ALIASES.iter().find(|(old, _)| *old == name) {
+                    found.push((attr, *modern));
+                }
+            }
+        }
+        let mut cursor = node.walk();
+        to_visit.extend(node.named_children(&mut cursor));
+    }
+    found.sort_by_key(|(attr, _)| attr.start_byte());
+    found
+}
+
+/// Rule that flags deprecated `TestCase` assertion aliases (`assertEquals`, `failUnless`).
+pub struct DeprecatedUnittestAliasRule;
+
+impl Rule for DeprecatedUnittestAliasRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-MNT-023"
+    }
+    fn name(&self) -> &'static str {
+        "DeprecatedUnittestAliasRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Maintenance
+    }
+    fn tags(&self) -> &'static [Tag] {
+        &[Tag::Deprecated]
+    }
+    fn fixable(&self) -> bool {
+        true
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if !module.test_functions.iter().any(|t| t.is_unittest) {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let source = module.source.as_bytes();
+        deprecated_alias_calls(tree.root_node(), source)
+            .into_iter()
+            .map(|(attr, modern)| {
+                let old = node_text(attr, source);
+                let test_name = module
+                    .test_functions
+                    .iter()
+                    .find(|t| (t.line..=t.end_line).contains(&(attr.start_position().row + 1)))
+                    .map(|t| t.name.clone());
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!("'self.{old}' is a deprecated alias of 'self.{modern}'"),
+                    module.file_path.clone(),
+                    attr.start_position().row + 1,
+                    Some(format!(
+                        "Use self.{modern}(); the alias was removed in Python 3.12"
+                    )),
+                    test_name,
+                );
+                set_span(&mut v, attr);
+                v.fix = Some(Fix::replace(attr.start_byte(), attr.end_byte(), modern));
+                v
+            })
+            .collect()
+    }
+}
+
+/// First call in `node` that reaches the network (`requests.get(...)`, `urlopen(...)`).
+fn first_network_call<'t>(node: Node<'t>, source: &[u8]) -> Option<Node<'t>> {
+    if node.kind() == "call" {
+        if let Some(func) = node.child_by_field_name("function") {
+            let is_network = match func.kind() {
+                "identifier" => ["urlopen", "create_connection"].contains(&node_text(func, source)),
+                // `requests.get(...)`, `urllib.request.urlopen(...)`, not `<call>.json()`
+                "attribute" => func
+                    .child_by_field_name("object")
+                    .filter(|o| matches!(o.kind(), "identifier" | "attribute"))
+                    .is_some_and(|o| {
+                        let object = node_text(o, source);
+                        NETWORK_MODULES.contains(&object.split('.').next().unwrap_or(object))
+                    }),
+                _ => false,
+            };
+            if is_network {
+                return Some(node);
+            }
+        }
+    }
+    let mut cursor = node.walk();
+    let found = node
+        .named_children(&mut cursor)
+        .find_map(|c| first_network_call(c, source));
+    found
+}
+
+/// `(name, node)` of the set-up methods defined directly in a class body.
+fn setup_methods<'t>(class: Node<'t>, source: &[u8]) -> Vec<(&'static str, Node<'t>)> {
+    let Some(body) = class.child_by_field_name("body") else {
+        return vec![];
+    };
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .filter_map(|stmt| match stmt.kind() {
+            "function_definition" => Some(stmt),
+            "decorated_definition" => stmt.child_by_field_name("definition"),
+            _ => None,
+        })
+        .filter_map(|func| {
+            let name = func
+                .child_by_field_name("name")
+                .map(|n| node_text(n, source))?;
+            SETUP_METHODS
+                .iter()
+                .find(|m| **m == name)
+                .map(|m| (*m, func))
+        })
+        .collect()
+}
+
+/// Rule that flags `TestCase.setUp` (and `setUpClass` / `asyncSetUp`) methods that sleep or
+/// access the network, a cost every test method of the class pays.
+pub struct UnittestSetUpSideEffectRule;
+
+impl Rule for UnittestSetUpSideEffectRule {
+    fn id(&self) -> &'static str {
+        "PYTEST-FLK-012"
+    }
+    fn name(&self) -> &'static str {
+        "UnittestSetUpSideEffectRule"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn category(&self) -> Category {
+        Category::Flakiness
+    }
+    fn check(
+        &self,
+        module: &ParsedModule,
+        _all_modules: &[ParsedModule],
+        ctx: &RuleContext,
+    ) -> Vec<Violation> {
+        if !module.test_functions.iter().any(|t| t.is_unittest) {
+            return vec![];
+        }
+        let Some(tree) = ctx.tree else {
+            return vec![];
+        };
+        let root = tree.root_node();
+        let source = module.source.as_bytes();
+        let sleeps = SleepFinder::new(root, source, &[]);
+
+        let mut classes = Vec::new();
+        let mut to_visit = vec![root];
+        while let Some(node) = to_visit.pop() {
+            if node.kind() == "class_definition" {
+                classes.push(node);
+            }
+            let mut cursor = node.walk();
+            to_visit.extend(node.named_children(&mut cursor));
+        }
+        classes.sort_by_key(Node::start_byte);
+
+        let mut violations = Vec::new();
+        for class in classes {
+            let lines = class.start_position().row + 1..=class.end_position().row + 1;
+            let tests = module
+                .test_functions
+                .iter()
+                .filter(|t| t.is_unittest && lines.contains(&t.line))
+                .count();
+            if tests == 0 {
+                continue;
+            }
+            let class_name = class
+                .child_by_field_name("name")
+                .map_or("", |n| node_text(n, source));
+            for (method, func) in setup_methods(class, source) {
+                let Some(body) = func.child_by_field_name("body") else {
+                    continue;
+                };
+                let sleep = sleeps.sleeps_in(body).into_iter().next();
+                let network = first_network_call(body, source);
+                let network_pos =
+                    network.map(|c| (c.start_position().row + 1, c.start_position().column + 1));
+                let sleep_first = match (&sleep, network_pos) {
+                    (Some(s), Some(pos)) => (s.line, s.col) < pos,
+                    (Some(_), None) => true,
+                    _ => false,
+                };
+                let what = if sleep_first {
+                    let s = sleep.as_ref().expect("sleep_first implies a sleep site");
+                    format!("sleeps ({}())", s.callee)
+                } else if let Some(call) = network {
+                    let callee = call
+                        .child_by_field_name("function")
+                        .map_or("", |f| node_text(f, source));
+                    format!("accesses the network ({callee}())")
+                } else {
+                    continue;
+                };
+                let when = if method == "setUpClass" {
+                    "once before"
+                } else {
+                    "before each of"
+                };
+                let plural = if tests == 1 { "" } else { "s" };
+                let mut v = make_violation(
+                    self.id(),
+                    self.name(),
+                    self.severity(),
+                    self.category(),
+                    format!(
+                        "{class_name}.{method} {what}, which runs {when} its {tests} test method{plural}"
+                    ),
+                    module.file_path.clone(),
+                    1,
+                    Some(
+                        "Fake the network or clock in setUp, or move the slow step into the tests that need it"
+                            .to_string(),
+                    ),
+                    None,
+                );
+                match (sleep_first, &sleep, network) {
+                    (true, Some(s), _) => {
+                        v.line = s.line;
+                        v.col = Some(s.col);
+                        v.end_line = Some(s.end.0);
+                        v.end_col = Some(s.end.1);
+                    }
+                    (_, _, Some(call)) => set_span(&mut v, call),
+                    _ => {}
+                }
+                violations.push(v);
+            }
+        }
+        violations
+    }
+}
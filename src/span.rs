@@ -0,0 +1,216 @@
+//! Source positions. Violations carry 1-based lines and 1-based byte columns (what
+//! tree-sitter reports); [`LineIndex`] converts those to byte offsets, character columns for
+//! terminal output and UTF-16 code units for LSP clients, so a multibyte character never
+//! shifts a column.
+
+use tree_sitter::{Node, Tree};
+
+use crate::models::Violation;
+use crate::rules::ast::set_span;
+
+/// Line start offsets of a source text, for converting between positions.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    #[must_use]
+    pub fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source, starts }
+    }
+
+    /// Number of lines; a trailing newline starts an (empty) last line.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Text of a 1-based line without its line terminator (`\n` or `\r\n`).
+    #[must_use]
+    pub fn line_text(&self, line: usize) -> Option<&'a str> {
+        let start = *self.starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+        let text = &self.source[start..end];
+        Some(text.strip_suffix('\r').unwrap_or(text))
+    }
+
+    /// Byte offset of a 1-based line and byte column. The column may point just past the
+    /// end of the line; columns inside a multibyte character are rejected.
+    #[must_use]
+    pub fn offset(&self, line: usize, col: usize) -> Option<usize> {
+        let text = self.line_text(line)?;
+        let within = col.checked_sub(1)?;
+        if within > text.len() || !text.is_char_boundary(within) {
+            return None;
+        }
+        Some(self.starts[line - 1] + within)
+    }
+
+    /// 1-based line and byte column of a byte offset.
+    #[must_use]
+    pub fn position(&self, offset: usize) -> Option<(usize, usize)> {
+        if offset > self.source.len() || !self.source.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.starts.partition_point(|&start| start <= offset);
+        Some((line, offset - self.starts[line - 1] + 1))
+    }
+
+    /// The line's text before a 1-based byte column, cut back to a character boundary.
+    fn prefix(&self, line: usize, col: usize) -> Option<&'a str> {
+        let text = self.line_text(line)?;
+        let mut end = col.saturating_sub(1).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(&text[..end])
+    }
+
+    /// 1-based character column of a 1-based byte column, for terminal output.
+    #[must_use]
+    pub fn char_col(&self, line: usize, col: usize) -> Option<usize> {
+        Some(self.prefix(line, col)?.chars().count() + 1)
+    }
+
+    /// 0-based UTF-16 code unit column of a 1-based byte column, as LSP expects.
+    #[must_use]
+    pub fn utf16_col(&self, line: usize, col: usize) -> Option<usize> {
+        Some(self.prefix(line, col)?.encode_utf16().count())
+    }
+
+    /// 1-based byte column of a 0-based UTF-16 column (clamped to the end of the line).
+    /// A column in the middle of a surrogate pair resolves to the start of the character.
+    #[must_use]
+    pub fn col_from_utf16(&self, line: usize, utf16: usize) -> Option<usize> {
+        let text = self.line_text(line)?;
+        let mut units = 0;
+        for (byte, ch) in text.char_indices() {
+            units += ch.len_utf16();
+            if units > utf16 {
+                return Some(byte + 1);
+            }
+        }
+        Some(text.len() + 1)
+    }
+}
+
+/// Give violations that only carry a line a span on the narrowest node that starts there:
+/// the name of a `def`, an `assert` statement, or a decorator. Violations of other files,
+/// and lines without such a node, keep covering the whole line.
+pub fn narrow_spans(violations: &mut [Violation], file: &std::path::Path, tree: Option<&Tree>) {
+    let mut pending = violations
+        .iter_mut()
+        .filter(|v| v.col.is_none() && v.file_path == file)
+        .peekable();
+    if pending.peek().is_none() {
+        return;
+    }
+    let Some(tree) = tree else {
+        return;
+    };
+    let root = tree.root_node();
+    for v in pending {
+        if let Some(node) = anchor_at_line(root, v.line) {
+            set_span(v, node);
+        }
+    }
+}
+
+/// The node a line-level violation on `line` (1-based) is about, if one starts there.
+fn anchor_at_line(root: Node, line: usize) -> Option<Node> {
+    let row = line.checked_sub(1)?;
+    let mut to_visit = vec![root];
+    while let Some(node) = to_visit.pop() {
+        if node.start_position().row > row || node.end_position().row < row {
+            continue;
+        }
+        if node.start_position().row == row {
+            match node.kind() {
+                "function_definition" => return node.child_by_field_name("name"),
+                "assert_statement" | "decorator" => return Some(node),
+                _ => {}
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        // Visit in source order so the first node on the line wins
+        to_visit.extend(children.into_iter().rev());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small deterministic generator, so the round-trip checks cover many inputs without a
+    /// property-testing dependency.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            usize::try_from(self.0 >> 33).unwrap_or(0) % bound
+        }
+    }
+
+    const PIECES: &[&str] = &[
+        "a", " ", "\t", "\n", "\r\n", "é", "ß", "中", "😀", "👍🏽", "\"", "#",
+    ];
+
+    fn random_source(rng: &mut Lcg) -> String {
+        (0..rng.next(60))
+            .map(|_| PIECES[rng.next(PIECES.len())])
+            .collect()
+    }
+
+    #[test]
+    fn test_offsets_and_positions_round_trip() {
+        let mut rng = Lcg(7);
+        for _ in 0..500 {
+            let source = random_source(&mut rng);
+            let index = LineIndex::new(&source);
+            for (offset, _) in source.char_indices().chain([(source.len(), ' ')]) {
+                let (line, col) = index.position(offset).unwrap();
+                assert!(line >= 1 && line <= index.line_count());
+                if index.line_text(line).unwrap().len() + 1 >= col {
+                    assert_eq!(
+                        index.offset(line, col),
+                        Some(offset),
+                        "{source:?} @ {offset}"
+                    );
+                    let utf16 = index.utf16_col(line, col).unwrap();
+                    assert_eq!(index.col_from_utf16(line, utf16), Some(col));
+                    assert!(index.char_col(line, col).unwrap() <= col);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_columns_count_characters_and_utf16_units() {
+        let source = "x = \"😀é\"; y\n";
+        let index = LineIndex::new(source);
+        let y = source.find('y').unwrap();
+        assert_eq!(index.position(y), Some((1, 15)));
+        assert_eq!(index.char_col(1, 15), Some(11));
+        // The emoji is a surrogate pair
+        assert_eq!(index.utf16_col(1, 15), Some(11));
+        assert_eq!(index.col_from_utf16(1, 11), Some(15));
+        // Inside the emoji: rejected as an offset, floored for display
+        assert_eq!(index.offset(1, 7), None);
+        assert_eq!(index.char_col(1, 7), Some(6));
+        assert_eq!(index.position(source.len()), Some((2, 1)));
+    }
+}
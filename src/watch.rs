@@ -0,0 +1,709 @@
+//! `--watch`: stay running and re-lint whenever a test file, `conftest.py` or configuration
+//! file changes.
+//!
+//! Configuration files are watched in the start directory and its parents, and in every
+//! directory holding a test file, so that directory configurations are picked up too.
+//!
+//! Files are polled for modification time and size rather than watched through OS
+//! notifications, so the mode works everywhere without extra dependencies. Each poll only
+//! stats the files and directories found by the last discovery; the paths are walked again
+//! when a directory's modification time changes, which is how a created file shows up.
+//!
+//! The parsed modules are kept between cycles. A change re-parses only the changed files
+//! and re-checks them together with the files whose diagnostics they can affect: the
+//! `conftest.py` chain above them, everything under a changed `conftest.py`, the files
+//! sharing a fixture with them, and in `--project-mode` the modules next to them. A
+//! configuration change rebuilds the engine and re-lints everything.
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+
+use crate::config::{Config, STANDALONE_CONFIG_FILES};
+use crate::discovery::FileDiscovery;
+use crate::engine::{write_format, LintEngine};
+use crate::models::{ParsedModule, Violation};
+use crate::output::SourceCache;
+use crate::parser::ParseOptions;
+use crate::project::ProjectIndex;
+
+/// How long the watched files must stay unchanged before a re-lint starts, so that a burst
+/// of saves produces one report.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Interval between two scans of the watched files.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Modification stamp of a file; `None` when it does not exist.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// Configuration files that would affect a run started in `start`: every standalone and
+/// `pyproject.toml` candidate from there up to the filesystem root, existing or not, so
+/// that creating one is noticed too.
+#[must_use]
+pub fn config_candidates(start: &Path) -> Vec<PathBuf> {
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    let first_dir = if start.is_file() {
+        start.parent().map(Path::to_path_buf)
+    } else {
+        Some(start)
+    };
+    let mut candidates = Vec::new();
+    let mut dir = first_dir.as_deref();
+    while let Some(current) = dir {
+        for name in STANDALONE_CONFIG_FILES.iter().chain(&["pyproject.toml"]) {
+            candidates.push(current.join(name));
+        }
+        dir = current.parent();
+    }
+    candidates
+}
+
+/// What changed between two scans.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Test files that were created or modified.
+    pub modified: Vec<PathBuf>,
+    /// Test files that no longer exist.
+    pub removed: Vec<PathBuf>,
+    /// A configuration file was created, modified or removed.
+    pub config: bool,
+}
+
+impl Changes {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.removed.is_empty() && !self.config
+    }
+
+    fn merge(&mut self, other: Changes) {
+        self.modified.extend(other.modified);
+        self.removed.extend(other.removed);
+        self.config |= other.config;
+    }
+
+    /// Sort out files seen both modified and removed over several scans by whether they
+    /// exist in the `latest` one.
+    fn settle(&mut self, latest: &Snapshot) {
+        let paths: BTreeSet<PathBuf> = self
+            .modified
+            .drain(..)
+            .chain(self.removed.drain(..))
+            .collect();
+        let (modified, removed) = paths
+            .into_iter()
+            .partition(|path| latest.tests.get(path).is_some_and(Option::is_some));
+        self.modified = modified;
+        self.removed = removed;
+    }
+}
+
+/// Stamps of the watched test files, configuration files and directories at one point in
+/// time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    tests: BTreeMap<PathBuf, Stamp>,
+    configs: BTreeMap<PathBuf, Stamp>,
+    dirs: BTreeMap<PathBuf, Stamp>,
+}
+
+impl Snapshot {
+    #[must_use]
+    pub fn take(test_files: &[PathBuf], config_files: &[PathBuf], dirs: &[PathBuf]) -> Self {
+        let stamps = |paths: &[PathBuf]| paths.iter().map(|p| (p.clone(), stamp(p))).collect();
+        Self {
+            tests: stamps(test_files),
+            configs: stamps(config_files),
+            dirs: stamps(dirs),
+        }
+    }
+
+    /// Whether a directory was created, removed or had an entry added or removed since
+    /// `self`, in which case the paths must be walked again.
+    #[must_use]
+    pub fn layout_changed(&self, newer: &Snapshot) -> bool {
+        self.dirs != newer.dirs
+    }
+
+    /// Changes from `self` to the later snapshot `newer`. A configuration file only watched
+    /// in one of them counts as changed if it exists.
+    #[must_use]
+    pub fn changes_to(&self, newer: &Snapshot) -> Changes {
+        let config_stamp =
+            |snapshot: &Snapshot, path| snapshot.configs.get(path).copied().flatten();
+        let mut changes = Changes {
+            config: self
+                .configs
+                .keys()
+                .chain(newer.configs.keys())
+                .any(|path| config_stamp(self, path) != config_stamp(newer, path)),
+            ..Changes::default()
+        };
+        for (path, stamp) in &newer.tests {
+            if stamp.is_some() && self.tests.get(path) != Some(stamp) {
+                changes.modified.push(path.clone());
+            }
+        }
+        for (path, stamp) in &self.tests {
+            let gone = newer.tests.get(path).is_none_or(Option::is_none);
+            if stamp.is_some() && gone {
+                changes.removed.push(path.clone());
+            }
+        }
+        changes
+    }
+}
+
+/// Fixture names defined and requested by a set of modules.
+#[derive(Debug, Default)]
+struct FixtureNames {
+    defined: BTreeSet<String>,
+    requested: BTreeSet<String>,
+}
+
+impl FixtureNames {
+    fn add(&mut self, module: &ParsedModule) {
+        self.defined
+            .extend(module.fixtures.iter().map(|f| f.name.clone()));
+        self.requested.extend(requested_fixtures(module).cloned());
+    }
+}
+
+fn requested_fixtures(module: &ParsedModule) -> impl Iterator<Item = &String> {
+    module
+        .test_functions
+        .iter()
+        .flat_map(|t| &t.fixture_deps)
+        .chain(module.fixtures.iter().flat_map(|f| &f.dependencies))
+}
+
+fn is_conftest(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == "conftest.py")
+}
+
+/// Indices of the `modules` to check again after the files `changed` were modified or
+/// removed, `fixtures` being the names of the changed files before and after the change:
+/// - the changed files themselves;
+/// - the `conftest.py` files in their directories and the parents, whose fixtures they use;
+/// - every file under the directory of a changed `conftest.py`;
+/// - the files requesting or defining a fixture the changed files define, and those
+///   defining a fixture they request, since fixture usage is checked across modules;
+/// - in project mode, the files in the same directory, which the project index links.
+fn affected(
+    modules: &[ParsedModule],
+    changed: &BTreeSet<PathBuf>,
+    fixtures: &FixtureNames,
+    project_mode: bool,
+) -> Vec<usize> {
+    let related = |path: &Path, changed: &Path| {
+        let (Some(dir), Some(changed_dir)) = (path.parent(), changed.parent()) else {
+            return false;
+        };
+        (is_conftest(changed) && path.starts_with(changed_dir))
+            || (is_conftest(path) && changed.starts_with(dir))
+            || (project_mode && dir == changed_dir)
+    };
+    let shares_fixture = |module: &ParsedModule| {
+        module
+            .fixtures
+            .iter()
+            .any(|f| fixtures.defined.contains(&f.name) || fixtures.requested.contains(&f.name))
+            || requested_fixtures(module).any(|name| fixtures.defined.contains(name))
+    };
+    modules
+        .iter()
+        .enumerate()
+        .filter(|(_, module)| {
+            let path = &module.file_path;
+            changed.contains(path)
+                || changed.iter().any(|c| related(path, c))
+                || shares_fixture(module)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// A running watch session.
+struct Session<'a> {
+    paths: &'a [PathBuf],
+    format: &'a str,
+    load_config: &'a dyn Fn() -> Result<Config>,
+    /// Configuration candidates of the start path.
+    start_configs: Vec<PathBuf>,
+    config: Config,
+    engine: LintEngine,
+    /// Test files found by the last discovery.
+    files: Vec<PathBuf>,
+    /// Directories walked by the last discovery, plus the parents of file arguments.
+    dirs: Vec<PathBuf>,
+    /// Configuration files that could apply to `files`.
+    configs: Vec<PathBuf>,
+    /// The parsed `files`, sorted by path.
+    modules: Vec<ParsedModule>,
+    /// The project index, in `--project-mode`.
+    index: Option<ProjectIndex>,
+    /// Diagnostics of the last cycle, by file.
+    diagnostics: BTreeMap<PathBuf, Vec<Violation>>,
+}
+
+impl<'a> Session<'a> {
+    fn new(
+        paths: &'a [PathBuf],
+        format: &'a str,
+        load_config: &'a dyn Fn() -> Result<Config>,
+    ) -> Result<Self> {
+        let config = load_config()?;
+        Ok(Self {
+            paths,
+            format,
+            load_config,
+            start_configs: paths
+                .first()
+                .map(|p| config_candidates(p))
+                .unwrap_or_default(),
+            engine: LintEngine::new(config.clone())?,
+            config,
+            files: Vec::new(),
+            dirs: Vec::new(),
+            configs: Vec::new(),
+            modules: Vec::new(),
+            index: None,
+            diagnostics: BTreeMap::new(),
+        })
+    }
+
+    /// Walk the paths again for the test files, the directories that hold them and the
+    /// configuration files that could apply: those of the start path and of every
+    /// directory holding a test file.
+    fn discover(&mut self) -> Result<()> {
+        let (files, mut dirs) =
+            FileDiscovery::from_config(&self.config)?.discover_with_dirs(self.paths);
+        dirs.extend(
+            self.paths
+                .iter()
+                .filter(|p| !p.is_dir())
+                .filter_map(|p| p.parent().map(Path::to_path_buf)),
+        );
+        dirs.sort();
+        dirs.dedup();
+        let test_dirs: BTreeSet<&Path> = files.iter().filter_map(|t| t.parent()).collect();
+        let configs: BTreeSet<PathBuf> = self
+            .start_configs
+            .iter()
+            .cloned()
+            .chain(test_dirs.into_iter().flat_map(config_candidates))
+            .collect();
+        self.files = files;
+        self.dirs = dirs;
+        self.configs = Vec::from_iter(configs);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::take(&self.files, &self.configs, &self.dirs)
+    }
+
+    /// Scan the watched files, walking the paths again first if a directory changed.
+    fn poll(&mut self, previous: &Snapshot) -> Result<(Snapshot, Changes)> {
+        let mut current = self.snapshot();
+        if previous.layout_changed(&current) {
+            self.discover()?;
+            current = self.snapshot();
+        }
+        let changes = previous.changes_to(&current);
+        Ok((current, changes))
+    }
+
+    fn update_index(&mut self) -> Result<()> {
+        if self.config.project_mode {
+            self.index = Some(ProjectIndex::build(
+                self.paths,
+                &FileDiscovery::from_config(&self.config)?,
+                &ParseOptions::from_config(&self.config),
+                self.index.as_ref(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parse and check every watched file.
+    fn lint_all(&mut self) -> Result<()> {
+        let (modules, parse_errors) = self.engine.parse_modules(&self.files)?;
+        self.modules = modules;
+        self.modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        self.index = None;
+        self.update_index()?;
+        self.diagnostics = self
+            .files
+            .iter()
+            .map(|path| (path.clone(), Vec::new()))
+            .collect();
+        let targets: Vec<usize> = (0..self.modules.len()).collect();
+        self.check(&targets, parse_errors)
+    }
+
+    /// Re-parse the changed files and re-check them with the files they affect. Files that
+    /// disappeared drop out of the report.
+    fn update(&mut self, changes: &Changes) -> Result<()> {
+        let changed: BTreeSet<PathBuf> = changes
+            .modified
+            .iter()
+            .chain(&changes.removed)
+            .cloned()
+            .collect();
+        let mut fixtures = FixtureNames::default();
+        for module in self
+            .modules
+            .iter()
+            .filter(|m| changed.contains(&m.file_path))
+        {
+            fixtures.add(module);
+        }
+        self.modules.retain(|m| !changed.contains(&m.file_path));
+        let modified = Vec::from_iter(
+            changed
+                .iter()
+                .filter(|p| !changes.removed.contains(p))
+                .cloned(),
+        );
+        let (parsed, parse_errors) = self.engine.parse_modules(&modified)?;
+        for module in &parsed {
+            fixtures.add(module);
+        }
+        self.modules.extend(parsed);
+        self.modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        for path in &changes.removed {
+            self.diagnostics.remove(path);
+        }
+        for path in modified {
+            self.diagnostics.insert(path, Vec::new());
+        }
+        self.update_index()?;
+        let targets = affected(&self.modules, &changed, &fixtures, self.config.project_mode);
+        self.check(&targets, parse_errors)
+    }
+
+    /// Replace the diagnostics of `modules[i]` for each of `targets` with a fresh check,
+    /// adding the parse errors of the cycle.
+    fn check(&mut self, targets: &[usize], parse_errors: Vec<Violation>) -> Result<()> {
+        let violations =
+            self.engine
+                .check_in_context(&self.modules, targets, self.index.as_ref())?;
+        for &i in targets {
+            self.diagnostics
+                .insert(self.modules[i].file_path.clone(), Vec::new());
+        }
+        for v in violations.into_iter().chain(parse_errors) {
+            self.diagnostics
+                .entry(v.file_path.clone())
+                .or_default()
+                .push(v);
+        }
+        Ok(())
+    }
+
+    fn reload_config(&mut self) -> Result<()> {
+        self.config = (self.load_config)()?;
+        self.engine = LintEngine::new(self.config.clone())?;
+        Ok(())
+    }
+
+    fn report(&self, out: &mut dyn Write, clear: bool, note: &str) -> Result<()> {
+        if clear {
+            write!(out, "\x1b[2J\x1b[H")?;
+        }
+        let violations: Vec<Violation> = self.diagnostics.values().flatten().cloned().collect();
+        write_format(
+            &violations,
+            self.format,
+            out,
+            &self.config,
+            None,
+            &mut SourceCache::default(),
+        )?;
+        writeln!(
+            out,
+            "\n{note}Watching {} test files for changes (Ctrl-C to stop)",
+            self.diagnostics.len()
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Lint `paths`, print the report, and keep re-linting on changes until `stop` is set.
+/// `load_config` builds the configuration, and is called again when a configuration file
+/// changes; an invalid configuration is reported and the previous one kept. Errors while
+/// re-linting are reported on stderr and the watch goes on.
+#[allow(clippy::missing_errors_doc)]
+pub fn watch(
+    paths: &[PathBuf],
+    format: &str,
+    load_config: &dyn Fn() -> Result<Config>,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let mut session = Session::new(paths, format, load_config)?;
+    let mut stdout = std::io::stdout();
+    let clear = matches!(format, "terminal" | "short") && stdout.is_terminal();
+
+    session.discover()?;
+    let mut snapshot = session.snapshot();
+    session.lint_all()?;
+    session.report(&mut stdout, clear, "")?;
+
+    let mut pending = Changes::default();
+    let mut last_change = Instant::now();
+    // After a failed cycle the session may be half updated, so the next one starts over
+    let mut failed = false;
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+        let (current, changes) = match session.poll(&snapshot) {
+            Ok(polled) => polled,
+            Err(e) => {
+                eprintln!("Error: {e:#}; still watching");
+                continue;
+            }
+        };
+        snapshot = current;
+        if !changes.is_empty() {
+            pending.merge(changes);
+            last_change = Instant::now();
+            continue;
+        }
+        if pending.is_empty() || last_change.elapsed() < DEBOUNCE {
+            continue;
+        }
+        let mut changes = std::mem::take(&mut pending);
+        changes.settle(&snapshot);
+        let mut note = describe(&changes);
+        let relinted = if changes.config || failed {
+            if changes.config {
+                if let Err(e) = session.reload_config() {
+                    note.push_str(&format!(
+                        "Configuration error, keeping the previous one: {e:#}\n"
+                    ));
+                }
+            }
+            // Start over: the configuration decides which files are test files
+            session.discover().and_then(|()| {
+                snapshot = session.snapshot();
+                session.lint_all()
+            })
+        } else {
+            session.update(&changes)
+        };
+        failed = relinted.is_err();
+        match relinted {
+            Ok(()) => session.report(&mut stdout, clear, &note)?,
+            Err(e) => {
+                eprintln!("Error: {e:#}; still watching, the next change re-lints every file")
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One line naming what triggered a re-lint, e.g. `Changed: tests/test_a.py; removed: 1 file`.
+fn describe(changes: &Changes) -> String {
+    let mut modified = changes.modified.clone();
+    modified.sort();
+    modified.dedup();
+    let mut parts = Vec::new();
+    if changes.config {
+        parts.push("configuration changed".to_string());
+    }
+    match modified.as_slice() {
+        [] => {}
+        [one] => parts.push(format!("changed: {}", one.display())),
+        many => parts.push(format!("changed: {} files", many.len())),
+    }
+    if !changes.removed.is_empty() {
+        parts.push(format!("removed: {}", changes.removed.len()));
+    }
+    let mut line = parts.join("; ");
+    if let Some(first) = line.get(..1) {
+        line = first.to_uppercase() + &line[1..];
+        line.push('\n');
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_changes_report_modified_removed_and_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("test_a.py");
+        let b = dir.path().join("test_b.py");
+        let config = dir.path().join("pytl.toml");
+        std::fs::write(&a, "def test_a():\n    assert f()\n").unwrap();
+        std::fs::write(&b, "def test_b():\n    assert f()\n").unwrap();
+        let tests = vec![a.clone(), b.clone()];
+        let configs = vec![config.clone()];
+        let before = Snapshot::take(&tests, &configs, &[]);
+        assert!(before
+            .changes_to(&Snapshot::take(&tests, &configs, &[]))
+            .is_empty());
+
+        // Size changes are seen even within the modification time resolution
+        std::fs::write(&a, "def test_a():\n    assert f() == 1\n").unwrap();
+        std::fs::remove_file(&b).unwrap();
+        let c = dir.path().join("test_c.py");
+        std::fs::write(&c, "def test_c():\n    assert f()\n").unwrap();
+        let after = Snapshot::take(&[a.clone(), c.clone()], &configs, &[]);
+        assert_eq!(
+            before.changes_to(&after),
+            Changes {
+                modified: vec![a, c],
+                removed: vec![b],
+                config: false,
+            }
+        );
+
+        std::fs::write(&config, "[rules]\n").unwrap();
+        let with_config = Snapshot::take(&tests, &configs, &[]);
+        assert!(before.changes_to(&with_config).config);
+
+        // A newly watched candidate that does not exist is not a change
+        let more_configs = vec![config, dir.path().join("pyproject.toml")];
+        let with_candidate = Snapshot::take(&tests, &more_configs, &[]);
+        assert!(!with_config.changes_to(&with_candidate).config);
+    }
+
+    fn module(path: &str, source: &str) -> ParsedModule {
+        crate::parser::PythonParser::new()
+            .unwrap()
+            .parse_source(source, Path::new(path))
+            .unwrap()
+    }
+
+    fn affected_paths(modules: &[ParsedModule], changed: &str, project_mode: bool) -> Vec<String> {
+        let changed_module = modules
+            .iter()
+            .find(|m| m.file_path == Path::new(changed))
+            .unwrap();
+        let mut fixtures = FixtureNames::default();
+        fixtures.add(changed_module);
+        let changed = BTreeSet::from([PathBuf::from(changed)]);
+        affected(modules, &changed, &fixtures, project_mode)
+            .into_iter()
+            .map(|i| modules[i].file_path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_affected_files_follow_conftest_chain_and_shared_fixtures() {
+        let modules = vec![
+            module(
+                "tests/conftest.py",
+                "import pytest\n\n@pytest.fixture\ndef db():\n    return 1\n",
+            ),
+            module(
+                "tests/other/test_c.py",
+                "import pytest\n\n@pytest.fixture\ndef helper():\n    return 1\n\n\
+                 def test_c(helper):\n    assert helper == 1\n",
+            ),
+            module(
+                "tests/unit/test_a.py",
+                "def test_a(db):\n    assert db == 1\n",
+            ),
+            module("tests/unit/test_b.py", "def test_b():\n    assert 1 == 1\n"),
+        ];
+
+        assert_eq!(
+            affected_paths(&modules, "tests/unit/test_b.py", false),
+            ["tests/conftest.py", "tests/unit/test_b.py"]
+        );
+        assert_eq!(
+            affected_paths(&modules, "tests/unit/test_b.py", true),
+            [
+                "tests/conftest.py",
+                "tests/unit/test_a.py",
+                "tests/unit/test_b.py"
+            ]
+        );
+        assert_eq!(
+            affected_paths(&modules, "tests/conftest.py", false),
+            [
+                "tests/conftest.py",
+                "tests/other/test_c.py",
+                "tests/unit/test_a.py",
+                "tests/unit/test_b.py"
+            ]
+        );
+        // test_c's own fixture is not shared, so only the conftest chain is re-checked
+        assert_eq!(
+            affected_paths(&modules, "tests/other/test_c.py", false),
+            ["tests/conftest.py", "tests/other/test_c.py"]
+        );
+    }
+
+    #[test]
+    fn test_session_rediscovers_created_files_and_matches_a_full_lint() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let tests_dir = root.join("tests");
+        std::fs::create_dir(&tests_dir).unwrap();
+        std::fs::write(
+            tests_dir.join("conftest.py"),
+            "import pytest\n\n@pytest.fixture\ndef db():\n    return 1\n",
+        )
+        .unwrap();
+        let a = tests_dir.join("test_a.py");
+        std::fs::write(&a, "def test_a(db):\n    assert db == 1\n").unwrap();
+
+        let paths = vec![tests_dir.clone()];
+        let load_config = || Ok(Config::default());
+        let mut session = Session::new(&paths, "json", &load_config).unwrap();
+        session.discover().unwrap();
+        let before = session.snapshot();
+        session.lint_all().unwrap();
+
+        // The conftest fixture loses its only user, and a new file appears in a new directory
+        std::fs::write(&a, "def test_a():\n    assert 1 == 1\n").unwrap();
+        let nested = tests_dir.join("unit");
+        std::fs::create_dir(&nested).unwrap();
+        let b = nested.join("test_b.py");
+        std::fs::write(&b, "def test_b():\n    assert True\n").unwrap();
+
+        let (after, mut changes) = session.poll(&before).unwrap();
+        assert!(before.layout_changed(&after));
+        changes.settle(&after);
+        assert_eq!(changes.modified, [a, b.clone()]);
+        assert!(session.files.contains(&b));
+
+        session.update(&changes).unwrap();
+        let mut watched: Vec<Violation> = session.diagnostics.values().flatten().cloned().collect();
+        let mut full = LintEngine::new(Config::default())
+            .unwrap()
+            .lint_paths(&paths)
+            .unwrap();
+        let key = |v: &Violation| (v.file_path.clone(), v.line, v.rule_id.clone());
+        watched.sort_by_key(key);
+        full.sort_by_key(key);
+        assert_eq!(watched, full);
+        assert!(!full.is_empty());
+    }
+
+    #[test]
+    fn test_config_candidates_walk_up_from_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("tests").join("unit");
+        std::fs::create_dir_all(&nested).unwrap();
+        let candidates = config_candidates(&nested);
+        let root = dir.path().canonicalize().unwrap();
+        assert!(candidates.contains(&nested.canonicalize().unwrap().join("pytl.toml")));
+        assert!(candidates.contains(&root.join("pyproject.toml")));
+        assert!(candidates.contains(&root.join("pytest-linter.toml")));
+    }
+}
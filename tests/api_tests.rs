@@ -0,0 +1,306 @@
+//! Tests of the library API at the crate root, run on the sample project in `examples/`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use pytest_linter::{
+    check_baseline, fix_source, lint_path, lint_paths, lint_source, lint_source_at, lint_status,
+    write_baseline, write_diagnostics, Config, Diagnostic, Language, LintReport, LintStatus,
+    OverrideConfig, RuleConfig, RuleId, Severity, SOURCE_PATH,
+};
+
+fn example_project() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/project")
+}
+
+fn checkout() -> PathBuf {
+    example_project().join("tests/payments/test_checkout.py")
+}
+
+/// Rule, line and column of each diagnostic, for comparing runs.
+fn positions(diagnostics: &[Diagnostic]) -> Vec<(String, usize, Option<usize>)> {
+    diagnostics
+        .iter()
+        .map(|d| (d.rule_id.to_string(), d.span.line, d.span.column))
+        .collect()
+}
+
+#[test]
+fn test_lint_path_reports_the_example_project() {
+    let report = lint_path(&example_project(), &Config::default()).unwrap();
+    assert_eq!(report.files.analyzed, 3);
+    assert_eq!(report.files.skipped, 0);
+    assert!(report.cache.is_none());
+
+    let find = |rule: &str| {
+        report
+            .diagnostics
+            .iter()
+            .find(|d| d.rule_id == RuleId::new(rule))
+            .unwrap_or_else(|| panic!("no {rule} in {:?}", positions(&report.diagnostics)))
+    };
+    let sleep = find("PYTEST-FLK-001");
+    assert_eq!(sleep.path, checkout());
+    assert_eq!(sleep.span.line, 12);
+    assert_eq!(sleep.test_name.as_deref(), Some("test_payment_is_captured"));
+    let duplicate = find("PYTEST-MNT-022");
+    assert_eq!(duplicate.severity, Severity::Error);
+    assert_eq!(duplicate.span.line, 16);
+    assert_eq!(
+        (duplicate.span.column, duplicate.span.end_column),
+        (Some(5), Some(29))
+    );
+    let unused = find("PYTEST-FIX-005");
+    assert!(unused.message.contains("'legacy_gateway'"));
+
+    let paths: Vec<&Path> = report
+        .diagnostics
+        .iter()
+        .map(|d| d.path.as_path())
+        .collect();
+    assert!(paths.windows(2).all(|w| w[0] <= w[1]), "{paths:?}");
+    let summary = report.summary();
+    assert_eq!(summary.total, report.diagnostics.len());
+    assert_eq!(summary.by_severity.error, 1);
+}
+
+#[test]
+fn test_lint_source_matches_lint_path_on_the_same_file() {
+    let config = Config::default();
+    let source = std::fs::read_to_string(checkout()).unwrap();
+    let from_source = lint_source(&source, Language::Python, &config);
+    let from_path = lint_path(&checkout(), &config).unwrap().diagnostics;
+
+    assert!(!from_source.is_empty());
+    assert!(from_source.iter().all(|d| d.path == Path::new(SOURCE_PATH)));
+    assert_eq!(positions(&from_source), positions(&from_path));
+}
+
+#[test]
+fn test_cli_reports_what_the_library_reports() {
+    let project = example_project();
+    let output = Command::new(env!("CARGO_BIN_EXE_pytest-linter"))
+        .args(["--format", "json", "--no-cache"])
+        .arg(&project)
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let cli: Vec<(String, String, u64)> = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            (
+                d["rule_id"].as_str().unwrap().to_string(),
+                d["path"].as_str().unwrap().to_string(),
+                d["span"]["line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+
+    let report = lint_paths(&[project], &Config::default()).unwrap();
+    let library: Vec<(String, String, u64)> = report
+        .diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.rule_id.to_string(),
+                d.path.display().to_string(),
+                d.span.line as u64,
+            )
+        })
+        .collect();
+    assert_eq!(cli, library);
+}
+
+#[test]
+fn test_cli_output_is_the_same_with_an_empty_baseline() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("baseline.json");
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_pytest-linter"))
+            .args(["--no-cache", "--no-color"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let empty = dir.path().join("empty");
+    std::fs::create_dir(&empty).unwrap();
+    let baseline_arg = baseline.to_str().unwrap();
+    assert!(
+        run(&["--write-baseline", baseline_arg, empty.to_str().unwrap()])
+            .status
+            .success()
+    );
+
+    let project = example_project();
+    let project = project.to_str().unwrap();
+    for format in ["sarif", "short", "terminal", "json"] {
+        let plain = run(&["--format", format, project]);
+        let baselined = run(&[
+            "--format",
+            format,
+            "--check-baseline",
+            baseline_arg,
+            project,
+        ]);
+        assert_eq!(
+            String::from_utf8_lossy(&baselined.stdout),
+            String::from_utf8_lossy(&plain.stdout),
+            "{format}"
+        );
+    }
+}
+
+#[test]
+fn test_report_and_config_round_trip_through_json() {
+    let report = lint_path(&example_project(), &Config::default()).unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert!(json["diagnostics"][0]["rule_id"].is_string());
+    assert!(json["diagnostics"][0]["span"]["line"].is_u64());
+    let back: LintReport = serde_json::from_value(json).unwrap();
+    assert_eq!(back, report);
+
+    // Missing fields take their defaults
+    let config: Config = serde_json::from_str(
+        r#"{"rules": {"PYTEST-FLK-001": {"enabled": false}}, "fail_on": "warning"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.fail_on, Some(Severity::Warning));
+    let source = std::fs::read_to_string(checkout()).unwrap();
+    let diagnostics = lint_source(&source, Language::Python, &config);
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.rule_id.as_str() != "PYTEST-FLK-001"));
+    let again: Config = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+    assert_eq!(again.rules, config.rules);
+}
+
+#[test]
+fn test_related_locations_round_trip_through_json() {
+    let source = "def test_a():\n    assert f()\n\ndef test_a():\n    assert g()\n";
+    let diagnostics = lint_source(source, Language::Python, &Config::default());
+    let duplicate = diagnostics
+        .iter()
+        .find(|d| d.rule_id.as_str() == "PYTEST-MNT-022")
+        .unwrap();
+    let related = &duplicate.related[0];
+    assert_eq!(related.path, Path::new(SOURCE_PATH));
+    assert_eq!((related.span.line, related.span.column), (1, Some(5)));
+
+    let json = serde_json::to_value(duplicate).unwrap();
+    assert_eq!(json["related"][0]["span"]["line"], 1);
+    let back: Diagnostic = serde_json::from_value(json).unwrap();
+    assert_eq!(&back, duplicate);
+    let other = diagnostics.iter().find(|d| d.related.is_empty()).unwrap();
+    assert!(serde_json::to_value(other)
+        .unwrap()
+        .get("related")
+        .is_none());
+}
+
+#[test]
+fn test_span_columns_count_bytes() {
+    let source = "def test_x():\n    s = \"😀é\"; assert s == 0.1\n";
+    let diagnostics = lint_source(source, Language::Python, &Config::default());
+    let float = diagnostics
+        .iter()
+        .find(|d| d.rule_id.as_str() == "PYTEST-FLK-013")
+        .unwrap();
+    let line = source.lines().nth(1).unwrap();
+    let start = float.span.column.unwrap() - 1;
+    let end = float.span.end_column.unwrap() - 1;
+    assert_eq!(&line[start..end], "s == 0.1");
+}
+
+#[test]
+fn test_lint_source_reports_an_unusable_config_as_a_diagnostic() {
+    let config = Config {
+        overrides: vec![OverrideConfig {
+            path: "[".to_string(),
+            rules: HashMap::from([("PYTEST-FLK-001".to_string(), RuleConfig::default())]),
+            base_dir: None,
+        }],
+        ..Config::default()
+    };
+    let diagnostics = lint_source("def test_a():\n    assert f()\n", Language::Python, &config);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].rule_id.as_str(), "PYTEST-INT-001");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn test_fix_source_applies_the_fixes_of_lint_source_at() {
+    let path = Path::new("tests/test_a.py");
+    let source = "def test_a():\n    x = 1\n    print(x)\n    assert x == 1\n";
+    let config = Config::default();
+    let diagnostics = lint_source_at(source, path, &config).unwrap();
+    assert!(diagnostics.iter().all(|d| d.path == path));
+    assert!(diagnostics.iter().any(|d| d.fix.is_some()));
+
+    let fixed = fix_source(source, path, &diagnostics);
+    assert_eq!(
+        fixed.source,
+        "def test_a():\n    x = 1\n    assert x == 1\n"
+    );
+    assert_eq!((fixed.summary.applied, fixed.summary.files), (1, 1));
+    assert!(fixed.summary.diff.contains("-    print(x)"));
+    let remaining = lint_source_at(&fixed.source, path, &config).unwrap();
+    assert!(remaining.iter().all(|d| d.fix.is_none()));
+
+    // Not a test file
+    assert!(lint_source_at(source, Path::new("src/app.py"), &config)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_check_baseline_keeps_only_new_diagnostics() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("baseline.json");
+    let config = Config::default();
+    let report = lint_path(&example_project(), &config).unwrap();
+    let (old, _) = report.diagnostics.split_at(1);
+    write_baseline(old, &baseline).unwrap();
+
+    let check = check_baseline(report.clone(), &baseline).unwrap();
+    assert_eq!(check.recorded, 1);
+    assert_eq!(check.new.files, report.files);
+    assert_eq!(check.new.diagnostics, report.diagnostics[1..]);
+    assert!(check.breakdown.contains("1 of 1 entries still match"));
+    assert_eq!(
+        lint_status(&check.new.diagnostics, &config),
+        lint_status(&report.diagnostics, &config)
+    );
+    assert_eq!(lint_status(&[], &config), LintStatus::Clean);
+}
+
+#[test]
+fn test_write_diagnostics_reads_snippets_from_the_buffer() {
+    let path = Path::new("tests/test_not_on_disk.py");
+    let source = "import time\n\ndef test_wait():\n    time.sleep(1)\n    assert True\n";
+    let config = Config::default();
+    let diagnostics = lint_source_at(source, path, &config).unwrap();
+    let mut out = Vec::new();
+    write_diagnostics(
+        &mut out,
+        &diagnostics,
+        "terminal",
+        &config,
+        None,
+        Some((path, source)),
+    )
+    .unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("4 |     time.sleep(1)"), "{text}");
+}
+
+#[test]
+fn test_language_from_path() {
+    assert_eq!(
+        Language::from_path(Path::new("tests/test_a.py")),
+        Some(Language::Python)
+    );
+    assert_eq!(Language::from_path(Path::new("src/app.ts")), None);
+}
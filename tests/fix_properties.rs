@@ -0,0 +1,461 @@
+//! Property tests of `--fix` on generated test modules seeded with fixable smells: applying
+//! every fix keeps the module parseable, with every suite non-empty and indented, leaves
+//! nothing for the fixed rules to report, and never produces overlapping or out-of-range
+//! edits.
+//!
+//! Modules are generated from a small structured model rather than as raw text, so a
+//! failing case is shrunk statement by statement to a minimal module before it is reported.
+//! Each property runs 500 cases; set `FIX_PROPERTY_CASES` to run fewer locally.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use pytest_linter::fix::{apply_fixes, FixOutcome};
+use pytest_linter::parser::PythonParser;
+use pytest_linter::rules::all_rules;
+use pytest_linter::rules::ast::parse_python;
+use pytest_linter::{lint_source, Config, Diagnostic, Fix, Language, RuleConfig};
+use tree_sitter::Node;
+
+const DEFAULT_CASES: usize = 500;
+
+/// Rules with a fix for every smell the generator seeds.
+const FIXED_RULES: &[&str] = &[
+    "PYTEST-FIX-016",
+    "PYTEST-MNT-002",
+    "PYTEST-MNT-020",
+    "PYTEST-MNT-023",
+    "PYTEST-MNT-026",
+    "PYTEST-MNT-027",
+];
+
+/// Small deterministic generator, as in the span tests, so the properties need no
+/// property-testing dependency and every failure reproduces.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(self.0 >> 33).unwrap_or(0) % bound
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.next(100) < percent
+    }
+}
+
+const PRINTS: &[&str] = &[
+    "print(result)",
+    "pprint.pprint(result)",
+    "print(\"debug\", result, sep=\": \")",
+    "result = compute(0); print(result)",
+];
+const DEBUGGERS: &[&str] = &[
+    "breakpoint()",
+    "pdb.set_trace()",
+    "import pdb; pdb.set_trace()",
+];
+const MAGIC_ASSERTS: &[&str] = &["assert True", "assert 1"];
+const SKIPS: &[&str] = &[
+    "@pytest.mark.skip",
+    "@pytest.mark.skip()",
+    "@pytest.mark.skipif(sys.platform == \"win32\")",
+];
+/// Deprecated alias and its arity.
+const ALIASES: &[(&str, usize)] = &[
+    ("assertEquals", 2),
+    ("failUnlessEqual", 2),
+    ("assertNotEquals", 2),
+    ("assert_", 1),
+    ("failIf", 1),
+    ("assertRegexpMatches", 2),
+];
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Assign(usize),
+    Check(usize),
+    Comment,
+    Print(usize),
+    Debugger(usize),
+    MagicAssert(usize),
+    /// `if`, `for` or `with` around a nested body.
+    Block(usize, Vec<Stmt>),
+}
+
+#[derive(Debug, Clone)]
+struct TestFn {
+    skip: Option<usize>,
+    body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+struct YieldFixture {
+    /// Yield a named value rather than an expression.
+    via_variable: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Module {
+    fixtures: Vec<YieldFixture>,
+    tests: Vec<TestFn>,
+    /// Alias calls of each `TestCase` method, by index into [`ALIASES`].
+    unittest: Vec<Vec<usize>>,
+}
+
+fn arb_stmt(rng: &mut Lcg, depth: usize) -> Stmt {
+    match rng.next(if depth < 2 { 8 } else { 6 }) {
+        0 => Stmt::Assign(rng.next(10)),
+        1 => Stmt::Check(rng.next(10)),
+        2 => Stmt::Comment,
+        3 => Stmt::Print(rng.next(PRINTS.len())),
+        4 => Stmt::Debugger(rng.next(DEBUGGERS.len())),
+        5 => Stmt::MagicAssert(rng.next(MAGIC_ASSERTS.len())),
+        6 => Stmt::Block(rng.next(3), arb_removable_body(rng)),
+        _ => Stmt::Block(rng.next(3), arb_body(rng, depth + 1)),
+    }
+}
+
+/// A body that different rules' fixes delete entirely: debugging calls mixed with
+/// tautological assertions.
+fn arb_removable_body(rng: &mut Lcg) -> Vec<Stmt> {
+    let mut body = vec![
+        if rng.chance(50) {
+            Stmt::Print(rng.next(PRINTS.len()))
+        } else {
+            Stmt::Debugger(rng.next(DEBUGGERS.len()))
+        },
+        Stmt::MagicAssert(rng.next(MAGIC_ASSERTS.len())),
+    ];
+    if rng.chance(50) {
+        body.reverse();
+    }
+    if rng.chance(30) {
+        body.push(Stmt::Comment);
+    }
+    body
+}
+
+fn arb_body(rng: &mut Lcg, depth: usize) -> Vec<Stmt> {
+    (0..=rng.next(4)).map(|_| arb_stmt(rng, depth)).collect()
+}
+
+/// A test module with prints, debugger calls, tautological assertions, skips without a
+/// reason, pointless yield fixtures and deprecated unittest aliases at random positions.
+fn arb_fixable_source(rng: &mut Lcg) -> Module {
+    Module {
+        fixtures: (0..rng.next(3))
+            .map(|_| YieldFixture {
+                via_variable: rng.chance(50),
+            })
+            .collect(),
+        tests: (0..=rng.next(3))
+            .map(|_| TestFn {
+                skip: rng.chance(30).then(|| rng.next(SKIPS.len())),
+                body: arb_body(rng, 0),
+            })
+            .collect(),
+        unittest: (0..rng.next(3))
+            .map(|_| (0..=rng.next(2)).map(|_| rng.next(ALIASES.len())).collect())
+            .collect(),
+    }
+}
+
+fn render_body(out: &mut String, body: &[Stmt], depth: usize) {
+    let indent = "    ".repeat(depth);
+    // A body of comments only is as empty to Python as no body at all
+    if body.iter().all(|stmt| matches!(stmt, Stmt::Comment)) {
+        out.push_str(&format!("{indent}pass\n"));
+    }
+    for stmt in body {
+        match stmt {
+            Stmt::Assign(n) => out.push_str(&format!("{indent}result = compute({n})\n")),
+            Stmt::Check(n) => out.push_str(&format!("{indent}assert result == {n}\n")),
+            Stmt::Comment => out.push_str(&format!("{indent}# keep the comment\n")),
+            Stmt::Print(k) => out.push_str(&format!("{indent}{}\n", PRINTS[*k])),
+            Stmt::Debugger(k) => out.push_str(&format!("{indent}{}\n", DEBUGGERS[*k])),
+            Stmt::MagicAssert(k) => out.push_str(&format!("{indent}{}\n", MAGIC_ASSERTS[*k])),
+            Stmt::Block(kind, inner) => {
+                let header = [
+                    "if result:",
+                    "for item in items():",
+                    "with open(\"f\") as fh:",
+                ];
+                out.push_str(&format!("{indent}{}\n", header[*kind]));
+                render_body(out, inner, depth + 1);
+            }
+        }
+    }
+}
+
+impl Module {
+    fn render(&self) -> String {
+        let mut out = String::from(
+            "import pdb\nimport pprint\nimport sys\nimport unittest\n\nimport pytest\n",
+        );
+        for (i, fixture) in self.fixtures.iter().enumerate() {
+            out.push_str(&format!("\n\n@pytest.fixture\ndef resource_{i}():\n"));
+            if fixture.via_variable {
+                out.push_str(&format!(
+                    "    value = make_resource({i})\n    yield value\n"
+                ));
+            } else {
+                out.push_str(&format!("    yield make_resource({i})\n"));
+            }
+        }
+        let params: Vec<String> = (0..self.fixtures.len())
+            .map(|i| format!("resource_{i}"))
+            .collect();
+        for (i, test) in self.tests.iter().enumerate() {
+            out.push_str("\n\n");
+            if let Some(skip) = test.skip {
+                out.push_str(&format!("{}\n", SKIPS[skip]));
+            }
+            out.push_str(&format!("def test_case_{i}({}):\n", params.join(", ")));
+            render_body(&mut out, &test.body, 1);
+        }
+        if !self.unittest.is_empty() {
+            out.push_str("\n\nclass TestLegacy(unittest.TestCase):\n");
+            for (i, calls) in self.unittest.iter().enumerate() {
+                out.push_str(&format!("    def test_legacy_{i}(self):\n"));
+                for &call in calls {
+                    let (alias, arity) = ALIASES[call];
+                    let args = if arity == 1 {
+                        format!("compute({i})")
+                    } else {
+                        format!("compute({i}), {i}")
+                    };
+                    out.push_str(&format!("        self.{alias}({args})\n"));
+                }
+            }
+        }
+        out
+    }
+
+    /// Smaller variants of the module: one fixture, test, method, call or statement
+    /// removed, or a block replaced by its body.
+    fn shrink(&self) -> Vec<Module> {
+        let mut smaller = Vec::new();
+        for i in 0..self.fixtures.len() {
+            let mut m = self.clone();
+            m.fixtures.remove(i);
+            smaller.push(m);
+        }
+        for i in 0..self.tests.len() {
+            let mut m = self.clone();
+            m.tests.remove(i);
+            smaller.push(m);
+            if self.tests[i].skip.is_some() {
+                let mut m = self.clone();
+                m.tests[i].skip = None;
+                smaller.push(m);
+            }
+            for body in shrink_body(&self.tests[i].body) {
+                let mut m = self.clone();
+                m.tests[i].body = body;
+                smaller.push(m);
+            }
+        }
+        for i in 0..self.unittest.len() {
+            let mut m = self.clone();
+            m.unittest.remove(i);
+            smaller.push(m);
+            for j in 0..self.unittest[i].len() {
+                let mut m = self.clone();
+                m.unittest[i].remove(j);
+                smaller.push(m);
+            }
+        }
+        smaller
+    }
+}
+
+fn shrink_body(body: &[Stmt]) -> Vec<Vec<Stmt>> {
+    let mut smaller = Vec::new();
+    for i in 0..body.len() {
+        let mut b = body.to_vec();
+        b.remove(i);
+        smaller.push(b);
+        if let Stmt::Block(kind, inner) = &body[i] {
+            let mut b = body.to_vec();
+            b.splice(i..=i, inner.iter().cloned());
+            smaller.push(b);
+            for inner in shrink_body(inner) {
+                let mut b = body.to_vec();
+                b[i] = Stmt::Block(*kind, inner);
+                smaller.push(b);
+            }
+        }
+    }
+    smaller
+}
+
+fn cases() -> usize {
+    std::env::var("FIX_PROPERTY_CASES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_CASES)
+}
+
+/// Run `property` on generated modules; on the first failure, shrink the module while the
+/// property still fails and panic with the minimal source.
+fn check(seed: u64, property: impl Fn(&str) -> Result<(), String>) {
+    let mut rng = Lcg(seed);
+    for case in 0..cases() {
+        let module = arb_fixable_source(&mut rng);
+        let Err(error) = property(&module.render()) else {
+            continue;
+        };
+        let (mut minimal, mut error) = (module, error);
+        while let Some((smaller, e)) = minimal
+            .shrink()
+            .into_iter()
+            .find_map(|m| property(&m.render()).err().map(|e| (m, e)))
+        {
+            (minimal, error) = (smaller, e);
+        }
+        panic!(
+            "case {case} failed: {error}\n--- minimal source ---\n{}",
+            minimal.render()
+        );
+    }
+}
+
+/// Only the fixed rules, which keeps 500 cases fast; no other rule has fixes.
+fn fixed_rules_config() -> Config {
+    let rules: HashMap<String, RuleConfig> = all_rules()
+        .iter()
+        .filter(|rule| !FIXED_RULES.contains(&rule.id()))
+        .map(|rule| {
+            let disabled = RuleConfig {
+                enabled: Some(false),
+                ..RuleConfig::default()
+            };
+            (rule.id().to_string(), disabled)
+        })
+        .collect();
+    Config {
+        rules,
+        ..Config::default()
+    }
+}
+
+/// Lint `source` and apply every fix, in output order as `--fix` does.
+fn fix_all(source: &str, config: &Config) -> (Vec<Diagnostic>, FixOutcome) {
+    let diagnostics = lint_source(source, Language::Python, config);
+    let fixes: Vec<&Fix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    let outcome = apply_fixes(source, &fixes);
+    (diagnostics, outcome)
+}
+
+/// Problems tree-sitter recovers from silently: a `block` without a statement, or a
+/// statement of a suite not indented past its header or not aligned with its siblings.
+fn structure_error(source: &str) -> Option<String> {
+    let tree = parse_python(source)?;
+    let mut to_visit = vec![tree.root_node()];
+    while let Some(node) = to_visit.pop() {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        if node.kind() == "block" {
+            let header = node
+                .parent()
+                .map(|p| p.start_position())
+                .unwrap_or_default();
+            let statements: Vec<&Node> =
+                children.iter().filter(|n| n.kind() != "comment").collect();
+            let Some(first) = statements.first() else {
+                return Some(format!("empty block at line {}", header.row + 1));
+            };
+            let indented = first.start_position().row == header.row
+                || statements.iter().all(|s| {
+                    s.start_position().column == first.start_position().column
+                        && s.start_position().column > header.column
+                });
+            if !indented {
+                return Some(format!("misindented block at line {}", header.row + 1));
+            }
+        }
+        to_visit.extend(children);
+    }
+    None
+}
+
+#[test]
+fn test_fixed_source_still_parses() {
+    let config = fixed_rules_config();
+    check(1, |source| {
+        let (_, outcome) = fix_all(source, &config);
+        let module = PythonParser::new()
+            .unwrap()
+            .parse_source(&outcome.source, Path::new("test_fixed.py"))
+            .map_err(|e| e.to_string())?;
+        let problem = module
+            .syntax_errors
+            .first()
+            .map(|error| format!("{} at line {}", error.message, error.line))
+            .or_else(|| structure_error(&outcome.source));
+        match problem {
+            Some(problem) => Err(format!(
+                "fixed source does not parse: {problem}\n--- fixed ---\n{}",
+                outcome.source
+            )),
+            None if outcome.rejected => Err("the fixes were rejected".to_string()),
+            None => Ok(()),
+        }
+    });
+}
+
+#[test]
+fn test_fixed_source_has_nothing_left_to_fix() {
+    let config = fixed_rules_config();
+    check(2, |source| {
+        let (_, outcome) = fix_all(source, &config);
+        let remaining: Vec<String> = lint_source(&outcome.source, Language::Python, &config)
+            .into_iter()
+            .filter(|d| FIXED_RULES.contains(&d.rule_id.as_str()))
+            .map(|d| format!("{} at line {}: {}", d.rule_id, d.span.line, d.message))
+            .collect();
+        if remaining.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "still reported after one pass: {remaining:?}\n--- fixed ---\n{}",
+                outcome.source
+            ))
+        }
+    });
+}
+
+#[test]
+fn test_fix_edits_are_in_bounds_and_disjoint() {
+    let config = fixed_rules_config();
+    check(3, |source| {
+        let (diagnostics, outcome) = fix_all(source, &config);
+        for d in &diagnostics {
+            for edit in d.fix.iter().flat_map(|f| &f.edits) {
+                if edit.start_byte > edit.end_byte
+                    || edit.end_byte > source.len()
+                    || !source.is_char_boundary(edit.start_byte)
+                    || !source.is_char_boundary(edit.end_byte)
+                {
+                    return Err(format!(
+                        "{} has an edit outside the file: {edit:?}",
+                        d.rule_id
+                    ));
+                }
+            }
+        }
+        if outcome.skipped > 0 {
+            return Err(format!("{} fixes conflicted", outcome.skipped));
+        }
+        for pair in outcome.edits.windows(2) {
+            if pair[0].end_byte > pair[1].start_byte || pair[0].start_byte == pair[1].start_byte {
+                return Err(format!("edits overlap: {:?} and {:?}", pair[0], pair[1]));
+            }
+        }
+        Ok(())
+    });
+}
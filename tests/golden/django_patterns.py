@@ -56,7 +56,7 @@ def test_setup_only():
 # --- MNT-006: assertion roulette ---
 def test_model_fields():
     assert True  # expect: PYTEST-MNT-006
-    assert 1 == 1
+    assert 1 == 1  # expect: PYTEST-MNT-021
     assert 2 == 2
     assert 3 == 3
 
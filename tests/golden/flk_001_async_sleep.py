@@ -4,6 +4,8 @@
 # expect: PYTEST-BDD-001
 # expect: PYTEST-DBC-001
 # expect: PYTEST-MNT-002
+# expect: PYTEST-MNT-004
+# expect: PYTEST-MNT-021
 
 import asyncio
 import anyio
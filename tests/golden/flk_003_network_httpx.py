@@ -4,6 +4,7 @@
 # expect: PYTEST-BDD-001
 # expect: PYTEST-DBC-001
 # expect: PYTEST-MNT-002
+# expect: PYTEST-MNT-004
 
 import httpx
 
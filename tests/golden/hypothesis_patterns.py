@@ -23,7 +23,7 @@ def test_dup(x):  # expect: PYTEST-MNT-015
 
 
 # --- MNT-002: magic assert ---
-def test_magic():
+def test_magic():  # expect: PYTEST-MNT-004
     assert True  # expect: PYTEST-MNT-002
 
 
@@ -52,7 +52,7 @@ def test_plain():  # expect: PYTEST-BDD-001
 
 # --- DBC-001: happy-path only ---
 def test_happy_only():  # expect: PYTEST-DBC-001
-    assert 1 + 1 == 2
+    assert 1 + 1 == 2  # expect: PYTEST-MNT-021
 
 
 # --- Clean: parametrize with good count ---
@@ -9,5 +9,5 @@ import requests
 
 
 def test_live_unmarked():
-    resp = requests.get("https://example.com")
+    resp = requests.get("https://example.com")  # expect: PYTEST-FLK-005
     assert resp.status_code == 200
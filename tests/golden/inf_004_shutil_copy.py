@@ -3,6 +3,7 @@
 # expect: PYTEST-BDD-001
 # expect: PYTEST-DBC-001
 # expect: PYTEST-MNT-002
+# expect: PYTEST-MNT-004
 
 import shutil
 from pathlib import Path
@@ -14,5 +15,5 @@ def test_uses_shutil_copy():
 
 
 def test_uses_finder_artefact_filename():
-    content = Path("data 2.txt").read_text()
+    content = Path("data 2.txt").read_text()  # expect: PYTEST-FLK-005
     assert content
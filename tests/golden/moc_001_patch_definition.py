@@ -2,6 +2,7 @@
 # expect: PYTEST-MOC-001
 # expect: PYTEST-BDD-001
 # expect: PYTEST-MNT-004
+# expect: PYTEST-MOC-007
 
 from myapp.models import User
 from myapp.service import create_user
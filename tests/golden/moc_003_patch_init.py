@@ -2,6 +2,7 @@
 # expect: PYTEST-MOC-003
 # expect: PYTEST-BDD-001
 # expect: PYTEST-MNT-004
+# expect: PYTEST-MOC-007
 
 from unittest.mock import patch
 
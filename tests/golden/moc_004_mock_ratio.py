@@ -3,6 +3,9 @@
 # expect: PYTEST-BDD-001
 # expect: PYTEST-DBC-001
 # expect: PYTEST-MNT-002
+# expect: PYTEST-MNT-004
+# expect: PYTEST-MOC-005
+# expect: PYTEST-MOC-007
 
 from unittest.mock import MagicMock, patch
 
@@ -70,12 +70,12 @@ def test_mock_only():
 
 # --- BDD-001: missing Gherkin ---
 def test_plain():  # expect: PYTEST-BDD-001
-    assert True
+    assert True  # expect: PYTEST-MNT-015
 
 
 # --- DBC-001: happy-path only ---
 def test_happy():  # expect: PYTEST-DBC-001
-    assert 1 + 1 == 2
+    assert 1 + 1 == 2  # expect: PYTEST-MNT-021
 
 
 # --- XDIST-002: session fixture with I/O ---
@@ -2,6 +2,7 @@
 # expect: PYTEST-VAL-001
 # expect: PYTEST-BDD-001
 # expect: PYTEST-DBC-001
+# expect: PYTEST-FLK-013
 
 
 def test_create_order():
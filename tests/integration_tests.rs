@@ -10,14 +10,14 @@ fn write_temp_file(dir: &std::path::Path, name: &str, content: &str) -> PathBuf
     path
 }
 
-fn parse_file(path: &PathBuf) -> pytest_linter::models::ParsedModule {
+fn parse_file(path: &Path) -> pytest_linter::models::ParsedModule {
     let mut parser = PythonParser::new().unwrap();
     parser.parse_file(path).unwrap()
 }
 
-fn lint_single_file(path: &PathBuf) -> Vec<pytest_linter::models::Violation> {
+fn lint_single_file(path: &Path) -> Vec<pytest_linter::models::Violation> {
     let engine = LintEngine::new(Config::default()).unwrap();
-    engine.lint_paths(&[path.clone()]).unwrap()
+    engine.lint_paths(&[path.to_path_buf()]).unwrap()
 }
 
 fn find_violation<'a>(
@@ -302,10 +302,10 @@ fn test_clean_file_has_no_error_violations() {
         "test_clean.py",
         r#"
 def test_addition():
-    assert 1 + 1 == 2
+    assert add(1, 1) == 2
 
 def test_subtraction():
-    assert 3 - 1 == 2
+    assert subtract(3, 1) == 2
 "#,
     );
     let violations = lint_single_file(&path);
@@ -643,7 +643,7 @@ fn test_run_linter_clean_file_returns_no_errors() {
         "test_clean_lint.py",
         r#"
 def test_ok():
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let has_errors =
@@ -675,7 +675,7 @@ fn test_run_linter_json_to_file() {
         "test_json_out.py",
         r#"
 def test_ok():
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let output_path = dir.path().join("output.json");
@@ -690,7 +690,7 @@ def test_ok():
     assert!(!has_errors, "Info-only violations should not be errors");
     let content = std::fs::read_to_string(&output_path).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(parsed.is_array());
+    assert!(parsed["diagnostics"].is_array());
 }
 
 #[test]
@@ -715,7 +715,8 @@ def test_bad():
     .unwrap();
     assert!(has_errors);
     let content = std::fs::read_to_string(&output_path).unwrap();
-    assert!(content.contains("ERROR"));
+    assert!(content.contains("error[PYTEST-MNT-004]"));
+    assert!(content.contains("2 | def test_bad():"));
     assert!(content.contains("Summary"));
 }
 
@@ -727,7 +728,7 @@ fn test_run_linter_terminal_with_info_violations() {
         "test_term_info.py",
         r#"
 def test_ok():
-    assert 1 + 1 == 2
+    assert add(1, 1) == 2
 "#,
     );
     let output_path = dir.path().join("info.txt");
@@ -778,7 +779,7 @@ def test_no_assert():
         .unwrap();
     let content = std::fs::read_to_string(&output_path).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
-    let arr = parsed.as_array().unwrap();
+    let arr = parsed["diagnostics"].as_array().unwrap();
     assert!(!arr.is_empty());
     let has_mnt004 = arr
         .iter()
@@ -1657,7 +1658,7 @@ fn test_run_linter_json_to_stdout() {
         "test_json_stdout.py",
         r#"
 def test_ok():
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let has_errors = pytest_linter::engine::run_linter(
@@ -2413,7 +2414,7 @@ import time
 
 def test_waits():
     time.sleep(1)
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let output_path = dir.path().join("warning_output.txt");
@@ -2427,7 +2428,7 @@ def test_waits():
     .unwrap();
     assert!(!has_errors);
     let content = std::fs::read_to_string(&output_path).unwrap();
-    assert!(content.contains("WARNING"));
+    assert!(content.contains("warning[PYTEST-FLK-001]"));
     assert!(content.contains("Summary"));
 }
 
@@ -3516,6 +3517,38 @@ def test_error():
     );
 }
 
+#[test]
+fn test_unittest_assert_raises_does_not_trigger_dbc001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_unittest_raises.py",
+        r#"
+import unittest
+
+class TestParse(unittest.TestCase):
+    def test_rejects_empty(self):
+        with self.assertRaises(ValueError):
+            parse("")
+        self.assertEqual(parse("1"), 1)
+
+    def test_rejects_none(self):
+        self.assertRaises(TypeError, parse, None)
+        self.assertEqual(parse("2"), 2)
+
+    def test_parses(self):
+        self.assertEqual(parse("3"), 3)
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let flagged: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-DBC-001")
+        .filter_map(|v| v.test_name.as_deref())
+        .collect();
+    assert_eq!(flagged, ["test_parses"]);
+}
+
 #[test]
 fn test_parametrized_does_not_trigger_dbc001() {
     let dir = tempfile::tempdir().unwrap();
@@ -3867,8 +3900,14 @@ fn test_save_and_load_baseline() {
             file_path: PathBuf::from("test_foo.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         },
         pytest_linter::models::Violation {
             rule_id: "PYTEST-FLK-001".to_string(),
@@ -3879,8 +3918,14 @@ fn test_save_and_load_baseline() {
             file_path: PathBuf::from("test_bar.py"),
             line: 10,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         },
     ];
 
@@ -3888,8 +3933,14 @@ fn test_save_and_load_baseline() {
     let loaded = pytest_linter::engine::load_baseline(&baseline_path).unwrap();
 
     assert_eq!(loaded.len(), 2);
-    assert!(loaded.contains(&("test_foo.py".to_string(), 5, "PYTEST-MNT-004".to_string())));
-    assert!(loaded.contains(&("test_bar.py".to_string(), 10, "PYTEST-FLK-001".to_string())));
+    assert!(loaded
+        .entries
+        .iter()
+        .any(|e| e.path == "test_foo.py" && e.rule_id == "PYTEST-MNT-004"));
+    assert!(loaded
+        .entries
+        .iter()
+        .any(|e| e.path == "test_bar.py" && e.rule_id == "PYTEST-FLK-001"));
 }
 
 #[test]
@@ -3919,11 +3970,17 @@ fn test_filter_new_violations_empty_baseline() {
         file_path: PathBuf::from("test_foo.py"),
         line: 5,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: None,
         test_name: None,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    let baseline = std::collections::HashSet::new();
+    let baseline = pytest_linter::baseline::Baseline::default();
     let filtered = pytest_linter::engine::filter_new_violations(&violations, &baseline);
     assert_eq!(filtered.len(), 1);
     assert_eq!(filtered[0].rule_id, "PYTEST-MNT-004");
@@ -3941,8 +3998,14 @@ fn test_filter_new_violations_with_baseline_match() {
             file_path: PathBuf::from("test_foo.py"),
             line: 5,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         },
         pytest_linter::models::Violation {
             rule_id: "PYTEST-FLK-001".to_string(),
@@ -3953,13 +4016,18 @@ fn test_filter_new_violations_with_baseline_match() {
             file_path: PathBuf::from("test_bar.py"),
             line: 10,
             col: None,
+            end_line: None,
+            end_col: None,
             suggestion: None,
             test_name: None,
+            fix: None,
+            metadata: Default::default(),
+            tags: Vec::new(),
+            related: Vec::new(),
         },
     ];
 
-    let mut baseline = std::collections::HashSet::new();
-    baseline.insert(("test_foo.py".to_string(), 5, "PYTEST-MNT-004".to_string()));
+    let baseline = pytest_linter::baseline::Baseline::from_violations(&violations[..1]);
 
     let filtered = pytest_linter::engine::filter_new_violations(&violations, &baseline);
     assert_eq!(filtered.len(), 1);
@@ -3977,12 +4045,17 @@ fn test_filter_new_violations_all_in_baseline() {
         file_path: PathBuf::from("test_foo.py"),
         line: 5,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: None,
         test_name: None,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    let mut baseline = std::collections::HashSet::new();
-    baseline.insert(("test_foo.py".to_string(), 5, "PYTEST-MNT-004".to_string()));
+    let baseline = pytest_linter::baseline::Baseline::from_violations(&violations);
 
     let filtered = pytest_linter::engine::filter_new_violations(&violations, &baseline);
     assert!(filtered.is_empty());
@@ -3995,7 +4068,76 @@ fn test_save_baseline_empty_violations() {
 
     pytest_linter::engine::save_baseline(&[], &baseline_path).unwrap();
     let content = std::fs::read_to_string(&baseline_path).unwrap();
-    assert_eq!(content.trim(), "[]");
+    let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(parsed["entries"], serde_json::json!([]));
+}
+
+fn baseline_run(dir: &Path, source: &str) -> Vec<pytest_linter::models::Violation> {
+    let path = write_temp_file(dir, "test_legacy.py", source);
+    pytest_linter::engine::collect_violations(&[path], Config::default()).unwrap()
+}
+
+#[test]
+fn test_baseline_survives_line_shifts() {
+    let dir = tempfile::tempdir().unwrap();
+    let before = "import time\n\ndef test_wait():\n    time.sleep(1)\n    assert True\n";
+    let baseline =
+        pytest_linter::baseline::Baseline::from_violations(&baseline_run(dir.path(), before));
+    assert!(!baseline.is_empty());
+
+    // Unrelated code above the test shifts every line down
+    let after = format!(
+        "import time\n\ndef test_other():\n    assert 1 == 1\n\n{}",
+        &before[12..]
+    );
+    let outcome = baseline.apply(&baseline_run(dir.path(), &after));
+    // Only the newly added test is reported
+    assert!(
+        outcome
+            .new_violations
+            .iter()
+            .all(|v| v.test_name.as_deref() == Some("test_other")),
+        "{:?}",
+        outcome.new_violations
+    );
+    assert_eq!(outcome.matched, baseline.len());
+    assert!(outcome.stale.is_empty());
+}
+
+#[test]
+fn test_baseline_reports_new_and_stale_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let before = "import time\n\ndef test_wait():\n    time.sleep(1)\n    assert True\n";
+    let baseline =
+        pytest_linter::baseline::Baseline::from_violations(&baseline_run(dir.path(), before));
+
+    // Editing the test body changes its fingerprint: the old entry goes stale and the
+    // violation is reported as new
+    let after = "import time\n\ndef test_wait():\n    time.sleep(2)\n    assert True\n";
+    let outcome = baseline.apply(&baseline_run(dir.path(), after));
+    assert!(outcome
+        .new_violations
+        .iter()
+        .any(|v| v.rule_id == "PYTEST-FLK-001"));
+    assert!(outcome
+        .stale
+        .iter()
+        .any(|e| e.rule_id == "PYTEST-FLK-001" && e.test_name.as_deref() == Some("test_wait")));
+    let report = pytest_linter::baseline::format_report(&baseline, &outcome);
+    assert!(report.contains("stale: "), "{report}");
+}
+
+#[test]
+fn test_load_baseline_rejects_old_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline_path = dir.path().join("old.json");
+    std::fs::write(
+        &baseline_path,
+        r#"[{"file_path": "test_a.py", "line": 1, "rule_id": "PYTEST-MNT-004"}]"#,
+    )
+    .unwrap();
+    let err = pytest_linter::engine::load_baseline(&baseline_path).unwrap_err();
+    assert!(err.to_string().contains("--write-baseline"));
 }
 
 #[test]
@@ -4027,15 +4169,64 @@ fn test_format_json_output_to_file() {
         file_path: PathBuf::from("test.py"),
         line: 1,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: Some("add assert".to_string()),
         test_name: Some("test_foo".to_string()),
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    pytest_linter::engine::format_json_output(&violations, Some(&output_path)).unwrap();
+    pytest_linter::engine::format_json_output(&violations, &Config::default(), Some(&output_path))
+        .unwrap();
     let content = std::fs::read_to_string(&output_path).unwrap();
     let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(parsed.is_array());
-    assert_eq!(parsed.as_array().unwrap().len(), 1);
+    assert_eq!(parsed["schema_version"], 2);
+    assert_eq!(parsed["version"], env!("CARGO_PKG_VERSION"));
+    let diagnostics = parsed["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["path"], "test.py");
+    assert_eq!(diagnostics[0]["suggestion"], "add assert");
+    assert_eq!(diagnostics[0]["span"]["line"], 1);
+}
+
+#[test]
+fn test_run_linter_json_lines_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_json_lines.py",
+        "import time\n\ndef test_bad():\n    time.sleep(1)\n",
+    );
+    let output_path = dir.path().join("out.jsonl");
+    pytest_linter::engine::run_linter(
+        &[path],
+        "json-lines",
+        Some(&output_path),
+        true,
+        Config::default(),
+    )
+    .unwrap();
+    let content = std::fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<serde_json::Value> = content
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert!(lines.len() >= 2);
+    let sleep = lines
+        .iter()
+        .find(|d| d["rule_id"] == "PYTEST-FLK-001")
+        .unwrap();
+    assert_eq!(sleep["span"]["line"], 4);
+    assert_eq!(sleep["span"]["column"], 5);
+    let (start, end) = (
+        sleep["span"]["start_byte"].as_u64().unwrap() as usize,
+        sleep["span"]["end_byte"].as_u64().unwrap() as usize,
+    );
+    let source = std::fs::read_to_string(dir.path().join("test_json_lines.py")).unwrap();
+    assert_eq!(&source[start..end], "time.sleep(1)");
 }
 
 #[test]
@@ -4051,8 +4242,14 @@ fn test_format_sarif_output_to_file() {
         file_path: PathBuf::from("test.py"),
         line: 1,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: None,
         test_name: None,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
     pytest_linter::engine::format_sarif_output(&violations, Some(&output_path)).unwrap();
@@ -4074,11 +4271,17 @@ fn test_format_terminal_output_to_file() {
         file_path: PathBuf::from("test.py"),
         line: 1,
         col: Some(5),
+        end_line: None,
+        end_col: None,
         suggestion: Some("add assert".to_string()),
         test_name: Some("test_foo".to_string()),
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    pytest_linter::engine::format_terminal_output(&violations, Some(&output_path), true).unwrap();
+    pytest_linter::engine::format_short_output(&violations, Some(&output_path), true).unwrap();
     let content = std::fs::read_to_string(&output_path).unwrap();
     assert!(content.contains("ERROR"));
     assert!(content.contains("PYTEST-MNT-004"));
@@ -4098,11 +4301,17 @@ fn test_format_terminal_output_with_col_none() {
         file_path: PathBuf::from("test.py"),
         line: 3,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: None,
         test_name: None,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    pytest_linter::engine::format_terminal_output(&violations, Some(&output_path), true).unwrap();
+    pytest_linter::engine::format_short_output(&violations, Some(&output_path), true).unwrap();
     let content = std::fs::read_to_string(&output_path).unwrap();
     assert!(content.contains("WARNING"));
     assert!(content.contains("-"));
@@ -4121,11 +4330,17 @@ fn test_format_terminal_output_info_severity() {
         file_path: PathBuf::from("test.py"),
         line: 1,
         col: None,
+        end_line: None,
+        end_col: None,
         suggestion: None,
         test_name: None,
+        fix: None,
+        metadata: Default::default(),
+        tags: Vec::new(),
+        related: Vec::new(),
     }];
 
-    pytest_linter::engine::format_terminal_output(&violations, Some(&output_path), true).unwrap();
+    pytest_linter::engine::format_short_output(&violations, Some(&output_path), true).unwrap();
     let content = std::fs::read_to_string(&output_path).unwrap();
     assert!(content.contains("INFO"));
 }
@@ -4166,6 +4381,67 @@ def test_bad():
     assert!(parsed.get("$schema").is_some());
 }
 
+#[test]
+fn test_run_linter_github_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_github.py",
+        r#"
+def test_bad():
+    pass
+"#,
+    );
+    let output_path = dir.path().join("annotations.txt");
+    let has_errors = pytest_linter::engine::run_linter(
+        &[path],
+        "github",
+        Some(&output_path),
+        true,
+        Config::default(),
+    )
+    .unwrap();
+    assert!(has_errors);
+    let content = std::fs::read_to_string(&output_path).unwrap();
+    let line = content
+        .lines()
+        .find(|l| l.contains("PYTEST-MNT-004"))
+        .unwrap();
+    assert!(line.starts_with("::error file="));
+    assert!(line.contains("test_github.py,line=2,"));
+    assert!(line.contains("title=no-assertion (PYTEST-MNT-004)::"));
+}
+
+#[test]
+fn test_run_linter_checkstyle_and_junit_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_xml.py",
+        r#"
+def test_bad():
+    pass
+"#,
+    );
+    for (format, needle) in [
+        ("checkstyle", "source=\"PYTEST-MNT-004\""),
+        ("junit", "<testcase name=\"PYTEST-MNT-004."),
+    ] {
+        let output_path = dir.path().join(format!("{format}.xml"));
+        pytest_linter::engine::run_linter(
+            std::slice::from_ref(&path),
+            format,
+            Some(&output_path),
+            true,
+            Config::default(),
+        )
+        .unwrap();
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.starts_with("<?xml"), "{format}");
+        assert!(content.contains(needle), "{format}: {content}");
+    }
+}
+
 #[test]
 fn test_run_linter_sarif_to_stdout() {
     let dir = tempfile::tempdir().unwrap();
@@ -4174,7 +4450,7 @@ fn test_run_linter_sarif_to_stdout() {
         "test_sarif_stdout.py",
         r#"
 def test_ok():
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let has_errors = pytest_linter::engine::run_linter(
@@ -4202,7 +4478,7 @@ fn test_run_linter_terminal_no_color_stdout() {
         "test_no_color.py",
         r#"
 def test_ok():
-    assert True
+    assert add(1, 1) == 2
 "#,
     );
     let has_errors = pytest_linter::engine::run_linter(
@@ -5895,6 +6171,114 @@ def test_second():
     assert!(v.message.contains("identical body"));
     assert!(v.message.contains("test_first"));
     assert!(v.message.contains("test_second"));
+    // Only the later copy is reported, pointing back at the original
+    assert_eq!(v.test_name.as_deref(), Some("test_second"));
+    assert_eq!(v.line, 6);
+    assert!(v.message.contains("(line 2)"));
+    assert_eq!(
+        violations
+            .iter()
+            .filter(|v| v.rule_id == "PYTEST-MNT-015")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_duplicate_test_bodies_ignore_comments_and_docstrings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_dup_normalized.py",
+        r#"
+def test_first():
+    result = compute(1)
+    assert result == 2
+
+def test_second():
+    """Same check, copied."""
+    result = compute( 1 )  # copied from above
+    assert result == 2
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-MNT-015").expect("Expected PYTEST-MNT-015");
+    assert_eq!(v.test_name.as_deref(), Some("test_second"));
+}
+
+#[test]
+fn test_duplicate_test_names_trigger_mnt022() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_dup_names.py",
+        r#"
+def test_parse():
+    assert parse("a") == ["a"]
+
+def test_parse():
+    assert parse("b") == ["b"]
+
+class TestParser:
+    def test_parse(self):
+        assert parse("c") == ["c"]
+
+    def test_parse(self):
+        assert parse("d") == ["d"]
+
+class TestOther:
+    def test_parse(self):
+        assert parse("e") == ["e"]
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let names: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-022")
+        .collect();
+    assert_eq!(names.len(), 2);
+    assert_eq!((names[0].line, names[0].col), (5, Some(5)));
+    assert!(names[0].message.contains("line 2"));
+    assert_eq!(names[1].line, 12);
+    assert!(names[1].message.contains("in class 'TestParser'"));
+    assert!(names[1].message.contains("line 9"));
+    assert_eq!(names[0].severity, Severity::Error);
+    // Each points back at the definition it shadows
+    let related: Vec<_> = names
+        .iter()
+        .map(|v| (v.related.len(), v.related[0].line, v.related[0].col))
+        .collect();
+    assert_eq!(related, vec![(1, 2, Some(5)), (1, 9, Some(9))]);
+    assert_eq!(names[0].related[0].file_path, path);
+    assert_eq!(
+        names[0].related[0].message,
+        "previous definition of 'test_parse', which never runs"
+    );
+}
+
+#[test]
+fn test_a_file_reached_through_two_paths_is_reported_once() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("unit")).unwrap();
+    write_temp_file(
+        dir.path(),
+        "test_sleep.py",
+        "import time\n\ndef test_wait():\n    time.sleep(1)\n",
+    );
+    let engine = LintEngine::new(Config::default()).unwrap();
+    let once = engine.lint_paths(&[dir.path().to_path_buf()]).unwrap();
+    assert!(!once.is_empty());
+    let detour = dir.path().join("unit").join("..").join("test_sleep.py");
+    let twice = engine
+        .lint_paths(&[dir.path().to_path_buf(), detour])
+        .unwrap();
+    let key = |v: &pytest_linter::models::Violation| {
+        (v.rule_id.clone(), v.line, v.col, v.message.clone())
+    };
+    assert_eq!(
+        twice.iter().map(key).collect::<Vec<_>>(),
+        once.iter().map(key).collect::<Vec<_>>()
+    );
 }
 
 #[test]
@@ -5942,6 +6326,41 @@ def test_slow():
     assert!(v.message.contains("> 0.1s"));
 }
 
+#[test]
+fn test_mnt016_names_the_sleep_it_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_names.py",
+        r#"
+import asyncio as aio
+import time
+
+async def test_waits():
+    time.sleep(0.05)
+    await aio.sleep(1)
+    assert True
+
+def test_naps():
+    time.sleep(2)
+    assert True
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let messages: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-016")
+        .map(|v| v.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Test 'test_waits' uses asyncio.sleep() with value > 0.1s — slows test suite",
+            "Test 'test_naps' uses time.sleep() with value > 0.1s — slows test suite",
+        ]
+    );
+}
+
 #[test]
 fn test_sleep_exactly_0_1_no_mnt016() {
     let dir = tempfile::tempdir().unwrap();
@@ -6344,3 +6763,3228 @@ def test_fetch(mock_fetch):
         "mocking non-stdlib module should NOT trigger MNT-005"
     );
 }
+
+#[test]
+fn test_too_many_assertions_reports_count_and_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_many_sites.py",
+        r#"
+def test_everything():
+    user = make_user()
+    assert user.name == "a", "name"
+    assert user.email == "b", "email"
+    assert user.age == 1, "age"
+    assert user.active, "active"
+    assert user.role == "r", "role"
+    assert_valid_user(user)
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-MNT-018").expect("Expected PYTEST-MNT-018");
+    assert_eq!(v.rule_name, "TooManyAssertionsRule");
+    assert!(
+        v.message.contains("has 6 assertions (limit 5)"),
+        "{}",
+        v.message
+    );
+    assert!(
+        find_violation(&violations, "PYTEST-MNT-006").is_none(),
+        "assertions carry messages, so this is not assertion roulette"
+    );
+}
+
+#[test]
+fn test_too_many_assertions_parametrized_counts_sites_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_param_sites.py",
+        r#"
+import pytest
+
+@pytest.mark.parametrize("a,b", [(1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 7)])
+def test_inc(a, b):
+    for step in [a, b]:
+        assert inc(step) == step + 1
+    assert b == a + 1
+"#,
+    );
+    let module = parse_file(&path);
+    assert_eq!(module.test_functions[0].assertion_site_count, 2);
+    let violations = lint_single_file(&path);
+    assert!(find_violation(&violations, "PYTEST-MNT-018").is_none());
+}
+
+#[test]
+fn test_too_many_assertions_respects_max_option() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("pytl.toml"),
+        "[rules.too-many-assertions]\nmax = 2\n",
+    )
+    .unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_three_sites.py",
+        r#"
+def test_three():
+    assert a == 1, "a"
+    assert b == 2, "b"
+    assert c == 3, "c"
+"#,
+    );
+    let config = Config::discover(dir.path()).unwrap();
+    let engine = LintEngine::new(config).unwrap();
+    let violations = engine.lint_paths(&[path]).unwrap();
+    let v = find_violation(&violations, "PYTEST-MNT-018").expect("Expected PYTEST-MNT-018");
+    assert!(
+        v.message.contains("has 3 assertions (limit 2)"),
+        "{}",
+        v.message
+    );
+}
+
+#[test]
+fn test_no_assertion_accepts_exception_assertions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_raises_only.py",
+        r#"
+import pytest
+import pytest as pt
+from pytest import raises as expect_error
+
+def test_with_raises():
+    with pytest.raises(ValueError):
+        int("x")
+
+def test_aliased_raises():
+    with expect_error(ValueError):
+        int("x")
+
+def test_module_alias_warns():
+    with pt.warns(UserWarning):
+        warn_user()
+
+def test_deprecated_call():
+    with pytest.deprecated_call():
+        old_api()
+
+def test_callable_form():
+    pytest.raises(ZeroDivisionError, divide, 1, 0)
+
+def test_unittest_style(case):
+    case.assertRaises(KeyError, lookup, "missing")
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let flagged: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-004")
+        .map(|v| v.test_name.clone().unwrap_or_default())
+        .collect();
+    assert!(flagged.is_empty(), "unexpected MNT-004 for {flagged:?}");
+}
+
+#[test]
+fn test_no_assertion_unimported_raises_still_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_fake_raises.py",
+        r#"
+def test_local_raises():
+    with raises(ValueError):
+        int("x")
+"#,
+    );
+    let violations = lint_single_file(&path);
+    assert!(find_violation(&violations, "PYTEST-MNT-004").is_some());
+}
+
+#[test]
+fn test_raises_block_trailing_code_triggers_mnt019() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_raises_trailing.py",
+        r#"
+import pytest
+
+def test_parse_rejects_garbage(log):
+    with pytest.raises(ValueError) as exc:
+        parse("garbage")
+        assert log.called
+    assert "garbage" in str(exc.value)
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-MNT-019").expect("Expected PYTEST-MNT-019");
+    assert_eq!(v.rule_name, "RaisesBlockTrailingCodeRule");
+    assert_eq!(v.line, 7);
+    assert_eq!(v.col, Some(9));
+    assert!(v.message.contains("`pytest.raises`"));
+}
+
+#[test]
+fn test_raises_block_single_statement_or_warns_does_not_trigger_mnt019() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_raises_clean.py",
+        r#"
+import pytest
+
+def test_single_statement():
+    with pytest.raises(ValueError):
+        parse("garbage")
+
+def test_warns_keeps_running():
+    with pytest.warns(UserWarning):
+        result = legacy()
+        assert result == 1
+"#,
+    );
+    let violations = lint_single_file(&path);
+    assert!(find_violation(&violations, "PYTEST-MNT-019").is_none());
+}
+
+#[test]
+fn test_time_sleep_aliased_import_triggers_flk001_at_call() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_alias.py",
+        r#"
+from time import sleep as wait
+
+def test_worker_finishes(worker):
+    worker.start()
+    wait(0.5)
+    assert worker.done
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-FLK-001").expect("Expected PYTEST-FLK-001");
+    assert_eq!(v.line, 6);
+    assert_eq!(v.col, Some(5));
+    assert!(v.message.contains("wait()"));
+}
+
+#[test]
+fn test_time_sleep_asyncio_and_module_alias_trigger_flk001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_async.py",
+        r#"
+import asyncio as aio
+import time as t
+
+async def test_poll(client):
+    await aio.sleep(1)
+    t.sleep(2)
+    assert client.ready
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let sleeps: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-001")
+        .collect();
+    assert_eq!(sleeps.len(), 2);
+    assert_eq!((sleeps[0].line, sleeps[0].col), (6, Some(11)));
+    assert_eq!((sleeps[1].line, sleeps[1].col), (7, Some(5)));
+}
+
+#[test]
+fn test_time_sleep_through_helper_triggers_flk001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_helper.py",
+        r#"
+import time
+
+def settle():
+    time.sleep(1)
+
+def wait_for_cache():
+    settle()
+
+def test_cache_warm(cache):
+    wait_for_cache()
+    assert cache.warm
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-FLK-001").expect("Expected PYTEST-FLK-001");
+    assert_eq!(v.line, 11);
+    assert!(v.message.contains("wait_for_cache()"));
+    assert!(v.message.contains("time.sleep()"));
+}
+
+#[test]
+fn test_time_sleep_in_retry_helper_does_not_trigger_flk001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_retry.py",
+        r#"
+import time
+
+def retry_until(check):
+    for _ in range(5):
+        if check():
+            return
+        time.sleep(0.1)
+
+def test_ready(service):
+    retry_until(service.ready)
+    assert service.ready()
+"#,
+    );
+    let violations = lint_single_file(&path);
+    assert!(find_violation(&violations, "PYTEST-FLK-001").is_none());
+}
+
+#[test]
+fn test_unrelated_sleep_method_does_not_trigger_flk001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_sleep_method.py",
+        r#"
+def test_device_sleeps(device):
+    device.sleep()
+    assert device.asleep
+"#,
+    );
+    let violations = lint_single_file(&path);
+    assert!(find_violation(&violations, "PYTEST-FLK-001").is_none());
+}
+
+fn mnt001_violations(source: &str, config: Config) -> Vec<pytest_linter::models::Violation> {
+    let engine = LintEngine::new(config).unwrap();
+    engine
+        .lint_source(source, Path::new("test_logic.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-001")
+        .collect()
+}
+
+#[test]
+fn test_logic_rule_flags_each_construct_at_its_keyword() {
+    let source = r#"
+def test_with_logic(items):
+    for item in items:
+        item.load()
+        assert item.ok
+    try:
+        items.commit()
+    except Exception:
+        pass
+    assert (1 if items else 0) == 1
+    while items.pending():
+        items.flush()
+"#;
+    let violations = mnt001_violations(source, Config::default());
+    let spans: Vec<_> = violations
+        .iter()
+        .map(|v| (v.line, v.col, v.end_col))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            (3, Some(5), Some(8)),
+            (6, Some(5), Some(8)),
+            (10, Some(15), Some(17)),
+            (11, Some(5), Some(10)),
+        ]
+    );
+    assert!(violations[0].message.contains("`for` loop"));
+    assert!(violations[2].message.contains("conditional expression"));
+}
+
+#[test]
+fn test_logic_rule_exempts_single_assert_loops_and_nested_helpers() {
+    let source = r#"
+import pytest
+
+def test_all_positive(values):
+    for v in values:
+        assert v > 0
+
+    def pick(x):
+        if x:
+            return x
+        return None
+
+    assert pick(values)
+"#;
+    assert!(mnt001_violations(source, Config::default()).is_empty());
+}
+
+#[test]
+fn test_logic_rule_allowed_constructs_option() {
+    let source = r#"
+def test_table(cases):
+    for case in cases:
+        result = run(case)
+        assert result.ok
+    if cases:
+        assert cases[0]
+"#;
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-001".to_string())
+        .or_default();
+    rc.options.insert(
+        "allowed".to_string(),
+        toml::Value::Array(vec![toml::Value::String("for".to_string())]),
+    );
+    rc.options.insert(
+        "allow_single_assert_loops".to_string(),
+        toml::Value::Boolean(false),
+    );
+    let violations = mnt001_violations(source, config);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].line, 6);
+    assert!(violations[0].message.contains("`if` statement"));
+}
+
+fn flk005_violations(source: &str, config: Config) -> Vec<pytest_linter::models::Violation> {
+    let engine = LintEngine::new(config).unwrap();
+    engine
+        .lint_source(source, Path::new("test_guest.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-005")
+        .collect()
+}
+
+#[test]
+fn test_mystery_guest_reports_network_paths_and_path_io() {
+    let source = r#"
+from pathlib import Path
+from urllib.request import urlopen as fetch
+import socket
+
+def test_sync(client):
+    body = fetch("https://example.com/feed")
+    conn = socket.create_connection(("db", 5432))
+    config = Path("settings.toml").read_text()
+    client.upload("/tmp/report.csv")
+    assert client.get("/api/status").ok
+"#;
+    let violations = flk005_violations(source, Config::default());
+    let found: Vec<_> = violations.iter().map(|v| (v.line, v.col)).collect();
+    assert_eq!(
+        found,
+        vec![(7, Some(12)), (8, Some(12)), (9, Some(14)), (10, Some(19))]
+    );
+    assert!(violations[0].message.contains("network call via fetch()"));
+    assert!(violations[2].message.contains("Path.read_text()"));
+    assert!(violations[3].message.contains("'/tmp/report.csv'"));
+}
+
+#[test]
+fn test_mystery_guest_tempfile_and_mock_layer_are_not_flagged() {
+    let source = r#"
+import tempfile
+import requests
+import responses
+
+@responses.activate
+def test_download():
+    with tempfile.NamedTemporaryFile() as tmp:
+        with open(tmp.name, "w") as f:
+            f.write(requests.get("https://example.com").text)
+    assert True
+"#;
+    assert!(flk005_violations(source, Config::default()).is_empty());
+}
+
+#[test]
+fn test_mystery_guest_allowlisted_fixtures_and_modules() {
+    let source = r#"
+from tests.fakes import requests
+
+def test_fake_network():
+    assert requests.get("https://example.com").ok
+
+def test_fake_files(fake_disk):
+    assert open("data.txt").read()
+"#;
+    assert_eq!(flk005_violations(source, Config::default()).len(), 1);
+
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-FLK-005".to_string())
+        .or_default();
+    rc.options.insert(
+        "allowed_fixtures".to_string(),
+        toml::Value::Array(vec![toml::Value::String("fake_disk".to_string())]),
+    );
+    assert!(flk005_violations(source, config).is_empty());
+}
+
+#[test]
+fn test_mystery_guest_allowed_modules_option() {
+    let source = r#"
+import socket
+
+def test_local_port():
+    assert socket.gethostname()
+"#;
+    assert_eq!(flk005_violations(source, Config::default()).len(), 1);
+
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-FLK-005".to_string())
+        .or_default();
+    rc.options.insert(
+        "allowed_modules".to_string(),
+        toml::Value::Array(vec![toml::Value::String("socket.gethostname".to_string())]),
+    );
+    assert!(flk005_violations(source, config).is_empty());
+}
+
+#[test]
+fn test_assertion_roulette_lists_bare_assertion_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_roulette_lines.py",
+        r#"
+def test_user_fields():
+    user = make_user()
+    assert user.name == "a", "name is kept"
+    assert user.email == "b"
+    assert user.age == 1
+    assert user.active
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-MNT-006").expect("Expected PYTEST-MNT-006");
+    assert!(
+        v.message.contains("4 assertions, 3 without messages"),
+        "{}",
+        v.message
+    );
+    assert!(v.message.ends_with("lines 5, 6, 7"), "{}", v.message);
+}
+
+#[test]
+fn test_assertion_roulette_respects_message_ratio_and_limit() {
+    let source = r#"
+def test_mostly_explained(user):
+    assert user.name == "a", "name"
+    assert user.email == "b", "email"
+    assert user.age == 1
+    assert user.active
+"#;
+    let roulette = |config: Config| {
+        let engine = LintEngine::new(config).unwrap();
+        engine
+            .lint_source(source, Path::new("test_ratio.py"))
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.rule_id == "PYTEST-MNT-006")
+            .count()
+    };
+    assert_eq!(
+        roulette(Config::default()),
+        0,
+        "half the asserts have messages"
+    );
+
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-006".to_string())
+        .or_default();
+    rc.options
+        .insert("min_message_ratio".to_string(), toml::Value::Float(0.75));
+    assert_eq!(roulette(config), 1);
+
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-006".to_string())
+        .or_default();
+    rc.options
+        .insert("min_message_ratio".to_string(), toml::Value::Float(0.75));
+    rc.options
+        .insert("max".to_string(), toml::Value::Integer(4));
+    assert_eq!(roulette(config), 0);
+}
+
+#[test]
+fn test_assertion_roulette_never_flags_single_assertion() {
+    let source = "def test_one():\n    assert compute() == 3\n";
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-006".to_string())
+        .or_default();
+    rc.options
+        .insert("max".to_string(), toml::Value::Integer(0));
+    let engine = LintEngine::new(config).unwrap();
+    let violations = engine
+        .lint_source(source, Path::new("test_single.py"))
+        .unwrap();
+    assert!(find_violation(&violations, "PYTEST-MNT-006").is_none());
+}
+
+#[test]
+fn test_skip_without_reason_triggers_mnt020() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_skips.py",
+        r#"
+import sys
+import pytest
+
+windows_only = pytest.mark.skipif(sys.platform != "win32")
+
+@pytest.mark.skip
+def test_disabled():
+    assert compute() == 1
+
+@pytest.mark.skip("upstream bug #12")
+def test_documented():
+    assert compute() == 1
+
+@windows_only
+def test_registry():
+    assert read_registry()
+
+@pytest.mark.skipif(sys.version_info < (3, 9), reason="needs 3.9")
+def test_new_syntax():
+    assert parse() == 1
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let skips: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-020")
+        .collect();
+    assert_eq!(skips.len(), 2);
+    assert_eq!(skips[0].rule_name, "SkipWithoutReasonRule");
+    assert_eq!((skips[0].line, skips[0].col), (7, Some(2)));
+    assert_eq!(skips[0].test_name.as_deref(), Some("test_disabled"));
+    assert_eq!(skips[1].line, 15);
+    assert!(skips[1].message.contains("via `windows_only`"));
+    assert!(skips[1].message.contains("pytest.mark.skipif"));
+}
+
+#[test]
+fn test_skip_without_reason_in_pytestmark_triggers_mnt020() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_pytestmark.py",
+        r#"
+import pytest
+
+pytestmark = [pytest.mark.slow, pytest.mark.skip()]
+
+class TestLegacy:
+    pytestmark = pytest.mark.skipif(True)
+
+    def test_old(self):
+        assert True
+"#,
+    );
+    let violations = lint_single_file(&path);
+    let skips: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-020")
+        .collect();
+    assert_eq!(skips.len(), 2);
+    assert_eq!((skips[0].line, skips[0].col), (4, Some(33)));
+    assert!(skips[0].message.contains("all tests in the module"));
+    assert!(skips[1].message.contains("all tests in the class"));
+}
+
+#[test]
+fn test_parallel_results_match_sequential() {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..40 {
+        let source = format!(
+            "import time\n\ndef test_sleep_{i}():\n    time.sleep(1)\n    time.sleep(2)\n\n\
+             def test_empty_{i}():\n    pass\n\n\
+             def test_logic_{i}(x):\n    if x:\n        assert x\n"
+        );
+        std::fs::write(dir.path().join(format!("test_mod_{i:02}.py")), source).unwrap();
+    }
+    let run = |jobs: Option<usize>| {
+        let config = Config {
+            jobs,
+            ..Config::default()
+        };
+        let engine = LintEngine::new(config).unwrap();
+        let violations = engine.lint_paths(&[dir.path().to_path_buf()]).unwrap();
+        serde_json::to_string(&violations).unwrap()
+    };
+    let sequential = run(Some(1));
+    assert!(sequential.contains("PYTEST-FLK-001"));
+    assert_eq!(run(Some(4)), sequential);
+    assert_eq!(run(None), sequential);
+}
+
+#[test]
+fn test_cache_serves_unchanged_files_and_invalidates_on_config_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_cached.py",
+        "import time\n\ndef test_wait():\n    time.sleep(1)\n",
+    );
+    let cache_dir = dir.path().join(pytest_linter::cache::CACHE_DIR_NAME);
+    let run = |config: Config| {
+        let engine = LintEngine::new(Config {
+            cache_dir: Some(cache_dir.clone()),
+            ..config
+        })
+        .unwrap();
+        let violations = engine.lint_paths(std::slice::from_ref(&path)).unwrap();
+        (
+            serde_json::to_string(&violations).unwrap(),
+            engine.cache_stats().unwrap(),
+        )
+    };
+
+    let (first, stats) = run(Config::default());
+    assert_eq!((stats.hits, stats.misses), (0, 1));
+    let (second, stats) = run(Config::default());
+    assert_eq!((stats.hits, stats.misses), (1, 0));
+    assert_eq!(first, second);
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-FLK-001".to_string())
+        .or_default()
+        .enabled = Some(false);
+    let (disabled, stats) = run(config);
+    assert_eq!((stats.hits, stats.misses), (0, 1));
+    assert!(!disabled.contains("PYTEST-FLK-001"));
+
+    // Editing the file is a miss even with the original configuration cached before
+    std::fs::write(&path, "def test_wait():\n    assert True\n").unwrap();
+    let (edited, stats) = run(Config::default());
+    assert_eq!(stats.misses, 1);
+    assert!(!edited.contains("PYTEST-FLK-001"));
+}
+
+#[test]
+fn test_directory_config_applies_below_its_directory_and_keys_the_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let e2e = dir.path().join("e2e");
+    std::fs::create_dir_all(&e2e).unwrap();
+    std::fs::write(dir.path().join("pytl.toml"), "[rules]\n").unwrap();
+    let source = "import time\n\ndef test_wait():\n    time.sleep(1)\n";
+    let top = write_temp_file(dir.path(), "test_top.py", source);
+    let nested = write_temp_file(&e2e, "test_nested.py", source);
+    let cache_dir = dir.path().join(pytest_linter::cache::CACHE_DIR_NAME);
+    let run = || {
+        let engine = LintEngine::new(Config {
+            cache_dir: Some(cache_dir.clone()),
+            ..Config::discover(dir.path()).unwrap()
+        })
+        .unwrap();
+        let violations = engine.lint_paths(&[top.clone(), nested.clone()]).unwrap();
+        let severities: Vec<_> = violations
+            .iter()
+            .filter(|v| v.rule_id == "PYTEST-FLK-001")
+            .map(|v| (v.file_path.file_name().unwrap().to_owned(), v.severity))
+            .collect();
+        (severities, engine.cache_stats().unwrap())
+    };
+
+    let (before, _) = run();
+    assert!(
+        before.iter().all(|(_, s)| *s == Severity::Warning),
+        "{before:?}"
+    );
+    assert_eq!(before.len(), 2);
+
+    std::fs::write(
+        e2e.join("pytl.toml"),
+        "[severity]\nsleepy-test = \"info\"\n",
+    )
+    .unwrap();
+    let (after, stats) = run();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+    assert_eq!(
+        after,
+        [
+            ("test_nested.py".into(), Severity::Info),
+            ("test_top.py".into(), Severity::Warning)
+        ]
+    );
+}
+
+#[test]
+fn test_fix_adds_skip_reason_and_removes_assert_true() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_fixable.py",
+        "import pytest\n\n\
+         @pytest.mark.skip\n\
+         def test_skipped():\n    assert compute() == 1\n\n\
+         @pytest.mark.skipif(sys.platform == 'win32')\n\
+         def test_posix():\n    x = compute()\n    assert True\n    assert x == 2\n\n\
+         def test_placeholder():\n    assert True\n",
+    );
+    let summary =
+        pytest_linter::engine::fix_paths(std::slice::from_ref(&path), Config::default(), false)
+            .unwrap();
+    assert_eq!(summary.applied, 4);
+    assert_eq!(summary.skipped, 0);
+    assert_eq!(summary.files, 1);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "import pytest\n\n\
+         @pytest.mark.skip(reason=\"TODO\")\n\
+         def test_skipped():\n    assert compute() == 1\n\n\
+         @pytest.mark.skipif(sys.platform == 'win32', reason=\"TODO\")\n\
+         def test_posix():\n    x = compute()\n    assert x == 2\n\n\
+         def test_placeholder():\n    pass\n"
+    );
+
+    // Re-running the rules finds nothing left to fix
+    let violations = lint_single_file(&path);
+    assert!(violations.iter().all(|v| v.fix.is_none()));
+    assert!(find_violation(&violations, "PYTEST-MNT-020").is_none());
+}
+
+#[test]
+fn test_fixes_emptying_a_block_together_leave_pass() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_block.py",
+        "def test_a():\n    x = 1\n    if x:\n        print(x)\n        assert True\n    assert x == 1\n",
+    );
+    let summary =
+        pytest_linter::engine::fix_paths(std::slice::from_ref(&path), Config::default(), false)
+            .unwrap();
+    assert_eq!(summary.applied, 2);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "def test_a():\n    x = 1\n    if x:\n        pass\n    assert x == 1\n"
+    );
+}
+
+#[test]
+fn test_fix_dry_run_prints_diff_without_writing() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "import pytest\n\nSKIP = pytest.mark.skip\n\n\
+                  @SKIP\ndef test_a():\n    assert f() == 1\n\n\
+                  @SKIP\ndef test_b():\n    assert f() == 2\n";
+    let path = write_temp_file(dir.path(), "test_dry.py", source);
+    let summary =
+        pytest_linter::engine::fix_paths(std::slice::from_ref(&path), Config::default(), true)
+            .unwrap();
+    // Both usages share the fix at the variable's definition
+    assert_eq!(summary.applied, 1);
+    assert_eq!(summary.skipped, 0);
+    assert!(summary
+        .diff
+        .contains("-SKIP = pytest.mark.skip\n+SKIP = pytest.mark.skip(reason=\"TODO\")\n"));
+    assert!(summary.diff.contains("@@ -1,6 +1,6 @@"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+    assert_eq!(summary.message(true), "Would apply 1 fix in 1 file");
+}
+
+#[test]
+fn test_tautological_assertions_trigger_mnt021_and_mnt004() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_tautology.py",
+        "def test_constants_only():\n    assert 1 == 1\n    assert 2 + 2 == 4\n\n\
+         def test_mixed():\n    assert 1 == 1\n    assert compute() == 2\n\n\
+         def test_named_values():\n    expected = 4\n    assert expected == 4\n",
+    );
+    let violations = lint_single_file(&path);
+    let tautological: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-021")
+        .map(|v| (v.test_name.as_deref().unwrap(), v.line))
+        .collect();
+    assert_eq!(
+        tautological,
+        [
+            ("test_constants_only", 2),
+            ("test_constants_only", 3),
+            ("test_mixed", 6)
+        ]
+    );
+
+    // Only the test whose every assertion is tautological counts as asserting nothing
+    let unasserted: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-004")
+        .collect();
+    assert_eq!(unasserted.len(), 1);
+    assert_eq!(
+        unasserted[0].test_name.as_deref(),
+        Some("test_constants_only")
+    );
+    assert!(unasserted[0].message.contains("tautological"));
+}
+
+#[test]
+fn test_suggest_parametrize_for_literal_only_differences() {
+    let source = r#"
+def test_parse_one():
+    assert parse("1") == 1
+
+def test_parse_two():
+    assert parse("2") == 2
+
+def test_parse_ten():
+    # same shape, different literals
+    assert parse("10") == 10
+
+def test_parse_hex():
+    assert parse_hex("ff") == 255
+
+def test_parse_with_setup():
+    value = parse("3")
+    assert value == 3
+"#;
+    let suggestions = |config: Config| {
+        let engine = LintEngine::new(config).unwrap();
+        engine
+            .lint_source(source, Path::new("test_parse.py"))
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.rule_id == "PYTEST-PARAM-004")
+            .collect::<Vec<_>>()
+    };
+    let found = suggestions(Config::default());
+    assert_eq!(found.len(), 1);
+    let v = &found[0];
+    assert_eq!(v.severity, Severity::Info);
+    assert_eq!(v.line, 2);
+    assert_eq!(v.test_name.as_deref(), Some("test_parse_one"));
+    assert!(v.message.contains("(test_parse_two, test_parse_ten)"));
+    assert!(v.message.contains("2 position(s)"));
+    assert!(v.message.contains("line 3: \"1\" / \"2\" / \"10\""));
+    assert!(!v.message.contains("test_parse_hex"));
+    assert!(!v.message.contains("test_parse_with_setup"));
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-PARAM-004".to_string())
+        .or_default()
+        .options
+        .insert("min_group_size".to_string(), toml::Value::Integer(4));
+    assert!(suggestions(config).is_empty());
+}
+
+#[test]
+fn test_unused_fixture_parameter_triggers_fix014_on_parameter_span() {
+    let source = r#"
+import pytest
+
+@pytest.mark.parametrize("value, expected", [(1, 2), (2, 3)])
+def test_increment(value, expected, tmp_path, capsys, db_session):
+    assert value + 1 == expected
+
+def test_uses_all(tmp_path, monkeypatch):
+    monkeypatch.setenv("HOME", str(tmp_path))
+    assert tmp_path.exists()
+"#;
+    let violations: Vec<_> = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_fix014.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FIX-014")
+        .collect();
+    let names: Vec<&str> = violations
+        .iter()
+        .map(|v| v.message.split('\'').nth(3).unwrap())
+        .collect();
+    assert_eq!(names, ["tmp_path", "db_session"]);
+    let v = &violations[0];
+    assert_eq!(v.test_name.as_deref(), Some("test_increment"));
+    assert_eq!((v.line, v.col, v.end_col), (5, Some(37), Some(45)));
+    assert!(v.suggestion.as_deref().unwrap().contains("usefixtures"));
+}
+
+#[test]
+fn test_only_injecting_decorators_exempt_tests_from_fix014() {
+    let source = r#"
+import pytest
+from hypothesis import given, strategies as st
+from unittest import mock
+
+@pytest.mark.parametrize("n", [1])
+def test_u(n, user, monkeypatch):
+    assert n == 1
+
+@pytest.mark.usefixtures("dispatcher")
+def test_given_name(given_user):
+    assert True
+
+@given(st.integers())
+def test_property(value):
+    assert value == value
+
+@mock.patch("app.send")
+def test_patched(send):
+    assert True
+"#;
+    let violations: Vec<_> = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_fix014_decorators.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FIX-014")
+        .map(|v| {
+            (
+                v.test_name.unwrap(),
+                v.message.split('\'').nth(3).unwrap().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        violations,
+        [
+            ("test_u".to_string(), "user".to_string()),
+            ("test_u".to_string(), "monkeypatch".to_string()),
+            ("test_given_name".to_string(), "given_user".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_autouse_conftest_io_and_sleep_trigger_fix015() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = r#"
+import time
+import pytest
+
+@pytest.fixture(autouse=True)
+def load_settings():
+    with open("settings.json") as f:
+        return f.read()
+
+@pytest.fixture(autouse=True)
+def wait_for_services():
+    time.sleep(1)
+
+@pytest.fixture(autouse=True)
+def reset_counter():
+    COUNTER.clear()
+"#;
+    let conftest = write_temp_file(dir.path(), "conftest.py", source);
+    let found: Vec<_> = lint_single_file(&conftest)
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FIX-015")
+        .collect();
+    assert_eq!(found.len(), 2);
+    assert!(found[0].message.contains("'load_settings'"));
+    assert!(found[0].message.contains("performs I/O (open())"));
+    assert_eq!((found[0].line, found[0].col), (7, Some(10)));
+    assert!(found[1].message.contains("sleeps (time.sleep())"));
+    assert_eq!(found[1].line, 12);
+
+    let plain = write_temp_file(dir.path(), "test_fixtures.py", source);
+    assert!(find_violation(&lint_single_file(&plain), "PYTEST-FIX-015").is_none());
+}
+
+#[test]
+fn test_pointless_yield_fixture_triggers_fix016_with_fix() {
+    let source = r#"
+import pytest
+
+@pytest.fixture
+def config():
+    yield {"debug": True}
+
+@pytest.fixture
+def connection():
+    conn = connect()
+    yield conn
+    conn.close()
+
+@pytest.fixture
+def resource():
+    with open_resource() as r:
+        yield r
+"#;
+    let violations: Vec<_> = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_fix016.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FIX-016")
+        .collect();
+    assert_eq!(violations.len(), 1);
+    let v = &violations[0];
+    assert!(v.message.contains("'config'"));
+    assert_eq!(v.line, 6);
+    let fix = v.fix.as_ref().expect("FIX-016 offers a fix");
+    let fixed = pytest_linter::fix::apply_fixes(source, &[fix]);
+    assert!(fixed.source.contains("    return {\"debug\": True}\n"));
+}
+
+#[test]
+fn test_changed_lines_filter_keeps_only_diagnostics_on_changed_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_diff_mode.py",
+        "def test_old():\n    helper()\n\n\ndef test_old():\n    assert compute() == 1\n\n\ndef test_added():\n    run()\n",
+    );
+    // The second `test_old` and `test_added` are new; the first `test_old` was already there.
+    let diff = "\
+--- a/test_diff_mode.py
++++ b/test_diff_mode.py
+@@ -2,0 +3,8 @@
++
++
++def test_old():
++    assert compute() == 1
++
++
++def test_added():
++    run()
+";
+    let config = Config {
+        changed_lines: Some(pytest_linter::diff::ChangedLines::parse(diff, dir.path())),
+        ..Config::default()
+    };
+    let violations = LintEngine::new(config)
+        .unwrap()
+        .lint_paths(&[path])
+        .unwrap();
+    assert!(violations.iter().all(|v| v.line >= 3), "{violations:?}");
+    // The duplicate name is only detectable with the whole file, but lands on a changed line.
+    let duplicate = find_violation(&violations, "PYTEST-MNT-022").expect("MNT-022 on the new copy");
+    assert_eq!(duplicate.line, 5);
+    let no_assert = find_violation(&violations, "PYTEST-MNT-004").expect("MNT-004 on test_added");
+    assert_eq!(no_assert.test_name.as_deref(), Some("test_added"));
+}
+
+#[test]
+fn test_unittest_assertions_count_for_no_assertion_and_too_many_assertions() {
+    let source = r#"
+import unittest
+
+class CheckMath(unittest.TestCase):
+    def test_add(self):
+        self.assertEqual(1 + 1, 2)
+
+    def test_nothing(self):
+        total = 1 + 1
+
+    def test_many(self):
+        self.assertEqual(1, 1)
+        self.assertEqual(2, 2)
+        self.assertEqual(3, 3)
+        self.assertEqual(4, 4)
+        self.assertEqual(5, 5)
+        self.assertEqual(6, 6)
+"#;
+    let violations = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_math.py"))
+        .unwrap();
+    let no_assert: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-004")
+        .map(|v| v.test_name.as_deref())
+        .collect();
+    assert_eq!(no_assert, [Some("test_nothing")]);
+    let too_many = find_violation(&violations, "PYTEST-MNT-018").expect("MNT-018 on test_many");
+    assert_eq!(too_many.test_name.as_deref(), Some("test_many"));
+}
+
+#[test]
+fn test_deprecated_unittest_alias_triggers_mnt023_with_fix() {
+    let source = r#"
+import unittest
+
+class TestLegacy(unittest.TestCase):
+    def test_old(self):
+        self.assertEquals(1, 1)
+        self.failUnless(True)
+        self.assertEqual(2, 2)
+"#;
+    let violations: Vec<_> = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_legacy.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-023")
+        .collect();
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].line, violations[0].col), (6, Some(14)));
+    assert!(violations[0].message.contains("'self.assertEquals'"));
+    assert!(violations[1].message.contains("'self.assertTrue'"));
+    let fixes: Vec<_> = violations.iter().filter_map(|v| v.fix.as_ref()).collect();
+    let fixed = pytest_linter::fix::apply_fixes(source, &fixes);
+    assert!(fixed
+        .source
+        .contains("        self.assertEqual(1, 1)\n        self.assertTrue(True)\n"));
+}
+
+#[test]
+fn test_unittest_setup_network_triggers_flk012_once() {
+    let source = r#"
+import time
+import unittest
+import requests
+
+class TestApi(unittest.TestCase):
+    def setUp(self):
+        self.token = requests.post("https://auth.example/token").json()
+
+    def test_a(self):
+        self.assertTrue(self.token)
+
+    def test_b(self):
+        self.assertIn("id", self.token)
+
+class TestSlow(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        time.sleep(2)
+
+    def test_c(self):
+        self.assertEqual(1, 1)
+
+class TestFine(unittest.TestCase):
+    def setUp(self):
+        self.value = 1
+
+    def test_d(self):
+        self.assertEqual(self.value, 1)
+"#;
+    let violations: Vec<_> = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_api.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-012")
+        .collect();
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].line, violations[0].col), (8, Some(22)));
+    assert_eq!(
+        violations[0].message,
+        "TestApi.setUp accesses the network (requests.post()), which runs before each of its 2 test methods"
+    );
+    assert_eq!(violations[1].line, 19);
+    assert!(violations[1].message.contains(
+        "TestSlow.setUpClass sleeps (time.sleep()), which runs once before its 1 test method"
+    ));
+}
+
+fn flk013_violations(source: &str, config: Config) -> Vec<pytest_linter::models::Violation> {
+    LintEngine::new(config)
+        .unwrap()
+        .lint_source(source, Path::new("test_floats.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-013")
+        .collect()
+}
+
+#[test]
+fn test_float_equality_triggers_flk013() {
+    let source = r#"
+import unittest
+import pytest
+
+def test_sum():
+    assert total() == 0.1 + 0.2
+    assert ratio() != -1.5
+    assert total() == pytest.approx(0.3)
+    assert count() == 3
+    assert balance() == 0.0
+
+class TestRatio(unittest.TestCase):
+    def test_ratio(self):
+        self.assertEqual(ratio(), 0.5)
+        self.assertAlmostEqual(ratio(), 0.5)
+"#;
+    let violations = flk013_violations(source, Config::default());
+    assert_eq!(violations.len(), 3);
+    assert_eq!((violations[0].line, violations[0].col), (6, Some(12)));
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_sum' compares floats for exact equality: total() == 0.1 + 0.2"
+    );
+    assert_eq!(violations[1].line, 7);
+    assert_eq!(violations[2].line, 14);
+    assert!(violations[0]
+        .suggestion
+        .as_deref()
+        .unwrap()
+        .contains("pytest.approx"));
+    assert!(violations[2]
+        .suggestion
+        .as_deref()
+        .unwrap()
+        .contains("assertAlmostEqual"));
+}
+
+#[test]
+fn test_float_equality_zero_exemption_is_configurable() {
+    let source = "def test_zero():\n    assert balance() == 0.0\n    assert offset() == -0.\n";
+    assert!(flk013_violations(source, Config::default()).is_empty());
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-FLK-013".to_string())
+        .or_default()
+        .options
+        .insert("allow_zero".to_string(), toml::Value::Boolean(false));
+    assert_eq!(flk013_violations(source, config).len(), 2);
+}
+
+fn rule_violations(
+    source: &str,
+    rule_id: &str,
+    config: Config,
+) -> Vec<pytest_linter::models::Violation> {
+    LintEngine::new(config)
+        .unwrap()
+        .lint_source(source, Path::new("test_rules.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == rule_id)
+        .collect()
+}
+
+#[test]
+fn test_assert_in_loop_over_possibly_empty_collection_triggers_mnt024() {
+    let source = r#"
+def test_unguarded():
+    for user in fetch_users():
+        assert user.active
+
+def test_guarded():
+    users = fetch_users()
+    assert len(users) == 3
+    for user in users:
+        assert user.active
+
+def test_literal():
+    for n in [1, 2, 3]:
+        assert n > 0
+    for i in range(5):
+        assert square(i) >= 0
+
+def test_nested_in_with():
+    with session() as s:
+        for    row in enumerate(s.rows.values()):
+            assert row
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-024", Config::default());
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].line, violations[0].col), (3, Some(5)));
+    assert_eq!(violations[0].severity, Severity::Info);
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_unguarded' asserts inside a loop over 'fetch_users()'; if it is empty, no assertion runs"
+    );
+    assert_eq!(violations[1].line, 20);
+}
+
+#[test]
+fn test_swallowed_assertion_triggers_mnt025() {
+    let source = r#"
+import pytest
+
+def test_swallowed():
+    try:
+        assert compute() == 3
+    except Exception:
+        pass
+
+def test_assertion_error_logged():
+    try:
+        assert compute() == 3
+    except (ValueError, AssertionError) as exc:
+        print(exc)
+
+def test_reraised():
+    try:
+        assert compute() == 3
+    except AssertionError:
+        log()
+        raise
+
+def test_pytest_fail():
+    try:
+        assert compute() == 3
+    except Exception as exc:
+        pytest.fail(str(exc))
+
+def test_narrow():
+    try:
+        assert compute() == 3
+    except KeyError:
+        pass
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-025", Config::default());
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].line, violations[0].col), (7, Some(5)));
+    assert_eq!(violations[0].severity, Severity::Error);
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_swallowed' asserts inside a try block whose except Exception handler swallows the failure; the test cannot fail"
+    );
+    assert_eq!(violations[1].line, 13);
+    assert!(violations[1]
+        .message
+        .contains("except AssertionError handler"));
+}
+
+#[test]
+fn test_debugger_calls_trigger_mnt026_with_statement_fixes() {
+    let source = r#"import pdb as debugger
+from pprint import pprint
+
+def test_stops():
+    value = compute()
+    breakpoint()
+    import ipdb; ipdb.set_trace()
+    debugger.set_trace()
+    assert value == 3
+
+def test_only_leftovers():
+    if flag():
+        print("here")
+        breakpoint()
+    assert flag()
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-026", Config::default());
+    assert_eq!(violations.len(), 4);
+    assert_eq!(violations[0].severity, Severity::Error);
+    assert_eq!((violations[0].line, violations[0].col), (6, Some(5)));
+    assert_eq!(
+        violations[2].message,
+        "Test 'test_stops' calls pdb.set_trace(), which stops at a debugger prompt"
+    );
+    let prints = rule_violations(source, "PYTEST-MNT-027", Config::default());
+    assert_eq!(prints.len(), 1);
+    assert_eq!(prints[0].severity, Severity::Warning);
+
+    let all: Vec<_> = violations.iter().chain(&prints).collect();
+    let fixes: Vec<_> = all.iter().filter_map(|v| v.fix.as_ref()).collect();
+    let fixed = pytest_linter::fix::apply_fixes(source, &fixes).source;
+    assert_eq!(
+        fixed,
+        r#"import pdb as debugger
+from pprint import pprint
+
+def test_stops():
+    value = compute()
+    import ipdb
+    assert value == 3
+
+def test_only_leftovers():
+    if flag():
+        pass
+    assert flag()
+"#
+    );
+    // Disabling one of the rules still leaves a parseable block
+    let only_debugger: Vec<_> = violations.iter().filter_map(|v| v.fix.as_ref()).collect();
+    let fixed = pytest_linter::fix::apply_fixes(source, &only_debugger).source;
+    assert!(fixed.contains("    if flag():\n        print(\"here\")\n    assert flag()\n"));
+}
+
+#[test]
+fn test_print_calls_trigger_mnt027_unless_allowed() {
+    let source = r#"import logging
+from rich import print as rprint
+from pprint import pprint
+
+def test_prints():
+    logging.info("fine")
+    rprint("x")
+    pprint({"a": 1})
+    print("done")
+    assert True
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-027", Config::default());
+    let callees: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+    assert_eq!(
+        callees,
+        [
+            "Test 'test_prints' calls rich.print(); debugging output left in the test",
+            "Test 'test_prints' calls pprint.pprint(); debugging output left in the test",
+            "Test 'test_prints' calls print(); debugging output left in the test",
+        ]
+    );
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MNT-027".to_string())
+        .or_default()
+        .options
+        .insert(
+            "allowed_loggers".to_string(),
+            toml::Value::Array(vec![toml::Value::String("rich".to_string())]),
+        );
+    assert_eq!(rule_violations(source, "PYTEST-MNT-027", config).len(), 2);
+}
+
+#[test]
+fn test_eager_test_triggers_mnt028_with_call_target_metadata() {
+    let source = r#"import json
+from shop import Cart, Coupon, checkout, ship, refund, notify, audit
+
+def test_everything(db):
+    cart = Cart()
+    cart.add("book")
+    cart.add("pen")
+    coupon = Coupon("SAVE10")
+    order = checkout(cart, coupon)
+    ship(order)
+    refund(order)
+    notify(order.customer)
+    audit.record(order)
+    db.commit()
+    payload = json.dumps({"id": order.id})
+    assert len(payload) > 0
+    assert order.total() == 10
+
+def test_focused(db):
+    cart = Cart()
+    cart.add("book")
+    assert checkout(cart).total() == 10
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-028", Config::default());
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_everything' exercises 8 distinct call targets (max 6): cart (2×), Cart, Coupon, checkout, ship, ..."
+    );
+    assert_eq!(violations[0].metadata["call_targets"], 8);
+
+    let diagnostic =
+        pytest_linter::output::json::JsonDiagnostic::from_violation(&violations[0], None);
+    let json = serde_json::to_value(&diagnostic).unwrap();
+    assert_eq!(json["metadata"]["call_targets"], 8);
+
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-028".to_string())
+        .or_default();
+    rc.options
+        .insert("max".to_string(), toml::Value::Integer(8));
+    assert!(rule_violations(source, "PYTEST-MNT-028", config).is_empty());
+}
+
+#[test]
+fn test_computed_expected_value_triggers_mnt029() {
+    let source = r#"import json
+import unittest
+from shop.codec import encode, encode_reference
+from shop.money import Money, total
+
+def test_reference(payload):
+    expected = encode_reference(payload)
+    assert encode(payload) == expected
+
+def test_recomputed(cart):
+    assert total(cart) == total(cart.items)
+
+def test_literal(cart):
+    assert total(cart) == 10
+    assert total(cart) == Money(10)
+    assert 3 == total([1, 2])
+    assert encode({}) == json.dumps({})
+
+class TestCodec(unittest.TestCase):
+    def test_unittest(self):
+        self.assertEqual(encode("a"), encode_reference("a"))
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-029", Config::default());
+    let lines: Vec<_> = violations.iter().map(|v| v.line).collect();
+    assert_eq!(lines, [8, 11, 21]);
+    assert_eq!(violations[0].severity, Severity::Info);
+    assert_eq!(violations[0].col, Some(31));
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_reference' computes the expected value with project code (encode_reference()); the assertion repeats the logic under test"
+    );
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MNT-029".to_string())
+        .or_default()
+        .options
+        .insert(
+            "project_packages".to_string(),
+            toml::Value::Array(vec![toml::Value::String("shop.money".to_string())]),
+        );
+    let lines: Vec<_> = rule_violations(source, "PYTEST-MNT-029", config)
+        .iter()
+        .map(|v| v.line)
+        .collect();
+    assert_eq!(lines, [11]);
+}
+
+/// Random multibyte text, from a small deterministic generator.
+fn multibyte_text(seed: &mut u64, max_len: usize) -> String {
+    const PIECES: &[&str] = &["a", "é", "ß", "中文", "😀", "👍🏽", " ", "_"];
+    let mut next = |bound: usize| {
+        *seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(*seed >> 33).unwrap_or(0) % bound
+    };
+    let len = next(max_len) + 1;
+    (0..len).map(|_| PIECES[next(PIECES.len())]).collect()
+}
+
+#[test]
+fn test_spans_stay_inside_multibyte_sources() {
+    use pytest_linter::span::LineIndex;
+
+    let mut seed = 42;
+    for n in 0..60 {
+        let mut text = || multibyte_text(&mut seed, 8);
+        let source = format!(
+            r#"import time
+import pdb
+
+# {}
+@pytest.mark.skip(reason="{}")
+def test_café_{n}():
+    time.sleep(0.1)
+    print("{}")
+    if "{}":
+        assert 0.1 + 0.2 == 0.3, "{}"
+    for x in get_items("{}"):
+        assert x
+    pdb.set_trace()
+
+def test_中文_{n}(tmp_path):  # {}
+    try:
+        assert open("{}").read() == "{}"
+    except AssertionError:
+        pass
+"#,
+            text(),
+            text(),
+            text(),
+            text(),
+            text(),
+            text(),
+            text(),
+            text(),
+            text(),
+        );
+        let violations = LintEngine::new(Config::default())
+            .unwrap()
+            .lint_source(&source, Path::new("test_spans.py"))
+            .unwrap();
+        assert!(violations.iter().any(|v| v.col.is_some()));
+        let index = LineIndex::new(&source);
+        for v in &violations {
+            let Some(col) = v.col else {
+                assert!(v.line >= 1 && v.line <= index.line_count());
+                continue;
+            };
+            let end = (v.end_line.unwrap_or(v.line), v.end_col.unwrap_or(col));
+            let start_byte = index.offset(v.line, col);
+            let end_byte = index.offset(end.0, end.1);
+            let (Some(start_byte), Some(end_byte)) = (start_byte, end_byte) else {
+                panic!(
+                    "{} span {:?} is outside {source:?}",
+                    v.rule_id,
+                    (v.line, col, end)
+                );
+            };
+            assert!(start_byte <= end_byte, "{} span is reversed", v.rule_id);
+            assert!(end_byte <= source.len());
+            assert_eq!(index.position(start_byte), Some((v.line, col)));
+            assert_eq!(index.position(end_byte), Some(end));
+            let utf16 = index.utf16_col(v.line, col).unwrap();
+            assert_eq!(index.col_from_utf16(v.line, utf16), Some(col));
+        }
+    }
+}
+
+#[test]
+fn test_json_summary_counts_reported_diagnostics_regardless_of_fail_on() {
+    let dir = tempfile::tempdir().unwrap();
+    write_temp_file(
+        dir.path(),
+        "test_summary.py",
+        r#"
+import time
+
+def test_sleepy():
+    time.sleep(1)
+    assert True
+
+def test_suppressed():
+    time.sleep(1)  # pytl: ignore[PYTEST-FLK-001]
+    assert True
+
+def test_no_assert():
+    pass
+"#,
+    );
+    write_temp_file(dir.path(), "test_broken.py", "def test_x(:\n");
+    let output_path = dir.path().join("out.json");
+    // Only errors fail the run; warnings are still counted
+    let config = Config {
+        fail_on: Some(Severity::Error),
+        ..Config::default()
+    };
+    let failed = pytest_linter::engine::run_linter(
+        &[dir.path().to_path_buf()],
+        "json",
+        Some(&output_path),
+        true,
+        config,
+    )
+    .unwrap();
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&output_path).unwrap()).unwrap();
+    let summary = &report["summary"];
+    let diagnostics = report["diagnostics"].as_array().unwrap();
+    assert_eq!(summary["total"], diagnostics.len());
+    let by_rule = summary["by_rule"].as_array().unwrap();
+    let sleep = by_rule
+        .iter()
+        .find(|r| r["rule_id"] == "PYTEST-FLK-001")
+        .unwrap();
+    // The suppressed sleep is not counted
+    assert_eq!((&sleep["count"], &sleep["files"]), (&1.into(), &1.into()));
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d["severity"] == "error")
+        .count();
+    assert_eq!(summary["by_severity"]["error"], errors);
+    assert_eq!(failed, errors > 0);
+    assert!(summary["by_severity"]["warning"].as_u64().unwrap() >= 1);
+    assert_eq!(summary["files"]["cached"], 0);
+    assert_eq!(
+        summary["files"]["analyzed"].as_u64().unwrap()
+            + summary["files"]["skipped"].as_u64().unwrap(),
+        2
+    );
+}
+
+#[test]
+fn test_run_summary_follows_baseline_and_lists_top_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = write_temp_file(
+        dir.path(),
+        "test_old.py",
+        "import time\n\ndef test_a():\n    time.sleep(1)\n    assert True\n",
+    );
+    let (violations, _, _) =
+        pytest_linter::engine::collect_run(std::slice::from_ref(&old), Config::default()).unwrap();
+    let baseline = pytest_linter::baseline::Baseline::from_violations(&violations);
+    let new = write_temp_file(
+        dir.path(),
+        "test_new.py",
+        "import time\n\ndef test_b():\n    time.sleep(1)\n    time.sleep(2)\n    assert True\n",
+    );
+
+    let mut config = Config {
+        statistics: true,
+        top_files: Some(5),
+        fail_on: Some(Severity::Warning),
+        ..Config::default()
+    };
+    let (violations, files, cache) =
+        pytest_linter::engine::collect_run(&[old, new.clone()], config.clone()).unwrap();
+    let reported = baseline.apply(&violations).new_violations;
+    assert!(reported.iter().all(|v| v.file_path == new));
+    let text = pytest_linter::engine::format_run_summary(&reported, files, cache, &config);
+    let sleeps = reported
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-001")
+        .count();
+    assert_eq!(sleeps, 2);
+    assert!(
+        text.contains(&format!("PYTEST-FLK-001       {sleeps}       1\n")),
+        "{text}"
+    );
+    assert!(
+        text.contains("Files: 2 analyzed, 0 skipped, 0 cached\n"),
+        "{text}"
+    );
+    assert!(text.contains("Top 1 file by diagnostics:\n"), "{text}");
+    assert!(!text.contains("test_old.py"), "{text}");
+    // The summary is informational: the exit policy still sees every reported warning
+    assert!(pytest_linter::engine::policy_failure(&reported, &config).is_some());
+
+    config.statistics = false;
+    config.top_files = None;
+    assert_eq!(
+        pytest_linter::engine::format_run_summary(&reported, files, cache, &config),
+        ""
+    );
+}
+
+#[test]
+fn test_lint_buffer_uses_virtual_path_for_config_and_reporting() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("pytest-linter.toml"),
+        "[[overrides]]\npath = \"tests/slow/**\"\nrules = { PYTEST-FLK-001 = { enabled = false } }\n",
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("tests")).unwrap();
+    // Unsaved buffer contents: the file on disk is different
+    let on_disk = write_temp_file(
+        &dir.path().join("tests"),
+        "test_buffer.py",
+        "def test_a():\n    assert f() == 1\n",
+    );
+    let buffer = "import time\n\ndef test_a():\n    time.sleep(1)\n    assert f() == 1\n";
+    let sleeps = |path: &Path| {
+        let config = Config::discover(path).unwrap();
+        pytest_linter::engine::lint_buffer(buffer, path, config)
+            .unwrap()
+            .into_iter()
+            .filter(|v| v.rule_id == "PYTEST-FLK-001")
+            .collect::<Vec<_>>()
+    };
+    let found = sleeps(&on_disk);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].file_path, on_disk);
+    assert_eq!(found[0].line, 4);
+    // The override matches the virtual path, which does not exist
+    assert!(sleeps(&dir.path().join("tests/slow/test_new.py")).is_empty());
+    // Only test files are linted, and blank input is clean
+    assert!(sleeps(&dir.path().join("tests/helpers.py")).is_empty());
+    let config = Config::discover(&on_disk).unwrap();
+    assert!(
+        pytest_linter::engine::lint_buffer("", &on_disk, config.clone())
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        pytest_linter::engine::lint_buffer(" \n\n", &on_disk, config)
+            .unwrap()
+            .is_empty()
+    );
+
+    // Reporters quote the buffer, not the file on disk
+    let mut out = Vec::new();
+    pytest_linter::engine::write_format(
+        &found,
+        "json-lines",
+        &mut out,
+        &Config::default(),
+        None,
+        &mut pytest_linter::output::SourceCache::with_source(&on_disk, buffer),
+    )
+    .unwrap();
+    let diagnostic: serde_json::Value =
+        serde_json::from_slice(out.split(|b| *b == b'\n').next().unwrap()).unwrap();
+    let start = diagnostic["span"]["start_byte"].as_u64().unwrap() as usize;
+    assert!(buffer[start..].starts_with("time.sleep(1)"));
+}
+
+#[test]
+fn test_uninformative_test_names_trigger_mnt030_on_the_name() {
+    let source = r#"import unittest
+
+def test_1():
+    assert f()
+
+def test_foo():
+    assert f()
+
+def test_it_works():
+    assert f()
+
+def test_test():
+    assert f()
+
+def test_login_rejects_expired_token():
+    assert f()
+
+def test_happy_path():
+    assert f()
+
+class TestCart(unittest.TestCase):
+    def test_something(self):
+        self.assertTrue(f())
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-030", Config::default());
+    let names: Vec<_> = violations
+        .iter()
+        .map(|v| v.test_name.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "test_1",
+            "test_foo",
+            "test_it_works",
+            "test_test",
+            "test_something"
+        ]
+    );
+    assert_eq!(
+        violations[1].message,
+        "Test name 'test_foo' does not say what behaviour is tested"
+    );
+    assert_eq!(
+        (violations[1].col, violations[1].end_col),
+        (Some(5), Some(13))
+    );
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MNT-030".to_string())
+        .or_default()
+        .options
+        .insert(
+            "forbidden_words".to_string(),
+            toml::Value::Array(vec![
+                toml::Value::String("Happy".to_string()),
+                toml::Value::String("path".to_string()),
+            ]),
+        );
+    assert_eq!(rule_violations(source, "PYTEST-MNT-030", config).len(), 6);
+}
+
+#[test]
+fn test_numbered_test_names_trigger_mnt031() {
+    let source = r#"import unittest
+
+def test_login2():
+    assert f()
+
+def test_login():
+    assert f()
+
+def test_login_3():
+    assert f()
+
+def test_sha256():
+    assert f()
+
+def test_sha1():
+    assert f()
+
+class TestA(unittest.TestCase):
+    def test_export(self):
+        self.assertTrue(f())
+
+class TestB(unittest.TestCase):
+    def test_export2(self):
+        self.assertTrue(f())
+
+    def test_export3(self):
+        self.assertTrue(f())
+"#;
+    let violations = rule_violations(source, "PYTEST-MNT-031", Config::default());
+    let messages: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Test 'test_login2' differs from 'test_login' (line 6) only by a trailing number",
+            "Test 'test_login_3' differs from 'test_login' (line 6) only by a trailing number",
+            "Test 'test_export3' differs from 'test_export2' (line 23) only by a trailing number",
+        ]
+    );
+    assert_eq!(violations[0].col, Some(5));
+}
+
+#[test]
+fn test_name_pattern_mnt032_is_off_until_configured() {
+    let source = r#"def test_cart_empty_total_is_zero():
+    assert f()
+
+def test_checkout():
+    assert f()
+"#;
+    assert!(rule_violations(source, "PYTEST-MNT-032", Config::default()).is_empty());
+
+    let dir = tempfile::tempdir().unwrap();
+    write_temp_file(
+        dir.path(),
+        "pytl.toml",
+        "[rules.test-name-pattern]\npattern = \"test_[a-z0-9]+_[a-z0-9]+_[a-z0-9_]+\"\n",
+    );
+    let config = Config::discover(dir.path()).unwrap();
+    let violations = rule_violations(source, "PYTEST-MNT-032", config);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].message,
+        "Test name 'test_checkout' does not match the naming convention `test_[a-z0-9]+_[a-z0-9]+_[a-z0-9_]+`"
+    );
+    assert_eq!(violations[0].line, 4);
+
+    write_temp_file(
+        dir.path(),
+        "pytl.toml",
+        "[rules.PYTEST-MNT-032]\npattern = \"test_(\"\n",
+    );
+    let err = format!("{:#}", Config::discover(dir.path()).unwrap_err());
+    assert!(
+        err.contains("`rules.PYTEST-MNT-032.pattern` is not a valid regular expression"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_shared_module_state_triggers_flk014() {
+    let source = r#"import pytest
+
+ITEMS = []
+SEEN = {}
+COUNTER = 0
+LIMIT = 3
+
+class Settings:
+    debug = False
+
+def test_add():
+    ITEMS.append(1)
+    assert len(ITEMS) == 1
+
+def test_count():
+    assert len(ITEMS) <= LIMIT
+
+def test_counter():
+    global COUNTER
+    COUNTER += 1
+    assert COUNTER
+
+def test_settings():
+    Settings.debug = True
+    assert Settings.debug
+
+def test_settings_again():
+    Settings.debug = False
+    assert not Settings.debug
+
+def test_local_shadow():
+    ITEMS = [2]
+    ITEMS.append(3)
+    assert ITEMS == [2, 3]
+
+@pytest.fixture
+def seen():
+    SEEN.clear()
+    yield SEEN
+
+def test_seen(seen):
+    SEEN["a"] = 1
+    assert seen
+"#;
+    let violations = rule_violations(source, "PYTEST-FLK-014", Config::default());
+    let messages: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Test 'test_add' modifies module-level 'ITEMS', also used by test_count",
+            "Test 'test_counter' modifies module-level 'COUNTER'; the change outlives the test",
+        ]
+    );
+    assert_eq!((violations[0].line, violations[0].col), (12, Some(5)));
+    assert_eq!(violations[0].metadata["symbol"], "ITEMS");
+    assert_eq!(violations[0].metadata["shared_with"][0], "test_count");
+}
+
+#[test]
+fn test_obscure_test_triggers_mnt033() {
+    let source = r#"import pytest
+
+def test_long_arrange():
+    """Docstrings and comments do not count."""
+    # Build the order
+    a = make(1)
+    b = make(2)
+    c = make(3)
+    d = make(4)
+    total = checkout(a, b, c, d)
+    assert total == 10
+
+def test_long_with_body():
+    with open_session() as s:
+        s.step(1)
+        s.step(2)
+        s.step(3)
+        s.step(4)
+        s.step(5)
+        s.step(6)
+        s.step(7)
+
+def test_raises_within_limits():
+    a = make(1)
+    b = make(2)
+    c = make(3)
+    with pytest.raises(ValueError):
+        checkout(a, b, c)
+
+@pytest.mark.parametrize("n", [
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10,
+    11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+])
+def test_parametrize_table_does_not_count(n):
+    assert make(n)
+"#;
+    let mut config = Config::default();
+    let rc = config
+        .rules
+        .entry("PYTEST-MNT-033".to_string())
+        .or_default();
+    rc.options
+        .insert("max_statements".to_string(), toml::Value::Integer(6));
+    rc.options
+        .insert("max_arrange".to_string(), toml::Value::Integer(3));
+    let violations = rule_violations(source, "PYTEST-MNT-033", config);
+    let messages: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "Test 'test_long_arrange' has 6 statements (limit 6), 4 of them before the first action (limit 3)",
+            "Test 'test_long_with_body' has 7 statements (limit 6), 0 of them before the first action (limit 3)",
+        ]
+    );
+    assert_eq!(violations[0].metadata["statements"], 6);
+    assert_eq!(violations[0].metadata["arrange_statements"], 4);
+    assert!(violations[0]
+        .suggestion
+        .as_deref()
+        .is_some_and(|s| s.contains("fixture or a builder")));
+}
+
+#[test]
+fn test_mock_overuse_counts_patch_decorators_moc005() {
+    let source = r#"from unittest.mock import MagicMock, patch
+
+@patch("shop.cart.fetch")
+@patch("shop.cart.save")
+def test_checkout(save, fetch, mocker):
+    client = MagicMock()
+    mocker.patch("shop.cart.audit")
+    with patch.object(client, "close"):
+        total = checkout(client)
+    assert total == 3
+
+@patch("shop.cart.fetch")
+def test_refund(fetch):
+    client = MagicMock()
+    assert refund(client) == 0
+"#;
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MOC-005".to_string())
+        .or_default()
+        .options
+        .insert("max_mocks".to_string(), toml::Value::Integer(4));
+    let violations = rule_violations(source, "PYTEST-MOC-005", config);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].message,
+        "Test 'test_checkout' creates 5 mocks (limit 4), 2 of them patch decorators"
+    );
+    assert_eq!(violations[0].metadata["mocks"], 5);
+}
+
+#[test]
+fn test_interaction_only_test_triggers_moc006() {
+    let source = r#"import pytest
+
+def test_sends_email(mailer):
+    notify(mailer)
+    mailer.send.assert_called_once()
+    mailer.send.assert_called_with("ada@example.com")
+
+def test_counts_calls(mailer):
+    notify(mailer)
+    assert mailer.send.call_count == 1
+
+def test_checks_result(mailer):
+    result = notify(mailer)
+    mailer.send.assert_called_once()
+    assert result.sent
+
+def test_checks_error(mailer):
+    with pytest.raises(ValueError):
+        notify(mailer, to=None)
+    mailer.send.assert_not_called()
+"#;
+    let violations = rule_violations(source, "PYTEST-MOC-006", Config::default());
+    let tests: Vec<_> = violations
+        .iter()
+        .map(|v| v.test_name.as_deref().unwrap())
+        .collect();
+    // test_sends_email is PYTEST-MNT-005's, which reports it as before
+    assert_eq!(tests, ["test_counts_calls"]);
+    assert!(violations.iter().all(|v| v.severity == Severity::Info));
+    let mock_only: Vec<_> = rule_violations(source, "PYTEST-MNT-005", Config::default())
+        .into_iter()
+        .map(|v| v.test_name.unwrap())
+        .collect();
+    assert_eq!(mock_only, ["test_sends_email"]);
+}
+
+#[test]
+fn test_specless_project_patch_triggers_moc007() {
+    let source = r#"from unittest import mock
+from unittest.mock import patch
+from shop import cart
+
+@patch("shop.cart.fetch")
+@patch("shop.cart.save", autospec=True)
+def test_decorated(save, fetch):
+    assert cart.total() == 0
+
+def test_context_managers(mocker):
+    with mock.patch.object(cart, "fetch", return_value=[]):
+        assert cart.total() == 0
+    with patch("shop.cart.save", spec=True), patch("os.getcwd"):
+        mocker.patch("shop.cart.audit", new=lambda *a: None)
+        mocker.patch.object(cart, "audit", autospec=False)
+        assert cart.total() == 0
+"#;
+    let violations = rule_violations(source, "PYTEST-MOC-007", Config::default());
+    let found: Vec<_> = violations
+        .iter()
+        .map(|v| (v.line, v.metadata["target"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        found,
+        [(5, "shop.cart.fetch"), (11, "shop.cart"), (15, "shop.cart")]
+    );
+    assert!(violations[0].message.contains("spec-less MagicMock"));
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MOC-007".to_string())
+        .or_default()
+        .options
+        .insert(
+            "project_packages".to_string(),
+            toml::Value::Array(vec![toml::Value::String("billing".to_string())]),
+        );
+    assert!(rule_violations(source, "PYTEST-MOC-007", config).is_empty());
+}
+
+/// A project with a `conftest.py` two directories above the tests:
+/// `conftest.py` (`db`, `stale`), `api/v1/conftest.py` (`client`) and `api/v1/test_api.py`.
+fn project_with_outer_conftest(dir: &Path) -> PathBuf {
+    let nested = dir.join("api").join("v1");
+    std::fs::create_dir_all(&nested).unwrap();
+    write_temp_file(
+        dir,
+        "conftest.py",
+        "import pytest\n\n\
+         @pytest.fixture\n\
+         def db():\n    return connect()\n\n\
+         @pytest.fixture\n\
+         def stale():\n    return 1\n",
+    );
+    write_temp_file(
+        &nested,
+        "conftest.py",
+        "import pytest\n\n\
+         @pytest.fixture(scope=\"session\")\n\
+         def client(db):\n    return Client(db)\n",
+    );
+    write_temp_file(
+        &nested,
+        "test_api.py",
+        "def test_get(client):\n    assert client.get(\"/\").status == 200\n",
+    );
+    nested
+}
+
+fn lint_in_project_mode(
+    paths: &[PathBuf],
+    project_mode: bool,
+) -> Vec<pytest_linter::models::Violation> {
+    let engine = LintEngine::new(Config {
+        project_mode,
+        ..Config::default()
+    })
+    .unwrap();
+    engine.lint_paths(paths).unwrap()
+}
+
+#[test]
+fn test_project_mode_resolves_fixture_scope_two_directories_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = project_with_outer_conftest(dir.path());
+
+    let plain = lint_in_project_mode(std::slice::from_ref(&nested), false);
+    assert!(find_violation(&plain, "PYTEST-FIX-003").is_none());
+
+    let project = lint_in_project_mode(&[nested], true);
+    let v = find_violation(&project, "PYTEST-FIX-003").expect("FIX-003 in project mode");
+    assert!(v.file_path.ends_with("api/v1/conftest.py"));
+    assert!(v.message.contains("'db' (scope=function)"), "{}", v.message);
+}
+
+#[test]
+fn test_project_mode_counts_requests_from_deeper_directories_for_fix005() {
+    let dir = tempfile::tempdir().unwrap();
+    project_with_outer_conftest(dir.path());
+    let conftest = dir.path().join("conftest.py");
+
+    let unused = |violations: &[pytest_linter::models::Violation]| -> Vec<String> {
+        violations
+            .iter()
+            .filter(|v| v.rule_id == "PYTEST-FIX-005")
+            .map(|v| v.message.clone())
+            .collect()
+    };
+    let plain = lint_in_project_mode(std::slice::from_ref(&conftest), false);
+    assert_eq!(unused(&plain).len(), 2, "{:?}", unused(&plain));
+    let project = lint_in_project_mode(&[conftest], true);
+    assert_eq!(
+        unused(&project),
+        ["Fixture 'stale' is not used by any test or fixture"]
+    );
+}
+
+#[test]
+fn test_project_mode_reports_overridden_conftest_fixture_fix004() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = project_with_outer_conftest(dir.path());
+    let module = write_temp_file(
+        &nested,
+        "test_db.py",
+        "import pytest\n\n\
+         @pytest.fixture\n\
+         def db():\n    return fake()\n\n\
+         @pytest.fixture\n\
+         def stale(stale):\n    return stale + 1\n\n\
+         def test_db(db, stale):\n    assert db.ping() == stale\n",
+    );
+    let other = write_temp_file(
+        &nested,
+        "test_other.py",
+        "import pytest\n\n\
+         @pytest.fixture\n\
+         def payload():\n    return {}\n\n\
+         def test_other(payload):\n    assert payload == {}\n",
+    );
+    let more = write_temp_file(
+        &nested,
+        "test_more.py",
+        "import pytest\n\n\
+         @pytest.fixture\n\
+         def payload():\n    return []\n\n\
+         def test_more(payload):\n    assert payload == []\n",
+    );
+
+    let violations = lint_in_project_mode(std::slice::from_ref(&nested), true);
+    let shadowed: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-FIX-004")
+        .collect();
+    // `stale(stale)` extends the outer fixture, and module-local `payload`s do not collide
+    assert_eq!(shadowed.len(), 1, "{shadowed:?}");
+    assert_eq!(shadowed[0].file_path, module);
+    let outer = dir.path().canonicalize().unwrap().join("conftest.py");
+    assert_eq!(
+        shadowed[0].message,
+        format!(
+            "Fixture 'db' shadows the definition in {}:4",
+            outer.display()
+        )
+    );
+
+    let plain = lint_in_project_mode(&[other, more], false);
+    assert!(plain
+        .iter()
+        .any(|v| v.rule_id == "PYTEST-FIX-004" && v.message.contains("'payload'")));
+}
+
+#[test]
+fn test_project_mode_reports_test_names_repeated_in_sibling_modules() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = write_temp_file(
+        dir.path(),
+        "test_orders.py",
+        "def test_create():\n    assert make_order().id\n\n\
+         class TestRefund:\n    def test_amount(self):\n        assert refund() == 0\n",
+    );
+    write_temp_file(
+        dir.path(),
+        "test_users.py",
+        "def test_create():\n    assert make_user().id\n\n\
+         def test_amount():\n    assert balance() == 0\n",
+    );
+
+    let plain = lint_in_project_mode(std::slice::from_ref(&a), false);
+    assert!(find_violation(&plain, "PYTEST-MNT-022").is_none());
+
+    let project = lint_in_project_mode(&[a], true);
+    let duplicates: Vec<_> = project
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-022")
+        .collect();
+    // Methods of test classes have their own namespace
+    assert_eq!(duplicates.len(), 1, "{duplicates:?}");
+    assert_eq!(duplicates[0].line, 1);
+    assert!(
+        duplicates[0]
+            .message
+            .contains("also defined in test_users.py"),
+        "{}",
+        duplicates[0].message
+    );
+}
+
+#[test]
+fn test_project_index_is_cached_and_invalidated_by_conftest_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = project_with_outer_conftest(dir.path());
+    let cache_dir = dir.path().join(pytest_linter::cache::CACHE_DIR_NAME);
+    let run = || {
+        let engine = LintEngine::new(Config {
+            project_mode: true,
+            cache_dir: Some(cache_dir.clone()),
+            ..Config::default()
+        })
+        .unwrap();
+        let violations = engine.lint_paths(std::slice::from_ref(&nested)).unwrap();
+        let scope_errors = violations
+            .iter()
+            .filter(|v| v.rule_id == "PYTEST-FIX-003")
+            .count();
+        (scope_errors, engine.cache_stats().unwrap())
+    };
+
+    let (errors, _) = run();
+    assert_eq!(errors, 1);
+    let cache = pytest_linter::cache::Cache::new(cache_dir.clone());
+    let index = cache.load_index().expect("index stored in the cache");
+    assert_eq!(index.files.len(), 3);
+
+    let (errors, stats) = run();
+    assert_eq!((errors, stats.hits, stats.misses), (1, 2, 0));
+
+    // Widening `db` two directories up fixes the scope error in the linted files
+    std::fs::write(
+        dir.path().join("conftest.py"),
+        "import pytest\n\n\
+         @pytest.fixture(scope=\"session\")\n\
+         def db():\n    return connect()\n",
+    )
+    .unwrap();
+    let (errors, stats) = run();
+    assert_eq!((errors, stats.hits, stats.misses), (0, 0, 2));
+}
+
+/// A project whose `pytl.toml` loads `custom_rules` from `custom_rules.toml`.
+fn project_with_custom_rules(root: &Path, config: &str, rules: &str) {
+    std::fs::write(
+        root.join("pytl.toml"),
+        format!("custom_rules = \"custom_rules.toml\"\n{config}"),
+    )
+    .unwrap();
+    std::fs::write(root.join("custom_rules.toml"), rules).unwrap();
+}
+
+const HOUSE_RULES: &str = r#"
+[[rule]]
+id = "custom/no-direct-db-session"
+call = "db.session.{method}"
+in = "tests"
+message = "Test '{function}' calls db.session.{method} directly"
+suggestion = "Use the `db` fixture"
+severity = "error"
+
+[[rule]]
+id = "custom/no-requests"
+import = "requests"
+message = "Import of {match}; use the `http` fixture"
+
+[[rule]]
+id = "custom/no-flaky-mark"
+decorator = "pytest.mark.{mark}"
+in = "tests"
+message = "pytest.mark.{mark} on '{function}'"
+severity = "info"
+"#;
+
+#[test]
+fn test_custom_rules_flag_calls_imports_and_decorators() {
+    let dir = tempfile::tempdir().unwrap();
+    project_with_custom_rules(dir.path(), "", HOUSE_RULES);
+    let path = write_temp_file(
+        dir.path(),
+        "test_orders.py",
+        "import pytest\n\
+         from requests import get\n\
+         import db as database\n\n\
+         def helper():\n    return database.session.query(1)\n\n\
+         @pytest.mark.slow\n\
+         def test_orders():\n    database.session.add(2)\n    assert helper()\n",
+    );
+    let engine = LintEngine::new(Config::discover(dir.path()).unwrap()).unwrap();
+    let violations = engine.lint_paths(&[path]).unwrap();
+    let custom: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id.starts_with("custom/"))
+        .map(|v| (v.rule_id.as_str(), v.line, v.severity, v.message.as_str()))
+        .collect();
+    assert_eq!(
+        custom,
+        [
+            (
+                "custom/no-requests",
+                2,
+                Severity::Warning,
+                "Import of requests; use the `http` fixture"
+            ),
+            (
+                "custom/no-flaky-mark",
+                8,
+                Severity::Info,
+                "pytest.mark.slow on 'test_orders'"
+            ),
+            (
+                "custom/no-direct-db-session",
+                10,
+                Severity::Error,
+                "Test 'test_orders' calls db.session.add directly"
+            ),
+        ]
+    );
+    let call = find_violation(&violations, "custom/no-direct-db-session").unwrap();
+    assert_eq!(call.test_name.as_deref(), Some("test_orders"));
+    assert_eq!(call.suggestion.as_deref(), Some("Use the `db` fixture"));
+    assert_eq!((call.col, call.end_col), (Some(5), Some(28)));
+}
+
+#[test]
+fn test_custom_rules_are_configured_suppressed_and_baselined_like_builtin_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    project_with_custom_rules(
+        dir.path(),
+        "disable = [\"custom/no-flaky-mark\"]\n\n[severity]\n\"custom/no-requests\" = \"error\"\n",
+        HOUSE_RULES,
+    );
+    let path = write_temp_file(
+        dir.path(),
+        "test_orders.py",
+        "import pytest\nimport requests\n\n\
+         @pytest.mark.slow\n\
+         def test_orders(db):\n\
+         \x20   db.session.add(1)  # pytl: ignore[custom/no-direct-db-session]\n\
+         \x20   db.session.commit()  # pytl: ignore[custom/no-such-rule]\n\
+         \x20   db.session.flush()  # noqa: custom/no-direct-db-session\n\
+         \x20   assert requests\n",
+    );
+    let lint = || {
+        LintEngine::new(Config::discover(dir.path()).unwrap())
+            .unwrap()
+            .lint_paths(std::slice::from_ref(&path))
+            .unwrap()
+    };
+    let violations = lint();
+    let custom: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id.starts_with("custom/"))
+        .map(|v| (v.rule_id.as_str(), v.line, v.severity))
+        .collect();
+    assert_eq!(
+        custom,
+        [
+            ("custom/no-requests", 2, Severity::Error),
+            ("custom/no-direct-db-session", 7, Severity::Error),
+        ]
+    );
+    // IDs in the custom namespace are never reported as unknown
+    assert!(find_violation(&violations, "PYTEST-SUP-001").is_none());
+
+    let baseline = pytest_linter::baseline::Baseline::from_violations(&violations);
+    assert!(pytest_linter::engine::filter_new_violations(&lint(), &baseline).is_empty());
+}
+
+#[test]
+fn test_invalid_custom_rules_are_configuration_errors() {
+    let error = |rules: &str| {
+        let dir = tempfile::tempdir().unwrap();
+        project_with_custom_rules(dir.path(), "", rules);
+        format!("{:#}", Config::discover(dir.path()).unwrap_err())
+    };
+    let message = error("[[rule]]\nid = \"no-prints\"\ncall = \"print\"\nmessage = \"x\"\n");
+    assert!(message.contains("custom_rules.toml"), "{message}");
+    assert!(message.contains("must be `custom/`"), "{message}");
+    let message =
+        error("[[rule]]\nid = \"custom/x\"\ncall = \"db.{method}\"\nmessage = \"{name}\"\n");
+    assert!(
+        message.contains("`{name}` in `{name}` is not a capture"),
+        "{message}"
+    );
+    assert!(
+        message.contains("{method}, {match}, {function}"),
+        "{message}"
+    );
+    let message = error("[[rule]]\nid = \"custom/x\"\nmessage = \"x\"\n");
+    assert!(
+        message.contains("set one of `call`, `import` or `decorator`"),
+        "{message}"
+    );
+    let message = error(
+        "[[rule]]\nid = \"custom/x\"\ncall = \"a\"\nmessage = \"x\"\n\n\
+         [[rule]]\nid = \"custom/x\"\nimport = \"b\"\nmessage = \"y\"\n",
+    );
+    assert!(message.contains("`custom/x` is defined twice"), "{message}");
+}
+
+/// (line, column, severity, bound) of each FLK-015 diagnostic for a test file.
+fn polling_loops(source: &str) -> Vec<(usize, Option<usize>, Severity, String)> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(dir.path(), "test_poll.py", source);
+    lint_single_file(&path)
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-015")
+        .map(|v| {
+            let bound = v.metadata["bound"].as_str().unwrap().to_string();
+            (v.line, v.col, v.severity, bound)
+        })
+        .collect()
+}
+
+#[test]
+fn test_unbounded_polling_loop_triggers_flk015_error() {
+    let source = "import time\n\n\
+                  def test_job_finishes(job):\n\
+                  \x20   job.start()\n\
+                  \x20   while not job.done():\n\
+                  \x20       time.sleep(0.1)\n\
+                  \x20   assert job.result == 1\n";
+    assert_eq!(
+        polling_loops(source),
+        [(5, Some(5), Severity::Error, "none".to_string())]
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(dir.path(), "test_poll.py", source);
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-FLK-015").unwrap();
+    assert_eq!(v.end_col, Some(10));
+    assert!(
+        v.message
+            .contains("(time.sleep() at line 6) with no timeout or attempt limit"),
+        "{}",
+        v.message
+    );
+    assert_eq!(v.test_name.as_deref(), Some("test_job_finishes"));
+
+    // Breaking on the condition alone is not a bound
+    let source = "from time import sleep\n\n\
+                  def test_ready(server):\n\
+                  \x20   while True:\n\
+                  \x20       if server.ready():\n\
+                  \x20           break\n\
+                  \x20       sleep(1)\n\
+                  \x20   assert server.ready()\n";
+    assert_eq!(
+        polling_loops(source),
+        [(4, Some(5), Severity::Error, "none".to_string())]
+    );
+}
+
+#[test]
+fn test_bounded_polling_loops_trigger_flk015_warning() {
+    let bound = |body: &str| {
+        let source = format!("import itertools\nimport time\n\ndef test_ready(server):\n{body}    assert server.ready()\n");
+        polling_loops(&source)
+            .into_iter()
+            .map(|(_, _, severity, bound)| (severity, bound))
+            .collect::<Vec<_>>()
+    };
+    let warning = |b: &str| vec![(Severity::Warning, b.to_string())];
+
+    assert_eq!(
+        bound("    for _ in range(50):\n        if server.ready():\n            break\n        time.sleep(0.1)\n"),
+        warning("attempts")
+    );
+    assert_eq!(
+        bound("    attempts = 0\n    while not server.ready() and attempts < 50:\n        attempts += 1\n        time.sleep(0.1)\n"),
+        warning("attempts")
+    );
+    assert_eq!(
+        bound("    deadline = time.monotonic() + 5\n    while not server.ready():\n        if time.monotonic() > deadline:\n            raise TimeoutError\n        time.sleep(0.1)\n"),
+        warning("deadline")
+    );
+    assert_eq!(
+        bound("    while not server.ready() and time.time() < start + timeout:\n        time.sleep(0.1)\n"),
+        warning("deadline")
+    );
+    // `itertools.count()` never runs out
+    assert_eq!(
+        bound("    for _ in itertools.count():\n        time.sleep(0.1)\n        if server.ready():\n            break\n"),
+        vec![(Severity::Error, "none".to_string())]
+    );
+}
+
+#[test]
+fn test_pytest_timeout_bounds_polling_loops_flk015() {
+    let source = "import time\nimport pytest\n\n\
+                  @pytest.mark.timeout(30)\n\
+                  def test_ready(server):\n\
+                  \x20   while not server.ready():\n\
+                  \x20       time.sleep(0.1)\n\
+                  \x20   assert server.ready()\n";
+    assert_eq!(
+        polling_loops(source),
+        [(6, Some(5), Severity::Warning, "test-timeout".to_string())]
+    );
+    let module_mark = source.replace("@pytest.mark.timeout(30)\n", "").replace(
+        "import pytest\n",
+        "import pytest\n\npytestmark = pytest.mark.timeout(30)\n",
+    );
+    assert_eq!(polling_loops(&module_mark)[0].3, "test-timeout");
+}
+
+#[test]
+fn test_sleep_outside_loops_and_in_nested_functions_does_not_trigger_flk015() {
+    let source = "import time\n\n\
+                  def test_wait(items):\n\
+                  \x20   time.sleep(1)\n\
+                  \x20   for item in items:\n\
+                  \x20       def later():\n\
+                  \x20           time.sleep(1)\n\
+                  \x20       item.on_done(later)\n\
+                  \x20   assert items\n";
+    assert!(polling_loops(source).is_empty());
+}
+
+#[test]
+fn test_polling_loop_in_fixture_and_configured_severity_flk015() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "conftest.py",
+        "import time\nimport pytest\n\n\
+         @pytest.fixture\n\
+         def server():\n\
+         \x20   s = start()\n\
+         \x20   while not s.ready():\n\
+         \x20       time.sleep(0.1)\n\
+         \x20   return s\n",
+    );
+    let violations = lint_single_file(&path);
+    let v = find_violation(&violations, "PYTEST-FLK-015").unwrap();
+    assert!(
+        v.message.starts_with("Fixture 'server' polls"),
+        "{}",
+        v.message
+    );
+    assert_eq!(
+        (v.line, v.severity, v.test_name.as_deref()),
+        (7, Severity::Error, None)
+    );
+
+    let mut config = Config::default();
+    config.rules.insert(
+        "PYTEST-FLK-015".to_string(),
+        pytest_linter::config::RuleConfig {
+            severity: Some(Severity::Info),
+            ..Default::default()
+        },
+    );
+    let violations = LintEngine::new(config)
+        .unwrap()
+        .lint_paths(&[path])
+        .unwrap();
+    let v = find_violation(&violations, "PYTEST-FLK-015").unwrap();
+    assert_eq!(v.severity, Severity::Info);
+}
+
+#[test]
+fn test_rule_tags_reach_diagnostics_and_json() {
+    let source = r#"
+import time
+import unittest
+
+def test_a(tmp_path):
+    time.sleep(1)
+    assert compute() == 3
+
+class TestLegacy(unittest.TestCase):
+    def test_old(self):
+        self.assertEquals(compute(), 3)
+"#;
+    let violations = LintEngine::new(Config::default())
+        .unwrap()
+        .lint_source(source, Path::new("test_tags.py"))
+        .unwrap();
+    let tags = |rule: &str| find_violation(&violations, rule).unwrap().tags.clone();
+    assert_eq!(tags("PYTEST-FIX-014"), [pytest_linter::Tag::Unnecessary]);
+    assert_eq!(tags("PYTEST-MNT-023"), [pytest_linter::Tag::Deprecated]);
+    assert!(tags("PYTEST-FLK-001").is_empty());
+    let hint = find_violation(&violations, "PYTEST-DBC-001").unwrap();
+    assert_eq!(hint.severity, Severity::Hint);
+
+    let json = |rule: &str| {
+        serde_json::to_value(pytest_linter::output::json::JsonDiagnostic::from_violation(
+            find_violation(&violations, rule).unwrap(),
+            Some(source),
+        ))
+        .unwrap()
+    };
+    assert_eq!(
+        json("PYTEST-FIX-014")["tags"],
+        serde_json::json!(["unnecessary"])
+    );
+    assert!(json("PYTEST-FLK-001").get("tags").is_none());
+    let rule = pytest_linter::explain::find_rule("PYTEST-MNT-023").unwrap();
+    assert!(pytest_linter::explain::explain(&rule).contains("Tags:      deprecated\n"));
+}
+
+#[test]
+fn test_severity_remapped_to_hint_fails_only_with_fail_on_hint() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("pytl.toml"),
+        "[severity]\ntime-sleep = \"hint\"\n",
+    )
+    .unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_wait.py",
+        "import time\n\ndef test_wait():\n    time.sleep(1)\n    assert True\n",
+    );
+    let mut config = Config::discover(dir.path()).unwrap();
+    let violations: Vec<_> = LintEngine::new(config.clone())
+        .unwrap()
+        .lint_paths(&[path])
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-001")
+        .collect();
+    assert_eq!(violations.len(), 1, "{violations:?}");
+    assert_eq!(violations[0].severity, Severity::Hint);
+    let summary = pytest_linter::output::summary::Summary::new(&violations, None);
+    assert_eq!(summary.by_severity.hint, 1);
+
+    config.fail_on = Some(Severity::Info);
+    assert_eq!(
+        pytest_linter::engine::policy_failure(&violations, &config),
+        None
+    );
+    config.fail_on = Some(Severity::Hint);
+    assert_eq!(
+        pytest_linter::engine::policy_failure(&violations, &config).as_deref(),
+        Some("1 hint")
+    );
+}
+
+fn snapshot_smells(source: &str, config: Config) -> Vec<pytest_linter::models::Violation> {
+    LintEngine::new(config)
+        .unwrap()
+        .lint_source(source, Path::new("test_snapshots.py"))
+        .unwrap()
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-MNT-034")
+        .collect()
+}
+
+#[test]
+fn test_snapshot_only_and_unnamed_snapshots_trigger_mnt034() {
+    let source = r#"
+import pytest
+
+def test_only_snapshot(snapshot):
+    assert render() == snapshot
+
+def test_steps(snapshot):
+    assert render(1) == snapshot
+    assert snapshot() == render(2)
+    assert len(render(2)) == 3
+
+def test_named(snapshot):
+    assert render(1) == snapshot(name="one")
+    assert render(2) == snapshot(name="two")
+    assert render(3) is not None
+
+def test_pytest_snapshot(snapshot):
+    snapshot.assert_match(render(), "out.txt")
+    with pytest.raises(ValueError):
+        render(-1)
+
+def test_no_fixture():
+    assert render() == snapshot
+"#;
+    let violations = snapshot_smells(source, Config::default());
+    let found: Vec<_> = violations
+        .iter()
+        .map(|v| (v.test_name.as_deref().unwrap(), v.severity, v.line, v.col))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("test_only_snapshot", Severity::Info, 4, Some(5)),
+            ("test_steps", Severity::Warning, 9, Some(12)),
+        ]
+    );
+    assert!(violations[1].message.contains("2 unnamed snapshots"));
+    assert!(violations[1]
+        .suggestion
+        .as_deref()
+        .unwrap()
+        .contains("name="));
+    assert_eq!(violations[1].metadata["unnamed_snapshots"], 2);
+}
+
+#[test]
+fn test_large_inline_snapshot_triggers_mnt034_above_configured_lines() {
+    let source = r#"
+from inline_snapshot import snapshot
+
+def test_report():
+    assert render() == snapshot(
+        """\
+header
+row 1
+row 2
+"""
+    )
+    assert render().startswith("header")
+
+def test_small():
+    assert render(1) == snapshot("ok")
+    assert render(1)
+"#;
+    assert!(snapshot_smells(source, Config::default()).is_empty());
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-MNT-034".to_string())
+        .or_default()
+        .options
+        .insert("max_inline_lines".to_string(), toml::Value::Integer(3));
+    let violations = snapshot_smells(source, config);
+    assert_eq!(violations.len(), 1, "{violations:?}");
+    let v = &violations[0];
+    assert_eq!(v.test_name.as_deref(), Some("test_report"));
+    assert_eq!((v.line, v.col, v.end_line), (5, Some(24), Some(11)));
+    assert_eq!(v.metadata["lines"], 5);
+    assert!(v.message.contains("(limit 3)"), "{}", v.message);
+}
+
+#[test]
+fn test_syntax_errors_are_reported_without_stopping_the_run() {
+    let dir = tempfile::tempdir().unwrap();
+    write_temp_file(
+        dir.path(),
+        "test_broken.py",
+        "def test_no_assert():\n    value = 1\n\n\ndef test_cut():\n    x = compute(\n",
+    );
+    let healthy = write_temp_file(
+        dir.path(),
+        "test_healthy.py",
+        "import time\n\ndef test_wait():\n    time.sleep(1)\n    assert ready() == 1\n",
+    );
+    // Other languages are not linted, truncated or not
+    write_temp_file(
+        dir.path(),
+        "broken.test.ts",
+        "describe(\"x\", () => {\n  it(\"y\", () => {\n",
+    );
+    write_temp_file(dir.path(), "broken_test.rs", "#[test]\nfn cut() {\n");
+
+    let mut config = Config::default();
+    let violations = LintEngine::new(config.clone())
+        .unwrap()
+        .lint_paths(&[dir.path().to_path_buf()])
+        .unwrap();
+    let in_file = |name: &str, rule: &str| -> Vec<usize> {
+        violations
+            .iter()
+            .filter(|v| v.file_path.ends_with(name) && v.rule_id == rule)
+            .map(|v| v.line)
+            .collect()
+    };
+
+    let syntax: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-SYN-001")
+        .collect();
+    assert_eq!(syntax.len(), 1, "{syntax:?}");
+    assert!(syntax[0].file_path.ends_with("test_broken.py"));
+    assert_eq!((syntax[0].line, syntax[0].col), (6, Some(5)));
+    assert_eq!(syntax[0].severity, Severity::Error);
+    assert!(
+        syntax[0].message.contains("compute("),
+        "{}",
+        syntax[0].message
+    );
+    // The complete test before the error is still checked; the truncated one is not
+    assert_eq!(in_file("test_broken.py", "PYTEST-MNT-004"), [1]);
+    assert_eq!(in_file("test_healthy.py", "PYTEST-FLK-001"), [4]);
+    // The healthy sibling gets the same diagnostics as when it is linted alone
+    let alone: Vec<_> = lint_single_file(&healthy)
+        .into_iter()
+        .map(|v| (v.rule_id, v.line))
+        .collect();
+    let beside: Vec<_> = violations
+        .iter()
+        .filter(|v| v.file_path.ends_with("test_healthy.py"))
+        .map(|v| (v.rule_id.clone(), v.line))
+        .collect();
+    assert_eq!(alone, beside);
+    assert!(violations
+        .iter()
+        .all(|v| v.file_path.extension().is_some_and(|e| e == "py")));
+
+    let without_broken: Vec<_> = violations
+        .iter()
+        .filter(|v| !v.file_path.ends_with("test_broken.py") || v.rule_id == "PYTEST-SYN-001")
+        .cloned()
+        .collect();
+    assert_eq!(
+        pytest_linter::engine::policy_failure(&without_broken, &config).as_deref(),
+        Some("1 error")
+    );
+    config.ignore_syntax_errors = true;
+    assert_eq!(
+        pytest_linter::engine::policy_failure(&without_broken, &config),
+        None
+    );
+}
+
+#[test]
+fn test_empty_suites_trigger_syn001() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_empty.py",
+        "def test_a():\n    x = 1\n    if x:\n    assert x == 1\n\n\
+         class TestEmpty:\n    # only a comment\n\n\
+         async def test_b():\n    for item in items():\n        pass\n    else:\n",
+    );
+    let violations = lint_single_file(&path);
+    let syntax: Vec<_> = violations
+        .iter()
+        .filter(|v| v.rule_id == "PYTEST-SYN-001")
+        .map(|v| (v.line, v.message.as_str()))
+        .collect();
+    assert_eq!(
+        syntax,
+        [
+            (3, "Syntax error: expected an indented block after `if`"),
+            (6, "Syntax error: expected an indented block after `class`"),
+            (12, "Syntax error: expected an indented block after `else`"),
+        ]
+    );
+}
+
+#[test]
+fn test_environment_reads_without_control_trigger_flk016() {
+    let source = r#"
+import os
+import time
+from datetime import date, datetime
+from unittest import mock
+
+from freezegun import freeze_time
+
+
+def test_home():
+    assert os.environ["HOME"] == "/root"
+
+
+def test_api_url():
+    url = os.getenv("API_URL")
+    response = fetch(url)
+    assert response.ok
+
+
+def test_invoice_date():
+    assert make_invoice().date == date.today()
+
+
+def test_timestamps():
+    stamps = []
+    stamps.append(time.time())
+    assert stamps
+"#;
+    let violations = rule_violations(source, "PYTEST-FLK-016", Config::default());
+    let found: Vec<(usize, &str)> = violations
+        .iter()
+        .map(|v| (v.line, v.metadata["dependency"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        found,
+        [(11, "env"), (15, "env"), (21, "clock"), (26, "clock")]
+    );
+    assert_eq!(violations[0].col, Some(12));
+    assert!(violations[2].message.contains("date.today()"));
+    assert_eq!(violations[0].severity, Severity::Warning);
+}
+
+#[test]
+fn test_controlled_or_unasserted_environment_reads_do_not_trigger_flk016() {
+    let source = r#"
+import locale
+import os
+import time
+import unittest
+from datetime import date, datetime
+from unittest import mock
+
+import pytest
+import time_machine
+from freezegun import freeze_time
+
+
+def test_setenv(monkeypatch):
+    monkeypatch.setenv("API_URL", "http://x")
+    assert os.environ["API_URL"] == "http://x"
+
+
+@mock.patch.dict(os.environ, {"A": "1"})
+def test_patched_environ():
+    assert os.environ.get("A") == "1"
+
+
+@freeze_time("2024-01-01")
+def test_frozen():
+    assert make_invoice().date == date.today()
+
+
+@pytest.mark.freeze_time
+def test_marker():
+    assert date.today().year == 2024
+
+
+def test_traveller():
+    with time_machine.travel("2024-01-01"):
+        assert datetime.now().year == 2024
+
+
+def test_freezer(freezer):
+    assert datetime.now().year == 2024
+
+
+def test_project_fixture(frozen_time):
+    assert datetime.now().year == 2024
+
+
+def test_locale():
+    locale.setlocale(locale.LC_ALL, "C")
+    assert locale.getlocale()[0] is None
+
+
+def test_duration():
+    start = time.time()
+    run()
+    print(f"took {time.time() - start:.2f}s")
+    assert run() == 1
+
+
+def test_skip_on_ci():
+    if os.environ.get("CI"):
+        pytest.skip("slow")
+    assert run() == 1
+
+
+@freeze_time("2024-01-01")
+class TestFrozen(unittest.TestCase):
+    def test_in_class(self):
+        self.assertEqual(date.today().year, 2024)
+"#;
+    let default = rule_violations(source, "PYTEST-FLK-016", Config::default());
+    let lines: Vec<usize> = default.iter().map(|v| v.line).collect();
+    assert_eq!(lines, [44], "{default:?}");
+
+    let mut config = Config::default();
+    config
+        .rules
+        .entry("PYTEST-FLK-016".to_string())
+        .or_default()
+        .options
+        .insert(
+            "controlled_fixtures".to_string(),
+            toml::Value::Array(vec![toml::Value::String("frozen_time".to_string())]),
+        );
+    let violations = rule_violations(source, "PYTEST-FLK-016", config);
+    assert!(violations.is_empty(), "{violations:?}");
+}
+
+#[test]
+fn test_text_and_ci_formats_report_character_columns() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_temp_file(
+        dir.path(),
+        "test_columns.py",
+        "def test_x():\n    s = \"😀é\"; assert s == 0.1\n",
+    );
+    let violations: Vec<_> = lint_single_file(&path)
+        .into_iter()
+        .filter(|v| v.rule_id == "PYTEST-FLK-013")
+        .collect();
+    // Byte column 26 is the 22nd character
+    assert_eq!(
+        (violations[0].col, violations[0].end_col),
+        (Some(26), Some(34))
+    );
+    let render = |format: &str| {
+        colored::control::set_override(false);
+        let mut out = Vec::new();
+        pytest_linter::engine::write_format(
+            &violations,
+            format,
+            &mut out,
+            &Config::default(),
+            None,
+            &mut pytest_linter::output::SourceCache::default(),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    let location = format!("{}:2:22", path.display());
+    assert!(render("terminal").contains(&location));
+    assert!(render("short").contains(&location));
+    assert!(render("junit").contains(&location));
+    assert!(render("github").contains("col=22,endColumn=29"));
+    assert!(render("checkstyle").contains("column=\"22\""));
+
+    let sarif: serde_json::Value = serde_json::from_str(&render("sarif")).unwrap();
+    let run = &sarif["runs"][0];
+    assert_eq!(run["columnKind"], "unicodeCodePoints");
+    let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(
+        (&region["startColumn"], &region["endColumn"]),
+        (&22.into(), &30.into())
+    );
+
+    // JSON keeps byte columns, as documented
+    let json: serde_json::Value = serde_json::from_str(&render("json")).unwrap();
+    assert_eq!(json["diagnostics"][0]["span"]["column"], 26);
+}